@@ -1,6 +1,8 @@
 //! Support for runtime configuration of the execution engine - as an integral property of the
 //! `EngineState` instance.
-use crate::shared::{system_config::SystemConfig, wasm_config::WasmConfig};
+use crate::shared::{
+    host_function_costs::HostFunctionCosts, system_config::SystemConfig, wasm_config::WasmConfig,
+};
 
 /// Default value for a maximum query depth configuration option.
 pub const DEFAULT_MAX_QUERY_DEPTH: u64 = 5;
@@ -14,7 +16,7 @@ pub const DEFAULT_MINIMUM_DELEGATION_AMOUNT: u64 = 500 * 1_000_000_000;
 pub const DEFAULT_STRICT_ARGUMENT_CHECKING: bool = false;
 
 /// The runtime configuration of the execution engine
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct EngineConfig {
     /// Max query depth of the engine.
     pub(crate) max_query_depth: u64,
@@ -81,6 +83,14 @@ impl EngineConfig {
         &self.wasm_config
     }
 
+    /// Returns this config with the host function costs replaced.
+    pub fn with_host_function_costs(mut self, host_function_costs: HostFunctionCosts) -> Self {
+        self.wasm_config = self
+            .wasm_config
+            .with_host_function_costs(host_function_costs);
+        self
+    }
+
     /// Returns the current system config.
     pub fn system_config(&self) -> &SystemConfig {
         &self.system_config
@@ -9,7 +9,10 @@ use casper_types::{
 use super::error;
 use crate::{
     core::execution::Error as ExecError,
-    shared::{execution_journal::ExecutionJournal, transform::Transform},
+    shared::{
+        execution_journal::ExecutionJournal, host_function_gas_report::HostFunctionGasReport,
+        transform::Transform,
+    },
 };
 
 fn make_payment_error_effects(
@@ -47,6 +50,8 @@ pub enum ExecutionResult {
         transfers: Vec<TransferAddr>,
         /// Gas consumed up to the point of the failure.
         cost: Gas,
+        /// Gas spent on each host function up to the point of the failure.
+        host_function_gas: HostFunctionGasReport,
         /// Journal of execution.
         execution_journal: ExecutionJournal,
     },
@@ -56,6 +61,8 @@ pub enum ExecutionResult {
         transfers: Vec<TransferAddr>,
         /// Gas cost.
         cost: Gas,
+        /// Gas spent on each host function.
+        host_function_gas: HostFunctionGasReport,
         /// Journal of execution.
         execution_journal: ExecutionJournal,
     },
@@ -67,6 +74,7 @@ impl Default for ExecutionResult {
             execution_journal: Default::default(),
             transfers: Default::default(),
             cost: Default::default(),
+            host_function_gas: Default::default(),
         }
     }
 }
@@ -93,6 +101,7 @@ impl ExecutionResult {
             error,
             transfers: Vec::default(),
             cost: Gas::default(),
+            host_function_gas: Default::default(),
             execution_journal: Default::default(),
         }
     }
@@ -136,6 +145,18 @@ impl ExecutionResult {
         }
     }
 
+    /// Returns the gas spent on each host function regardless of variant.
+    pub fn host_function_gas(&self) -> &HostFunctionGasReport {
+        match self {
+            ExecutionResult::Failure {
+                host_function_gas, ..
+            } => host_function_gas,
+            ExecutionResult::Success {
+                host_function_gas, ..
+            } => host_function_gas,
+        }
+    }
+
     /// Returns list of transfers regardless of variant.
     pub fn transfers(&self) -> &Vec<TransferAddr> {
         match self {
@@ -165,21 +186,25 @@ impl ExecutionResult {
             ExecutionResult::Failure {
                 error,
                 transfers,
+                host_function_gas,
                 execution_journal,
                 ..
             } => ExecutionResult::Failure {
                 error,
                 transfers,
                 cost,
+                host_function_gas,
                 execution_journal,
             },
             ExecutionResult::Success {
                 transfers,
+                host_function_gas,
                 execution_journal,
                 ..
             } => ExecutionResult::Success {
                 transfers,
                 cost,
+                host_function_gas,
                 execution_journal,
             },
         }
@@ -194,21 +219,25 @@ impl ExecutionResult {
             ExecutionResult::Failure {
                 error,
                 cost,
+                host_function_gas,
                 execution_journal,
                 ..
             } => ExecutionResult::Failure {
                 error,
                 transfers,
                 cost,
+                host_function_gas,
                 execution_journal,
             },
             ExecutionResult::Success {
                 cost,
+                host_function_gas,
                 execution_journal,
                 ..
             } => ExecutionResult::Success {
                 transfers,
                 cost,
+                host_function_gas,
                 execution_journal,
             },
         }
@@ -224,20 +253,24 @@ impl ExecutionResult {
                 error,
                 transfers,
                 cost,
+                host_function_gas,
                 execution_journal: _,
             } => ExecutionResult::Failure {
                 error,
                 transfers,
                 cost,
+                host_function_gas,
                 execution_journal,
             },
             ExecutionResult::Success {
                 transfers,
                 cost,
+                host_function_gas,
                 execution_journal: _,
             } => ExecutionResult::Success {
                 transfers,
                 cost,
+                host_function_gas,
                 execution_journal,
             },
         }
@@ -328,6 +361,7 @@ impl ExecutionResult {
             execution_journal,
             transfers,
             cost: gas_cost,
+            host_function_gas: Default::default(),
         })
     }
 
@@ -349,6 +383,7 @@ impl From<&ExecutionResult> for casper_types::ExecutionResult {
                 transfers,
                 cost,
                 execution_journal,
+                ..
             } => casper_types::ExecutionResult::Success {
                 effect: execution_journal.into(),
                 transfers: transfers.clone(),
@@ -359,6 +394,7 @@ impl From<&ExecutionResult> for casper_types::ExecutionResult {
                 transfers,
                 cost,
                 execution_journal,
+                ..
             } => casper_types::ExecutionResult::Failure {
                 effect: execution_journal.into(),
                 transfers: transfers.clone(),
@@ -441,6 +477,21 @@ impl ExecutionResultBuilder {
         payment_cost + session_cost
     }
 
+    /// Returns the gas spent on each host function by the payment and the session code.
+    ///
+    /// If either a payment or session code is not specified then it is skipped.
+    pub fn host_function_gas(&self) -> HostFunctionGasReport {
+        let mut host_function_gas = HostFunctionGasReport::default();
+        for result in self
+            .payment_execution_result
+            .iter()
+            .chain(&self.session_execution_result)
+        {
+            host_function_gas.merge(result.host_function_gas().clone());
+        }
+        host_function_gas
+    }
+
     /// Returns transfers from a session's execution result.
     ///
     /// If the session's execution result is not supplied then an empty [`Vec`] is returned.
@@ -458,6 +509,7 @@ impl ExecutionResultBuilder {
         let mut error: Option<error::Error> = None;
         let mut transfers = self.transfers();
         let cost = self.total_cost();
+        let host_function_gas = self.host_function_gas();
 
         let mut journal = match self.payment_execution_result {
             Some(result @ ExecutionResult::Failure { .. }) => return Ok(result),
@@ -475,6 +527,7 @@ impl ExecutionResultBuilder {
                 transfers: session_transfers,
                 execution_journal: _,
                 cost: _,
+                host_function_gas: _,
             }) => {
                 error = Some(session_error);
                 transfers = session_transfers;
@@ -502,12 +555,14 @@ impl ExecutionResultBuilder {
             None => Ok(ExecutionResult::Success {
                 transfers,
                 cost,
+                host_function_gas,
                 execution_journal: journal,
             }),
             Some(error) => Ok(ExecutionResult::Failure {
                 error,
                 transfers,
                 cost,
+                host_function_gas,
                 execution_journal: journal,
             }),
         }
@@ -24,6 +24,7 @@ use std::{
     collections::{BTreeMap, BTreeSet},
     convert::TryFrom,
     rc::Rc,
+    sync::RwLock,
 };
 
 use num::Zero;
@@ -83,7 +84,10 @@ use crate::{
         runtime::RuntimeStack,
         tracking_copy::{TrackingCopy, TrackingCopyExt},
     },
-    shared::{additive_map::AdditiveMap, newtypes::CorrelationId, transform::Transform},
+    shared::{
+        additive_map::AdditiveMap, host_function_costs::HostFunctionCosts, newtypes::CorrelationId,
+        transform::Transform,
+    },
     storage::{
         global_state::{
             lmdb::LmdbGlobalState, scratch::ScratchGlobalState, CommitProvider, StateProvider,
@@ -114,6 +118,12 @@ pub const WASMLESS_TRANSFER_FIXED_GAS_PRICE: u64 = 1;
 #[derive(Debug)]
 pub struct EngineState<S> {
     config: EngineConfig,
+    /// Configs with a host function cost table that replaces the configured one from the given
+    /// protocol version on.
+    ///
+    /// These are built when the table is scheduled, so that selecting one per request is as cheap
+    /// as using the configured one.
+    scheduled_configs: RwLock<BTreeMap<ProtocolVersion, EngineConfig>>,
     state: S,
 }
 
@@ -141,7 +151,13 @@ impl EngineState<LmdbGlobalState> {
     /// Provide a local cached-only version of engine-state.
     pub fn get_scratch_engine_state(&self) -> EngineState<ScratchGlobalState> {
         EngineState {
-            config: self.config,
+            config: self.config.clone(),
+            scheduled_configs: RwLock::new(
+                self.scheduled_configs
+                    .read()
+                    .expect("scheduled configs read lock")
+                    .clone(),
+            ),
             state: self.state.create_scratch(),
         }
     }
@@ -166,7 +182,11 @@ where
 {
     /// Creates new engine state.
     pub fn new(state: S, config: EngineConfig) -> EngineState<S> {
-        EngineState { config, state }
+        EngineState {
+            config,
+            scheduled_configs: RwLock::new(BTreeMap::new()),
+            state,
+        }
     }
 
     /// Returns engine config.
@@ -175,10 +195,53 @@ where
     }
 
     /// Updates current engine config with a new instance.
+    ///
+    /// Scheduled host function cost tables are kept, and applied on top of the new config.
     pub fn update_config(&mut self, new_config: EngineConfig) {
+        let scheduled_configs = self
+            .scheduled_configs
+            .get_mut()
+            .expect("scheduled configs write lock");
+        for config in scheduled_configs.values_mut() {
+            let host_function_costs = config.wasm_config().host_function_costs().clone();
+            *config = new_config
+                .clone()
+                .with_host_function_costs(host_function_costs);
+        }
         self.config = new_config
     }
 
+    /// Schedules a host function cost table to be used for all executions with the given protocol
+    /// version or a later one, e.g. once an upgrade activates at an era boundary.
+    ///
+    /// The table is selected by the protocol version of each request, so all nodes use the same
+    /// costs for the same block, and a request never mixes two tables.
+    pub fn schedule_host_function_costs(
+        &self,
+        protocol_version: ProtocolVersion,
+        host_function_costs: HostFunctionCosts,
+    ) {
+        let config = self
+            .config
+            .clone()
+            .with_host_function_costs(host_function_costs);
+        self.scheduled_configs
+            .write()
+            .expect("scheduled configs write lock")
+            .insert(protocol_version, config);
+    }
+
+    /// Returns the engine config for executions with the given protocol version: the configured
+    /// one, with the most recent host function cost table scheduled for that version, if any.
+    pub fn config_for(&self, protocol_version: ProtocolVersion) -> EngineConfig {
+        self.scheduled_configs
+            .read()
+            .expect("scheduled configs read lock")
+            .range(..=protocol_version)
+            .next_back()
+            .map_or_else(|| self.config.clone(), |(_, config)| config.clone())
+    }
+
     /// Commits genesis process.
     ///
     /// This process is run only once per network to initiate the system. By definition users are
@@ -495,7 +558,7 @@ where
         correlation_id: CorrelationId,
         mut exec_request: ExecuteRequest,
     ) -> Result<ExecutionResults, Error> {
-        let executor = Executor::new(*self.config());
+        let executor = Executor::new(self.config_for(exec_request.protocol_version));
 
         let deploys = exec_request.take_deploys();
         let mut results = ExecutionResults::with_capacity(deploys.len());
@@ -1794,7 +1857,7 @@ where
             Ok(Some(tracking_copy)) => Rc::new(RefCell::new(tracking_copy)),
         };
 
-        let executor = Executor::new(*self.config());
+        let executor = Executor::new(self.config_for(step_request.protocol_version));
 
         let system_account_addr = PublicKey::System.to_account_hash();
 
@@ -2076,3 +2139,59 @@ where
         RuntimeStack::new_system_call_stack(max_height)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::global_state::in_memory::InMemoryGlobalState;
+
+    #[test]
+    fn should_switch_host_function_costs_at_protocol_version() {
+        let state = InMemoryGlobalState::empty().unwrap();
+        let mut engine_state = EngineState::new(state, EngineConfig::default());
+        let old_costs = HostFunctionCosts::default();
+        let mut new_costs = HostFunctionCosts::default();
+        new_costs.transfer = HostFunctionCosts::default().transfer.with_call_limit(1);
+        let v1 = ProtocolVersion::from_parts(1, 0, 0);
+        let v2 = ProtocolVersion::from_parts(2, 0, 0);
+        engine_state.schedule_host_function_costs(v2, new_costs.clone());
+
+        let host_function_costs = |version| {
+            engine_state
+                .config_for(version)
+                .wasm_config()
+                .host_function_costs()
+                .clone()
+        };
+        assert_eq!(host_function_costs(v1), old_costs);
+        assert_eq!(host_function_costs(v2), new_costs);
+        assert_eq!(
+            host_function_costs(ProtocolVersion::from_parts(2, 1, 0)),
+            new_costs
+        );
+        // Everything else in the config stays the same.
+        assert_eq!(
+            engine_state.config_for(v2).wasm_config().opcode_costs(),
+            engine_state.config().wasm_config().opcode_costs()
+        );
+
+        // A new config keeps the scheduled table, but replaces everything else.
+        let defaults = EngineConfig::default();
+        let new_config = EngineConfig::new(
+            1,
+            defaults.max_associated_keys() + 1,
+            defaults.max_runtime_call_stack_height(),
+            defaults.minimum_delegation_amount(),
+            defaults.strict_argument_checking(),
+            defaults.wasm_config().clone(),
+            *defaults.system_config(),
+        );
+        engine_state.update_config(new_config);
+        let config = engine_state.config_for(v2);
+        assert_eq!(config.wasm_config().host_function_costs(), &new_costs);
+        assert_eq!(
+            config.max_associated_keys(),
+            defaults.max_associated_keys() + 1
+        );
+    }
+}
@@ -10,7 +10,7 @@ use casper_types::{
 
 use crate::{
     core::{resolvers::error::ResolverError, runtime::stack},
-    shared::wasm_prep,
+    shared::{host_function_costs::CostError, wasm_prep},
     storage,
 };
 
@@ -169,6 +169,9 @@ pub enum Error {
     /// The runtime stack is `None`.
     #[error("Runtime stack missing")]
     MissingRuntimeStack,
+    /// The gas cost of a host function call could not be calculated.
+    #[error("Host function cost: {0}")]
+    HostFunctionCost(CostError),
 }
 
 impl From<wasm_prep::PreprocessingError> for Error {
@@ -1,5 +1,7 @@
 use std::{cell::RefCell, collections::BTreeSet, rc::Rc};
 
+use tracing::trace;
+
 use casper_types::{
     account::{Account, AccountHash},
     bytesrepr::FromBytes,
@@ -100,7 +102,7 @@ impl Executor {
             spending_limit,
         );
 
-        let mut runtime = Runtime::new(self.config, context);
+        let mut runtime = Runtime::new(self.config.clone(), context);
 
         let result = match execution_kind {
             ExecutionKind::Module(module_bytes) => {
@@ -117,17 +119,22 @@ impl Executor {
             }
         };
 
+        let host_function_gas = runtime.take_host_function_gas_report();
+        trace!(report = ?host_function_gas, "gas spent on host functions");
+
         match result {
             Ok(_) => ExecutionResult::Success {
                 execution_journal: runtime.context().execution_journal(),
                 transfers: runtime.context().transfers().to_owned(),
                 cost: runtime.context().gas_counter(),
+                host_function_gas,
             },
             Err(error) => ExecutionResult::Failure {
                 error: error.into(),
                 execution_journal: runtime.context().execution_journal(),
                 transfers: runtime.context().transfers().to_owned(),
                 cost: runtime.context().gas_counter(),
+                host_function_gas,
             },
         }
     }
@@ -190,19 +197,24 @@ impl Executor {
 
         // Standard payment is executed in the calling account's context; the stack already
         // captures that.
-        let mut runtime = Runtime::new(self.config, runtime_context);
+        let mut runtime = Runtime::new(self.config.clone(), runtime_context);
 
-        match runtime.call_host_standard_payment(stack) {
+        let result = runtime.call_host_standard_payment(stack);
+        let host_function_gas = runtime.take_host_function_gas_report();
+
+        match result {
             Ok(()) => ExecutionResult::Success {
                 execution_journal: runtime.context().execution_journal(),
                 transfers: runtime.context().transfers().to_owned(),
                 cost: runtime.context().gas_counter(),
+                host_function_gas,
             },
             Err(error) => ExecutionResult::Failure {
                 execution_journal,
                 error: error.into(),
                 transfers: runtime.context().transfers().to_owned(),
                 cost: runtime.context().gas_counter(),
+                host_function_gas,
             },
         }
     }
@@ -305,7 +317,7 @@ impl Executor {
             remaining_spending_limit,
         );
 
-        let mut runtime = Runtime::new(self.config, runtime_context);
+        let mut runtime = Runtime::new(self.config.clone(), runtime_context);
 
         // DO NOT alter this logic to call a system contract directly (such as via mint_internal,
         // etc). Doing so would bypass necessary context based security checks in some use cases. It
@@ -314,6 +326,7 @@ impl Executor {
         // execution path.
         let result =
             runtime.call_contract_with_stack(contract_hash, entry_point_name, runtime_args, stack);
+        let host_function_gas = runtime.take_host_function_gas_report();
 
         match result {
             Ok(value) => match value.into_t() {
@@ -321,6 +334,7 @@ impl Executor {
                     execution_journal: runtime.context().execution_journal(),
                     transfers: runtime.context().transfers().to_owned(),
                     cost: runtime.context().gas_counter(),
+                    host_function_gas,
                 }
                 .take_with_ret(ret),
                 Err(error) => ExecutionResult::Failure {
@@ -328,6 +342,7 @@ impl Executor {
                     error: Error::CLValue(error).into(),
                     transfers: runtime.context().transfers().to_owned(),
                     cost: runtime.context().gas_counter(),
+                    host_function_gas,
                 }
                 .take_without_ret(),
             },
@@ -336,6 +351,7 @@ impl Executor {
                 error: error.into(),
                 transfers: runtime.context().transfers().to_owned(),
                 cost: runtime.context().gas_counter(),
+                host_function_gas,
             }
             .take_without_ret(),
         }
@@ -386,7 +402,7 @@ impl Executor {
             protocol_version,
             correlation_id,
             phase,
-            self.config,
+            self.config.clone(),
             transfers,
             remaining_spending_limit,
         )
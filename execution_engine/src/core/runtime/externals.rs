@@ -5,7 +5,7 @@ use wasmi::{Externals, RuntimeArgs, RuntimeValue, Trap};
 use casper_types::{
     account::AccountHash,
     api_error,
-    bytesrepr::{self, ToBytes},
+    bytesrepr::{self, FromBytes, ToBytes},
     contracts::{ContractPackageStatus, EntryPoints, NamedKeys},
     crypto,
     system::auction::EraInfo,
@@ -32,8 +32,6 @@ where
     ) -> Result<Option<RuntimeValue>, Trap> {
         let func = FunctionIndex::try_from(index).expect("unknown function index");
 
-        let host_function_costs = self.config.wasm_config().take_host_function_costs();
-
         match func {
             FunctionIndex::ReadFuncIndex => {
                 // args(0) = pointer to key in Wasm memory
@@ -41,7 +39,8 @@ where
                 // args(2) = pointer to output size (output param)
                 let (key_ptr, key_size, output_size_ptr) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.read_value,
+                    "read_value",
+                    self.host_function_costs().read_value,
                     [key_ptr, key_size, output_size_ptr],
                 )?;
                 let ret = self.read(key_ptr, key_size, output_size_ptr)?;
@@ -53,7 +52,8 @@ where
                 // args(1) = pointer to amount of serialized bytes (output)
                 let (total_keys_ptr, result_size_ptr) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.load_named_keys,
+                    "load_named_keys",
+                    self.host_function_costs().load_named_keys,
                     [total_keys_ptr, result_size_ptr],
                 )?;
                 let ret = self.load_named_keys(total_keys_ptr, result_size_ptr)?;
@@ -67,7 +67,8 @@ where
                 // args(3) = size of value
                 let (key_ptr, key_size, value_ptr, value_size) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.write,
+                    "write",
+                    self.host_function_costs().write,
                     [key_ptr, key_size, value_ptr, value_size],
                 )?;
                 self.write(key_ptr, key_size, value_ptr, value_size)?;
@@ -81,7 +82,8 @@ where
                 // args(3) = size of value
                 let (key_ptr, key_size, value_ptr, value_size) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.add,
+                    "add",
+                    self.host_function_costs().add,
                     [key_ptr, key_size, value_ptr, value_size],
                 )?;
                 self.add(key_ptr, key_size, value_ptr, value_size)?;
@@ -94,7 +96,8 @@ where
                 // args(2) = size of initial value
                 let (uref_ptr, value_ptr, value_size) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.new_uref,
+                    "new_uref",
+                    self.host_function_costs().new_uref,
                     [uref_ptr, value_ptr, value_size],
                 )?;
                 self.new_uref(uref_ptr, value_ptr, value_size)?;
@@ -105,7 +108,11 @@ where
                 // args(0) = pointer to value
                 // args(1) = size of value
                 let (value_ptr, value_size) = Args::parse(args)?;
-                self.charge_host_function_call(&host_function_costs.ret, [value_ptr, value_size])?;
+                self.charge_host_function_call(
+                    "ret",
+                    self.host_function_costs().ret,
+                    [value_ptr, value_size],
+                )?;
                 Err(self.ret(value_ptr, value_size as usize))
             }
 
@@ -118,7 +125,8 @@ where
                 let (name_ptr, name_size, output_ptr, output_size, bytes_written) =
                     Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.get_key,
+                    "get_key",
+                    self.host_function_costs().get_key,
                     [name_ptr, name_size, output_ptr, output_size, bytes_written],
                 )?;
                 let ret = self.load_key(
@@ -136,7 +144,8 @@ where
                 // args(1) = size of key name
                 let (name_ptr, name_size) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.has_key,
+                    "has_key",
+                    self.host_function_costs().has_key,
                     [name_ptr, name_size],
                 )?;
                 let result = self.has_key(name_ptr, name_size)?;
@@ -150,7 +159,8 @@ where
                 // args(3) = size of key
                 let (name_ptr, name_size, key_ptr, key_size) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.put_key,
+                    "put_key",
+                    self.host_function_costs().put_key,
                     [name_ptr, name_size, key_ptr, key_size],
                 )?;
                 self.put_key(name_ptr, name_size, key_ptr, key_size)?;
@@ -162,7 +172,8 @@ where
                 // args(1) = size of key name
                 let (name_ptr, name_size) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.remove_key,
+                    "remove_key",
+                    self.host_function_costs().remove_key,
                     [name_ptr, name_size],
                 )?;
                 self.remove_key(name_ptr, name_size)?;
@@ -172,7 +183,11 @@ where
             FunctionIndex::GetCallerIndex => {
                 // args(0) = pointer where a size of serialized bytes will be stored
                 let (output_size,) = Args::parse(args)?;
-                self.charge_host_function_call(&host_function_costs.get_caller, [output_size])?;
+                self.charge_host_function_call(
+                    "get_caller",
+                    self.host_function_costs().get_caller,
+                    [output_size],
+                )?;
                 let ret = self.get_caller(output_size)?;
                 Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
             }
@@ -180,7 +195,11 @@ where
             FunctionIndex::GetBlocktimeIndex => {
                 // args(0) = pointer to Wasm memory where to write.
                 let (dest_ptr,) = Args::parse(args)?;
-                self.charge_host_function_call(&host_function_costs.get_blocktime, [dest_ptr])?;
+                self.charge_host_function_call(
+                    "get_blocktime",
+                    self.host_function_costs().get_blocktime,
+                    [dest_ptr],
+                )?;
                 self.get_blocktime(dest_ptr)?;
                 Ok(None)
             }
@@ -198,7 +217,8 @@ where
                 // args(1) = size of value
                 let (uref_ptr, uref_size) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.is_valid_uref,
+                    "is_valid_uref",
+                    self.host_function_costs().is_valid_uref,
                     [uref_ptr, uref_size],
                 )?;
                 Ok(Some(RuntimeValue::I32(i32::from(
@@ -209,7 +229,11 @@ where
             FunctionIndex::RevertFuncIndex => {
                 // args(0) = status u32
                 let (status,) = Args::parse(args)?;
-                self.charge_host_function_call(&host_function_costs.revert, [status])?;
+                self.charge_host_function_call(
+                    "revert",
+                    self.host_function_costs().revert,
+                    [status],
+                )?;
                 Err(self.revert(status))
             }
 
@@ -219,7 +243,8 @@ where
                 // args(2) = weight of the key
                 let (account_hash_ptr, account_hash_size, weight_value) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.add_associated_key,
+                    "add_associated_key",
+                    self.host_function_costs().add_associated_key,
                     [account_hash_ptr, account_hash_size, weight_value as Cost],
                 )?;
                 let value = self.add_associated_key(
@@ -235,7 +260,8 @@ where
                 // args(1) = size of an account hash
                 let (account_hash_ptr, account_hash_size) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.remove_associated_key,
+                    "remove_associated_key",
+                    self.host_function_costs().remove_associated_key,
                     [account_hash_ptr, account_hash_size],
                 )?;
                 let value =
@@ -249,7 +275,8 @@ where
                 // args(2) = weight of the key
                 let (account_hash_ptr, account_hash_size, weight_value) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.update_associated_key,
+                    "update_associated_key",
+                    self.host_function_costs().update_associated_key,
                     [account_hash_ptr, account_hash_size, weight_value as Cost],
                 )?;
                 let value = self.update_associated_key(
@@ -265,7 +292,8 @@ where
                 // args(1) = new threshold
                 let (action_type_value, threshold_value) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.set_action_threshold,
+                    "set_action_threshold",
+                    self.host_function_costs().set_action_threshold,
                     [action_type_value, threshold_value as Cost],
                 )?;
                 let value = self.set_action_threshold(action_type_value, threshold_value)?;
@@ -278,7 +306,8 @@ where
                 let (dest_ptr, dest_size) = Args::parse(args)?;
 
                 self.charge_host_function_call(
-                    &host_function_costs.create_purse,
+                    "create_purse",
+                    self.host_function_costs().create_purse,
                     [dest_ptr, dest_size],
                 )?;
 
@@ -307,7 +336,8 @@ where
                 let (key_ptr, key_size, amount_ptr, amount_size, id_ptr, id_size, result_ptr) =
                     Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.transfer_to_account,
+                    "transfer_to_account",
+                    self.host_function_costs().transfer_to_account,
                     [
                         key_ptr,
                         key_size,
@@ -367,7 +397,8 @@ where
                     result_ptr,
                 ) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.transfer_from_purse_to_account,
+                    "transfer_from_purse_to_account",
+                    self.host_function_costs().transfer_from_purse_to_account,
                     [
                         source_ptr,
                         source_size,
@@ -435,7 +466,8 @@ where
                     id_size,
                 ) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.transfer_from_purse_to_purse,
+                    "transfer_from_purse_to_purse",
+                    self.host_function_costs().transfer_from_purse_to_purse,
                     [
                         source_ptr,
                         source_size,
@@ -466,7 +498,8 @@ where
                 // args(2) = pointer to output size (output)
                 let (ptr, ptr_size, output_size_ptr) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.get_balance,
+                    "get_balance",
+                    self.host_function_costs().get_balance,
                     [ptr, ptr_size, output_size_ptr],
                 )?;
                 let ret = self.get_balance_host_buffer(ptr, ptr_size as usize, output_size_ptr)?;
@@ -476,7 +509,11 @@ where
             FunctionIndex::GetPhaseIndex => {
                 // args(0) = pointer to Wasm memory where to write.
                 let (dest_ptr,) = Args::parse(args)?;
-                self.charge_host_function_call(&host_function_costs.get_phase, [dest_ptr])?;
+                self.charge_host_function_call(
+                    "get_phase",
+                    self.host_function_costs().get_phase,
+                    [dest_ptr],
+                )?;
                 self.get_phase(dest_ptr)?;
                 Ok(None)
             }
@@ -487,7 +524,8 @@ where
                 // args(2) = dest pointer size
                 let (system_contract_index, dest_ptr, dest_size) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.get_system_contract,
+                    "get_system_contract",
+                    self.host_function_costs().get_system_contract,
                     [system_contract_index, dest_ptr, dest_size],
                 )?;
                 let ret = self.get_system_contract(system_contract_index, dest_ptr, dest_size)?;
@@ -497,7 +535,11 @@ where
             FunctionIndex::GetMainPurseIndex => {
                 // args(0) = pointer to Wasm memory where to write.
                 let (dest_ptr,) = Args::parse(args)?;
-                self.charge_host_function_call(&host_function_costs.get_main_purse, [dest_ptr])?;
+                self.charge_host_function_call(
+                    "get_main_purse",
+                    self.host_function_costs().get_main_purse,
+                    [dest_ptr],
+                )?;
                 self.get_main_purse(dest_ptr)?;
                 Ok(None)
             }
@@ -506,7 +548,8 @@ where
                 // args(0) = pointer to Wasm memory where to write size.
                 let (dest_ptr, dest_size, bytes_written_ptr) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.read_host_buffer,
+                    "read_host_buffer",
+                    self.host_function_costs().read_host_buffer,
                     [dest_ptr, dest_size, bytes_written_ptr],
                 )?;
                 let ret = self.read_host_buffer(dest_ptr, dest_size as usize, bytes_written_ptr)?;
@@ -519,7 +562,8 @@ where
                 // args(2) = boolean flag to determine if the contract can be versioned
                 let (hash_dest_ptr, access_dest_ptr, is_locked) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.create_contract_package_at_hash,
+                    "create_contract_package_at_hash",
+                    self.host_function_costs().create_contract_package_at_hash,
                     [hash_dest_ptr, access_dest_ptr],
                 )?;
                 let package_status = ContractPackageStatus::new(is_locked);
@@ -551,7 +595,8 @@ where
                     output_size_ptr,
                 ) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.create_contract_user_group,
+                    "create_contract_user_group",
+                    self.host_function_costs().create_contract_user_group,
                     [
                         package_key_ptr,
                         package_key_size,
@@ -603,7 +648,8 @@ where
                     bytes_written_ptr,
                 ) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.add_contract_version,
+                    "add_contract_version",
+                    self.host_function_costs().add_contract_version,
                     [
                         contract_package_hash_ptr,
                         contract_package_hash_size,
@@ -643,7 +689,8 @@ where
                 let (package_key_ptr, package_key_size, contract_hash_ptr, contract_hash_size) =
                     Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.disable_contract_version,
+                    "disable_contract_version",
+                    self.host_function_costs().disable_contract_version,
                     [
                         package_key_ptr,
                         package_key_size,
@@ -676,8 +723,11 @@ where
                     args_size,
                     result_size_ptr,
                 ) = Args::parse(args)?;
+                // The number of runtime args is only known after reading them from memory, so
+                // it is charged separately, after the rest of the call.
                 self.charge_host_function_call(
-                    &host_function_costs.call_contract,
+                    "call_contract",
+                    self.host_function_costs().call_contract,
                     [
                         contract_hash_ptr,
                         contract_hash_size,
@@ -686,17 +736,23 @@ where
                         args_ptr,
                         args_size,
                         result_size_ptr,
+                        0,
                     ],
                 )?;
+                let args_bytes: Vec<u8> = {
+                    let args_size: u32 = args_size;
+                    self.bytes_from_mem(args_ptr, args_size as usize)?
+                };
+                self.charge_host_function_weight(
+                    "call_contract",
+                    self.host_function_costs().call_contract.arguments()[7],
+                    runtime_args_count(&args_bytes),
+                )?;
 
                 let contract_hash: ContractHash =
                     self.t_from_mem(contract_hash_ptr, contract_hash_size)?;
                 let entry_point_name: String =
                     self.t_from_mem(entry_point_name_ptr, entry_point_name_size)?;
-                let args_bytes: Vec<u8> = {
-                    let args_size: u32 = args_size;
-                    self.bytes_from_mem(args_ptr, args_size as usize)?
-                };
 
                 let ret = self.call_contract_host_buffer(
                     contract_hash,
@@ -728,8 +784,11 @@ where
                     args_size,
                     result_size_ptr,
                 ) = Args::parse(args)?;
+                // The number of runtime args is only known after reading them from memory, so
+                // it is charged separately, after the rest of the call.
                 self.charge_host_function_call(
-                    &host_function_costs.call_versioned_contract,
+                    "call_versioned_contract",
+                    self.host_function_costs().call_versioned_contract,
                     [
                         contract_package_hash_ptr,
                         contract_package_hash_size,
@@ -740,8 +799,20 @@ where
                         args_ptr,
                         args_size,
                         result_size_ptr,
+                        0,
                     ],
                 )?;
+                let args_bytes: Vec<u8> = {
+                    let args_size: u32 = args_size;
+                    self.bytes_from_mem(args_ptr, args_size as usize)?
+                };
+                self.charge_host_function_weight(
+                    "call_versioned_contract",
+                    self.host_function_costs()
+                        .call_versioned_contract
+                        .arguments()[9],
+                    runtime_args_count(&args_bytes),
+                )?;
 
                 let contract_package_hash: ContractPackageHash =
                     self.t_from_mem(contract_package_hash_ptr, contract_package_hash_size)?;
@@ -749,10 +820,6 @@ where
                     self.t_from_mem(contract_version_ptr, contract_package_size)?;
                 let entry_point_name: String =
                     self.t_from_mem(entry_point_name_ptr, entry_point_name_size)?;
-                let args_bytes: Vec<u8> = {
-                    let args_size: u32 = args_size;
-                    self.bytes_from_mem(args_ptr, args_size as usize)?
-                };
 
                 let ret = self.call_versioned_contract_host_buffer(
                     contract_package_hash,
@@ -767,7 +834,11 @@ where
             #[cfg(feature = "test-support")]
             FunctionIndex::PrintIndex => {
                 let (text_ptr, text_size) = Args::parse(args)?;
-                self.charge_host_function_call(&host_function_costs.print, [text_ptr, text_size])?;
+                self.charge_host_function_call(
+                    "print",
+                    self.host_function_costs().print,
+                    [text_ptr, text_size],
+                )?;
                 self.print(text_ptr, text_size)?;
                 Ok(None)
             }
@@ -778,7 +849,8 @@ where
                 // args(2) = pointer to a argument size (output)
                 let (name_ptr, name_size, size_ptr) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.get_named_arg_size,
+                    "get_named_arg_size",
+                    self.host_function_costs().get_named_arg_size,
                     [name_ptr, name_size, size_ptr],
                 )?;
                 let ret = self.get_named_arg_size(name_ptr, name_size as usize, size_ptr)?;
@@ -792,7 +864,8 @@ where
                 // args(3) = size of available data under output pointer
                 let (name_ptr, name_size, dest_ptr, dest_size) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.get_named_arg,
+                    "get_named_arg",
+                    self.host_function_costs().get_named_arg,
                     [name_ptr, name_size, dest_ptr, dest_size],
                 )?;
                 let ret =
@@ -807,7 +880,8 @@ where
                 // args(3) = size of serialized group label
                 let (package_key_ptr, package_key_size, label_ptr, label_size) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.remove_contract_user_group,
+                    "remove_contract_user_group",
+                    self.host_function_costs().remove_contract_user_group,
                     [package_key_ptr, package_key_size, label_ptr, label_size],
                 )?;
                 let package_key = self.t_from_mem(package_key_ptr, package_key_size)?;
@@ -826,7 +900,9 @@ where
                 let (package_ptr, package_size, label_ptr, label_size, value_size_ptr) =
                     Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.provision_contract_user_group_uref,
+                    "provision_contract_user_group_uref",
+                    self.host_function_costs()
+                        .provision_contract_user_group_uref,
                     [
                         package_ptr,
                         package_size,
@@ -855,7 +931,8 @@ where
                 let (package_ptr, package_size, label_ptr, label_size, urefs_ptr, urefs_size) =
                     Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.remove_contract_user_group_urefs,
+                    "remove_contract_user_group_urefs",
+                    self.host_function_costs().remove_contract_user_group_urefs,
                     [
                         package_ptr,
                         package_size,
@@ -879,7 +956,8 @@ where
             FunctionIndex::Blake2b => {
                 let (in_ptr, in_size, out_ptr, out_size) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.blake2b,
+                    "blake2b",
+                    self.host_function_costs().blake2b,
                     [in_ptr, in_size, out_ptr, out_size],
                 )?;
                 let input: Vec<u8> = self.bytes_from_mem(in_ptr, in_size as usize)?;
@@ -941,7 +1019,8 @@ where
                 let (output_size_ptr,): (u32,) = Args::parse(args)?;
 
                 self.charge_host_function_call(
-                    &DEFAULT_HOST_FUNCTION_NEW_DICTIONARY,
+                    "new_dictionary",
+                    DEFAULT_HOST_FUNCTION_NEW_DICTIONARY,
                     [output_size_ptr],
                 )?;
                 let ret = self.new_dictionary(output_size_ptr)?;
@@ -962,7 +1041,8 @@ where
                     _,
                 ) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.dictionary_get,
+                    "dictionary_get",
+                    self.host_function_costs().dictionary_get,
                     [key_bytes_ptr, key_bytes_size, output_size_ptr],
                 )?;
                 let ret = self.dictionary_get(
@@ -984,7 +1064,8 @@ where
                 // args(5) = pointer to value bytes size in Wasm memory
                 let (uref_ptr, uref_size, key_bytes_ptr, key_bytes_size, value_ptr, value_ptr_size): (_, u32, _, u32, _, u32) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.dictionary_put,
+                    "dictionary_put",
+                    self.host_function_costs().dictionary_put,
                     [key_bytes_ptr, key_bytes_size, value_ptr, value_ptr_size],
                 )?;
                 let ret = self.dictionary_put(
@@ -1004,7 +1085,8 @@ where
                 let (call_stack_len_ptr, result_size_ptr) = Args::parse(args)?;
                 // TODO: add cost table entry once we can upgrade safely
                 self.charge_host_function_call(
-                    &HostFunction::fixed(10_000),
+                    "load_call_stack",
+                    HostFunction::fixed(10_000),
                     [call_stack_len_ptr, result_size_ptr],
                 )?;
                 let ret = self.load_call_stack(call_stack_len_ptr, result_size_ptr)?;
@@ -1016,7 +1098,8 @@ where
                 // args(1) (Output) Pointer to size in bytes of the total bytes.
                 let (len_ptr, result_size_ptr) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &HostFunction::fixed(10_000),
+                    "load_authorization_keys",
+                    HostFunction::fixed(10_000),
                     [len_ptr, result_size_ptr],
                 )?;
                 let ret = self.load_authorization_keys(len_ptr, result_size_ptr)?;
@@ -1026,7 +1109,8 @@ where
             FunctionIndex::RandomBytes => {
                 let (out_ptr, out_size) = Args::parse(args)?;
                 self.charge_host_function_call(
-                    &host_function_costs.random_bytes,
+                    "random_bytes",
+                    self.host_function_costs().random_bytes,
                     [out_ptr, out_size],
                 )?;
 
@@ -1050,3 +1134,10 @@ where
         }
     }
 }
+
+/// Returns the number of runtime args in their serialized form, as declared by its length prefix.
+///
+/// Malformed args are counted as none; the call rejects them when it deserializes them.
+fn runtime_args_count(args_bytes: &[u8]) -> Cost {
+    u32::from_bytes(args_bytes).map_or(0, |(count, _)| count)
+}
@@ -14,6 +14,7 @@ use std::{
     collections::{BTreeMap, BTreeSet},
     convert::{TryFrom, TryInto},
     iter::FromIterator,
+    mem,
 };
 
 use parity_wasm::elements::Module;
@@ -36,8 +37,9 @@ use casper_types::{
     },
     AccessRights, ApiError, CLTyped, CLValue, ContextAccessRights, ContractHash,
     ContractPackageHash, ContractVersionKey, ContractWasm, DeployHash, EntryPointType, EraId, Gas,
-    GrantedAccess, Key, NamedArg, Parameter, Phase, PublicKey, RuntimeArgs, StoredValue, Transfer,
-    TransferResult, TransferredTo, URef, DICTIONARY_ITEM_KEY_MAX_LENGTH, U512,
+    GrantedAccess, Key, NamedArg, Parameter, Phase, ProtocolVersion, PublicKey, RuntimeArgs,
+    StoredValue, Transfer, TransferResult, TransferredTo, URef, DICTIONARY_ITEM_KEY_MAX_LENGTH,
+    U512,
 };
 
 use crate::{
@@ -49,7 +51,8 @@ use crate::{
         tracking_copy::TrackingCopyExt,
     },
     shared::{
-        host_function_costs::{Cost, HostFunction},
+        host_function_costs::{Cost, HostFunction, HostFunctionCosts},
+        host_function_gas_report::HostFunctionGasReport,
         wasm_prep::{self, PreprocessingError},
     },
     storage::global_state::StateReader,
@@ -60,6 +63,15 @@ use crate::{
 };
 pub use stack::{RuntimeStack, RuntimeStackFrame, RuntimeStackOverflow};
 
+/// The size of a Wasm memory page in bytes.
+const WASM_PAGE_SIZE: u32 = 65_536;
+
+/// The first protocol version whose host function calls are checked before they are charged.
+///
+/// Executions with earlier protocol versions are charged without the checks, so that replaying
+/// their deploys yields the same results as when they were first executed.
+const CHECKED_HOST_FUNCTION_CALLS_VERSION: ProtocolVersion = ProtocolVersion::from_parts(1, 5, 0);
+
 /// Represents the runtime properties of a WASM execution.
 pub struct Runtime<'a, R> {
     config: EngineConfig,
@@ -69,6 +81,10 @@ pub struct Runtime<'a, R> {
     context: RuntimeContext<'a, R>,
     stack: Option<RuntimeStack>,
     host_function_flag: HostFunctionFlag,
+    host_function_gas: HostFunctionGasReport,
+    /// The gas spent on host functions by the callers before this runtime was invoked. It is only
+    /// used to enforce call limits across the whole execution.
+    caller_host_function_gas: HostFunctionGasReport,
 }
 
 impl<'a, R> Runtime<'a, R>
@@ -86,6 +102,8 @@ where
             context,
             stack: None,
             host_function_flag: HostFunctionFlag::default(),
+            host_function_gas: HostFunctionGasReport::default(),
+            caller_host_function_gas: HostFunctionGasReport::default(),
         }
     }
 
@@ -98,14 +116,20 @@ where
         stack: RuntimeStack,
     ) -> Self {
         Self::check_preconditions(&stack);
+        // The called contract starts its own report, which is merged into ours once it returns,
+        // but call limits apply to the whole execution.
+        let mut caller_host_function_gas = self.caller_host_function_gas.clone();
+        caller_host_function_gas.merge(self.host_function_gas.clone());
         Runtime {
-            config: self.config,
+            config: self.config.clone(),
             memory: Some(memory),
             module: Some(module),
             host_buffer: None,
             context,
             stack: Some(stack),
             host_function_flag: self.host_function_flag.clone(),
+            host_function_gas: HostFunctionGasReport::default(),
+            caller_host_function_gas,
         }
     }
 
@@ -117,13 +141,15 @@ where
     ) -> Self {
         Self::check_preconditions(&stack);
         Runtime {
-            config: self.config,
+            config: self.config.clone(),
             memory: None,
             module: None,
             host_buffer: None,
             context,
             stack: Some(stack),
             host_function_flag: self.host_function_flag.clone(),
+            host_function_gas: HostFunctionGasReport::default(),
+            caller_host_function_gas: HostFunctionGasReport::default(),
         }
     }
 
@@ -146,6 +172,12 @@ where
         &self.context
     }
 
+    /// Takes the gas spent on each host function so far, including the calls made by called
+    /// contracts, leaving an empty report.
+    pub(crate) fn take_host_function_gas_report(&mut self) -> HostFunctionGasReport {
+        mem::take(&mut self.host_function_gas)
+    }
+
     fn gas(&mut self, amount: Gas) -> Result<(), Error> {
         self.context.charge_gas(amount)
     }
@@ -1017,7 +1049,7 @@ where
     ) -> Result<CLValue, Error> {
         let protocol_version = self.context.protocol_version();
         let wasm_config = self.config.wasm_config();
-        let module = wasm_prep::preprocess(*wasm_config, module_bytes)?;
+        let module = wasm_prep::preprocess(wasm_config, module_bytes)?;
         let (instance, memory) =
             utils::instance_and_memory(module.clone(), protocol_version, wasm_config)?;
         self.memory = Some(memory);
@@ -1366,6 +1398,8 @@ where
         // charged by the sub-call was added to its counter - so let's copy the correct value of the
         // counter from there to our counter.
         self.context.set_gas_counter(runtime.context.gas_counter());
+        self.host_function_gas
+            .merge(mem::take(&mut runtime.host_function_gas));
 
         {
             let transfers = self.context.transfers_mut();
@@ -2696,17 +2730,60 @@ where
         Ok(Ok(()))
     }
 
-    /// Calculate gas cost for a host function
+    /// Returns the costs of the host functions in the current configuration.
+    fn host_function_costs(&self) -> &HostFunctionCosts {
+        self.config.wasm_config().host_function_costs()
+    }
+
+    /// Calculate gas cost for a host function, and record it under the host function's `name`.
+    ///
+    /// From `CHECKED_HOST_FUNCTION_CALLS_VERSION` on, argument sizes are rejected if they exceed
+    /// the maximum memory of a contract, since no such argument can exist. The call is rejected if
+    /// the host function's call limit for this execution is reached.
     fn charge_host_function_call<T>(
         &mut self,
-        host_function: &HostFunction<T>,
+        name: &'static str,
+        host_function: HostFunction<T>,
         weights: T,
     ) -> Result<(), Trap>
     where
         T: AsRef<[Cost]> + Copy,
     {
-        let cost = host_function.calculate_gas_cost(weights);
+        let cost = if self.context.protocol_version() >= CHECKED_HOST_FUNCTION_CALLS_VERSION {
+            let calls = [&self.caller_host_function_gas, &self.host_function_gas]
+                .iter()
+                .filter_map(|report| report.get(name))
+                .fold(0u64, |calls, entry| calls.saturating_add(entry.calls));
+            host_function
+                .check_call_limit(calls)
+                .map_err(Error::HostFunctionCost)?;
+            let max_argument_size = self
+                .config
+                .wasm_config()
+                .max_memory
+                .saturating_mul(WASM_PAGE_SIZE);
+            host_function
+                .checked_gas_cost(weights, max_argument_size)
+                .map_err(Error::HostFunctionCost)?
+        } else {
+            host_function.calculate_gas_cost(weights)
+        };
+        self.gas(cost)?;
+        self.host_function_gas.record(name, cost);
+        Ok(())
+    }
+
+    /// Charges the weight of a host function argument whose value is only known after the call
+    /// itself was charged, e.g. because it has to be read from the Wasm memory first.
+    fn charge_host_function_weight(
+        &mut self,
+        name: &'static str,
+        weight: Cost,
+        value: Cost,
+    ) -> Result<(), Trap> {
+        let cost = Gas::new(U512::from(weight) * U512::from(value));
         self.gas(cost)?;
+        self.host_function_gas.record_additional_gas(name, cost);
         Ok(())
     }
 
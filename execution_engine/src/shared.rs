@@ -2,6 +2,7 @@
 pub mod additive_map;
 pub mod execution_journal;
 pub mod host_function_costs;
+pub mod host_function_gas_report;
 pub mod logging;
 pub mod newtypes;
 pub mod opcode_costs;
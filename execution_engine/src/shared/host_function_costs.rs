@@ -1,82 +1,141 @@
 //! Support for host function gas cost tables.
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+};
+
 use datasize::DataSize;
 use rand::{distributions::Standard, prelude::Distribution, Rng};
-use serde::{Deserialize, Serialize};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+use thiserror::Error;
 
+use casper_hashing::Digest;
 use casper_types::{
-    bytesrepr::{self, FromBytes, ToBytes, U32_SERIALIZED_LENGTH},
-    Gas,
+    bytesrepr::{
+        self, FromBytes, ToBytes, U32_SERIALIZED_LENGTH, U64_SERIALIZED_LENGTH,
+        U8_SERIALIZED_LENGTH,
+    },
+    Gas, U512,
 };
 
 /// Representation of argument's cost.
+///
+/// This is also the type of the argument sizes the runtime passes in, which are Wasm `u32`
+/// values, so it stays a `u32` even though the fixed cost of a host function is a `u64`.
 pub type Cost = u32;
 
 const COST_SERIALIZED_LENGTH: usize = U32_SERIALIZED_LENGTH;
 
+/// The legacy serialized layout of a `HostFunction`, without a version byte: the fixed cost
+/// followed by the argument weights.
+const HOST_FUNCTION_VERSION_1: u8 = 1;
+/// The version of the serialized layout of a `HostFunction` with a call limit: like version 1,
+/// followed by the call limit.
+const HOST_FUNCTION_VERSION_2: u8 = 2;
+/// The version of the serialized layout of a `HostFunction` with a fixed cost that doesn't fit in
+/// a `u32`: the fixed cost as a `u64`, followed by the argument weights and the optional call
+/// limit.
+const HOST_FUNCTION_VERSION_3: u8 = 3;
+/// The fixed cost in the legacy layout that instead marks a newer layout, in which it is followed
+/// by the version byte.
+const HOST_FUNCTION_VERSION_MARKER: u32 = u32::max_value();
+/// The byte following `HOST_FUNCTION_VERSION_MARKER` at the start of a serialized cost table with
+/// extra host functions. It is never a host function version, so it can't be mistaken for the
+/// first fixed host function.
+const EXTRA_HOST_FUNCTIONS_TAG: u8 = u8::max_value();
+
 /// An identifier that represents an unused argument.
 const NOT_USED: Cost = 0;
 
 /// An arbitrary default fixed cost for host functions that were not researched yet.
-const DEFAULT_FIXED_COST: Cost = 200;
+const DEFAULT_FIXED_COST: u64 = 200;
 
-const DEFAULT_ADD_ASSOCIATED_KEY_COST: u32 = 9_000;
-const DEFAULT_ADD_COST: u32 = 5_800;
+const DEFAULT_ADD_ASSOCIATED_KEY_COST: u64 = 9_000;
+const DEFAULT_ADD_COST: u64 = 5_800;
 
-const DEFAULT_CALL_CONTRACT_COST: u32 = 4_500;
+const DEFAULT_BLAKE2B_INPUT_SIZE_WEIGHT: u32 = 420;
+
+const DEFAULT_CALL_CONTRACT_COST: u64 = 4_500;
 const DEFAULT_CALL_CONTRACT_ARGS_SIZE_WEIGHT: u32 = 420;
+const DEFAULT_CALL_CONTRACT_ARGS_COUNT_WEIGHT: u32 = NOT_USED;
 
-const DEFAULT_CREATE_PURSE_COST: u32 = 2_500_000_000;
-const DEFAULT_GET_BALANCE_COST: u32 = 3_800;
-const DEFAULT_GET_BLOCKTIME_COST: u32 = 330;
-const DEFAULT_GET_CALLER_COST: u32 = 380;
-const DEFAULT_GET_KEY_COST: u32 = 2_000;
+const DEFAULT_CREATE_PURSE_COST: u64 = 2_500_000_000;
+const DEFAULT_GET_BALANCE_COST: u64 = 3_800;
+const DEFAULT_GET_BLOCKTIME_COST: u64 = 330;
+const DEFAULT_GET_CALLER_COST: u64 = 380;
+const DEFAULT_GET_KEY_COST: u64 = 2_000;
 const DEFAULT_GET_KEY_NAME_SIZE_WEIGHT: u32 = 440;
-const DEFAULT_GET_MAIN_PURSE_COST: u32 = 1_300;
-const DEFAULT_GET_PHASE_COST: u32 = 710;
-const DEFAULT_GET_SYSTEM_CONTRACT_COST: u32 = 1_100;
-const DEFAULT_HAS_KEY_COST: u32 = 1_500;
+const DEFAULT_GET_MAIN_PURSE_COST: u64 = 1_300;
+const DEFAULT_GET_NAMED_ARG_COST: u64 = 200;
+const DEFAULT_GET_NAMED_ARG_DEST_SIZE_WEIGHT: u32 = 310;
+const DEFAULT_GET_PHASE_COST: u64 = 710;
+const DEFAULT_GET_SYSTEM_CONTRACT_COST: u64 = 1_100;
+const DEFAULT_HAS_KEY_COST: u64 = 1_500;
 const DEFAULT_HAS_KEY_NAME_SIZE_WEIGHT: u32 = 840;
-const DEFAULT_IS_VALID_UREF_COST: u32 = 760;
-const DEFAULT_LOAD_NAMED_KEYS_COST: u32 = 42_000;
-const DEFAULT_NEW_UREF_COST: u32 = 17_000;
+const DEFAULT_IS_VALID_UREF_COST: u64 = 760;
+const DEFAULT_LOAD_NAMED_KEYS_COST: u64 = 42_000;
+const DEFAULT_NEW_UREF_COST: u64 = 17_000;
 const DEFAULT_NEW_UREF_VALUE_SIZE_WEIGHT: u32 = 590;
 
-const DEFAULT_PRINT_COST: u32 = 20_000;
+const DEFAULT_PRINT_COST: u64 = 20_000;
 const DEFAULT_PRINT_TEXT_SIZE_WEIGHT: u32 = 4_600;
 
-const DEFAULT_PUT_KEY_COST: u32 = 38_000;
+const DEFAULT_PUT_KEY_COST: u64 = 38_000;
 const DEFAULT_PUT_KEY_NAME_SIZE_WEIGHT: u32 = 1_100;
 
-const DEFAULT_READ_HOST_BUFFER_COST: u32 = 3_500;
+const DEFAULT_READ_HOST_BUFFER_COST: u64 = 3_500;
 const DEFAULT_READ_HOST_BUFFER_DEST_SIZE_WEIGHT: u32 = 310;
 
-const DEFAULT_READ_VALUE_COST: u32 = 6_000;
-const DEFAULT_DICTIONARY_GET_COST: u32 = 5_500;
+const DEFAULT_READ_VALUE_COST: u64 = 6_000;
+const DEFAULT_DICTIONARY_GET_COST: u64 = 5_500;
 const DEFAULT_DICTIONARY_GET_KEY_SIZE_WEIGHT: u32 = 590;
 
-const DEFAULT_REMOVE_ASSOCIATED_KEY_COST: u32 = 4_200;
+const DEFAULT_REMOVE_ASSOCIATED_KEY_COST: u64 = 4_200;
 
-const DEFAULT_REMOVE_KEY_COST: u32 = 61_000;
+const DEFAULT_REMOVE_KEY_COST: u64 = 61_000;
 const DEFAULT_REMOVE_KEY_NAME_SIZE_WEIGHT: u32 = 3_200;
 
-const DEFAULT_RET_COST: u32 = 23_000;
+const DEFAULT_RET_COST: u64 = 23_000;
 const DEFAULT_RET_VALUE_SIZE_WEIGHT: u32 = 420;
 
-const DEFAULT_REVERT_COST: u32 = 500;
-const DEFAULT_SET_ACTION_THRESHOLD_COST: u32 = 74_000;
-const DEFAULT_TRANSFER_FROM_PURSE_TO_ACCOUNT_COST: u32 = 2_500_000_000;
-const DEFAULT_TRANSFER_FROM_PURSE_TO_PURSE_COST: u32 = 82_000;
-const DEFAULT_TRANSFER_TO_ACCOUNT_COST: u32 = 2_500_000_000;
-const DEFAULT_UPDATE_ASSOCIATED_KEY_COST: u32 = 4_200;
+const DEFAULT_REVERT_COST: u64 = 500;
+const DEFAULT_SET_ACTION_THRESHOLD_COST: u64 = 74_000;
+const DEFAULT_TRANSFER_FROM_PURSE_TO_ACCOUNT_COST: u64 = 2_500_000_000;
+const DEFAULT_TRANSFER_FROM_PURSE_TO_PURSE_COST: u64 = 82_000;
+const DEFAULT_TRANSFER_TO_ACCOUNT_COST: u64 = 2_500_000_000;
+const DEFAULT_TRANSFER_COST: u64 = 82_000;
+const DEFAULT_UPDATE_ASSOCIATED_KEY_COST: u64 = 4_200;
 
-const DEFAULT_WRITE_COST: u32 = 14_000;
+const DEFAULT_WRITE_COST: u64 = 14_000;
 const DEFAULT_WRITE_VALUE_SIZE_WEIGHT: u32 = 980;
 
-const DEFAULT_DICTIONARY_PUT_COST: u32 = 9_500;
+const DEFAULT_DICTIONARY_PUT_COST: u64 = 9_500;
 const DEFAULT_DICTIONARY_PUT_KEY_BYTES_SIZE_WEIGHT: u32 = 1_800;
 const DEFAULT_DICTIONARY_PUT_VALUE_SIZE_WEIGHT: u32 = 520;
 
-const DEFAULT_NEW_DICTIONARY_COST: u32 = DEFAULT_NEW_UREF_COST;
+const DEFAULT_NEW_DICTIONARY_COST: u64 = DEFAULT_NEW_UREF_COST;
+
+/// The host functions whose cost must grow with the size of their input, each with the index of
+/// an argument weight that is charged per byte.
+///
+/// `get_named_arg` and `blake2b` are size-dependent too, but mainnet has always charged them a
+/// fixed cost, so they are not listed.
+pub const SIZE_DEPENDENT_HOST_FUNCTIONS: &[(&str, usize)] = &[
+    ("dictionary_get", 1),
+    ("write", 3),
+    ("dictionary_put", 1),
+    ("dictionary_put", 3),
+    ("new_uref", 2),
+    ("ret", 1),
+    ("get_key", 1),
+    ("has_key", 1),
+    ("put_key", 1),
+    ("remove_key", 1),
+    ("read_host_buffer", 1),
+    ("call_contract", 5),
+    ("call_versioned_contract", 7),
+    ("print", 1),
+];
 
 pub(crate) const DEFAULT_HOST_FUNCTION_NEW_DICTIONARY: HostFunction<[Cost; 1]> =
     HostFunction::new(DEFAULT_NEW_DICTIONARY_COST, [NOT_USED]);
@@ -85,12 +144,16 @@ pub(crate) const DEFAULT_HOST_FUNCTION_NEW_DICTIONARY: HostFunction<[Cost; 1]> =
 ///
 /// The total gas cost is equal to `cost` + sum of each argument weight multiplied by the byte size
 /// of the data.
-#[derive(Copy, Clone, PartialEq, Eq, Deserialize, Serialize, Debug, DataSize)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Deserialize, Serialize, Debug, DataSize)]
 pub struct HostFunction<T> {
     /// How much the user is charged for calling the host function.
-    cost: Cost,
+    cost: u64,
     /// Weights of the function arguments.
     arguments: T,
+    /// The maximum number of times the host function can be called in a single execution, or
+    /// `None` if that is unlimited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    call_limit: Option<u32>,
 }
 
 impl<T> Default for HostFunction<T>
@@ -104,14 +167,41 @@ where
 
 impl<T> HostFunction<T> {
     /// Creates a new instance of `HostFunction` with a fixed call cost and argument weights.
-    pub const fn new(cost: Cost, arguments: T) -> Self {
-        Self { cost, arguments }
+    pub const fn new(cost: u64, arguments: T) -> Self {
+        Self {
+            cost,
+            arguments,
+            call_limit: None,
+        }
+    }
+
+    /// Returns this host function cost with the given maximum number of calls per execution.
+    pub fn with_call_limit(mut self, call_limit: u32) -> Self {
+        self.call_limit = Some(call_limit);
+        self
     }
 
     /// Returns the base gas fee for calling the host function.
-    pub fn cost(&self) -> Cost {
+    pub fn cost(&self) -> u64 {
         self.cost
     }
+
+    /// Returns the maximum number of calls per execution, or `None` if that is unlimited.
+    pub fn call_limit(&self) -> Option<u32> {
+        self.call_limit
+    }
+
+    /// Returns an error if the host function was already called `calls` times in the current
+    /// execution and must not be called again.
+    ///
+    /// This is independent of gas, and bounds the work done by a single execution even if the
+    /// host function's cost is miscalibrated.
+    pub fn check_call_limit(&self, calls: u64) -> Result<(), CostError> {
+        match self.call_limit {
+            Some(limit) if calls >= u64::from(limit) => Err(CostError::CallLimitExceeded { limit }),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl<T> HostFunction<T>
@@ -119,7 +209,7 @@ where
     T: Default,
 {
     /// Creates a new fixed host function cost with argument weights of zero.
-    pub fn fixed(cost: Cost) -> Self {
+    pub fn fixed(cost: u64) -> Self {
         Self {
             cost,
             ..Default::default()
@@ -136,6 +226,80 @@ where
         self.arguments.as_ref()
     }
 
+    /// Returns the host function cost as an inline TOML table.
+    fn to_toml_value(&self) -> String {
+        let arguments: Vec<String> = self.arguments().iter().map(Cost::to_string).collect();
+        match self.call_limit {
+            None => format!(
+                "{{ cost = {}, arguments = [{}] }}",
+                self.cost,
+                arguments.join(", ")
+            ),
+            Some(call_limit) => format!(
+                "{{ cost = {}, arguments = [{}], call_limit = {} }}",
+                self.cost,
+                arguments.join(", "),
+                call_limit
+            ),
+        }
+    }
+
+    /// Returns the fixed cost plus the sum of all argument weights.
+    ///
+    /// This is only a heuristic for ranking host functions by cost: the actual gas charged for a
+    /// call depends on the sizes of its arguments.
+    pub fn nominal_cost(&self) -> u64 {
+        self.arguments().iter().fold(self.cost, |sum, weight| {
+            sum.saturating_add(u64::from(*weight))
+        })
+    }
+
+    /// Returns `true` if calling the host function costs nothing, whatever the arguments.
+    pub fn is_free(&self) -> bool {
+        self.cost == 0 && self.arguments().iter().all(|weight| *weight == 0)
+    }
+
+    /// Returns `true` if both host function costs charge the same for every call, even if they are
+    /// represented differently.
+    ///
+    /// Trailing argument weights of zero are treated as absent, since they don't contribute to the
+    /// gas cost. Unlike `==`, this can compare costs with different argument types, e.g. a fixed
+    /// array with a `Vec`.
+    pub fn semantically_equal<U>(&self, other: &HostFunction<U>) -> bool
+    where
+        U: AsRef<[Cost]>,
+    {
+        fn significant_weights(weights: &[Cost]) -> &[Cost] {
+            let len = weights
+                .iter()
+                .rposition(|weight| *weight != 0)
+                .map_or(0, |index| index + 1);
+            &weights[..len]
+        }
+        self.cost == other.cost
+            && self.call_limit == other.call_limit
+            && significant_weights(self.arguments()) == significant_weights(other.arguments())
+    }
+
+    /// Returns the cost formula in a human-readable form, e.g. `100 + 2*len(key) + 5*len(value)`.
+    ///
+    /// The arguments are named by `arg_names`, in order. Arguments without a name are called
+    /// `arg0`, `arg1` etc. by their index. Arguments with a weight of zero are omitted.
+    pub fn explain(&self, arg_names: &[&str]) -> String {
+        let mut terms = vec![self.cost.to_string()];
+        for (index, weight) in self.arguments().iter().enumerate() {
+            if *weight == 0 {
+                continue;
+            }
+            let term = match arg_names.get(index) {
+                Some(name) => format!("{}*len({})", weight, name),
+                None => format!("{}*len(arg{})", weight, index),
+            };
+            terms.push(term);
+        }
+        terms.join(" + ")
+    }
+
     /// Calculate gas cost for a host function
     pub fn calculate_gas_cost(&self, weights: T) -> Gas {
         let mut gas = Gas::new(self.cost.into());
@@ -146,6 +310,308 @@ where
         }
         gas
     }
+
+    /// Calculate gas cost for a host function, rejecting arguments larger than
+    /// `max_argument_size`.
+    ///
+    /// Only arguments with a non-zero weight are sizes and are checked; the others are ignored.
+    /// Since both weights and checked sizes fit in a `u32`, each product fits in a `u64`, so the
+    /// only possible overflow is in the sum, which is reported as an error as well.
+    pub fn checked_gas_cost(&self, weights: T, max_argument_size: Cost) -> Result<Gas, CostError> {
+        let sizes = weights.as_ref().iter();
+        let gas =
+            self.arguments()
+                .iter()
+                .zip(sizes)
+                .try_fold(self.cost, |gas, (weight, size)| {
+                    if *weight == 0 {
+                        return Ok(gas);
+                    }
+                    if *size > max_argument_size {
+                        return Err(CostError::ArgumentTooLarge {
+                            size: *size,
+                            max_size: max_argument_size,
+                        });
+                    }
+                    let argument_gas = u64::from(*weight) * u64::from(*size);
+                    gas.checked_add(argument_gas).ok_or(CostError::Overflow)
+                })?;
+        Ok(Gas::from(gas))
+    }
+
+    /// Calculate gas cost for a host function, rounded up to the next multiple of `granularity`.
+    ///
+    /// A granularity of 0 or 1 leaves the cost unchanged.
+    pub fn calculate_gas_cost_with_granularity(&self, weights: T, granularity: u64) -> Gas {
+        let gas = self.calculate_gas_cost(weights);
+        let granularity = U512::from(granularity);
+        if granularity <= U512::one() {
+            return gas;
+        }
+        let remainder = gas.value() % granularity;
+        if remainder.is_zero() {
+            gas
+        } else {
+            Gas::new(gas.value() + (granularity - remainder))
+        }
+    }
+
+    /// Returns the upper bound of the gas cost of a call, if each argument has at most the given
+    /// size in bytes.
+    ///
+    /// Unlike `calculate_gas_cost`, this uses checked `u64` arithmetic, so that a gas ceiling
+    /// estimate for a deploy can be checked before execution.
+    pub fn max_gas(&self, max_arg_sizes: &[usize]) -> Result<u64, CostError> {
+        let arguments = self.arguments();
+        if arguments.len() != max_arg_sizes.len() {
+            return Err(CostError::InvalidArgumentCount {
+                expected: arguments.len(),
+                actual: max_arg_sizes.len(),
+            });
+        }
+        arguments
+            .iter()
+            .zip(max_arg_sizes)
+            .try_fold(self.cost, |gas, (weight, max_size)| {
+                let max_size = u64::try_from(*max_size).map_err(|_| CostError::Overflow)?;
+                u64::from(*weight)
+                    .checked_mul(max_size)
+                    .and_then(|argument_gas| gas.checked_add(argument_gas))
+                    .ok_or(CostError::Overflow)
+            })
+    }
+
+    /// Returns the gas cost of a call with arguments of the given sizes in bytes, saturating at
+    /// `u64::MAX` instead of overflowing.
+    ///
+    /// Use `max_gas` where an overflow should be reported, e.g. when estimating the cost of a
+    /// deploy up front. Use this where the result is charged against a gas limit: an overflowing
+    /// cost then deterministically exhausts the available gas instead of aborting the execution
+    /// with an error.
+    ///
+    /// Missing sizes count as zero, like arguments that are omitted. Sizes beyond the host
+    /// function's arity are ignored, so use `check_arity` first where they must be rejected.
+    pub fn calculate_gas_saturating(&self, arg_sizes: &[usize]) -> u64 {
+        self.arguments()
+            .iter()
+            .zip(arg_sizes)
+            .fold(self.cost, |gas, (weight, size)| {
+                let size = u64::try_from(*size).unwrap_or(u64::MAX);
+                gas.saturating_add(u64::from(*weight).saturating_mul(size))
+            })
+    }
+
+    /// Returns an error if there are more argument sizes than the host function takes arguments.
+    ///
+    /// The host function takes as many arguments as it has weights, so with more sizes than that,
+    /// the call being metered can't be this host function's. Fewer sizes are fine.
+    pub fn check_arity(&self, arg_sizes: &[usize]) -> Result<(), CostError> {
+        let max = self.arguments().len();
+        if arg_sizes.len() > max {
+            return Err(CostError::TooManyArguments {
+                max,
+                actual: arg_sizes.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<T> HostFunction<T>
+where
+    T: AsRef<[Cost]>,
+{
+    /// Returns a copy of this host function cost with the argument weights stored in a `Vec`.
+    fn to_dynamic(&self) -> HostFunction<Vec<Cost>> {
+        HostFunction {
+            cost: self.cost,
+            arguments: self.arguments.as_ref().to_vec(),
+            call_limit: self.call_limit,
+        }
+    }
+}
+
+impl HostFunction<Vec<Cost>> {
+    /// Converts the host function cost `name` into one with a fixed number of argument weights.
+    fn try_into_fixed<T>(
+        self,
+        name: &'static str,
+    ) -> Result<HostFunction<T>, HostFunctionCostsError>
+    where
+        T: Default + AsMut<[Cost]>,
+    {
+        let mut arguments = T::default();
+        let expected = arguments.as_mut().len();
+        if self.arguments.len() != expected {
+            return Err(HostFunctionCostsError::InvalidArgumentCount {
+                name,
+                expected,
+                actual: self.arguments.len(),
+            });
+        }
+        arguments.as_mut().copy_from_slice(&self.arguments);
+        Ok(HostFunction {
+            cost: self.cost,
+            arguments,
+            call_limit: self.call_limit,
+        })
+    }
+}
+
+/// Deserializes a host function cost whose last argument weight was added later, so that
+/// chainspecs written before can still be read: if that weight is missing, it defaults to zero.
+fn deserialize_with_added_last_weight<'de, D, T>(
+    deserializer: D,
+) -> Result<HostFunction<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + AsMut<[Cost]>,
+{
+    let host_function = HostFunction::<Vec<Cost>>::deserialize(deserializer)?;
+    let mut arguments = T::default();
+    let expected = arguments.as_mut().len();
+    let actual = host_function.arguments.len();
+    if actual != expected && actual + 1 != expected {
+        let expected = format!("{} or {} argument weights", expected - 1, expected);
+        return Err(D::Error::invalid_length(actual, &expected.as_str()));
+    }
+    arguments.as_mut()[..actual].copy_from_slice(&host_function.arguments);
+    Ok(HostFunction {
+        cost: host_function.cost,
+        arguments,
+        call_limit: host_function.call_limit,
+    })
+}
+
+/// Returns the cost of the `enable_contract_version` host function in chainspecs written before it
+/// was added.
+fn default_enable_contract_version() -> HostFunction<[Cost; 4]> {
+    HostFunction::fixed(DEFAULT_FIXED_COST)
+}
+
+/// Returns the cost of the `transfer` host function in chainspecs written before it was added.
+fn default_transfer() -> HostFunction<[Cost; 4]> {
+    HostFunction::fixed(DEFAULT_TRANSFER_COST)
+}
+
+/// An error returned when constructing `HostFunctionCosts` from a map of host function costs.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum HostFunctionCostsError {
+    /// The map contains a key which is not the name of a host function.
+    #[error("unknown host function: {0}")]
+    UnknownKey(String),
+    /// The map is missing the cost of a host function.
+    #[error("missing cost of host function: {0}")]
+    MissingKey(&'static str),
+    /// The cost of a host function has the wrong number of argument weights.
+    #[error("host function {name} expects {expected} argument weights, got {actual}")]
+    InvalidArgumentCount {
+        /// The name of the host function.
+        name: &'static str,
+        /// The number of argument weights of the host function.
+        expected: usize,
+        /// The number of argument weights provided.
+        actual: usize,
+    },
+    /// Some host functions cost less than the gas floor.
+    #[error("host functions cost less than the gas floor of {min_cost}: {names:?}")]
+    BelowGasFloor {
+        /// The minimum cost of a host function.
+        min_cost: u64,
+        /// The names of the host functions that cost less.
+        names: Vec<&'static str>,
+    },
+    /// Some size-dependent host functions don't charge anything per byte of input.
+    #[error("size-dependent host functions have a zero per-byte weight: {names:?}")]
+    ZeroPerByteWeight {
+        /// The names of the host functions.
+        names: Vec<&'static str>,
+    },
+}
+
+/// An error returned when computing the gas cost of a host function.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum CostError {
+    /// The number of argument sizes doesn't match the number of argument weights.
+    #[error("host function expects {expected} arguments, got {actual}")]
+    InvalidArgumentCount {
+        /// The number of argument weights of the host function.
+        expected: usize,
+        /// The number of argument sizes provided.
+        actual: usize,
+    },
+    /// There are more argument sizes than the host function takes arguments.
+    #[error("host function takes at most {max} arguments, got {actual}")]
+    TooManyArguments {
+        /// The number of arguments of the host function.
+        max: usize,
+        /// The number of argument sizes provided.
+        actual: usize,
+    },
+    /// The gas cost doesn't fit in a `u64`.
+    #[error("gas cost overflow")]
+    Overflow,
+    /// An argument size exceeds the maximum.
+    #[error("argument size {size} exceeds the maximum of {max_size}")]
+    ArgumentTooLarge {
+        /// The size of the argument in bytes.
+        size: Cost,
+        /// The maximum size of an argument in bytes.
+        max_size: Cost,
+    },
+    /// The host function was called as often as its call limit allows.
+    #[error("host function call limit of {limit} exceeded")]
+    CallLimitExceeded {
+        /// The maximum number of calls per execution.
+        limit: u32,
+    },
+    /// There is no host function with the given name.
+    #[error("unknown host function {name}")]
+    UnknownFunction {
+        /// The name of the host function.
+        name: String,
+    },
+}
+
+/// A host function call observed during a real or simulated execution.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostCallRecord {
+    /// The name of the host function.
+    pub name: String,
+    /// The sizes of the arguments in bytes, as passed to the host function.
+    pub arg_sizes: Vec<usize>,
+}
+
+impl HostCallRecord {
+    /// Creates a record of a call of the host function `name` with the given argument sizes.
+    pub fn new(name: impl Into<String>, arg_sizes: Vec<usize>) -> Self {
+        HostCallRecord {
+            name: name.into(),
+            arg_sizes,
+        }
+    }
+}
+
+/// What to do with host functions that cost less than the gas floor.
+#[derive(Copy, Clone, PartialEq, Eq, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum GasFloorPolicy {
+    /// Reject the whole cost table.
+    Reject,
+    /// Raise the fixed cost of the host functions to the gas floor.
+    Clamp,
+}
+
+/// A minimum cost of every host function call, so that no host function can be made free by
+/// accident.
+#[derive(Copy, Clone, PartialEq, Eq, Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct GasFloor {
+    /// The minimum fixed cost of a host function, i.e. the cost of a call whose arguments all
+    /// have size zero.
+    pub min_cost: u64,
+    /// What to do with host functions that cost less.
+    pub policy: GasFloorPolicy,
 }
 
 impl<T> Distribution<HostFunction<T>> for Standard
@@ -154,27 +620,81 @@ where
     T: AsRef<[Cost]>,
 {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> HostFunction<T> {
-        let cost = rng.gen::<Cost>();
+        // TOML integers are signed, so costs are limited to their range.
+        let cost = rng.gen_range(0..=i64::max_value() as u64);
         let arguments = rng.gen();
         HostFunction::new(cost, arguments)
     }
 }
 
+/// Version 1 is the legacy layout of a serialized `HostFunction`, without any prefix: the fixed
+/// cost as a `u32`, followed by each argument weight, in order. Newer versions start with
+/// `HOST_FUNCTION_VERSION_MARKER` in place of that cost, followed by a version byte and the fields
+/// of that version. Version 2 is like version 1, followed by the call limit. Version 3 contains
+/// the fixed cost as a `u64`, then each argument weight and then the optional call limit.
+///
+/// Each host function is written in the oldest version that can represent it, so the layout of
+/// existing cost tables is unchanged, and blobs written before versioning can still be read. A
+/// fixed cost of `HOST_FUNCTION_VERSION_MARKER` or above is always written in version 3.
+impl<T> HostFunction<T> {
+    /// Returns the version of the serialized layout of this host function.
+    fn serialization_version(&self) -> u8 {
+        if self.cost >= u64::from(HOST_FUNCTION_VERSION_MARKER) {
+            HOST_FUNCTION_VERSION_3
+        } else if self.call_limit.is_some() {
+            HOST_FUNCTION_VERSION_2
+        } else {
+            HOST_FUNCTION_VERSION_1
+        }
+    }
+}
+
 impl<T> ToBytes for HostFunction<T>
 where
     T: AsRef<[Cost]>,
 {
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
         let mut ret = bytesrepr::unchecked_allocate_buffer(self);
-        ret.append(&mut self.cost.to_bytes()?);
+        let version = self.serialization_version();
+        if version != HOST_FUNCTION_VERSION_1 {
+            ret.append(&mut HOST_FUNCTION_VERSION_MARKER.to_bytes()?);
+            ret.push(version);
+        }
+        if version == HOST_FUNCTION_VERSION_3 {
+            ret.append(&mut self.cost.to_bytes()?);
+        } else {
+            let cost = u32::try_from(self.cost).map_err(|_| bytesrepr::Error::Formatting)?;
+            ret.append(&mut cost.to_bytes()?);
+        }
         for value in self.arguments.as_ref().iter() {
             ret.append(&mut value.to_bytes()?);
         }
+        if version == HOST_FUNCTION_VERSION_3 {
+            ret.append(&mut self.call_limit.to_bytes()?);
+        } else if let Some(call_limit) = self.call_limit {
+            ret.append(&mut call_limit.to_bytes()?);
+        }
         Ok(ret)
     }
 
     fn serialized_length(&self) -> usize {
-        self.cost.serialized_length() + (COST_SERIALIZED_LENGTH * self.arguments.as_ref().len())
+        let (prefix_length, cost_length, call_limit_length) = match self.serialization_version() {
+            HOST_FUNCTION_VERSION_3 => (
+                U32_SERIALIZED_LENGTH + U8_SERIALIZED_LENGTH,
+                U64_SERIALIZED_LENGTH,
+                self.call_limit.serialized_length(),
+            ),
+            HOST_FUNCTION_VERSION_2 => (
+                U32_SERIALIZED_LENGTH + U8_SERIALIZED_LENGTH,
+                U32_SERIALIZED_LENGTH,
+                U32_SERIALIZED_LENGTH,
+            ),
+            _ => (0, U32_SERIALIZED_LENGTH, 0),
+        };
+        prefix_length
+            + cost_length
+            + (COST_SERIALIZED_LENGTH * self.arguments.as_ref().len())
+            + call_limit_length
     }
 }
 
@@ -183,586 +703,813 @@ where
     T: Default + AsMut<[Cost]>,
 {
     fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
-        let (cost, mut bytes) = FromBytes::from_bytes(bytes)?;
-        let mut arguments = T::default();
-        let arguments_mut = arguments.as_mut();
-        for ith_argument in arguments_mut {
+        Self::from_bytes_with_arguments(bytes, T::default())
+    }
+}
+
+impl<T> HostFunction<T>
+where
+    T: AsMut<[Cost]>,
+{
+    /// Deserializes a host function with as many argument weights as `arguments` holds, and
+    /// stores them in `arguments`.
+    fn from_bytes_with_arguments(
+        bytes: &[u8],
+        mut arguments: T,
+    ) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (legacy_cost, bytes) = u32::from_bytes(bytes)?;
+        let (version, cost, mut bytes) = if legacy_cost != HOST_FUNCTION_VERSION_MARKER {
+            (HOST_FUNCTION_VERSION_1, u64::from(legacy_cost), bytes)
+        } else {
+            let (version, bytes) = u8::from_bytes(bytes)?;
+            match version {
+                HOST_FUNCTION_VERSION_2 => {
+                    let (cost, rem) = u32::from_bytes(bytes)?;
+                    (version, u64::from(cost), rem)
+                }
+                HOST_FUNCTION_VERSION_3 => {
+                    let (cost, rem) = u64::from_bytes(bytes)?;
+                    (version, cost, rem)
+                }
+                _ => return Err(bytesrepr::Error::Formatting),
+            }
+        };
+        for ith_argument in arguments.as_mut() {
             let (cost, rem) = FromBytes::from_bytes(bytes)?;
             *ith_argument = cost;
             bytes = rem;
         }
-        Ok((Self { cost, arguments }, bytes))
+        let mut call_limit = None;
+        if version == HOST_FUNCTION_VERSION_2 {
+            let (limit, rem) = u32::from_bytes(bytes)?;
+            call_limit = Some(limit);
+            bytes = rem;
+        } else if version == HOST_FUNCTION_VERSION_3 {
+            let (limit, rem) = FromBytes::from_bytes(bytes)?;
+            call_limit = limit;
+            bytes = rem;
+        }
+        let host_function = Self {
+            cost,
+            arguments,
+            call_limit,
+        };
+        Ok((host_function, bytes))
+    }
+}
+
+/// Returns `key` as a TOML key: bare if possible, quoted otherwise.
+fn toml_key(key: &str) -> String {
+    let is_bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if is_bare {
+        return key.to_string();
+    }
+    let mut quoted = String::from("\"");
+    for c in key.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", u32::from(c))),
+            c => quoted.push(c),
+        }
     }
+    quoted.push('"');
+    quoted
+}
+
+/// Returns the bytes following the prefix that marks a serialized cost table with extra host
+/// functions, or `None` if the bytes don't start with that prefix.
+fn strip_extra_host_functions_prefix(bytes: &[u8]) -> Option<&[u8]> {
+    let (marker, rem) = u32::from_bytes(bytes).ok()?;
+    let (tag, rem) = u8::from_bytes(rem).ok()?;
+    if marker == HOST_FUNCTION_VERSION_MARKER && tag == EXTRA_HOST_FUNCTIONS_TAG {
+        Some(rem)
+    } else {
+        None
+    }
+}
+
+/// Serializes the extra host functions: their number, followed by the name, the number of
+/// argument weights and the host function of each, ordered by name.
+fn extra_host_functions_to_bytes(
+    extra: &BTreeMap<String, HostFunction<Vec<Cost>>>,
+) -> Result<Vec<u8>, bytesrepr::Error> {
+    let mut ret = Vec::with_capacity(extra_host_functions_serialized_length(extra));
+    let count = u32::try_from(extra.len()).map_err(|_| bytesrepr::Error::NotRepresentable)?;
+    ret.append(&mut count.to_bytes()?);
+    for (name, host_function) in extra {
+        let arity = u32::try_from(host_function.arguments.len())
+            .map_err(|_| bytesrepr::Error::NotRepresentable)?;
+        ret.append(&mut name.to_bytes()?);
+        ret.append(&mut arity.to_bytes()?);
+        ret.append(&mut host_function.to_bytes()?);
+    }
+    Ok(ret)
+}
+
+fn extra_host_functions_serialized_length(
+    extra: &BTreeMap<String, HostFunction<Vec<Cost>>>,
+) -> usize {
+    U32_SERIALIZED_LENGTH
+        + extra
+            .iter()
+            .map(|(name, host_function)| {
+                name.serialized_length() + U32_SERIALIZED_LENGTH + host_function.serialized_length()
+            })
+            .sum::<usize>()
+}
+
+/// Deserializes the extra host functions written by `extra_host_functions_to_bytes`.
+///
+/// The names must be in strictly ascending order, so that every map has a single serialized
+/// form.
+#[allow(clippy::type_complexity)]
+fn extra_host_functions_from_bytes(
+    bytes: &[u8],
+) -> Result<(BTreeMap<String, HostFunction<Vec<Cost>>>, &[u8]), bytesrepr::Error> {
+    let (count, mut rem) = u32::from_bytes(bytes)?;
+    let mut extra = BTreeMap::new();
+    for _ in 0..count {
+        let (name, bytes) = String::from_bytes(rem)?;
+        let (arity, bytes) = u32::from_bytes(bytes)?;
+        // Only allocate the argument weights if the bytes can hold them.
+        let arity = arity as usize;
+        if bytes.len() / COST_SERIALIZED_LENGTH < arity {
+            return Err(bytesrepr::Error::EarlyEndOfStream);
+        }
+        let (host_function, bytes) =
+            HostFunction::from_bytes_with_arguments(bytes, vec![NOT_USED; arity])?;
+        if matches!(extra.keys().next_back(), Some(last) if *last >= name) {
+            return Err(bytesrepr::Error::Formatting);
+        }
+        extra.insert(name, host_function);
+        rem = bytes;
+    }
+    Ok((extra, rem))
+}
+
+/// Defines the host function cost table, together with its `Default`, `ToBytes`, `FromBytes`,
+/// `TryFrom<HashMap<..>>` and `Distribution` implementations and the `gens` module, from a single
+/// list of fields.
+///
+/// The fields are serialized in the order in which they are listed, so new host functions must be
+/// appended at the end to keep the serialized layout of the existing ones.
+///
+/// If there are extra host functions, the table starts with `HOST_FUNCTION_VERSION_MARKER` and
+/// `EXTRA_HOST_FUNCTIONS_TAG`, and the extra host functions follow the fixed fields. Otherwise
+/// the table has the legacy layout of just the fixed fields.
+macro_rules! host_function_costs {
+    (
+        $(
+            $(#[$attr:meta])*
+            $field:ident: $ty:ty = $default:expr,
+        )+
+    ) => {
+        /// Definition of a host function cost table.
+        #[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug, DataSize)]
+        pub struct HostFunctionCosts {
+            $(
+                $(#[$attr])*
+                pub $field: $ty,
+            )+
+            /// Costs of experimental host functions outside the fixed set, keyed by name.
+            ///
+            /// These aren't subject to the checks of the fixed host functions, such as the gas
+            /// floor, and are only looked up by name if there is no fixed host function with that
+            /// name.
+            #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+            pub extra: BTreeMap<String, HostFunction<Vec<Cost>>>,
+        }
+
+        impl Default for HostFunctionCosts {
+            fn default() -> Self {
+                Self {
+                    $($field: $default,)+
+                    extra: BTreeMap::new(),
+                }
+            }
+        }
+
+        impl ToBytes for HostFunctionCosts {
+            fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+                let mut ret = bytesrepr::unchecked_allocate_buffer(self);
+                if !self.extra.is_empty() {
+                    ret.append(&mut HOST_FUNCTION_VERSION_MARKER.to_bytes()?);
+                    ret.push(EXTRA_HOST_FUNCTIONS_TAG);
+                }
+                $(ret.append(&mut self.$field.to_bytes()?);)+
+                if !self.extra.is_empty() {
+                    ret.append(&mut extra_host_functions_to_bytes(&self.extra)?);
+                }
+                Ok(ret)
+            }
+
+            fn serialized_length(&self) -> usize {
+                let mut length = 0;
+                $(length += self.$field.serialized_length();)+
+                if !self.extra.is_empty() {
+                    length += U32_SERIALIZED_LENGTH
+                        + U8_SERIALIZED_LENGTH
+                        + extra_host_functions_serialized_length(&self.extra);
+                }
+                length
+            }
+        }
+
+        // Every field has a fixed number of argument weights, so no length prefix is read for
+        // them, and the extra host functions are only allocated as far as the input holds them.
+        impl FromBytes for HostFunctionCosts {
+            fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+                let (has_extra, rem) = match strip_extra_host_functions_prefix(bytes) {
+                    Some(rem) => (true, rem),
+                    None => (false, bytes),
+                };
+                $(let ($field, rem) = FromBytes::from_bytes(rem)?;)+
+                let (extra, rem) = if has_extra {
+                    let (extra, rem) = extra_host_functions_from_bytes(rem)?;
+                    // An empty map is written in the legacy layout.
+                    if extra.is_empty() {
+                        return Err(bytesrepr::Error::Formatting);
+                    }
+                    (extra, rem)
+                } else {
+                    (BTreeMap::new(), rem)
+                };
+                Ok((HostFunctionCosts { $($field,)+ extra }, rem))
+            }
+        }
+
+        impl HostFunctionCosts {
+            /// The names of all host functions, in declaration order.
+            const NAMES: &'static [&'static str] = &[$(stringify!($field),)+];
+
+            /// Returns the number of arguments of the host function `name`, i.e. the maximum number
+            /// of argument sizes its gas cost is calculated from, or `None` if there is no host
+            /// function with that name.
+            ///
+            /// The arities are fixed by the types of the fields, so they are the same for every
+            /// cost table.
+            pub fn max_arity(name: &str) -> Option<usize> {
+                let defaults = Self::default();
+                match name {
+                    $(stringify!($field) => Some(defaults.$field.arguments().len()),)+
+                    _ => None,
+                }
+            }
+
+            /// Returns the cost of the host function `name`, or `None` if there is no host function
+            /// with that name. The fixed host functions are checked before the extra ones.
+            pub fn get(&self, name: &str) -> Option<HostFunction<Vec<Cost>>> {
+                match name {
+                    $(stringify!($field) => Some(self.$field.to_dynamic()),)+
+                    _ => self.extra.get(name).cloned(),
+                }
+            }
+
+            /// Returns the costs of all fixed host functions, keyed by host function name.
+            ///
+            /// The extra host functions are not included.
+            pub fn to_map(&self) -> HashMap<String, HostFunction<Vec<Cost>>> {
+                let mut map = HashMap::with_capacity(Self::NAMES.len());
+                $(map.insert(stringify!($field).to_string(), self.$field.to_dynamic());)+
+                map
+            }
+
+            /// Returns the cost table in TOML format, with one line per host function, in
+            /// declaration order.
+            ///
+            /// Unlike a generic TOML serializer, this always formats equal tables identically, so
+            /// the output can be stored and compared across releases.
+            pub fn to_toml_string(&self) -> String {
+                let mut toml = String::new();
+                $(
+                    toml.push_str(stringify!($field));
+                    toml.push_str(" = ");
+                    toml.push_str(&self.$field.to_toml_value());
+                    toml.push('\n');
+                )+
+                for (name, host_function) in &self.extra {
+                    toml.push_str("extra.");
+                    toml.push_str(&toml_key(name));
+                    toml.push_str(" = ");
+                    toml.push_str(&host_function.to_toml_value());
+                    toml.push('\n');
+                }
+                toml
+            }
+
+            /// Returns the names and nominal costs of all host functions, the most expensive ones
+            /// first. Host functions with equal nominal costs are in declaration order.
+            ///
+            /// See [`HostFunction::nominal_cost`] for why this is only a rough ranking.
+            pub fn ranked_by_nominal_cost(&self) -> Vec<(&'static str, u64)> {
+                let mut ranking = vec![$((stringify!($field), self.$field.nominal_cost()),)+];
+                ranking.sort_by(|(_, cost0), (_, cost1)| cost1.cmp(cost0));
+                ranking
+            }
+
+            /// Returns the names of the host functions that cost nothing, in declaration order.
+            pub fn free_host_functions(&self) -> Vec<&'static str> {
+                let mut names = vec![];
+                $(
+                    if self.$field.is_free() {
+                        names.push(stringify!($field));
+                    }
+                )+
+                names
+            }
+
+            /// Returns the names of the host functions whose fixed cost is below `min_cost`.
+            pub fn below_gas_floor(&self, min_cost: u64) -> Vec<&'static str> {
+                let mut names = vec![];
+                $(
+                    if self.$field.cost < min_cost {
+                        names.push(stringify!($field));
+                    }
+                )+
+                names
+            }
+
+            /// Returns the names of the host functions whose costs are equal to the defaults, in
+            /// declaration order.
+            ///
+            /// Many defaults are placeholders, so these are likely entries missing from the
+            /// chainspec rather than deliberate choices.
+            pub fn defaulted_fields(&self) -> Vec<&'static str> {
+                let defaults = Self::default();
+                let mut names = vec![];
+                $(
+                    if self.$field == defaults.$field {
+                        names.push(stringify!($field));
+                    }
+                )+
+                names
+            }
+
+            /// Checks that no host function costs less than the gas floor. Depending on its
+            /// policy, any cheaper host function is either an error or has its fixed cost raised.
+            pub fn apply_gas_floor(&mut self, gas_floor: GasFloor) -> Result<(), HostFunctionCostsError> {
+                let GasFloor { min_cost, policy } = gas_floor;
+                match policy {
+                    GasFloorPolicy::Reject => {
+                        let names = self.below_gas_floor(min_cost);
+                        if !names.is_empty() {
+                            return Err(HostFunctionCostsError::BelowGasFloor { min_cost, names });
+                        }
+                    }
+                    GasFloorPolicy::Clamp => {
+                        $(self.$field.cost = self.$field.cost.max(min_cost);)+
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        impl TryFrom<HashMap<String, HostFunction<Vec<Cost>>>> for HostFunctionCosts {
+            type Error = HostFunctionCostsError;
+
+            fn try_from(
+                mut map: HashMap<String, HostFunction<Vec<Cost>>>,
+            ) -> Result<Self, Self::Error> {
+                if let Some(unknown) = map
+                    .keys()
+                    .find(|name| !Self::NAMES.contains(&name.as_str()))
+                {
+                    return Err(HostFunctionCostsError::UnknownKey(unknown.clone()));
+                }
+                $(
+                    let $field = map
+                        .remove(stringify!($field))
+                        .ok_or(HostFunctionCostsError::MissingKey(stringify!($field)))?
+                        .try_into_fixed(stringify!($field))?;
+                )+
+                Ok(HostFunctionCosts {
+                    $($field,)+
+                    extra: BTreeMap::new(),
+                })
+            }
+        }
+
+        impl Distribution<HostFunctionCosts> for Standard {
+            fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> HostFunctionCosts {
+                HostFunctionCosts {
+                    $($field: rng.gen(),)+
+                    extra: BTreeMap::new(),
+                }
+            }
+        }
+
+        #[doc(hidden)]
+        #[cfg(any(feature = "gens", test))]
+        pub mod gens {
+            use std::collections::BTreeMap;
+
+            use proptest::{collection, prelude::*};
+            use rand::{Rng, SeedableRng};
+            use rand_chacha::ChaChaRng;
+
+            use super::{Cost, HostFunction, HostFunctionCosts};
+
+            /// Returns a random cost table generated from the given seed.
+            ///
+            /// The same seed always yields the same table, so a table that caused a failure can
+            /// be reproduced in a standalone test.
+            pub fn host_function_costs_from_seed(seed: u64) -> HostFunctionCosts {
+                ChaChaRng::seed_from_u64(seed).gen()
+            }
+
+            pub fn host_function_cost_arb<T: Copy + Arbitrary>(
+            ) -> impl Strategy<Value = HostFunction<T>> {
+                // TOML integers are signed, so costs are limited to their range, like in the
+                // `Distribution` implementation.
+                (0..=i64::max_value() as u64, any::<T>())
+                    .prop_map(|(cost, arguments)| HostFunction::new(cost, arguments))
+            }
+
+            /// Returns up to two extra host functions, with names that no fixed host function
+            /// has.
+            pub fn extra_host_functions_arb(
+            ) -> impl Strategy<Value = BTreeMap<String, HostFunction<Vec<Cost>>>> {
+                let host_function = (
+                    0..=i64::max_value() as u64,
+                    collection::vec(any::<Cost>(), 0..4),
+                )
+                    .prop_map(|(cost, arguments)| HostFunction::new(cost, arguments));
+                collection::btree_map("experimental_[a-z_]{1,16}", host_function, 0..3)
+            }
+
+            prop_compose! {
+                pub fn host_function_costs_arb() (
+                    $($field in host_function_cost_arb(),)+
+                    extra in extra_host_functions_arb(),
+                ) -> HostFunctionCosts {
+                    HostFunctionCosts {
+                        $($field,)+
+                        extra,
+                    }
+                }
+            }
+        }
+    };
 }
 
-/// Definition of a host function cost table.
-#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Debug, DataSize)]
-pub struct HostFunctionCosts {
+host_function_costs! {
     /// Cost of calling the `read_value` host function.
-    pub read_value: HostFunction<[Cost; 3]>,
+    read_value: HostFunction<[Cost; 3]> = HostFunction::fixed(DEFAULT_READ_VALUE_COST),
     /// Cost of calling the `dictionary_get` host function.
+    ///
+    /// This replaced the entry for `read_value_local` in place, so the serialized layout is
+    /// unchanged, but its costs only take effect with a protocol upgrade that updates the
+    /// chainspec.
     #[serde(alias = "read_value_local")]
-    pub dictionary_get: HostFunction<[Cost; 3]>,
+    dictionary_get: HostFunction<[Cost; 3]> = HostFunction::new(
+        DEFAULT_DICTIONARY_GET_COST,
+        [NOT_USED, DEFAULT_DICTIONARY_GET_KEY_SIZE_WEIGHT, NOT_USED],
+    ),
     /// Cost of calling the `write` host function.
-    pub write: HostFunction<[Cost; 4]>,
+    write: HostFunction<[Cost; 4]> = HostFunction::new(
+        DEFAULT_WRITE_COST,
+        [NOT_USED, NOT_USED, NOT_USED, DEFAULT_WRITE_VALUE_SIZE_WEIGHT],
+    ),
     /// Cost of calling the `dictionary_put` host function.
+    ///
+    /// Like `dictionary_get`, this replaced the entry for `write_local` in place.
     #[serde(alias = "write_local")]
-    pub dictionary_put: HostFunction<[Cost; 4]>,
+    dictionary_put: HostFunction<[Cost; 4]> = HostFunction::new(
+        DEFAULT_DICTIONARY_PUT_COST,
+        [
+            NOT_USED,
+            DEFAULT_DICTIONARY_PUT_KEY_BYTES_SIZE_WEIGHT,
+            NOT_USED,
+            DEFAULT_DICTIONARY_PUT_VALUE_SIZE_WEIGHT,
+        ],
+    ),
     /// Cost of calling the `add` host function.
-    pub add: HostFunction<[Cost; 4]>,
+    add: HostFunction<[Cost; 4]> = HostFunction::fixed(DEFAULT_ADD_COST),
     /// Cost of calling the `new_uref` host function.
-    pub new_uref: HostFunction<[Cost; 3]>,
+    new_uref: HostFunction<[Cost; 3]> = HostFunction::new(
+        DEFAULT_NEW_UREF_COST,
+        [NOT_USED, NOT_USED, DEFAULT_NEW_UREF_VALUE_SIZE_WEIGHT],
+    ),
     /// Cost of calling the `load_named_keys` host function.
-    pub load_named_keys: HostFunction<[Cost; 2]>,
+    load_named_keys: HostFunction<[Cost; 2]> = HostFunction::fixed(DEFAULT_LOAD_NAMED_KEYS_COST),
     /// Cost of calling the `ret` host function.
-    pub ret: HostFunction<[Cost; 2]>,
+    ret: HostFunction<[Cost; 2]> = HostFunction::new(
+        DEFAULT_RET_COST,
+        [NOT_USED, DEFAULT_RET_VALUE_SIZE_WEIGHT],
+    ),
     /// Cost of calling the `get_key` host function.
-    pub get_key: HostFunction<[Cost; 5]>,
+    get_key: HostFunction<[Cost; 5]> = HostFunction::new(
+        DEFAULT_GET_KEY_COST,
+        [NOT_USED, DEFAULT_GET_KEY_NAME_SIZE_WEIGHT, NOT_USED, NOT_USED, NOT_USED],
+    ),
     /// Cost of calling the `has_key` host function.
-    pub has_key: HostFunction<[Cost; 2]>,
+    has_key: HostFunction<[Cost; 2]> = HostFunction::new(
+        DEFAULT_HAS_KEY_COST,
+        [NOT_USED, DEFAULT_HAS_KEY_NAME_SIZE_WEIGHT],
+    ),
     /// Cost of calling the `put_key` host function.
-    pub put_key: HostFunction<[Cost; 4]>,
+    put_key: HostFunction<[Cost; 4]> = HostFunction::new(
+        DEFAULT_PUT_KEY_COST,
+        [NOT_USED, DEFAULT_PUT_KEY_NAME_SIZE_WEIGHT, NOT_USED, NOT_USED],
+    ),
     /// Cost of calling the `remove_key` host function.
-    pub remove_key: HostFunction<[Cost; 2]>,
+    remove_key: HostFunction<[Cost; 2]> = HostFunction::new(
+        DEFAULT_REMOVE_KEY_COST,
+        [NOT_USED, DEFAULT_REMOVE_KEY_NAME_SIZE_WEIGHT],
+    ),
     /// Cost of calling the `revert` host function.
-    pub revert: HostFunction<[Cost; 1]>,
+    revert: HostFunction<[Cost; 1]> = HostFunction::fixed(DEFAULT_REVERT_COST),
     /// Cost of calling the `is_valid_uref` host function.
-    pub is_valid_uref: HostFunction<[Cost; 2]>,
+    is_valid_uref: HostFunction<[Cost; 2]> = HostFunction::fixed(DEFAULT_IS_VALID_UREF_COST),
     /// Cost of calling the `add_associated_key` host function.
-    pub add_associated_key: HostFunction<[Cost; 3]>,
+    add_associated_key: HostFunction<[Cost; 3]> = HostFunction::fixed(
+        DEFAULT_ADD_ASSOCIATED_KEY_COST,
+    ),
     /// Cost of calling the `remove_associated_key` host function.
-    pub remove_associated_key: HostFunction<[Cost; 2]>,
+    remove_associated_key: HostFunction<[Cost; 2]> = HostFunction::fixed(
+        DEFAULT_REMOVE_ASSOCIATED_KEY_COST,
+    ),
     /// Cost of calling the `update_associated_key` host function.
-    pub update_associated_key: HostFunction<[Cost; 3]>,
+    update_associated_key: HostFunction<[Cost; 3]> = HostFunction::fixed(
+        DEFAULT_UPDATE_ASSOCIATED_KEY_COST,
+    ),
     /// Cost of calling the `set_action_threshold` host function.
-    pub set_action_threshold: HostFunction<[Cost; 2]>,
+    set_action_threshold: HostFunction<[Cost; 2]> = HostFunction::fixed(
+        DEFAULT_SET_ACTION_THRESHOLD_COST,
+    ),
     /// Cost of calling the `get_caller` host function.
-    pub get_caller: HostFunction<[Cost; 1]>,
+    get_caller: HostFunction<[Cost; 1]> = HostFunction::fixed(DEFAULT_GET_CALLER_COST),
     /// Cost of calling the `get_blocktime` host function.
-    pub get_blocktime: HostFunction<[Cost; 1]>,
+    get_blocktime: HostFunction<[Cost; 1]> = HostFunction::fixed(DEFAULT_GET_BLOCKTIME_COST),
     /// Cost of calling the `create_purse` host function.
-    pub create_purse: HostFunction<[Cost; 2]>,
+    create_purse: HostFunction<[Cost; 2]> = HostFunction::fixed(DEFAULT_CREATE_PURSE_COST),
     /// Cost of calling the `transfer_to_account` host function.
-    pub transfer_to_account: HostFunction<[Cost; 7]>,
+    transfer_to_account: HostFunction<[Cost; 7]> = HostFunction::fixed(
+        DEFAULT_TRANSFER_TO_ACCOUNT_COST,
+    ),
     /// Cost of calling the `transfer_from_purse_to_account` host function.
-    pub transfer_from_purse_to_account: HostFunction<[Cost; 9]>,
+    transfer_from_purse_to_account: HostFunction<[Cost; 9]> = HostFunction::fixed(
+        DEFAULT_TRANSFER_FROM_PURSE_TO_ACCOUNT_COST,
+    ),
     /// Cost of calling the `transfer_from_purse_to_purse` host function.
-    pub transfer_from_purse_to_purse: HostFunction<[Cost; 8]>,
+    transfer_from_purse_to_purse: HostFunction<[Cost; 8]> = HostFunction::fixed(
+        DEFAULT_TRANSFER_FROM_PURSE_TO_PURSE_COST,
+    ),
     /// Cost of calling the `get_balance` host function.
-    pub get_balance: HostFunction<[Cost; 3]>,
+    get_balance: HostFunction<[Cost; 3]> = HostFunction::fixed(DEFAULT_GET_BALANCE_COST),
     /// Cost of calling the `get_phase` host function.
-    pub get_phase: HostFunction<[Cost; 1]>,
+    get_phase: HostFunction<[Cost; 1]> = HostFunction::fixed(DEFAULT_GET_PHASE_COST),
     /// Cost of calling the `get_system_contract` host function.
-    pub get_system_contract: HostFunction<[Cost; 3]>,
+    get_system_contract: HostFunction<[Cost; 3]> = HostFunction::fixed(
+        DEFAULT_GET_SYSTEM_CONTRACT_COST,
+    ),
     /// Cost of calling the `get_main_purse` host function.
-    pub get_main_purse: HostFunction<[Cost; 1]>,
+    get_main_purse: HostFunction<[Cost; 1]> = HostFunction::fixed(DEFAULT_GET_MAIN_PURSE_COST),
     /// Cost of calling the `read_host_buffer` host function.
-    pub read_host_buffer: HostFunction<[Cost; 3]>,
+    read_host_buffer: HostFunction<[Cost; 3]> = HostFunction::new(
+        DEFAULT_READ_HOST_BUFFER_COST,
+        [NOT_USED, DEFAULT_READ_HOST_BUFFER_DEST_SIZE_WEIGHT, NOT_USED],
+    ),
     /// Cost of calling the `create_contract_package_at_hash` host function.
-    pub create_contract_package_at_hash: HostFunction<[Cost; 2]>,
+    create_contract_package_at_hash: HostFunction<[Cost; 2]> = HostFunction::default(),
     /// Cost of calling the `create_contract_user_group` host function.
-    pub create_contract_user_group: HostFunction<[Cost; 8]>,
+    create_contract_user_group: HostFunction<[Cost; 8]> = HostFunction::default(),
     /// Cost of calling the `add_contract_version` host function.
-    pub add_contract_version: HostFunction<[Cost; 10]>,
+    add_contract_version: HostFunction<[Cost; 10]> = HostFunction::default(),
     /// Cost of calling the `disable_contract_version` host function.
-    pub disable_contract_version: HostFunction<[Cost; 4]>,
+    disable_contract_version: HostFunction<[Cost; 4]> = HostFunction::default(),
     /// Cost of calling the `call_contract` host function.
-    pub call_contract: HostFunction<[Cost; 7]>,
+    ///
+    /// The last weight is charged per runtime arg passed to the called contract.
+    #[serde(deserialize_with = "deserialize_with_added_last_weight")]
+    call_contract: HostFunction<[Cost; 8]> = HostFunction::new(
+        DEFAULT_CALL_CONTRACT_COST,
+        [
+            NOT_USED,
+            NOT_USED,
+            NOT_USED,
+            NOT_USED,
+            NOT_USED,
+            DEFAULT_CALL_CONTRACT_ARGS_SIZE_WEIGHT,
+            NOT_USED,
+            DEFAULT_CALL_CONTRACT_ARGS_COUNT_WEIGHT,
+        ],
+    ),
     /// Cost of calling the `call_versioned_contract` host function.
-    pub call_versioned_contract: HostFunction<[Cost; 9]>,
+    ///
+    /// The last weight is charged per runtime arg passed to the called contract.
+    #[serde(deserialize_with = "deserialize_with_added_last_weight")]
+    call_versioned_contract: HostFunction<[Cost; 10]> = HostFunction::new(
+        DEFAULT_CALL_CONTRACT_COST,
+        [
+            NOT_USED,
+            NOT_USED,
+            NOT_USED,
+            NOT_USED,
+            NOT_USED,
+            NOT_USED,
+            NOT_USED,
+            DEFAULT_CALL_CONTRACT_ARGS_SIZE_WEIGHT,
+            NOT_USED,
+            DEFAULT_CALL_CONTRACT_ARGS_COUNT_WEIGHT,
+        ],
+    ),
     /// Cost of calling the `get_named_arg_size` host function.
-    pub get_named_arg_size: HostFunction<[Cost; 3]>,
+    get_named_arg_size: HostFunction<[Cost; 3]> = HostFunction::default(),
     /// Cost of calling the `get_named_arg` host function.
-    pub get_named_arg: HostFunction<[Cost; 4]>,
+    ///
+    /// The last weight is charged per byte of the argument value copied into contract memory.
+    get_named_arg: HostFunction<[Cost; 4]> = HostFunction::new(
+        DEFAULT_GET_NAMED_ARG_COST,
+        [NOT_USED, NOT_USED, NOT_USED, DEFAULT_GET_NAMED_ARG_DEST_SIZE_WEIGHT],
+    ),
     /// Cost of calling the `remove_contract_user_group` host function.
-    pub remove_contract_user_group: HostFunction<[Cost; 4]>,
+    remove_contract_user_group: HostFunction<[Cost; 4]> = HostFunction::default(),
     /// Cost of calling the `provision_contract_user_group_uref` host function.
-    pub provision_contract_user_group_uref: HostFunction<[Cost; 5]>,
+    provision_contract_user_group_uref: HostFunction<[Cost; 5]> = HostFunction::default(),
     /// Cost of calling the `remove_contract_user_group_urefs` host function.
-    pub remove_contract_user_group_urefs: HostFunction<[Cost; 6]>,
+    remove_contract_user_group_urefs: HostFunction<[Cost; 6]> = HostFunction::default(),
     /// Cost of calling the `print` host function.
-    pub print: HostFunction<[Cost; 2]>,
+    print: HostFunction<[Cost; 2]> = HostFunction::new(
+        DEFAULT_PRINT_COST,
+        [NOT_USED, DEFAULT_PRINT_TEXT_SIZE_WEIGHT],
+    ),
     /// Cost of calling the `blake2b` host function.
-    pub blake2b: HostFunction<[Cost; 4]>,
-    /// Cost of calling the `next address` host function.
-    pub random_bytes: HostFunction<[Cost; 2]>,
+    ///
+    /// Hashing is linear in the size of the input, so the second argument weight is charged per
+    /// byte of input.
+    blake2b: HostFunction<[Cost; 4]> = HostFunction::new(
+        DEFAULT_FIXED_COST,
+        [NOT_USED, DEFAULT_BLAKE2B_INPUT_SIZE_WEIGHT, NOT_USED, NOT_USED],
+    ),
+    /// Cost of calling the `random_bytes` host function.
+    ///
+    /// This comes after all older host functions in the serialized representation, so that the
+    /// layout of the preceding fields is unaffected by its addition.
+    random_bytes: HostFunction<[Cost; 2]> = HostFunction::default(),
+    /// Cost of calling the `enable_contract_version` host function.
+    ///
+    /// Chainspecs for protocol versions before this was added don't list it, and use the default.
+    #[serde(default = "default_enable_contract_version")]
+    enable_contract_version: HostFunction<[Cost; 4]> = default_enable_contract_version(),
+    /// Cost of calling the `transfer` host function, a shortcut for transferring from the
+    /// caller's main purse.
+    ///
+    /// Transfers are frequent, so this is tunable independently of the other transfer variants.
+    /// Chainspecs for protocol versions before this was added don't list it, and use the default.
+    #[serde(default = "default_transfer")]
+    transfer: HostFunction<[Cost; 4]> = default_transfer(),
 }
 
-impl Default for HostFunctionCosts {
-    fn default() -> Self {
-        Self {
-            read_value: HostFunction::fixed(DEFAULT_READ_VALUE_COST),
-            dictionary_get: HostFunction::new(
-                DEFAULT_DICTIONARY_GET_COST,
-                [NOT_USED, DEFAULT_DICTIONARY_GET_KEY_SIZE_WEIGHT, NOT_USED],
-            ),
-            write: HostFunction::new(
-                DEFAULT_WRITE_COST,
-                [
-                    NOT_USED,
-                    NOT_USED,
-                    NOT_USED,
-                    DEFAULT_WRITE_VALUE_SIZE_WEIGHT,
-                ],
-            ),
-            dictionary_put: HostFunction::new(
-                DEFAULT_DICTIONARY_PUT_COST,
-                [
-                    NOT_USED,
-                    DEFAULT_DICTIONARY_PUT_KEY_BYTES_SIZE_WEIGHT,
-                    NOT_USED,
-                    DEFAULT_DICTIONARY_PUT_VALUE_SIZE_WEIGHT,
-                ],
-            ),
-            add: HostFunction::fixed(DEFAULT_ADD_COST),
-            new_uref: HostFunction::new(
-                DEFAULT_NEW_UREF_COST,
-                [NOT_USED, NOT_USED, DEFAULT_NEW_UREF_VALUE_SIZE_WEIGHT],
-            ),
-            load_named_keys: HostFunction::fixed(DEFAULT_LOAD_NAMED_KEYS_COST),
-            ret: HostFunction::new(DEFAULT_RET_COST, [NOT_USED, DEFAULT_RET_VALUE_SIZE_WEIGHT]),
-            get_key: HostFunction::new(
-                DEFAULT_GET_KEY_COST,
-                [
-                    NOT_USED,
-                    DEFAULT_GET_KEY_NAME_SIZE_WEIGHT,
-                    NOT_USED,
-                    NOT_USED,
-                    NOT_USED,
-                ],
-            ),
-            has_key: HostFunction::new(
-                DEFAULT_HAS_KEY_COST,
-                [NOT_USED, DEFAULT_HAS_KEY_NAME_SIZE_WEIGHT],
-            ),
-            put_key: HostFunction::new(
-                DEFAULT_PUT_KEY_COST,
-                [
-                    NOT_USED,
-                    DEFAULT_PUT_KEY_NAME_SIZE_WEIGHT,
-                    NOT_USED,
-                    NOT_USED,
-                ],
-            ),
-            remove_key: HostFunction::new(
-                DEFAULT_REMOVE_KEY_COST,
-                [NOT_USED, DEFAULT_REMOVE_KEY_NAME_SIZE_WEIGHT],
-            ),
-            revert: HostFunction::fixed(DEFAULT_REVERT_COST),
-            is_valid_uref: HostFunction::fixed(DEFAULT_IS_VALID_UREF_COST),
-            add_associated_key: HostFunction::fixed(DEFAULT_ADD_ASSOCIATED_KEY_COST),
-            remove_associated_key: HostFunction::fixed(DEFAULT_REMOVE_ASSOCIATED_KEY_COST),
-            update_associated_key: HostFunction::fixed(DEFAULT_UPDATE_ASSOCIATED_KEY_COST),
-            set_action_threshold: HostFunction::fixed(DEFAULT_SET_ACTION_THRESHOLD_COST),
-            get_caller: HostFunction::fixed(DEFAULT_GET_CALLER_COST),
-            get_blocktime: HostFunction::fixed(DEFAULT_GET_BLOCKTIME_COST),
-            create_purse: HostFunction::fixed(DEFAULT_CREATE_PURSE_COST),
-            transfer_to_account: HostFunction::fixed(DEFAULT_TRANSFER_TO_ACCOUNT_COST),
-            transfer_from_purse_to_account: HostFunction::fixed(
-                DEFAULT_TRANSFER_FROM_PURSE_TO_ACCOUNT_COST,
-            ),
-            transfer_from_purse_to_purse: HostFunction::fixed(
-                DEFAULT_TRANSFER_FROM_PURSE_TO_PURSE_COST,
-            ),
-            get_balance: HostFunction::fixed(DEFAULT_GET_BALANCE_COST),
-            get_phase: HostFunction::fixed(DEFAULT_GET_PHASE_COST),
-            get_system_contract: HostFunction::fixed(DEFAULT_GET_SYSTEM_CONTRACT_COST),
-            get_main_purse: HostFunction::fixed(DEFAULT_GET_MAIN_PURSE_COST),
-            read_host_buffer: HostFunction::new(
-                DEFAULT_READ_HOST_BUFFER_COST,
-                [
-                    NOT_USED,
-                    DEFAULT_READ_HOST_BUFFER_DEST_SIZE_WEIGHT,
-                    NOT_USED,
-                ],
-            ),
-            create_contract_package_at_hash: HostFunction::default(),
-            create_contract_user_group: HostFunction::default(),
-            add_contract_version: HostFunction::default(),
-            disable_contract_version: HostFunction::default(),
-            call_contract: HostFunction::new(
-                DEFAULT_CALL_CONTRACT_COST,
-                [
-                    NOT_USED,
-                    NOT_USED,
-                    NOT_USED,
-                    NOT_USED,
-                    NOT_USED,
-                    DEFAULT_CALL_CONTRACT_ARGS_SIZE_WEIGHT,
-                    NOT_USED,
-                ],
-            ),
-            call_versioned_contract: HostFunction::default(),
-            get_named_arg_size: HostFunction::default(),
-            get_named_arg: HostFunction::default(),
-            remove_contract_user_group: HostFunction::default(),
-            provision_contract_user_group_uref: HostFunction::default(),
-            remove_contract_user_group_urefs: HostFunction::default(),
-            print: HostFunction::new(
-                DEFAULT_PRINT_COST,
-                [NOT_USED, DEFAULT_PRINT_TEXT_SIZE_WEIGHT],
+impl HostFunctionCosts {
+    /// Returns the cost table of mainnet since protocol version 1.0.0, as in the production
+    /// chainspec.
+    ///
+    /// This must never change: A new cost table for mainnet needs a new preset, activated with a
+    /// protocol upgrade. Host functions added later aren't listed in that chainspec, so they have
+    /// the costs that chainspecs without them default to.
+    pub fn mainnet_v1() -> Self {
+        HostFunctionCosts {
+            read_value: HostFunction::new(6_000, [0, 0, 0]),
+            dictionary_get: HostFunction::new(5_500, [0, 590, 0]),
+            write: HostFunction::new(14_000, [0, 0, 0, 980]),
+            dictionary_put: HostFunction::new(9_500, [0, 1_800, 0, 520]),
+            add: HostFunction::new(5_800, [0, 0, 0, 0]),
+            new_uref: HostFunction::new(17_000, [0, 0, 590]),
+            load_named_keys: HostFunction::new(42_000, [0, 0]),
+            ret: HostFunction::new(23_000, [0, 420_000]),
+            get_key: HostFunction::new(2_000, [0, 440, 0, 0, 0]),
+            has_key: HostFunction::new(1_500, [0, 840]),
+            put_key: HostFunction::new(38_000, [0, 1_100, 0, 0]),
+            remove_key: HostFunction::new(61_000, [0, 3_200]),
+            revert: HostFunction::new(500, [0]),
+            is_valid_uref: HostFunction::new(760, [0, 0]),
+            add_associated_key: HostFunction::new(9_000, [0, 0, 0]),
+            remove_associated_key: HostFunction::new(4_200, [0, 0]),
+            update_associated_key: HostFunction::new(4_200, [0, 0, 0]),
+            set_action_threshold: HostFunction::new(74_000, [0, 0]),
+            get_caller: HostFunction::new(380, [0]),
+            get_blocktime: HostFunction::new(330, [0]),
+            create_purse: HostFunction::new(2_500_000_000, [0, 0]),
+            transfer_to_account: HostFunction::new(2_500_000_000, [0, 0, 0, 0, 0, 0, 0]),
+            transfer_from_purse_to_account: HostFunction::new(
+                2_500_000_000,
+                [0, 0, 0, 0, 0, 0, 0, 0, 0],
             ),
-            blake2b: HostFunction::default(),
-            random_bytes: HostFunction::default(),
+            transfer_from_purse_to_purse: HostFunction::new(82_000, [0, 0, 0, 0, 0, 0, 0, 0]),
+            get_balance: HostFunction::new(3_800, [0, 0, 0]),
+            get_phase: HostFunction::new(710, [0]),
+            get_system_contract: HostFunction::new(1_100, [0, 0, 0]),
+            get_main_purse: HostFunction::new(1_300, [0]),
+            read_host_buffer: HostFunction::new(3_500, [0, 310, 0]),
+            create_contract_package_at_hash: HostFunction::new(200, [0, 0]),
+            create_contract_user_group: HostFunction::new(200, [0, 0, 0, 0, 0, 0, 0, 0]),
+            add_contract_version: HostFunction::new(200, [0, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+            disable_contract_version: HostFunction::new(200, [0, 0, 0, 0]),
+            call_contract: HostFunction::new(4_500, [0, 0, 0, 0, 0, 420, 0, 0]),
+            call_versioned_contract: HostFunction::new(4_500, [0, 0, 0, 0, 0, 0, 0, 420, 0, 0]),
+            get_named_arg_size: HostFunction::new(200, [0, 0, 0]),
+            get_named_arg: HostFunction::new(200, [0, 0, 0, 0]),
+            remove_contract_user_group: HostFunction::new(200, [0, 0, 0, 0]),
+            provision_contract_user_group_uref: HostFunction::new(200, [0, 0, 0, 0, 0]),
+            remove_contract_user_group_urefs: HostFunction::new(200, [0, 0, 0, 0, 0, 0]),
+            print: HostFunction::new(20_000, [0, 4_600]),
+            blake2b: HostFunction::new(200, [0, 0, 0, 0]),
+            random_bytes: HostFunction::new(200, [0, 0]),
+            enable_contract_version: default_enable_contract_version(),
+            transfer: default_transfer(),
+            extra: BTreeMap::new(),
         }
     }
-}
-
-impl ToBytes for HostFunctionCosts {
-    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
-        let mut ret = bytesrepr::unchecked_allocate_buffer(self);
-        ret.append(&mut self.read_value.to_bytes()?);
-        ret.append(&mut self.dictionary_get.to_bytes()?);
-        ret.append(&mut self.write.to_bytes()?);
-        ret.append(&mut self.dictionary_put.to_bytes()?);
-        ret.append(&mut self.add.to_bytes()?);
-        ret.append(&mut self.new_uref.to_bytes()?);
-        ret.append(&mut self.load_named_keys.to_bytes()?);
-        ret.append(&mut self.ret.to_bytes()?);
-        ret.append(&mut self.get_key.to_bytes()?);
-        ret.append(&mut self.has_key.to_bytes()?);
-        ret.append(&mut self.put_key.to_bytes()?);
-        ret.append(&mut self.remove_key.to_bytes()?);
-        ret.append(&mut self.revert.to_bytes()?);
-        ret.append(&mut self.is_valid_uref.to_bytes()?);
-        ret.append(&mut self.add_associated_key.to_bytes()?);
-        ret.append(&mut self.remove_associated_key.to_bytes()?);
-        ret.append(&mut self.update_associated_key.to_bytes()?);
-        ret.append(&mut self.set_action_threshold.to_bytes()?);
-        ret.append(&mut self.get_caller.to_bytes()?);
-        ret.append(&mut self.get_blocktime.to_bytes()?);
-        ret.append(&mut self.create_purse.to_bytes()?);
-        ret.append(&mut self.transfer_to_account.to_bytes()?);
-        ret.append(&mut self.transfer_from_purse_to_account.to_bytes()?);
-        ret.append(&mut self.transfer_from_purse_to_purse.to_bytes()?);
-        ret.append(&mut self.get_balance.to_bytes()?);
-        ret.append(&mut self.get_phase.to_bytes()?);
-        ret.append(&mut self.get_system_contract.to_bytes()?);
-        ret.append(&mut self.get_main_purse.to_bytes()?);
-        ret.append(&mut self.read_host_buffer.to_bytes()?);
-        ret.append(&mut self.create_contract_package_at_hash.to_bytes()?);
-        ret.append(&mut self.create_contract_user_group.to_bytes()?);
-        ret.append(&mut self.add_contract_version.to_bytes()?);
-        ret.append(&mut self.disable_contract_version.to_bytes()?);
-        ret.append(&mut self.call_contract.to_bytes()?);
-        ret.append(&mut self.call_versioned_contract.to_bytes()?);
-        ret.append(&mut self.get_named_arg_size.to_bytes()?);
-        ret.append(&mut self.get_named_arg.to_bytes()?);
-        ret.append(&mut self.remove_contract_user_group.to_bytes()?);
-        ret.append(&mut self.provision_contract_user_group_uref.to_bytes()?);
-        ret.append(&mut self.remove_contract_user_group_urefs.to_bytes()?);
-        ret.append(&mut self.print.to_bytes()?);
-        ret.append(&mut self.blake2b.to_bytes()?);
-        ret.append(&mut self.random_bytes.to_bytes()?);
-        Ok(ret)
-    }
 
-    fn serialized_length(&self) -> usize {
-        self.read_value.serialized_length()
-            + self.dictionary_get.serialized_length()
-            + self.write.serialized_length()
-            + self.dictionary_put.serialized_length()
-            + self.add.serialized_length()
-            + self.new_uref.serialized_length()
-            + self.load_named_keys.serialized_length()
-            + self.ret.serialized_length()
-            + self.get_key.serialized_length()
-            + self.has_key.serialized_length()
-            + self.put_key.serialized_length()
-            + self.remove_key.serialized_length()
-            + self.revert.serialized_length()
-            + self.is_valid_uref.serialized_length()
-            + self.add_associated_key.serialized_length()
-            + self.remove_associated_key.serialized_length()
-            + self.update_associated_key.serialized_length()
-            + self.set_action_threshold.serialized_length()
-            + self.get_caller.serialized_length()
-            + self.get_blocktime.serialized_length()
-            + self.create_purse.serialized_length()
-            + self.transfer_to_account.serialized_length()
-            + self.transfer_from_purse_to_account.serialized_length()
-            + self.transfer_from_purse_to_purse.serialized_length()
-            + self.get_balance.serialized_length()
-            + self.get_phase.serialized_length()
-            + self.get_system_contract.serialized_length()
-            + self.get_main_purse.serialized_length()
-            + self.read_host_buffer.serialized_length()
-            + self.create_contract_package_at_hash.serialized_length()
-            + self.create_contract_user_group.serialized_length()
-            + self.add_contract_version.serialized_length()
-            + self.disable_contract_version.serialized_length()
-            + self.call_contract.serialized_length()
-            + self.call_versioned_contract.serialized_length()
-            + self.get_named_arg_size.serialized_length()
-            + self.get_named_arg.serialized_length()
-            + self.remove_contract_user_group.serialized_length()
-            + self.provision_contract_user_group_uref.serialized_length()
-            + self.remove_contract_user_group_urefs.serialized_length()
-            + self.print.serialized_length()
-            + self.blake2b.serialized_length()
-            + self.random_bytes.serialized_length()
+    /// Returns a hash of the canonical serialization of the cost table.
+    ///
+    /// Equal tables always have equal digests, so nodes can compare their cost tables without
+    /// exchanging them.
+    pub fn digest(&self) -> [u8; Digest::LENGTH] {
+        let bytes = self
+            .to_bytes()
+            .expect("should serialize host function costs");
+        Digest::hash(bytes).value()
     }
-}
 
-impl FromBytes for HostFunctionCosts {
-    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
-        let (read_value, rem) = FromBytes::from_bytes(bytes)?;
-        let (dictionary_get, rem) = FromBytes::from_bytes(rem)?;
-        let (write, rem) = FromBytes::from_bytes(rem)?;
-        let (dictionary_put, rem) = FromBytes::from_bytes(rem)?;
-        let (add, rem) = FromBytes::from_bytes(rem)?;
-        let (new_uref, rem) = FromBytes::from_bytes(rem)?;
-        let (load_named_keys, rem) = FromBytes::from_bytes(rem)?;
-        let (ret, rem) = FromBytes::from_bytes(rem)?;
-        let (get_key, rem) = FromBytes::from_bytes(rem)?;
-        let (has_key, rem) = FromBytes::from_bytes(rem)?;
-        let (put_key, rem) = FromBytes::from_bytes(rem)?;
-        let (remove_key, rem) = FromBytes::from_bytes(rem)?;
-        let (revert, rem) = FromBytes::from_bytes(rem)?;
-        let (is_valid_uref, rem) = FromBytes::from_bytes(rem)?;
-        let (add_associated_key, rem) = FromBytes::from_bytes(rem)?;
-        let (remove_associated_key, rem) = FromBytes::from_bytes(rem)?;
-        let (update_associated_key, rem) = FromBytes::from_bytes(rem)?;
-        let (set_action_threshold, rem) = FromBytes::from_bytes(rem)?;
-        let (get_caller, rem) = FromBytes::from_bytes(rem)?;
-        let (get_blocktime, rem) = FromBytes::from_bytes(rem)?;
-        let (create_purse, rem) = FromBytes::from_bytes(rem)?;
-        let (transfer_to_account, rem) = FromBytes::from_bytes(rem)?;
-        let (transfer_from_purse_to_account, rem) = FromBytes::from_bytes(rem)?;
-        let (transfer_from_purse_to_purse, rem) = FromBytes::from_bytes(rem)?;
-        let (get_balance, rem) = FromBytes::from_bytes(rem)?;
-        let (get_phase, rem) = FromBytes::from_bytes(rem)?;
-        let (get_system_contract, rem) = FromBytes::from_bytes(rem)?;
-        let (get_main_purse, rem) = FromBytes::from_bytes(rem)?;
-        let (read_host_buffer, rem) = FromBytes::from_bytes(rem)?;
-        let (create_contract_package_at_hash, rem) = FromBytes::from_bytes(rem)?;
-        let (create_contract_user_group, rem) = FromBytes::from_bytes(rem)?;
-        let (add_contract_version, rem) = FromBytes::from_bytes(rem)?;
-        let (disable_contract_version, rem) = FromBytes::from_bytes(rem)?;
-        let (call_contract, rem) = FromBytes::from_bytes(rem)?;
-        let (call_versioned_contract, rem) = FromBytes::from_bytes(rem)?;
-        let (get_named_arg_size, rem) = FromBytes::from_bytes(rem)?;
-        let (get_named_arg, rem) = FromBytes::from_bytes(rem)?;
-        let (remove_contract_user_group, rem) = FromBytes::from_bytes(rem)?;
-        let (provision_contract_user_group_uref, rem) = FromBytes::from_bytes(rem)?;
-        let (remove_contract_user_group_urefs, rem) = FromBytes::from_bytes(rem)?;
-        let (print, rem) = FromBytes::from_bytes(rem)?;
-        let (blake2b, rem) = FromBytes::from_bytes(rem)?;
-        let (random_bytes, rem) = FromBytes::from_bytes(rem)?;
-        Ok((
-            HostFunctionCosts {
-                read_value,
-                dictionary_get,
-                write,
-                dictionary_put,
-                add,
-                new_uref,
-                load_named_keys,
-                ret,
-                get_key,
-                has_key,
-                put_key,
-                remove_key,
-                revert,
-                is_valid_uref,
-                add_associated_key,
-                remove_associated_key,
-                update_associated_key,
-                set_action_threshold,
-                get_caller,
-                get_blocktime,
-                create_purse,
-                transfer_to_account,
-                transfer_from_purse_to_account,
-                transfer_from_purse_to_purse,
-                get_balance,
-                get_phase,
-                get_system_contract,
-                get_main_purse,
-                read_host_buffer,
-                create_contract_package_at_hash,
-                create_contract_user_group,
-                add_contract_version,
-                disable_contract_version,
-                call_contract,
-                call_versioned_contract,
-                get_named_arg_size,
-                get_named_arg,
-                remove_contract_user_group,
-                provision_contract_user_group_uref,
-                remove_contract_user_group_urefs,
-                print,
-                blake2b,
-                random_bytes,
-            },
-            rem,
-        ))
+    /// Deserializes a cost table like `from_bytes`, but returns `bytesrepr::Error::LeftOverBytes`
+    /// instead of the remainder if the cost table doesn't use up all the bytes.
+    ///
+    /// Use this for cost tables from untrusted sources, where trailing data means the blob is
+    /// malformed.
+    pub fn checked_from_bytes(bytes: &[u8]) -> Result<HostFunctionCosts, bytesrepr::Error> {
+        bytesrepr::deserialize_from_slice(bytes)
     }
-}
 
-impl Distribution<HostFunctionCosts> for Standard {
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> HostFunctionCosts {
-        HostFunctionCosts {
-            read_value: rng.gen(),
-            dictionary_get: rng.gen(),
-            write: rng.gen(),
-            dictionary_put: rng.gen(),
-            add: rng.gen(),
-            new_uref: rng.gen(),
-            load_named_keys: rng.gen(),
-            ret: rng.gen(),
-            get_key: rng.gen(),
-            has_key: rng.gen(),
-            put_key: rng.gen(),
-            remove_key: rng.gen(),
-            revert: rng.gen(),
-            is_valid_uref: rng.gen(),
-            add_associated_key: rng.gen(),
-            remove_associated_key: rng.gen(),
-            update_associated_key: rng.gen(),
-            set_action_threshold: rng.gen(),
-            get_caller: rng.gen(),
-            get_blocktime: rng.gen(),
-            create_purse: rng.gen(),
-            transfer_to_account: rng.gen(),
-            transfer_from_purse_to_account: rng.gen(),
-            transfer_from_purse_to_purse: rng.gen(),
-            get_balance: rng.gen(),
-            get_phase: rng.gen(),
-            get_system_contract: rng.gen(),
-            get_main_purse: rng.gen(),
-            read_host_buffer: rng.gen(),
-            create_contract_package_at_hash: rng.gen(),
-            create_contract_user_group: rng.gen(),
-            add_contract_version: rng.gen(),
-            disable_contract_version: rng.gen(),
-            call_contract: rng.gen(),
-            call_versioned_contract: rng.gen(),
-            get_named_arg_size: rng.gen(),
-            get_named_arg: rng.gen(),
-            remove_contract_user_group: rng.gen(),
-            provision_contract_user_group_uref: rng.gen(),
-            remove_contract_user_group_urefs: rng.gen(),
-            print: rng.gen(),
-            blake2b: rng.gen(),
-            random_bytes: rng.gen(),
+    /// Checks that every size-dependent host function has a non-zero per-byte weight, so that
+    /// large inputs are never free.
+    ///
+    /// See [`SIZE_DEPENDENT_HOST_FUNCTIONS`] for the weights that are checked.
+    pub fn check_per_byte_weights(&self) -> Result<(), HostFunctionCostsError> {
+        let map = self.to_map();
+        let mut names: Vec<&'static str> = SIZE_DEPENDENT_HOST_FUNCTIONS
+            .iter()
+            .filter(|(name, index)| {
+                map.get(*name)
+                    .and_then(|host_function| host_function.arguments().get(*index))
+                    == Some(&0)
+            })
+            .map(|(name, _)| *name)
+            .collect();
+        names.dedup();
+        if names.is_empty() {
+            Ok(())
+        } else {
+            Err(HostFunctionCostsError::ZeroPerByteWeight { names })
         }
     }
-}
 
-#[doc(hidden)]
-#[cfg(any(feature = "gens", test))]
-pub mod gens {
-    use proptest::prelude::*;
+    /// Returns the total gas cost of the given host function calls, without executing anything.
+    ///
+    /// Each call is given as the name of the host function and the sizes of its arguments, e.g.
+    /// as anticipated by a deploy author. Unknown names and argument counts that don't match the
+    /// host function are errors.
+    pub fn estimate(&self, calls: &[(&str, Vec<usize>)]) -> Result<u64, CostError> {
+        calls.iter().try_fold(0u64, |total, (name, arg_sizes)| {
+            let host_function = self.get(name).ok_or_else(|| CostError::UnknownFunction {
+                name: name.to_string(),
+            })?;
+            let gas = host_function.max_gas(arg_sizes)?;
+            total.checked_add(gas).ok_or(CostError::Overflow)
+        })
+    }
 
-    use super::{Cost, HostFunction, HostFunctionCosts};
-
-    pub fn host_function_cost_arb<T: Copy + Arbitrary>() -> impl Strategy<Value = HostFunction<T>> {
-        (any::<Cost>(), any::<T>()).prop_map(|(cost, arguments)| HostFunction::new(cost, arguments))
-    }
-
-    prop_compose! {
-        pub fn host_function_costs_arb() (
-            read_value in host_function_cost_arb(),
-            dictionary_get in host_function_cost_arb(),
-            write in host_function_cost_arb(),
-            dictionary_put in host_function_cost_arb(),
-            add in host_function_cost_arb(),
-            new_uref in host_function_cost_arb(),
-            load_named_keys in host_function_cost_arb(),
-            ret in host_function_cost_arb(),
-            get_key in host_function_cost_arb(),
-            has_key in host_function_cost_arb(),
-            put_key in host_function_cost_arb(),
-            remove_key in host_function_cost_arb(),
-            revert in host_function_cost_arb(),
-            is_valid_uref in host_function_cost_arb(),
-            add_associated_key in host_function_cost_arb(),
-            remove_associated_key in host_function_cost_arb(),
-            update_associated_key in host_function_cost_arb(),
-            set_action_threshold in host_function_cost_arb(),
-            get_caller in host_function_cost_arb(),
-            get_blocktime in host_function_cost_arb(),
-            create_purse in host_function_cost_arb(),
-            transfer_to_account in host_function_cost_arb(),
-            transfer_from_purse_to_account in host_function_cost_arb(),
-            transfer_from_purse_to_purse in host_function_cost_arb(),
-            get_balance in host_function_cost_arb(),
-            get_phase in host_function_cost_arb(),
-            get_system_contract in host_function_cost_arb(),
-            get_main_purse in host_function_cost_arb(),
-            read_host_buffer in host_function_cost_arb(),
-            create_contract_package_at_hash in host_function_cost_arb(),
-            create_contract_user_group in host_function_cost_arb(),
-            add_contract_version in host_function_cost_arb(),
-            disable_contract_version in host_function_cost_arb(),
-            call_contract in host_function_cost_arb(),
-            call_versioned_contract in host_function_cost_arb(),
-            get_named_arg_size in host_function_cost_arb(),
-            get_named_arg in host_function_cost_arb(),
-            remove_contract_user_group in host_function_cost_arb(),
-            provision_contract_user_group_uref in host_function_cost_arb(),
-            remove_contract_user_group_urefs in host_function_cost_arb(),
-            print in host_function_cost_arb(),
-            blake2b in host_function_cost_arb(),
-            random_bytes in host_function_cost_arb(),
-        ) -> HostFunctionCosts {
-            HostFunctionCosts {
-                read_value,
-                dictionary_get,
-                write,
-                dictionary_put,
-                add,
-                new_uref,
-                load_named_keys,
-                ret,
-                get_key,
-                has_key,
-                put_key,
-                remove_key,
-                revert,
-                is_valid_uref,
-                add_associated_key,
-                remove_associated_key,
-                update_associated_key,
-                set_action_threshold,
-                get_caller,
-                get_blocktime,
-                create_purse,
-                transfer_to_account,
-                transfer_from_purse_to_account,
-                transfer_from_purse_to_purse,
-                get_balance,
-                get_phase,
-                get_system_contract,
-                get_main_purse,
-                read_host_buffer,
-                create_contract_package_at_hash,
-                create_contract_user_group,
-                add_contract_version,
-                disable_contract_version,
-                call_contract,
-                call_versioned_contract,
-                get_named_arg_size,
-                get_named_arg,
-                remove_contract_user_group,
-                provision_contract_user_group_uref,
-                remove_contract_user_group_urefs,
-                print,
-                blake2b,
-                random_bytes,
-            }
-        }
+    /// Returns the total gas charged for the host function calls in the trace.
+    ///
+    /// Unlike `estimate`, this computes each call's cost like the metering does, so missing
+    /// argument sizes count as zero and a single call's cost saturates. Only an overflow of the
+    /// total is an error.
+    pub fn estimate_from_trace(&self, trace: &[HostCallRecord]) -> Result<u64, CostError> {
+        trace.iter().try_fold(0u64, |total, record| {
+            let host_function =
+                self.get(&record.name)
+                    .ok_or_else(|| CostError::UnknownFunction {
+                        name: record.name.clone(),
+                    })?;
+            host_function.check_arity(&record.arg_sizes)?;
+            let gas = host_function.calculate_gas_saturating(&record.arg_sizes);
+            total.checked_add(gas).ok_or(CostError::Overflow)
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use casper_types::U512;
+    use std::cmp::Reverse;
 
     use super::*;
 
-    const COST: Cost = 42;
+    const COST: u64 = 42;
     const ARGUMENT_COSTS: [Cost; 3] = [123, 456, 789];
     const WEIGHTS: [Cost; 3] = [1000, 1100, 1200];
 
@@ -770,21 +1517,66 @@ mod tests {
     fn calculate_gas_cost_for_host_function() {
         let host_function = HostFunction::new(COST, ARGUMENT_COSTS);
         let expected_cost = COST
-            + (ARGUMENT_COSTS[0] * WEIGHTS[0])
-            + (ARGUMENT_COSTS[1] * WEIGHTS[1])
-            + (ARGUMENT_COSTS[2] * WEIGHTS[2]);
+            + u64::from(ARGUMENT_COSTS[0] * WEIGHTS[0])
+            + u64::from(ARGUMENT_COSTS[1] * WEIGHTS[1])
+            + u64::from(ARGUMENT_COSTS[2] * WEIGHTS[2]);
         assert_eq!(
             host_function.calculate_gas_cost(WEIGHTS),
             Gas::new(expected_cost.into())
         );
     }
 
+    #[test]
+    fn should_explain_cost_formula() {
+        let host_function = HostFunction::new(100, [2, 5]);
+        assert_eq!(
+            host_function.explain(&["key", "value"]),
+            "100 + 2*len(key) + 5*len(value)"
+        );
+        // Missing names fall back to the index, and unused arguments are omitted.
+        let host_function = HostFunction::new(100, [0, 2, 5]);
+        assert_eq!(
+            host_function.explain(&["ptr"]),
+            "100 + 2*len(arg1) + 5*len(arg2)"
+        );
+        assert_eq!(HostFunction::<[Cost; 2]>::fixed(7).explain(&[]), "7");
+    }
+
+    #[test]
+    fn should_treat_trailing_zero_weights_as_absent() {
+        let without_arguments: HostFunction<Vec<Cost>> = HostFunction::new(5, vec![]);
+        let with_zero_argument: HostFunction<Vec<Cost>> = HostFunction::new(5, vec![0]);
+        assert_ne!(without_arguments, with_zero_argument);
+        assert!(without_arguments.semantically_equal(&with_zero_argument));
+        assert!(with_zero_argument.semantically_equal(&without_arguments));
+        assert!(HostFunction::new(5, [2, 0, 0]).semantically_equal(&HostFunction::new(5, vec![2])));
+    }
+
+    #[test]
+    fn should_not_treat_different_costs_as_semantically_equal() {
+        let host_function = HostFunction::new(5, [0, 2]);
+        assert!(!host_function.semantically_equal(&HostFunction::new(5, [2, 0])));
+        assert!(!host_function.semantically_equal(&HostFunction::new(6, [0, 2])));
+        assert!(!host_function.semantically_equal(&host_function.with_call_limit(1)));
+    }
+
+    #[test]
+    fn get_named_arg_cost_should_scale_with_value_size() {
+        let get_named_arg = HostFunctionCosts::default().get_named_arg;
+        // The name is the same, only the size of the copied value differs.
+        let small = get_named_arg.calculate_gas_cost([0, 6, 0, 10]);
+        let large = get_named_arg.calculate_gas_cost([0, 6, 0, 10_000]);
+        let per_byte = Gas::new(DEFAULT_GET_NAMED_ARG_DEST_SIZE_WEIGHT.into());
+        assert_eq!(large - small, per_byte * Gas::new(9_990.into()));
+        assert!(large > small * Gas::new(100.into()));
+    }
+
     #[test]
     fn calculate_gas_cost_would_overflow() {
         let large_value = Cost::max_value();
 
         let host_function = HostFunction::new(
-            large_value,
+            u64::from(large_value),
             [large_value, large_value, large_value, large_value],
         );
 
@@ -796,19 +1588,1222 @@ mod tests {
 
         assert_eq!(lhs, Gas::new(rhs));
     }
-}
 
-#[cfg(test)]
-mod proptests {
-    use proptest::prelude::*;
+    #[test]
+    fn calculate_gas_cost_with_granularity() {
+        // The cost is 10 + 2 * 3 = 16.
+        let host_function = HostFunction::new(10, [2]);
+        let cost = |granularity| {
+            host_function
+                .calculate_gas_cost_with_granularity([3], granularity)
+                .value()
+        };
+        // The default granularity leaves the cost unchanged.
+        assert_eq!(cost(1), U512::from(16));
+        assert_eq!(cost(0), U512::from(16));
+        // Costs at a boundary are not rounded.
+        assert_eq!(cost(8), U512::from(16));
+        assert_eq!(cost(16), U512::from(16));
+        // Costs between boundaries are rounded up.
+        assert_eq!(cost(5), U512::from(20));
+        assert_eq!(cost(10), U512::from(20));
+        assert_eq!(cost(100), U512::from(100));
+    }
 
-    use casper_types::bytesrepr;
+    #[test]
+    fn max_gas_for_host_function() {
+        let host_function = HostFunction::new(COST, ARGUMENT_COSTS);
+        let max_sizes = [10, 0, 1_000];
+        let expected =
+            COST + u64::from(ARGUMENT_COSTS[0]) * 10 + u64::from(ARGUMENT_COSTS[2]) * 1_000;
+        assert_eq!(host_function.max_gas(&max_sizes), Ok(expected));
+        // The bound matches the actual cost of a call with arguments of the maximum sizes.
+        assert_eq!(
+            host_function.calculate_gas_cost([10, 0, 1_000]),
+            Gas::from(expected)
+        );
+        assert_eq!(
+            host_function.max_gas(&[10, 0]),
+            Err(CostError::InvalidArgumentCount {
+                expected: 3,
+                actual: 2
+            })
+        );
+    }
 
-    use super::*;
+    #[test]
+    fn max_gas_would_overflow() {
+        let host_function = HostFunction::new(u64::from(Cost::max_value()), [Cost::max_value(), 1]);
+        assert_eq!(
+            host_function.max_gas(&[0, usize::max_value()]),
+            Err(CostError::Overflow)
+        );
+        assert_eq!(
+            host_function.max_gas(&[usize::max_value(), 0]),
+            Err(CostError::Overflow)
+        );
+        assert_eq!(
+            host_function.max_gas(&[1, 0]),
+            Ok(2 * u64::from(Cost::max_value()))
+        );
+    }
 
-    type Signature = [Cost; 10];
+    #[test]
+    fn calculate_gas_saturating_should_match_max_gas_without_overflow() {
+        let host_function = HostFunction::new(COST, ARGUMENT_COSTS);
+        for arg_sizes in &[[0_usize, 0, 0], [10, 0, 1_000], [1, 2, 3]] {
+            assert_eq!(
+                Ok(host_function.calculate_gas_saturating(arg_sizes)),
+                host_function.max_gas(arg_sizes)
+            );
+        }
+    }
+
+    #[test]
+    fn calculate_gas_saturating_should_saturate_on_overflow() {
+        let host_function = HostFunction::new(u64::from(Cost::max_value()), [Cost::max_value(), 1]);
+        for arg_sizes in &[[0, usize::max_value()], [usize::max_value(), 0]] {
+            assert_eq!(host_function.max_gas(arg_sizes), Err(CostError::Overflow));
+            assert_eq!(host_function.calculate_gas_saturating(arg_sizes), u64::MAX);
+        }
+        // Below the overflow, both agree.
+        assert_eq!(
+            Ok(host_function.calculate_gas_saturating(&[1, 0])),
+            host_function.max_gas(&[1, 0])
+        );
+    }
+
+    #[test]
+    fn should_reject_more_argument_sizes_than_the_arity() {
+        let host_function = HostFunction::new(COST, ARGUMENT_COSTS);
+        let expected = COST + u64::from(ARGUMENT_COSTS[0]) + 2 * u64::from(ARGUMENT_COSTS[1]);
+        // With the host function's arity, each size is weighted.
+        assert_eq!(host_function.check_arity(&[1, 2, 0]), Ok(()));
+        assert_eq!(host_function.calculate_gas_saturating(&[1, 2, 0]), expected);
+        // Too few sizes: the missing ones count as zero.
+        assert_eq!(host_function.check_arity(&[1, 2]), Ok(()));
+        assert_eq!(host_function.calculate_gas_saturating(&[1, 2]), expected);
+        assert_eq!(host_function.check_arity(&[]), Ok(()));
+        assert_eq!(host_function.calculate_gas_saturating(&[]), COST);
+        // Too many sizes are an error, even if the extra ones are zero.
+        assert_eq!(
+            host_function.check_arity(&[1, 2, 0, 0]),
+            Err(CostError::TooManyArguments { max: 3, actual: 4 })
+        );
+    }
+
+    #[test]
+    fn should_look_up_max_arity_by_name() {
+        assert_eq!(HostFunctionCosts::max_arity("read_value"), Some(3));
+        assert_eq!(HostFunctionCosts::max_arity("write"), Some(4));
+        assert_eq!(HostFunctionCosts::max_arity("no_such_function"), None);
+        // The arities don't depend on the cost table.
+        for (name, host_function) in gens::host_function_costs_from_seed(1).to_map() {
+            assert_eq!(
+                HostFunctionCosts::max_arity(&name),
+                Some(host_function.arguments().len())
+            );
+        }
+    }
+
+    #[test]
+    fn checked_gas_cost_should_reject_too_large_arguments() {
+        let host_function = HostFunction::new(COST, ARGUMENT_COSTS);
+        let max_size = 1_000;
+        // At the maximum size, the cost matches the unchecked one.
+        assert_eq!(
+            host_function.checked_gas_cost([max_size, 0, max_size], max_size),
+            Ok(host_function.calculate_gas_cost([max_size, 0, max_size]))
+        );
+        assert_eq!(
+            host_function.checked_gas_cost([0, max_size + 1, 0], max_size),
+            Err(CostError::ArgumentTooLarge {
+                size: max_size + 1,
+                max_size
+            })
+        );
+        // Unweighted arguments are not sizes, so they are not checked.
+        let host_function = HostFunction::new(COST, [0, 1]);
+        assert_eq!(
+            host_function.checked_gas_cost([Cost::max_value(), max_size], max_size),
+            Ok(Gas::from(COST + u64::from(max_size)))
+        );
+    }
+
+    #[test]
+    fn checked_gas_cost_should_fit_in_u64_at_maximum_size() {
+        let max = Cost::max_value();
+        let host_function = HostFunction::new(u64::from(max), [max]);
+        let expected = u64::from(max) + u64::from(max) * u64::from(max);
+        assert_eq!(
+            host_function.checked_gas_cost([max], max),
+            Ok(Gas::from(expected))
+        );
+        // With more arguments, only the sum can overflow.
+        let host_function = HostFunction::new(u64::from(max), [max, max]);
+        assert_eq!(
+            host_function.checked_gas_cost([max, max], max),
+            Err(CostError::Overflow)
+        );
+    }
+
+    #[test]
+    fn should_serialize_random_bytes_after_older_host_functions() {
+        let random_bytes = HostFunction::new(COST, [ARGUMENT_COSTS[0], ARGUMENT_COSTS[1]]);
+        let host_function_costs = HostFunctionCosts {
+            random_bytes,
+            ..Default::default()
+        };
+
+        let bytes = host_function_costs.to_bytes().unwrap();
+        let random_bytes_bytes = random_bytes.to_bytes().unwrap();
+        let tail_length = host_function_costs
+            .enable_contract_version
+            .serialized_length()
+            + host_function_costs.transfer.serialized_length();
+        assert!(bytes[..bytes.len() - tail_length].ends_with(&random_bytes_bytes));
+
+        let (deserialized, rem) = HostFunctionCosts::from_bytes(&bytes).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(deserialized.random_bytes, random_bytes);
+        assert_eq!(deserialized, host_function_costs);
+    }
+
+    #[test]
+    fn should_serialize_fields_in_declaration_order() {
+        let host_function_costs = HostFunctionCosts {
+            read_value: HostFunction::new(1, [2, 3, 4]),
+            transfer: HostFunction::new(5, [6, 7, 8, 9]),
+            ..Default::default()
+        };
+        let bytes = host_function_costs.to_bytes().unwrap();
+        assert!(bytes.starts_with(&host_function_costs.read_value.to_bytes().unwrap()));
+        assert!(bytes.ends_with(&host_function_costs.transfer.to_bytes().unwrap()));
+        bytesrepr::test_serialization_roundtrip(&host_function_costs);
+    }
+
+    #[test]
+    fn should_convert_complete_map() {
+        let mut map = HostFunctionCosts::default().to_map();
+        assert_eq!(map.len(), HostFunctionCosts::NAMES.len());
+        map.insert("write".to_string(), HostFunction::new(1, vec![2, 3, 4, 5]));
+
+        let host_function_costs = HostFunctionCosts::try_from(map).unwrap();
+        assert_eq!(
+            host_function_costs.write,
+            HostFunction::new(1, [2, 3, 4, 5])
+        );
+        assert_eq!(
+            host_function_costs.read_value,
+            HostFunctionCosts::default().read_value
+        );
+    }
+
+    #[test]
+    fn should_not_convert_map_with_unknown_key() {
+        let mut map = HostFunctionCosts::default().to_map();
+        map.insert("read_value_local".to_string(), HostFunction::fixed(1));
+        assert_eq!(
+            HostFunctionCosts::try_from(map),
+            Err(HostFunctionCostsError::UnknownKey(
+                "read_value_local".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn should_not_convert_incomplete_map() {
+        let mut map = HostFunctionCosts::default().to_map();
+        map.remove("random_bytes");
+        assert_eq!(
+            HostFunctionCosts::try_from(map),
+            Err(HostFunctionCostsError::MissingKey("random_bytes"))
+        );
+    }
+
+    #[test]
+    fn should_not_convert_map_with_wrong_argument_count() {
+        let mut map = HostFunctionCosts::default().to_map();
+        map.insert("revert".to_string(), HostFunction::new(1, vec![2, 3]));
+        assert_eq!(
+            HostFunctionCosts::try_from(map),
+            Err(HostFunctionCostsError::InvalidArgumentCount {
+                name: "revert",
+                expected: 1,
+                actual: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn should_generate_same_costs_from_same_seed() {
+        let host_function_costs = gens::host_function_costs_from_seed(42);
+        assert_eq!(host_function_costs, gens::host_function_costs_from_seed(42));
+        assert_ne!(host_function_costs, gens::host_function_costs_from_seed(43));
+    }
+
+    #[test]
+    fn should_charge_blake2b_per_input_byte() {
+        let blake2b = HostFunctionCosts::default().blake2b;
+        let (in_ptr, in_size, out_ptr, out_size) = (1024, 100, 2048, 32);
+        let expected_cost = U512::from(DEFAULT_FIXED_COST)
+            + U512::from(DEFAULT_BLAKE2B_INPUT_SIZE_WEIGHT) * U512::from(in_size);
+        assert_eq!(
+            blake2b.calculate_gas_cost([in_ptr, in_size, out_ptr, out_size]),
+            Gas::new(expected_cost)
+        );
+        // Hashing an empty input only costs the base fee.
+        assert_eq!(
+            blake2b.calculate_gas_cost([in_ptr, 0, out_ptr, out_size]),
+            Gas::new(DEFAULT_FIXED_COST.into())
+        );
+    }
+
+    #[test]
+    fn should_charge_dictionary_get_per_key_byte() {
+        let dictionary_get = HostFunctionCosts::default().dictionary_get;
+        let (key_ptr, key_size, output_size_ptr) = (1024, 64, 2048);
+        let expected_cost = U512::from(DEFAULT_DICTIONARY_GET_COST)
+            + U512::from(DEFAULT_DICTIONARY_GET_KEY_SIZE_WEIGHT) * U512::from(key_size);
+        assert_eq!(
+            dictionary_get.calculate_gas_cost([key_ptr, key_size, output_size_ptr]),
+            Gas::new(expected_cost)
+        );
+    }
+
+    #[test]
+    fn should_charge_dictionary_put_per_key_and_value_byte() {
+        let dictionary_put = HostFunctionCosts::default().dictionary_put;
+        let (key_ptr, key_size, value_ptr, value_size) = (1024, 64, 2048, 1_000);
+        let expected_cost = U512::from(DEFAULT_DICTIONARY_PUT_COST)
+            + U512::from(DEFAULT_DICTIONARY_PUT_KEY_BYTES_SIZE_WEIGHT) * U512::from(key_size)
+            + U512::from(DEFAULT_DICTIONARY_PUT_VALUE_SIZE_WEIGHT) * U512::from(value_size);
+        assert_eq!(
+            dictionary_put.calculate_gas_cost([key_ptr, key_size, value_ptr, value_size]),
+            Gas::new(expected_cost)
+        );
+        // The pointers are not charged, so the cost only depends on the sizes.
+        assert_eq!(
+            dictionary_put.calculate_gas_cost([0, key_size, 0, value_size]),
+            Gas::new(expected_cost)
+        );
+    }
+
+    #[test]
+    fn should_charge_call_contract_per_arg_byte_and_count() {
+        let (cost, per_byte, per_arg) = (4_500, 420, 1_000);
+        let call_contract = HostFunction::new(cost, [0, 0, 0, 0, 0, per_byte, 0, per_arg]);
+        let (args_size, args_count) = (250, 3);
+        let expected_cost = U512::from(cost)
+            + U512::from(per_byte) * U512::from(args_size)
+            + U512::from(per_arg) * U512::from(args_count);
+        assert_eq!(
+            call_contract.calculate_gas_cost([1, 32, 2, 8, 3, args_size, 4, args_count]),
+            Gas::new(expected_cost)
+        );
+        // Without runtime args, only the byte size of the (empty) args is charged.
+        assert_eq!(
+            call_contract.calculate_gas_cost([1, 32, 2, 8, 3, 4, 4, 0]),
+            Gas::new(U512::from(cost) + U512::from(per_byte) * U512::from(4))
+        );
+    }
+
+    #[test]
+    fn should_read_call_contract_costs_without_args_count_weight() {
+        #[derive(Deserialize)]
+        struct Costs {
+            #[serde(deserialize_with = "deserialize_with_added_last_weight")]
+            call_contract: HostFunction<[Cost; 8]>,
+        }
+
+        let old = "call_contract = { cost = 104, arguments = [0, 1, 2, 3, 4, 5, 6] }";
+        let parsed: Costs = toml::from_str(old).unwrap();
+        assert_eq!(
+            parsed.call_contract,
+            HostFunction::new(104, [0, 1, 2, 3, 4, 5, 6, 0])
+        );
+
+        let new = "call_contract = { cost = 104, arguments = [0, 1, 2, 3, 4, 5, 6, 7] }";
+        let parsed: Costs = toml::from_str(new).unwrap();
+        assert_eq!(
+            parsed.call_contract,
+            HostFunction::new(104, [0, 1, 2, 3, 4, 5, 6, 7])
+        );
+
+        let too_short = "call_contract = { cost = 104, arguments = [0, 1, 2, 3, 4, 5] }";
+        assert!(toml::from_str::<Costs>(too_short).is_err());
+    }
+
+    #[test]
+    fn should_digest_costs() {
+        let host_function_costs = gens::host_function_costs_from_seed(1);
+        assert_eq!(
+            host_function_costs.digest(),
+            gens::host_function_costs_from_seed(1).digest()
+        );
+        assert_ne!(
+            host_function_costs.digest(),
+            HostFunctionCosts::default().digest()
+        );
+        // A single changed weight changes the digest.
+        let mut changed = host_function_costs.clone();
+        changed.blake2b.arguments[1] = changed.blake2b.arguments[1].wrapping_add(1);
+        assert_ne!(host_function_costs.digest(), changed.digest());
+    }
+
+    #[test]
+    fn should_flag_zero_per_byte_weights() {
+        // All listed weights exist and are non-zero by default.
+        let defaults = HostFunctionCosts::default().to_map();
+        for (name, index) in SIZE_DEPENDENT_HOST_FUNCTIONS {
+            let weight = defaults[*name].arguments().get(*index).copied();
+            assert!(weight.unwrap_or(0) > 0, "{} argument {}", name, index);
+        }
+        assert_eq!(
+            HostFunctionCosts::default().check_per_byte_weights(),
+            Ok(())
+        );
+        assert_eq!(
+            HostFunctionCosts::mainnet_v1().check_per_byte_weights(),
+            Ok(())
+        );
+
+        let host_function_costs = HostFunctionCosts {
+            write: HostFunction::new(DEFAULT_WRITE_COST, [1, 1, 1, 0]),
+            ..Default::default()
+        };
+        assert_eq!(
+            host_function_costs.check_per_byte_weights(),
+            Err(HostFunctionCostsError::ZeroPerByteWeight {
+                names: vec!["write"]
+            })
+        );
+    }
+
+    /// The digest of `HostFunctionCosts::mainnet_v1()`.
+    const MAINNET_V1_DIGEST: &str =
+        "9c82c831a8f72ffeacbe727563001ae13f7ff24fc26c08c7f2361077c380fb05";
+
+    #[test]
+    fn should_not_change_presets() {
+        // If this fails, the preset was changed. Presets are the costs of existing networks, so
+        // add a new preset instead.
+        assert_eq!(
+            base16::encode_lower(&HostFunctionCosts::mainnet_v1().digest()),
+            MAINNET_V1_DIGEST
+        );
+    }
+
+    #[test]
+    fn should_charge_for_every_host_function_in_presets() {
+        assert!(HostFunctionCosts::mainnet_v1()
+            .below_gas_floor(1)
+            .is_empty());
+    }
+
+    #[test]
+    fn should_estimate_gas_of_multiple_calls() {
+        let host_function_costs = HostFunctionCosts {
+            read_value: HostFunction::new(10, [1, 2, 3]),
+            transfer: HostFunction::new(100, [0, 0, 0, 0]),
+            ..Default::default()
+        };
+        let calls = [
+            ("read_value", vec![4, 5, 6]),
+            ("transfer", vec![32, 32, 8, 8]),
+            ("read_value", vec![0, 0, 1]),
+        ];
+        let expected = (10 + 4 + 2 * 5 + 3 * 6) + 100 + (10 + 3);
+        assert_eq!(host_function_costs.estimate(&calls), Ok(expected));
+        assert_eq!(host_function_costs.estimate(&[]), Ok(0));
+
+        assert_eq!(
+            host_function_costs.estimate(&[("read_value", vec![1, 2])]),
+            Err(CostError::InvalidArgumentCount {
+                expected: 3,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn should_not_estimate_gas_of_unknown_function() {
+        let calls = [("read_value", vec![0, 0, 0]), ("no_such_function", vec![])];
+        assert_eq!(
+            HostFunctionCosts::default().estimate(&calls),
+            Err(CostError::UnknownFunction {
+                name: "no_such_function".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn should_estimate_gas_from_trace() {
+        let host_function_costs = HostFunctionCosts {
+            read_value: HostFunction::new(10, [1, 2, 3]),
+            write: HostFunction::new(20, [0, 1, 0, 4]),
+            get_caller: HostFunction::fixed(5),
+            ..Default::default()
+        };
+        let trace = [
+            HostCallRecord::new("get_caller", vec![]),
+            HostCallRecord::new("read_value", vec![4, 5, 6]),
+            HostCallRecord::new("write", vec![8, 32, 8, 100]),
+            HostCallRecord::new("read_value", vec![0, 7]),
+        ];
+        let expected = 5 + (10 + 4 + 2 * 5 + 3 * 6) + (20 + 32 + 4 * 100) + (10 + 2 * 7);
+        assert_eq!(
+            host_function_costs.estimate_from_trace(&trace),
+            Ok(expected)
+        );
+        assert_eq!(host_function_costs.estimate_from_trace(&[]), Ok(0));
+
+        let unknown = [HostCallRecord::new("no_such_function", vec![])];
+        assert_eq!(
+            host_function_costs.estimate_from_trace(&unknown),
+            Err(CostError::UnknownFunction {
+                name: "no_such_function".to_string()
+            })
+        );
+        let too_many_args = [HostCallRecord::new("get_caller", vec![1, 2])];
+        assert_eq!(
+            host_function_costs.estimate_from_trace(&too_many_args),
+            Err(CostError::TooManyArguments { max: 1, actual: 2 })
+        );
+    }
+
+    #[test]
+    fn should_format_costs_as_toml() {
+        let host_function_costs = HostFunctionCosts {
+            read_value: HostFunction::new(1, [2, 3, 4]),
+            ..Default::default()
+        };
+        let toml = host_function_costs.to_toml_string();
+        assert!(toml.starts_with("read_value = { cost = 1, arguments = [2, 3, 4] }\n"));
+        assert_eq!(toml.lines().count(), HostFunctionCosts::NAMES.len());
+        let names: Vec<&str> = toml
+            .lines()
+            .map(|line| line.split(" = ").next().unwrap())
+            .collect();
+        assert_eq!(names, HostFunctionCosts::NAMES);
+    }
+
+    #[test]
+    fn should_only_consider_zero_cost_without_weights_free() {
+        assert!(HostFunction::<[Cost; 3]>::fixed(0).is_free());
+        assert!(!HostFunction::<[Cost; 3]>::fixed(COST).is_free());
+        assert!(!HostFunction::<[Cost; 3]>::new(0, [0, 1, 0]).is_free());
+    }
+
+    #[test]
+    fn should_list_free_host_functions() {
+        assert!(HostFunctionCosts::default()
+            .free_host_functions()
+            .is_empty());
+        let host_function_costs = HostFunctionCosts {
+            revert: HostFunction::fixed(0),
+            get_phase: HostFunction::fixed(0),
+            blake2b: HostFunction::new(0, [0, 1, 0, 0]),
+            ..Default::default()
+        };
+        assert_eq!(
+            host_function_costs.free_host_functions(),
+            vec!["revert", "get_phase"]
+        );
+    }
+
+    #[test]
+    fn should_list_defaulted_fields() {
+        assert_eq!(
+            HostFunctionCosts::default().defaulted_fields(),
+            HostFunctionCosts::NAMES
+        );
+        // A table where all but three entries were configured with non-default costs.
+        let defaults = HostFunctionCosts::default();
+        let host_function_costs = HostFunctionCosts {
+            read_value: defaults.read_value,
+            get_phase: defaults.get_phase,
+            enable_contract_version: defaults.enable_contract_version,
+            ..gens::host_function_costs_from_seed(1)
+        };
+        assert_eq!(
+            host_function_costs.defaulted_fields(),
+            vec!["read_value", "get_phase", "enable_contract_version"]
+        );
+    }
+
+    #[test]
+    fn should_rank_host_functions_by_nominal_cost() {
+        let write: HostFunction<[Cost; 4]> = HostFunction::new(100, [0, 10, 0, 50]);
+        let read: HostFunction<[Cost; 4]> = HostFunction::new(160, [0, 0, 0, 0]);
+        let revert: HostFunction<[Cost; 4]> = HostFunction::new(200, [0, 0, 0, 0]);
+        let max: HostFunction<[Cost; 4]> =
+            HostFunction::new(u64::from(Cost::max_value()), [Cost::max_value(); 4]);
+        assert_eq!(write.nominal_cost(), 160);
+        assert_eq!(max.nominal_cost(), 5 * u64::from(Cost::max_value()));
+
+        let mut functions = vec![
+            ("write", write),
+            ("max", max),
+            ("read", read),
+            ("revert", revert),
+        ];
+        functions.sort_by_key(|(_, function)| Reverse(function.nominal_cost()));
+        let names: Vec<&str> = functions.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, vec!["max", "revert", "write", "read"]);
+
+        let ranking = HostFunctionCosts::default().ranked_by_nominal_cost();
+        assert_eq!(ranking.len(), HostFunctionCosts::NAMES.len());
+        assert!(ranking.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        assert_eq!(
+            ranking[..3],
+            [
+                ("create_purse", 2_500_000_000),
+                ("transfer_to_account", 2_500_000_000),
+                ("transfer_from_purse_to_account", 2_500_000_000)
+            ]
+        );
+    }
+
+    #[test]
+    fn should_reject_costs_below_gas_floor() {
+        let mut host_function_costs = HostFunctionCosts {
+            revert: HostFunction::fixed(0),
+            get_phase: HostFunction::new(10, [123]),
+            ..Default::default()
+        };
+        let original = host_function_costs.clone();
+        let gas_floor = GasFloor {
+            min_cost: 100,
+            policy: GasFloorPolicy::Reject,
+        };
+        assert_eq!(
+            host_function_costs.apply_gas_floor(gas_floor),
+            Err(HostFunctionCostsError::BelowGasFloor {
+                min_cost: 100,
+                names: vec!["revert", "get_phase"],
+            })
+        );
+        assert_eq!(host_function_costs, original);
+
+        // The default costs are all above the floor.
+        let mut host_function_costs = HostFunctionCosts::default();
+        assert_eq!(host_function_costs.apply_gas_floor(gas_floor), Ok(()));
+        assert_eq!(host_function_costs, HostFunctionCosts::default());
+    }
+
+    #[test]
+    fn should_clamp_costs_below_gas_floor() {
+        let mut host_function_costs = HostFunctionCosts {
+            revert: HostFunction::fixed(0),
+            get_phase: HostFunction::new(10, [123]),
+            ..Default::default()
+        };
+        let gas_floor = GasFloor {
+            min_cost: 100,
+            policy: GasFloorPolicy::Clamp,
+        };
+        assert_eq!(host_function_costs.apply_gas_floor(gas_floor), Ok(()));
+        assert!(host_function_costs.below_gas_floor(100).is_empty());
+        assert_eq!(host_function_costs.revert, HostFunction::fixed(100));
+        // Only the fixed cost is raised; the argument weights are kept.
+        assert_eq!(host_function_costs.get_phase, HostFunction::new(100, [123]));
+        assert_eq!(
+            host_function_costs.write,
+            HostFunctionCosts::default().write
+        );
+    }
+
+    #[test]
+    fn should_roundtrip_blake2b() {
+        let blake2b = HostFunction::new(COST, [0, 123, 0, 0]);
+        let host_function_costs = HostFunctionCosts {
+            blake2b,
+            ..Default::default()
+        };
+        let bytes = host_function_costs.to_bytes().unwrap();
+        let (deserialized, rem) = HostFunctionCosts::from_bytes(&bytes).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(deserialized.blake2b, blake2b);
+        assert_eq!(deserialized, host_function_costs);
+    }
+
+    #[test]
+    fn should_roundtrip_dictionary_costs() {
+        let dictionary_get = HostFunction::new(COST, [0, 123, 0]);
+        let dictionary_put = HostFunction::new(COST, [0, 456, 0, 789]);
+        let host_function_costs = HostFunctionCosts {
+            dictionary_get,
+            dictionary_put,
+            ..Default::default()
+        };
+        let bytes = host_function_costs.to_bytes().unwrap();
+        let (deserialized, rem) = HostFunctionCosts::from_bytes(&bytes).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(deserialized.dictionary_get, dictionary_get);
+        assert_eq!(deserialized.dictionary_put, dictionary_put);
+        assert_eq!(deserialized, host_function_costs);
+
+        // Chainspecs predating the rename still use the old names.
+        let toml = host_function_costs
+            .to_toml_string()
+            .replace("dictionary_get =", "read_value_local =")
+            .replace("dictionary_put =", "write_local =");
+        let from_toml: HostFunctionCosts = toml::from_str(&toml).unwrap();
+        assert_eq!(from_toml, host_function_costs);
+    }
+
+    /// The serialized bytes of `golden_host_function_costs`, one host function per entry.
+    ///
+    /// The serialized cost table is part of the chainspec and the global state, so its layout is
+    /// consensus-critical. If this test fails after an intentional change, e.g. because a host
+    /// function was added, the protocol version needs to be bumped, and these entries replaced by
+    /// the output of `golden_vector_source`.
+    const GOLDEN_HOST_FUNCTION_COSTS: &[&str] = &[
+        // read_value
+        "e8030000e9030000ea030000eb030000",
+        // dictionary_get
+        "d0070000d1070000d2070000d3070000",
+        // write
+        "b80b0000b90b0000ba0b0000bb0b0000bc0b0000",
+        // dictionary_put
+        "a00f0000a10f0000a20f0000a30f0000a40f0000",
+        // add
+        "88130000891300008a1300008b1300008c130000",
+        // new_uref
+        "70170000711700007217000073170000",
+        // load_named_keys
+        "581b0000591b00005a1b0000",
+        // ret
+        "401f0000411f0000421f0000",
+        // get_key
+        "28230000292300002a2300002b2300002c2300002d230000",
+        // has_key
+        "102700001127000012270000",
+        // put_key
+        "f82a0000f92a0000fa2a0000fb2a0000fc2a0000",
+        // remove_key
+        "e02e0000e12e0000e22e0000",
+        // revert
+        "c8320000c9320000",
+        // is_valid_uref
+        "b0360000b1360000b2360000",
+        // add_associated_key
+        "983a0000993a00009a3a00009b3a0000",
+        // remove_associated_key
+        "803e0000813e0000823e0000",
+        // update_associated_key
+        "68420000694200006a4200006b420000",
+        // set_action_threshold
+        "504600005146000052460000",
+        // get_caller
+        "384a0000394a0000",
+        // get_blocktime
+        "204e0000214e0000",
+        // create_purse
+        "08520000095200000a520000",
+        // transfer_to_account
+        "f0550000f1550000f2550000f3550000f4550000f5550000f6550000f7550000",
+        // transfer_from_purse_to_account
+        "d8590000d9590000da590000db590000dc590000dd590000de590000df590000e0590000e1590000",
+        // transfer_from_purse_to_purse
+        "c05d0000c15d0000c25d0000c35d0000c45d0000c55d0000c65d0000c75d0000c85d0000",
+        // get_balance
+        "a8610000a9610000aa610000ab610000",
+        // get_phase
+        "9065000091650000",
+        // get_system_contract
+        "78690000796900007a6900007b690000",
+        // get_main_purse
+        "606d0000616d0000",
+        // read_host_buffer
+        "48710000497100004a7100004b710000",
+        // create_contract_package_at_hash
+        "307500003175000032750000",
+        // create_contract_user_group
+        "18790000197900001a7900001b7900001c7900001d7900001e7900001f79000020790000",
+        // add_contract_version
+        "007d0000017d0000027d0000037d0000047d0000057d0000067d0000077d0000087d0000097d00000a7d0000",
+        // disable_contract_version
+        "e8800000e9800000ea800000eb800000ec800000",
+        // call_contract
+        "d0840000d1840000d2840000d3840000d4840000d5840000d6840000d7840000d8840000",
+        // call_versioned_contract
+        "b8880000b9880000ba880000bb880000bc880000bd880000be880000bf880000c0880000c1880000c2880000",
+        // get_named_arg_size
+        "a08c0000a18c0000a28c0000a38c0000",
+        // get_named_arg
+        "88900000899000008a9000008b9000008c900000",
+        // remove_contract_user_group
+        "7094000071940000729400007394000074940000",
+        // provision_contract_user_group_uref
+        "58980000599800005a9800005b9800005c9800005d980000",
+        // remove_contract_user_group_urefs
+        "409c0000419c0000429c0000439c0000449c0000459c0000469c0000",
+        // print
+        "28a0000029a000002aa00000",
+        // blake2b
+        "10a4000011a4000012a4000013a4000014a40000",
+        // random_bytes
+        "f8a70000f9a70000faa70000",
+        // enable_contract_version
+        "e0ab0000e1ab0000e2ab0000e3ab0000e4ab0000",
+        // transfer
+        "c8af0000c9af0000caaf0000cbaf0000ccaf0000",
+    ];
+
+    /// Returns a cost table where every cost and argument weight is distinct, so that any change
+    /// to the order or layout of the fields changes the serialized bytes.
+    fn golden_host_function_costs() -> HostFunctionCosts {
+        let defaults = HostFunctionCosts::default().to_map();
+        let map = HostFunctionCosts::NAMES
+            .iter()
+            .zip(1..)
+            .map(|(name, index)| {
+                let cost = 1000 * index;
+                let arguments = (1..=defaults[*name].arguments().len() as Cost)
+                    .map(|i| cost + i)
+                    .collect();
+                (
+                    name.to_string(),
+                    HostFunction::new(u64::from(cost), arguments),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        HostFunctionCosts::try_from(map).expect("should convert complete map")
+    }
+
+    #[test]
+    fn should_read_legacy_host_function() {
+        // The `write` cost of mainnet, as written before host functions were versioned: the cost
+        // as a `u32`, and the argument weights.
+        let bytes = base16::decode("b0360000000000000000000000000000d4030000").unwrap();
+        let (host_function, rem) = HostFunction::<[Cost; 4]>::from_bytes(&bytes).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(host_function, HostFunction::new(14_000, [0, 0, 0, 980]));
+
+        // Newly written blobs without a call limit or a large cost use the same layout.
+        let written = host_function.to_bytes().unwrap();
+        assert_eq!(written, bytes);
+        assert_eq!(written.len(), host_function.serialized_length());
+        bytesrepr::test_serialization_roundtrip(&host_function);
+    }
+
+    #[test]
+    fn should_read_legacy_host_function_costs() {
+        // A whole cost table in the legacy layout, i.e. the fields concatenated without any
+        // version bytes.
+        let map = HostFunctionCosts::mainnet_v1().to_map();
+        let mut bytes = vec![];
+        for host_function in HostFunctionCosts::NAMES.iter().map(|name| &map[*name]) {
+            let cost = u32::try_from(host_function.cost()).unwrap();
+            bytes.extend_from_slice(&cost.to_le_bytes());
+            for weight in host_function.arguments() {
+                bytes.extend_from_slice(&weight.to_le_bytes());
+            }
+        }
+        assert_eq!(
+            HostFunctionCosts::checked_from_bytes(&bytes),
+            Ok(HostFunctionCosts::mainnet_v1())
+        );
+        assert_eq!(HostFunctionCosts::mainnet_v1().to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn should_reject_unknown_host_function_version() {
+        let mut bytes = HostFunction::new(COST, [7, 8])
+            .with_call_limit(3)
+            .to_bytes()
+            .unwrap();
+        assert_eq!(bytes[..4], HOST_FUNCTION_VERSION_MARKER.to_le_bytes());
+        for version in &[0, HOST_FUNCTION_VERSION_1, 4, u8::max_value()] {
+            bytes[4] = *version;
+            assert_eq!(
+                HostFunction::<[Cost; 2]>::from_bytes(&bytes),
+                Err(bytesrepr::Error::Formatting)
+            );
+        }
+    }
+
+    #[test]
+    fn should_not_limit_calls_by_default() {
+        let host_function: HostFunction<[Cost; 2]> = HostFunction::new(COST, [7, 8]);
+        assert_eq!(host_function.call_limit(), None);
+        assert_eq!(host_function.check_call_limit(u64::max_value()), Ok(()));
+        assert!(HostFunctionCosts::default()
+            .to_map()
+            .values()
+            .all(|host_function| host_function.call_limit().is_none()));
+
+        // Without a call limit, the legacy layout is written.
+        assert_eq!(
+            host_function.to_bytes().unwrap().len(),
+            3 * U32_SERIALIZED_LENGTH
+        );
+        let toml = toml::to_string(&host_function).unwrap();
+        assert!(!toml.contains("call_limit"));
+    }
+
+    #[test]
+    fn should_enforce_call_limit() {
+        let host_function: HostFunction<[Cost; 2]> =
+            HostFunction::new(COST, [7, 8]).with_call_limit(3);
+        assert_eq!(host_function.check_call_limit(0), Ok(()));
+        assert_eq!(host_function.check_call_limit(2), Ok(()));
+        assert_eq!(
+            host_function.check_call_limit(3),
+            Err(CostError::CallLimitExceeded { limit: 3 })
+        );
+
+        // The call limit is written in version 2, and survives a round trip through bytes, TOML
+        // and the map of host function costs.
+        let bytes = host_function.to_bytes().unwrap();
+        assert_eq!(bytes[..4], HOST_FUNCTION_VERSION_MARKER.to_le_bytes());
+        assert_eq!(bytes[4], HOST_FUNCTION_VERSION_2);
+        assert_eq!(bytes.len(), host_function.serialized_length());
+        bytesrepr::test_serialization_roundtrip(&host_function);
+        let host_function_costs = HostFunctionCosts {
+            random_bytes: host_function,
+            ..Default::default()
+        };
+        bytesrepr::test_serialization_roundtrip(&host_function_costs);
+        let toml = host_function_costs.to_toml_string();
+        assert!(toml.contains("random_bytes = { cost = 42, arguments = [7, 8], call_limit = 3 }"));
+        let parsed: HostFunctionCosts = toml::from_str(&toml).unwrap();
+        assert_eq!(parsed, host_function_costs);
+        let map = host_function_costs.to_map();
+        assert_eq!(HostFunctionCosts::try_from(map), Ok(host_function_costs));
+    }
+
+    #[test]
+    fn should_write_costs_above_u32_in_version_3() {
+        // The largest cost below the marker is still written in the legacy layout.
+        let legacy =
+            HostFunction::<[Cost; 2]>::new(u64::from(HOST_FUNCTION_VERSION_MARKER) - 1, [7, 8]);
+        let bytes = legacy.to_bytes().unwrap();
+        assert_eq!(bytes.len(), 3 * U32_SERIALIZED_LENGTH);
+        bytesrepr::test_serialization_roundtrip(&legacy);
+
+        // The marker itself can't be a legacy cost, so it is written in version 3.
+        let marker =
+            HostFunction::<[Cost; 2]>::new(u64::from(HOST_FUNCTION_VERSION_MARKER), [7, 8]);
+        let bytes = marker.to_bytes().unwrap();
+        assert_eq!(bytes[4], HOST_FUNCTION_VERSION_3);
+        bytesrepr::test_serialization_roundtrip(&marker);
+
+        // TOML integers are signed, so a cost table can't use the whole `u64` range.
+        let cost = 10_000_000_000;
+        for call_limit in &[None, Some(3)] {
+            let mut host_function = HostFunction::<[Cost; 2]>::new(cost, [7, 8]);
+            host_function.call_limit = *call_limit;
+            let bytes = host_function.to_bytes().unwrap();
+            assert_eq!(bytes[..4], HOST_FUNCTION_VERSION_MARKER.to_le_bytes());
+            assert_eq!(bytes[4], HOST_FUNCTION_VERSION_3);
+            assert_eq!(bytes[5..13], cost.to_le_bytes());
+            assert_eq!(bytes.len(), host_function.serialized_length());
+            bytesrepr::test_serialization_roundtrip(&host_function);
+
+            let host_function_costs = HostFunctionCosts {
+                random_bytes: host_function,
+                ..Default::default()
+            };
+            bytesrepr::test_serialization_roundtrip(&host_function_costs);
+            let toml = host_function_costs.to_toml_string();
+            let parsed: HostFunctionCosts = toml::from_str(&toml).unwrap();
+            assert_eq!(parsed, host_function_costs);
+        }
+    }
+
+    /// Returns the entries of `GOLDEN_HOST_FUNCTION_COSTS` for the given cost table, as Rust
+    /// source code.
+    fn golden_vector_source(host_function_costs: &HostFunctionCosts) -> String {
+        let map = host_function_costs.to_map();
+        HostFunctionCosts::NAMES
+            .iter()
+            .map(|name| {
+                let bytes = map[*name].to_bytes().unwrap();
+                format!(
+                    "        // {}\n        \"{}\",\n",
+                    name,
+                    base16::encode_lower(&bytes)
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_match_golden_serialization() {
+        let host_function_costs = golden_host_function_costs();
+        let bytes = host_function_costs.to_bytes().unwrap();
+        assert_eq!(
+            base16::encode_lower(&bytes),
+            GOLDEN_HOST_FUNCTION_COSTS.concat(),
+            "the serialized layout of the cost table changed; if this is intentional, bump the \
+             protocol version and update the golden vector to:\n{}",
+            golden_vector_source(&host_function_costs)
+        );
+        let (deserialized, rem) = HostFunctionCosts::from_bytes(&bytes).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(deserialized, host_function_costs);
+    }
+
+    #[test]
+    fn checked_from_bytes_should_reject_trailing_bytes() {
+        let host_function_costs = golden_host_function_costs();
+        let mut bytes = host_function_costs.to_bytes().unwrap();
+        assert_eq!(
+            HostFunctionCosts::checked_from_bytes(&bytes),
+            Ok(host_function_costs.clone())
+        );
+        bytes.push(0);
+        assert_eq!(
+            HostFunctionCosts::checked_from_bytes(&bytes),
+            Err(bytesrepr::Error::LeftOverBytes)
+        );
+        // The unchecked version returns the trailing byte instead.
+        let (deserialized, rem) = HostFunctionCosts::from_bytes(&bytes).unwrap();
+        assert_eq!(deserialized, host_function_costs);
+        assert_eq!(rem, [0]);
+    }
+
+    #[test]
+    fn should_reject_truncated_bytes_with_large_leading_value() {
+        // Bytes that would be a huge length prefix in a variable-length encoding.
+        let mut bytes = (u32::max_value() - 1).to_bytes().unwrap();
+        bytes.extend_from_slice(&[0xff; 16]);
+        assert_eq!(
+            HostFunctionCosts::from_bytes(&bytes),
+            Err(bytesrepr::Error::EarlyEndOfStream)
+        );
+        // Only the bytes of the cost table are consumed, whatever the values.
+        let map = HostFunctionCosts::default()
+            .to_map()
+            .into_iter()
+            .map(|(name, host_function)| {
+                let arguments = vec![u32::max_value(); host_function.arguments().len()];
+                (
+                    name,
+                    HostFunction::new(u64::from(u32::max_value() - 1), arguments),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        let mut bytes = HostFunctionCosts::try_from(map)
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+        bytes.push(0);
+        let (host_function_costs, rem) = HostFunctionCosts::from_bytes(&bytes).unwrap();
+        assert_eq!(rem, &[0]);
+        assert_eq!(
+            host_function_costs.read_value.cost(),
+            u64::from(u32::max_value() - 1)
+        );
+    }
+
+    #[test]
+    fn should_roundtrip_enable_contract_version() {
+        let enable_contract_version = HostFunction::new(COST, [123, 456, 789, 1011]);
+        let host_function_costs = HostFunctionCosts {
+            enable_contract_version,
+            ..Default::default()
+        };
+
+        let bytes = host_function_costs.to_bytes().unwrap();
+        assert_eq!(bytes.len(), host_function_costs.serialized_length());
+        let tail_length = host_function_costs.transfer.serialized_length();
+        assert!(bytes[..bytes.len() - tail_length]
+            .ends_with(&enable_contract_version.to_bytes().unwrap()));
+
+        let (deserialized, rem) = HostFunctionCosts::from_bytes(&bytes).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(
+            deserialized.enable_contract_version,
+            enable_contract_version
+        );
+        assert_eq!(deserialized, host_function_costs);
+
+        // Chainspecs predating the host function don't list it.
+        let toml = HostFunctionCosts::mainnet_v1()
+            .to_toml_string()
+            .lines()
+            .filter(|line| !line.starts_with("enable_contract_version ="))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let from_toml: HostFunctionCosts = toml::from_str(&toml).unwrap();
+        assert_eq!(from_toml, HostFunctionCosts::mainnet_v1());
+    }
+
+    #[test]
+    fn should_roundtrip_transfer() {
+        assert_eq!(HostFunctionCosts::NAMES.len(), 45);
+        assert_eq!(HostFunctionCosts::NAMES.last(), Some(&"transfer"));
+
+        let transfer = HostFunction::new(COST, [12, 34, 56, 78]);
+        let host_function_costs = HostFunctionCosts {
+            transfer,
+            ..Default::default()
+        };
+        let bytes = host_function_costs.to_bytes().unwrap();
+        assert_eq!(bytes.len(), host_function_costs.serialized_length());
+        assert!(bytes.ends_with(&transfer.to_bytes().unwrap()));
+
+        let (deserialized, rem) = HostFunctionCosts::from_bytes(&bytes).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(deserialized.transfer, transfer);
+        assert_eq!(deserialized, host_function_costs);
+
+        // Chainspecs predating the host function don't list it.
+        let toml = HostFunctionCosts::mainnet_v1()
+            .to_toml_string()
+            .lines()
+            .filter(|line| !line.starts_with("transfer ="))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let from_toml: HostFunctionCosts = toml::from_str(&toml).unwrap();
+        assert_eq!(from_toml, HostFunctionCosts::mainnet_v1());
+    }
+
+    fn with_extra_host_functions() -> HostFunctionCosts {
+        let mut host_function_costs = HostFunctionCosts::mainnet_v1();
+        host_function_costs.extra.insert(
+            "experimental_hash".to_string(),
+            HostFunction::new(300, vec![0, 25]),
+        );
+        host_function_costs.extra.insert(
+            "experimental \"nop\"".to_string(),
+            HostFunction::new(10, vec![]).with_call_limit(2),
+        );
+        host_function_costs
+    }
+
+    #[test]
+    fn should_keep_legacy_layout_without_extra_host_functions() {
+        let host_function_costs = HostFunctionCosts::mainnet_v1();
+        assert!(host_function_costs.extra.is_empty());
+        let map = host_function_costs.to_map();
+        let legacy: Vec<u8> = HostFunctionCosts::NAMES
+            .iter()
+            .flat_map(|name| map[*name].to_bytes().unwrap())
+            .collect();
+        assert_eq!(host_function_costs.to_bytes().unwrap(), legacy);
+        assert_eq!(host_function_costs.serialized_length(), legacy.len());
+        assert!(!host_function_costs.to_toml_string().contains("extra"));
+    }
+
+    #[test]
+    fn should_roundtrip_extra_host_functions() {
+        let host_function_costs = with_extra_host_functions();
+        let bytes = host_function_costs.to_bytes().unwrap();
+        assert_eq!(bytes.len(), host_function_costs.serialized_length());
+        assert_eq!(bytes[..4], HOST_FUNCTION_VERSION_MARKER.to_le_bytes());
+        assert_eq!(bytes[4], EXTRA_HOST_FUNCTIONS_TAG);
+        // The fixed host functions follow in the legacy layout.
+        let legacy = HostFunctionCosts::mainnet_v1().to_bytes().unwrap();
+        assert_eq!(bytes[5..5 + legacy.len()], legacy[..]);
+        bytesrepr::test_serialization_roundtrip(&host_function_costs);
+        assert_ne!(
+            host_function_costs.digest(),
+            HostFunctionCosts::mainnet_v1().digest()
+        );
+
+        let toml = host_function_costs.to_toml_string();
+        assert!(toml.contains("extra.experimental_hash = { cost = 300, arguments = [0, 25] }\n"));
+        let parsed: HostFunctionCosts = toml::from_str(&toml).unwrap();
+        assert_eq!(parsed, host_function_costs);
+    }
+
+    #[test]
+    fn should_reject_non_canonical_extra_host_functions() {
+        // An empty map must be written in the legacy layout.
+        let mut bytes = HOST_FUNCTION_VERSION_MARKER.to_bytes().unwrap();
+        bytes.push(EXTRA_HOST_FUNCTIONS_TAG);
+        bytes.extend(HostFunctionCosts::mainnet_v1().to_bytes().unwrap());
+        bytes.extend(0u32.to_bytes().unwrap());
+        assert_eq!(
+            HostFunctionCosts::from_bytes(&bytes),
+            Err(bytesrepr::Error::Formatting)
+        );
+
+        // The names must be in ascending order.
+        let host_function = HostFunction::<Vec<Cost>>::fixed(1);
+        let mut bytes = HOST_FUNCTION_VERSION_MARKER.to_bytes().unwrap();
+        bytes.push(EXTRA_HOST_FUNCTIONS_TAG);
+        bytes.extend(HostFunctionCosts::mainnet_v1().to_bytes().unwrap());
+        bytes.extend(2u32.to_bytes().unwrap());
+        for name in &["b", "a"] {
+            bytes.extend(name.to_string().to_bytes().unwrap());
+            bytes.extend(0u32.to_bytes().unwrap());
+            bytes.extend(host_function.to_bytes().unwrap());
+        }
+        assert_eq!(
+            HostFunctionCosts::from_bytes(&bytes),
+            Err(bytesrepr::Error::Formatting)
+        );
+
+        // A number of argument weights that the bytes can't hold isn't allocated. The last extra
+        // host function ends with its number of weights, its cost and its two weights.
+        let mut bytes = with_extra_host_functions().to_bytes().unwrap();
+        let arity_index = bytes.len() - 4 * U32_SERIALIZED_LENGTH;
+        bytes[arity_index..arity_index + 4].copy_from_slice(&u32::max_value().to_le_bytes());
+        assert_eq!(
+            HostFunctionCosts::from_bytes(&bytes),
+            Err(bytesrepr::Error::EarlyEndOfStream)
+        );
+    }
+
+    #[test]
+    fn should_look_up_extra_host_functions_after_fixed_ones() {
+        let mut host_function_costs = with_extra_host_functions();
+        assert_eq!(
+            host_function_costs.get("experimental_hash"),
+            Some(HostFunction::new(300, vec![0, 25]))
+        );
+        assert_eq!(
+            host_function_costs.get("write"),
+            Some(HostFunction::new(14_000, vec![0, 0, 0, 980]))
+        );
+        // An extra host function can't replace a fixed one.
+        host_function_costs
+            .extra
+            .insert("write".to_string(), HostFunction::fixed(1));
+        assert_eq!(
+            host_function_costs.get("write"),
+            Some(HostFunction::new(14_000, vec![0, 0, 0, 980]))
+        );
+        assert_eq!(host_function_costs.get("no_such_function"), None);
+
+        let calls = [
+            ("experimental_hash", vec![0, 4]),
+            ("write", vec![0, 0, 0, 1]),
+        ];
+        assert_eq!(
+            host_function_costs.estimate(&calls),
+            Ok(300 + 25 * 4 + 14_000 + 980)
+        );
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use std::convert::TryInto;
+
+    use proptest::prelude::*;
+
+    use casper_types::bytesrepr;
+
+    use super::*;
+
+    type Signature = [Cost; 10];
 
     proptest! {
+        #[test]
+        fn test_host_function_costs_toml_roundtrip(host_function_costs in gens::host_function_costs_arb()) {
+            let toml = host_function_costs.to_toml_string();
+            let parsed: HostFunctionCosts = toml::from_str(&toml).expect("should parse toml");
+            prop_assert_eq!(&parsed, &host_function_costs);
+            prop_assert_eq!(parsed.to_toml_string(), toml);
+        }
+
         #[test]
         fn test_host_function(host_function in gens::host_function_cost_arb::<Signature>()) {
             bytesrepr::test_serialization_roundtrip(&host_function);
@@ -818,5 +2813,37 @@ mod proptests {
         fn test_host_function_costs(host_function_costs in gens::host_function_costs_arb()) {
             bytesrepr::test_serialization_roundtrip(&host_function_costs);
         }
+
+        #[test]
+        fn test_host_function_costs_serialized_length(
+            host_function_costs in gens::host_function_costs_arb(),
+            trailing_bytes in proptest::collection::vec(any::<u8>(), 0..16),
+        ) {
+            let mut bytes = host_function_costs.to_bytes().unwrap();
+            prop_assert_eq!(bytes.len(), host_function_costs.serialized_length());
+
+            let (deserialized, rem) = HostFunctionCosts::from_bytes(&bytes).unwrap();
+            prop_assert!(rem.is_empty(), "{} trailing bytes after round-trip", rem.len());
+            prop_assert_eq!(&deserialized, &host_function_costs);
+
+            // Deserialization must consume exactly the bytes written by `to_bytes`.
+            bytes.extend_from_slice(&trailing_bytes);
+            let (deserialized, rem) = HostFunctionCosts::from_bytes(&bytes).unwrap();
+            prop_assert_eq!(rem, &trailing_bytes[..]);
+            prop_assert_eq!(&deserialized, &host_function_costs);
+        }
+
+        #[test]
+        fn test_host_function_costs_map_roundtrip(
+            host_function_costs in gens::host_function_costs_arb(),
+        ) {
+            let converted: HostFunctionCosts = host_function_costs.to_map().try_into().unwrap();
+            // The map only holds the fixed host functions.
+            let expected = HostFunctionCosts {
+                extra: BTreeMap::new(),
+                ..host_function_costs
+            };
+            prop_assert_eq!(converted, expected);
+        }
     }
 }
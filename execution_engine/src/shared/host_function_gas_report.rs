@@ -0,0 +1,110 @@
+//! Accounting of the gas spent on each host function.
+
+use std::collections::BTreeMap;
+
+use casper_types::Gas;
+
+/// The gas spent on the calls of a single host function.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct HostFunctionGas {
+    /// The total gas charged for the calls.
+    pub gas: Gas,
+    /// The number of calls.
+    pub calls: u64,
+}
+
+/// The gas spent on each host function during an execution, keyed by host function name.
+///
+/// This only covers the gas charged for calling host functions, not for executing Wasm opcodes or
+/// for storage.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HostFunctionGasReport(BTreeMap<&'static str, HostFunctionGas>);
+
+impl HostFunctionGasReport {
+    /// Records a call of the host function `name` that was charged the given amount of gas.
+    pub fn record(&mut self, name: &'static str, gas: Gas) {
+        let entry = self.0.entry(name).or_default();
+        entry.gas = entry.gas.checked_add(gas).unwrap_or(entry.gas);
+        entry.calls = entry.calls.saturating_add(1);
+    }
+
+    /// Adds gas charged for an already recorded call of the host function `name`, e.g. for an
+    /// argument that is only known after the call was charged.
+    pub fn record_additional_gas(&mut self, name: &'static str, gas: Gas) {
+        let entry = self.0.entry(name).or_default();
+        entry.gas = entry.gas.checked_add(gas).unwrap_or(entry.gas);
+    }
+
+    /// Adds all calls recorded in `other`, e.g. by a called contract, to this report.
+    pub fn merge(&mut self, other: HostFunctionGasReport) {
+        for (name, other_entry) in other.0 {
+            let entry = self.0.entry(name).or_default();
+            entry.gas = entry.gas.checked_add(other_entry.gas).unwrap_or(entry.gas);
+            entry.calls = entry.calls.saturating_add(other_entry.calls);
+        }
+    }
+
+    /// Returns the gas spent on the host function `name`, or `None` if it was never called.
+    pub fn get(&self, name: &str) -> Option<&HostFunctionGas> {
+        self.0.get(name)
+    }
+
+    /// Returns an iterator over all called host functions, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &HostFunctionGas)> {
+        self.0.iter().map(|(name, entry)| (*name, entry))
+    }
+
+    /// Returns the total gas spent on all host functions.
+    pub fn total_gas(&self) -> Gas {
+        self.0.values().map(|entry| entry.gas).sum()
+    }
+
+    /// Returns `true` if no host function was called.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::host_function_costs::{Cost, HostFunction};
+
+    #[test]
+    fn should_accumulate_gas_per_host_function() {
+        let read_value: HostFunction<[Cost; 3]> = HostFunction::new(100, [0, 2, 0]);
+        let write: HostFunction<[Cost; 4]> = HostFunction::new(1_000, [0, 3, 0, 5]);
+
+        let mut report = HostFunctionGasReport::default();
+        assert!(report.is_empty());
+        report.record("read_value", read_value.calculate_gas_cost([0, 10, 0]));
+        report.record("read_value", read_value.calculate_gas_cost([0, 20, 0]));
+        report.record("write", write.calculate_gas_cost([0, 10, 0, 100]));
+
+        assert_eq!(
+            report.get("read_value"),
+            Some(&HostFunctionGas {
+                gas: Gas::from(100 + 20 + 100 + 40_u64),
+                calls: 2,
+            })
+        );
+        assert_eq!(
+            report.get("write"),
+            Some(&HostFunctionGas {
+                gas: Gas::from(1_000 + 30 + 500_u64),
+                calls: 1,
+            })
+        );
+        assert_eq!(report.get("add"), None);
+        assert_eq!(report.total_gas(), Gas::from(1_790_u64));
+
+        // Calls recorded by a called contract are added to the caller's report.
+        let mut callee_report = HostFunctionGasReport::default();
+        callee_report.record("write", Gas::from(10_u64));
+        report.merge(callee_report);
+        let names: Vec<&str> = report.iter().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["read_value", "write"]);
+        assert_eq!(report.get("write").map(|entry| entry.calls), Some(2));
+        assert_eq!(report.total_gas(), Gas::from(1_800_u64));
+    }
+}
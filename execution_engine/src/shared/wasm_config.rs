@@ -18,7 +18,7 @@ pub const DEFAULT_MAX_STACK_HEIGHT: u32 = 188;
 ///
 /// This structure contains various Wasm execution configuration options, such as memory limits,
 /// stack limits and costs.
-#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug, DataSize)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug, DataSize)]
 pub struct WasmConfig {
     /// Maximum amount of heap memory (represented in 64kB pages) each contract can use.
     pub max_memory: u32,
@@ -60,10 +60,21 @@ impl WasmConfig {
         self.storage_costs
     }
 
+    /// Returns host function costs.
+    pub fn host_function_costs(&self) -> &HostFunctionCosts {
+        &self.host_function_costs
+    }
+
     /// Returns host function costs and consumes this object.
     pub fn take_host_function_costs(self) -> HostFunctionCosts {
         self.host_function_costs
     }
+
+    /// Returns this config with the host function costs replaced.
+    pub fn with_host_function_costs(mut self, host_function_costs: HostFunctionCosts) -> Self {
+        self.host_function_costs = host_function_costs;
+        self
+    }
 }
 
 impl Default for WasmConfig {
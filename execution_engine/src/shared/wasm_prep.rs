@@ -68,7 +68,7 @@ fn memory_section(module: &Module) -> Option<&MemorySection> {
 /// In case the preprocessing rules can't be applied, an error is returned.
 /// Otherwise, this method returns a valid module ready to be executed safely on the host.
 pub fn preprocess(
-    wasm_config: WasmConfig,
+    wasm_config: &WasmConfig,
     module_bytes: &[u8],
 ) -> Result<Module, PreprocessingError> {
     let module = deserialize(module_bytes)?;
@@ -112,7 +112,7 @@ mod tests {
             0x0f, 0x0b, 0x02, 0x00, 0x0b,
         ];
 
-        match preprocess(WasmConfig::default(), &MODULE_BYTES_WITH_EMPTY_MEMORY).unwrap_err() {
+        match preprocess(&WasmConfig::default(), &MODULE_BYTES_WITH_EMPTY_MEMORY).unwrap_err() {
             PreprocessingError::MissingMemorySection => (),
             error => panic!("expected MissingMemorySection, got {:?}", error),
         }
@@ -329,7 +329,7 @@ fn create_run_genesis_request(
     let exec_config = {
         ExecConfig::new(
             genesis_accounts,
-            *DEFAULT_WASM_CONFIG,
+            DEFAULT_WASM_CONFIG.clone(),
             *DEFAULT_SYSTEM_CONFIG,
             validator_slots,
             DEFAULT_AUCTION_DELAY,
@@ -129,7 +129,7 @@ pub static DEFAULT_SYSTEM_CONFIG: Lazy<SystemConfig> = Lazy::new(SystemConfig::d
 pub static DEFAULT_EXEC_CONFIG: Lazy<ExecConfig> = Lazy::new(|| {
     ExecConfig::new(
         DEFAULT_ACCOUNTS.clone(),
-        *DEFAULT_WASM_CONFIG,
+        DEFAULT_WASM_CONFIG.clone(),
         *DEFAULT_SYSTEM_CONFIG,
         DEFAULT_VALIDATOR_SLOTS,
         DEFAULT_AUCTION_DELAY,
@@ -134,7 +134,7 @@ pub fn read_wasm_file_bytes<T: AsRef<Path>>(contract_file: T) -> Vec<u8> {
 
 /// Returns an [`ExecConfig`].
 pub fn create_exec_config(accounts: Vec<GenesisAccount>) -> ExecConfig {
-    let wasm_config = *DEFAULT_WASM_CONFIG;
+    let wasm_config = DEFAULT_WASM_CONFIG.clone();
     let system_config = *DEFAULT_SYSTEM_CONFIG;
     let validator_slots = DEFAULT_VALIDATOR_SLOTS;
     let auction_delay = DEFAULT_AUCTION_DELAY;
@@ -70,7 +70,7 @@ fn main() {
 
     let exec_config = ExecConfig::new(
         DEFAULT_ACCOUNTS.clone(),
-        *DEFAULT_WASM_CONFIG,
+        DEFAULT_WASM_CONFIG.clone(),
         *DEFAULT_SYSTEM_CONFIG,
         DEFAULT_VALIDATOR_SLOTS,
         DEFAULT_AUCTION_DELAY,
@@ -564,7 +564,10 @@ fn should_fail_payment_stored_at_named_key_with_incompatible_major_version() {
     let mut upgrade_request = make_upgrade_request(new_protocol_version).build();
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     // next make another deploy that USES stored payment logic
@@ -647,7 +650,10 @@ fn should_fail_payment_stored_at_hash_with_incompatible_major_version() {
     let mut upgrade_request = make_upgrade_request(new_protocol_version).build();
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     // next make another deploy that USES stored payment logic
@@ -742,7 +748,10 @@ fn should_fail_session_stored_at_named_key_with_incompatible_major_version() {
     let mut upgrade_request = make_upgrade_request(new_protocol_version).build();
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     // Call stored session code
@@ -827,7 +836,10 @@ fn should_fail_session_stored_at_named_key_with_missing_new_major_version() {
     let mut upgrade_request = make_upgrade_request(new_protocol_version).build();
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     // Call stored session code
@@ -913,7 +925,10 @@ fn should_fail_session_stored_at_hash_with_incompatible_major_version() {
     let mut upgrade_request = make_upgrade_request(new_protocol_version).build();
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     // Call stored session code
@@ -993,7 +1008,10 @@ fn should_execute_stored_payment_and_session_code_with_new_major_version() {
     let mut upgrade_request = make_upgrade_request(new_protocol_version).build();
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     // first, store payment contract for v2.0.0
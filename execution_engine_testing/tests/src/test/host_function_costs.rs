@@ -133,6 +133,12 @@ fn should_measure_nested_host_function_call_cost() {
         !level_1_cost.is_zero(),
         "executing nested call should not cost zero"
     );
+    let level_1_calls = builder
+        .last_exec_result()
+        .host_function_gas()
+        .get("call_versioned_contract")
+        .map(|entry| entry.calls);
+    assert_eq!(level_1_calls, Some(1));
 
     //
     // Measure level 2 - call to an entrypoint that calls 'do nothing'
@@ -160,6 +166,14 @@ fn should_measure_nested_host_function_call_cost() {
         level_2_cost,
         level_1_cost,
     );
+
+    // The call made by the nested contract is included in the report.
+    let level_2_calls = builder
+        .last_exec_result()
+        .host_function_gas()
+        .get("call_versioned_contract")
+        .map(|entry| entry.calls);
+    assert_eq!(level_2_calls, Some(2));
 }
 
 #[ignore]
@@ -693,7 +693,10 @@ fn should_transfer_after_major_version_bump_from_1_2_0() {
     };
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     let transfer_args = runtime_args! {
@@ -741,7 +744,10 @@ fn should_transfer_after_minor_version_bump_from_1_2_0() {
     };
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     let transfer = ExecuteRequestBuilder::transfer(*DEFAULT_ACCOUNT_ADDR, transfer_args)
@@ -774,7 +780,10 @@ fn should_add_bid_after_major_bump() {
     };
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     let _default_account = builder
@@ -823,7 +832,10 @@ fn should_add_bid_after_minor_bump() {
     };
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     let _default_account = builder
@@ -869,7 +881,10 @@ fn should_wasm_transfer_after_major_bump() {
     };
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     let _default_account = builder
@@ -917,7 +932,10 @@ fn should_wasm_transfer_after_minor_bump() {
     };
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     let _default_account = builder
@@ -965,6 +983,9 @@ fn should_upgrade_from_1_3_1_rel_fixture() {
     };
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 }
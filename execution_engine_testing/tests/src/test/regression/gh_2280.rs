@@ -140,7 +140,7 @@ fn gh_2280_transfer_should_always_cost_the_same_gas() {
 
     let new_wasm_config = make_wasm_config(
         new_host_function_costs,
-        *builder.get_engine_state().config().wasm_config(),
+        builder.get_engine_state().config().wasm_config().clone(),
     );
 
     // Inflate affected system contract entry point cost to the maximum
@@ -251,7 +251,8 @@ fn gh_2280_create_purse_should_always_cost_the_same_gas() {
         .get_engine_state()
         .config()
         .wasm_config()
-        .take_host_function_costs();
+        .host_function_costs()
+        .clone();
 
     let default_create_purse_cost = host_function_costs.create_purse.cost();
     let new_create_purse_cost = default_create_purse_cost
@@ -266,7 +267,7 @@ fn gh_2280_create_purse_should_always_cost_the_same_gas() {
 
     let new_wasm_config = make_wasm_config(
         new_host_function_costs,
-        *builder.get_engine_state().config().wasm_config(),
+        builder.get_engine_state().config().wasm_config().clone(),
     );
 
     // Inflate affected system contract entry point cost to the maximum
@@ -394,7 +395,7 @@ fn gh_2280_transfer_purse_to_account_should_always_cost_the_same_gas() {
 
     let new_wasm_config = make_wasm_config(
         new_host_function_costs,
-        *builder.get_engine_state().config().wasm_config(),
+        builder.get_engine_state().config().wasm_config().clone(),
     );
 
     // Inflate affected system contract entry point cost to the maximum
@@ -523,7 +524,7 @@ fn gh_2280_stored_transfer_to_account_should_always_cost_the_same_gas() {
 
     let new_wasm_config = make_wasm_config(
         new_host_function_costs,
-        *builder.get_engine_state().config().wasm_config(),
+        builder.get_engine_state().config().wasm_config().clone(),
     );
 
     // Inflate affected system contract entry point cost to the maximum
@@ -649,7 +650,7 @@ fn gh_2280_stored_faucet_call_should_cost_the_same() {
 
     let new_wasm_config = make_wasm_config(
         new_host_function_costs,
-        *builder.get_engine_state().config().wasm_config(),
+        builder.get_engine_state().config().wasm_config().clone(),
     );
 
     // Inflate affected system contract entry point cost to the maximum
@@ -126,6 +126,9 @@ static NEW_HOST_FUNCTION_COSTS: Lazy<HostFunctionCosts> = Lazy::new(|| HostFunct
     print: HostFunction::fixed(0),
     blake2b: HostFunction::fixed(0),
     random_bytes: HostFunction::fixed(0),
+    enable_contract_version: HostFunction::fixed(0),
+    transfer: HostFunction::fixed(0),
+    extra: Default::default(),
 });
 static STORAGE_COSTS_ONLY: Lazy<WasmConfig> = Lazy::new(|| {
     WasmConfig::new(
@@ -133,7 +136,7 @@ static STORAGE_COSTS_ONLY: Lazy<WasmConfig> = Lazy::new(|| {
         DEFAULT_MAX_STACK_HEIGHT,
         NEW_OPCODE_COSTS,
         StorageCosts::default(),
-        *NEW_HOST_FUNCTION_COSTS,
+        NEW_HOST_FUNCTION_COSTS.clone(),
     )
 });
 
@@ -166,7 +169,7 @@ fn initialize_isolated_storage_costs() -> InMemoryWasmTestBuilder {
         DEFAULT_MAX_RUNTIME_CALL_STACK_HEIGHT,
         DEFAULT_MINIMUM_DELEGATION_AMOUNT,
         DEFAULT_STRICT_ARGUMENT_CHECKING,
-        *STORAGE_COSTS_ONLY,
+        STORAGE_COSTS_ONLY.clone(),
         SystemConfig::default(),
     );
 
@@ -119,7 +119,7 @@ fn should_run_genesis() {
 #[test]
 fn should_track_total_token_supply_in_mint() {
     let accounts = GENESIS_CUSTOM_ACCOUNTS.clone();
-    let wasm_config = *DEFAULT_WASM_CONFIG;
+    let wasm_config = DEFAULT_WASM_CONFIG.clone();
     let system_config = *DEFAULT_SYSTEM_CONFIG;
     let protocol_version = ProtocolVersion::V1_0_0;
     let validator_slots = DEFAULT_VALIDATOR_SLOTS;
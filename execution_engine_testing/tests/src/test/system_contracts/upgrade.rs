@@ -86,7 +86,7 @@ fn should_upgrade_only_protocol_version() {
 
     builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
 
-    let old_wasm_config = *builder.get_engine_state().config().wasm_config();
+    let old_wasm_config = builder.get_engine_state().config().wasm_config().clone();
 
     let sem_ver = PROTOCOL_VERSION.value();
     let new_protocol_version =
@@ -101,14 +101,17 @@ fn should_upgrade_only_protocol_version() {
     };
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     let upgraded_engine_config = builder.get_engine_state().config();
 
     assert_eq!(
         old_wasm_config,
-        *upgraded_engine_config.wasm_config(),
+        upgraded_engine_config.wasm_config().clone(),
         "upgraded costs should equal original costs"
     );
 }
@@ -140,7 +143,7 @@ fn should_allow_only_wasm_costs_patch_version() {
         DEFAULT_MAX_RUNTIME_CALL_STACK_HEIGHT,
         DEFAULT_MINIMUM_DELEGATION_AMOUNT,
         DEFAULT_STRICT_ARGUMENT_CHECKING,
-        new_wasm_config,
+        new_wasm_config.clone(),
         SystemConfig::default(),
     );
 
@@ -152,7 +155,7 @@ fn should_allow_only_wasm_costs_patch_version() {
 
     assert_eq!(
         new_wasm_config,
-        *upgraded_engine_config.wasm_config(),
+        upgraded_engine_config.wasm_config().clone(),
         "upgraded costs should equal new costs"
     );
 }
@@ -184,7 +187,7 @@ fn should_allow_only_wasm_costs_minor_version() {
         DEFAULT_MAX_RUNTIME_CALL_STACK_HEIGHT,
         DEFAULT_MINIMUM_DELEGATION_AMOUNT,
         DEFAULT_STRICT_ARGUMENT_CHECKING,
-        new_wasm_config,
+        new_wasm_config.clone(),
         SystemConfig::default(),
     );
 
@@ -196,7 +199,7 @@ fn should_allow_only_wasm_costs_minor_version() {
 
     assert_eq!(
         new_wasm_config,
-        *upgraded_engine_config.wasm_config(),
+        upgraded_engine_config.wasm_config().clone(),
         "upgraded costs should equal new costs"
     );
 }
@@ -208,7 +211,7 @@ fn should_not_downgrade() {
 
     builder.run_genesis(&PRODUCTION_RUN_GENESIS_REQUEST);
 
-    let old_wasm_config = *builder.get_engine_state().config().wasm_config();
+    let old_wasm_config = builder.get_engine_state().config().wasm_config().clone();
 
     let new_protocol_version = ProtocolVersion::from_parts(2, 0, 0);
 
@@ -221,14 +224,17 @@ fn should_not_downgrade() {
     };
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     let upgraded_engine_config = builder.get_engine_state().config();
 
     assert_eq!(
         old_wasm_config,
-        *upgraded_engine_config.wasm_config(),
+        upgraded_engine_config.wasm_config().clone(),
         "upgraded costs should equal original costs"
     );
 
@@ -240,8 +246,10 @@ fn should_not_downgrade() {
             .build()
     };
 
-    builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut downgrade_request);
+    builder.upgrade_with_upgrade_request(
+        builder.get_engine_state().config().clone(),
+        &mut downgrade_request,
+    );
 
     let maybe_upgrade_result = builder.get_upgrade_result(1).expect("should have response");
 
@@ -272,8 +280,10 @@ fn should_not_skip_major_versions() {
             .build()
     };
 
-    builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request);
+    builder.upgrade_with_upgrade_request(
+        builder.get_engine_state().config().clone(),
+        &mut upgrade_request,
+    );
 
     let maybe_upgrade_result = builder.get_upgrade_result(0).expect("should have response");
 
@@ -301,8 +311,10 @@ fn should_allow_skip_minor_versions() {
             .build()
     };
 
-    builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request);
+    builder.upgrade_with_upgrade_request(
+        builder.get_engine_state().config().clone(),
+        &mut upgrade_request,
+    );
 
     let maybe_upgrade_result = builder.get_upgrade_result(0).expect("should have response");
 
@@ -346,7 +358,10 @@ fn should_upgrade_only_validator_slots() {
     };
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     let after_validator_slots: u32 = builder
@@ -401,7 +416,10 @@ fn should_upgrade_only_auction_delay() {
     };
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     let after_auction_delay: u64 = builder
@@ -456,7 +474,10 @@ fn should_upgrade_only_locked_funds_period() {
     };
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     let after_locked_funds_period_millis: u64 = builder
@@ -511,7 +532,10 @@ fn should_upgrade_only_round_seigniorage_rate() {
     };
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     let after_round_seigniorage_rate: Ratio<U512> = builder
@@ -573,7 +597,10 @@ fn should_upgrade_only_unbonding_delay() {
     };
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     let after_unbonding_delay: u64 = builder
@@ -637,7 +664,10 @@ fn should_apply_global_state_upgrade() {
     };
 
     builder
-        .upgrade_with_upgrade_request(*builder.get_engine_state().config(), &mut upgrade_request)
+        .upgrade_with_upgrade_request(
+            builder.get_engine_state().config().clone(),
+            &mut upgrade_request,
+        )
         .expect_upgrade_success();
 
     let after_unbonding_delay: u64 = builder
@@ -682,7 +712,7 @@ fn should_increase_max_associated_keys_after_upgrade() {
         DEFAULT_MAX_RUNTIME_CALL_STACK_HEIGHT,
         DEFAULT_MINIMUM_DELEGATION_AMOUNT,
         DEFAULT_STRICT_ARGUMENT_CHECKING,
-        *DEFAULT_WASM_CONFIG,
+        DEFAULT_WASM_CONFIG.clone(),
         new_system_config,
     );
 
@@ -695,7 +725,7 @@ fn should_increase_max_associated_keys_after_upgrade() {
     };
 
     builder
-        .upgrade_with_upgrade_request(new_engine_config, &mut upgrade_request)
+        .upgrade_with_upgrade_request(new_engine_config.clone(), &mut upgrade_request)
         .expect_upgrade_success();
 
     for n in (0..DEFAULT_MAX_ASSOCIATED_KEYS).map(U256::from) {
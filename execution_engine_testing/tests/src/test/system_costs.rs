@@ -974,6 +974,9 @@ fn should_verify_wasm_add_bid_wasm_cost_is_not_recursive() {
         print: HostFunction::fixed(0),
         blake2b: HostFunction::fixed(0),
         random_bytes: HostFunction::fixed(0),
+        enable_contract_version: HostFunction::fixed(0),
+        transfer: HostFunction::fixed(0),
+        extra: Default::default(),
     };
 
     let new_wasm_config = WasmConfig::new(
@@ -27,46 +27,123 @@ use std::{
 
 use datasize::DataSize;
 use derive_more::From;
-use hex_fmt::HexFmt;
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
-use casper_types::{EraId, PublicKey, Timestamp};
+use casper_types::{crypto, EraId, PublicKey, SecretKey, Signature, Timestamp};
 
 use crate::{
     components::Component,
     effect::{
         announcements::{BlocklistAnnouncement, ConsensusAnnouncement},
-        diagnostics_port::DumpConsensusStateRequest,
+        diagnostics_port::{ConsensusAction, ConsensusActionRequest, DumpConsensusStateRequest},
         incoming::ConsensusMessageIncoming,
         requests::{
             BlockProposerRequest, BlockValidationRequest, ChainspecLoaderRequest, ConsensusRequest,
-            ContractRuntimeRequest, NetworkInfoRequest, NetworkRequest, StorageRequest,
+            ContractRuntimeRequest, NetworkInfoRequest, NetworkRequest, StateStoreRequest,
+            StorageRequest,
         },
         EffectBuilder, EffectExt, Effects,
     },
+    fatal,
     protocol::Message,
     reactor::ReactorEvent,
-    types::{ActivationPoint, BlockHash, BlockHeader, BlockPayload, NodeId},
+    types::{ActivationPoint, BlockHash, BlockHeader, BlockPayload, BlockSignatures, NodeId},
     NodeRng,
 };
+use error::ConsensusError;
+use utils::RedactedPayload;
 
 pub(crate) use cl_context::ClContext;
 pub(crate) use config::{ChainspecConsensusExt, Config};
 pub(crate) use consensus_protocol::{BlockContext, EraReport, ProposedBlock};
-pub(crate) use era_supervisor::{debug::EraDump, EraSupervisor};
+pub(crate) use era_supervisor::{debug::EraDump, Checkpoint, EraSupervisor};
 pub(crate) use protocols::highway::HighwayProtocol;
 
 pub(crate) use utils::{check_sufficient_finality_signatures, get_minimal_set_of_signatures};
 pub(crate) use validator_change::ValidatorChange;
 
-#[derive(DataSize, Clone, Serialize, Deserialize)]
+/// A message to or from the consensus component of a peer.
+///
+/// The serialized layout is part of the wire protocol: reordering or renaming variants or fields
+/// breaks compatibility with other node versions, so any intentional change requires a wire
+/// version bump, and new variants must be added at the end. The golden vectors in
+/// `tests::wire_format` guard against accidental changes.
+#[derive(DataSize, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub(crate) enum ConsensusMessage {
     /// A protocol message, to be handled by the instance in the specified era.
     Protocol { era_id: EraId, payload: Vec<u8> },
     /// A request for evidence against the specified validator, from any era that is still bonded
     /// in `era_id`.
     EvidenceRequest { era_id: EraId, pub_key: PublicKey },
+    /// A latency probe. The recipient answers with a `Pong` with the same nonce.
+    Ping { era_id: EraId, nonce: u64 },
+    /// The answer to a `Ping`, used to measure the round-trip time to the sender.
+    Pong { era_id: EraId, nonce: u64 },
+    /// Several protocol messages for the same era, each of which is handled like a separate
+    /// `Protocol` message.
+    ProtocolBatch {
+        era_id: EraId,
+        payloads: Vec<Vec<u8>>,
+    },
+    /// A request for the finality signatures of the specified block in `era_id`.
+    BlockSignaturesRequest {
+        era_id: EraId,
+        block_hash: BlockHash,
+    },
+    /// The answer to a `BlockSignaturesRequest`: the finality signatures of the block, or `None`
+    /// if the sender doesn't have them.
+    BlockSignaturesResponse {
+        era_id: EraId,
+        block_hash: BlockHash,
+        signatures: Option<BlockSignatures>,
+    },
+    /// A protocol message signed by the node that sent it, so that it can't be altered or
+    /// attributed to a validator in transit. Once the signature is verified, it is handled like a
+    /// `Protocol` message.
+    SignedProtocol {
+        era_id: EraId,
+        payload: Vec<u8>,
+        public_key: PublicKey,
+        signature: Signature,
+    },
+}
+
+impl ConsensusMessage {
+    /// Creates a `SignedProtocol` message, signed with the given key.
+    pub(crate) fn new_signed_protocol(
+        era_id: EraId,
+        payload: Vec<u8>,
+        secret_key: &SecretKey,
+        public_key: PublicKey,
+    ) -> Self {
+        let signature = crypto::sign(signed_bytes(era_id, &payload), secret_key, &public_key);
+        ConsensusMessage::SignedProtocol {
+            era_id,
+            payload,
+            public_key,
+            signature,
+        }
+    }
+}
+
+/// Returns the bytes a `SignedProtocol` message's signature is computed over: the era ID and the
+/// payload. The era is included so that a message can't be replayed in a different era.
+fn signed_bytes(era_id: EraId, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = era_id.to_le_bytes().to_vec();
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+/// Verifies the signature of a `SignedProtocol` message with the given era ID and payload.
+pub(crate) fn verify_protocol_signature(
+    era_id: EraId,
+    payload: &[u8],
+    public_key: &PublicKey,
+    signature: &Signature,
+) -> Result<(), crypto::Error> {
+    crypto::verify(signed_bytes(era_id, payload), signature, public_key)
 }
 
 /// An ID to distinguish different timers. What they are used for is specific to each consensus
@@ -130,11 +207,51 @@ pub(crate) enum Event {
         /// The most recent switch block headers
         switch_blocks: Vec<BlockHeader>,
     },
+    /// A switch block needed to create a new era was not in storage.
+    SwitchBlocksUnavailable {
+        era_id: EraId,
+        switch_block_era_id: EraId,
+    },
+    /// The backoff after failing to load the switch blocks for the era has passed, and they are
+    /// requested again.
+    RetrySwitchBlocks { era_id: EraId },
+    /// The batching window has passed, and the batch of outgoing messages for the era is sent.
+    FlushMessageBatch { era_id: EraId },
+    /// The coalescing window ending at `fire_at` has passed, and all protocol timers in it are
+    /// handled.
+    CoalescedTimers { fire_at: Timestamp },
+    /// Some of the queued incoming protocol messages should be handled.
+    HandleQueuedMessages,
+    /// An unrecoverable error occurred, and the node needs to shut down.
+    Failed(ConsensusError),
     /// Got the result of checking for an upgrade activation point.
     GotUpgradeActivationPoint(ActivationPoint),
+    /// Evidence against a validator was loaded from storage.
+    LoadedEvidence {
+        era_id: EraId,
+        pub_key: PublicKey,
+        /// The serialized evidence, as returned by the protocol.
+        evidence: Vec<u8>,
+    },
+    /// The index of the messages we gossiped in an era was loaded from storage.
+    LoadedGossipIndex {
+        era_id: EraId,
+        /// The compact keys of the gossiped messages.
+        keys: Vec<u64>,
+    },
+    /// The supervisor should resume from the latest persisted checkpoint. This is sent once on
+    /// startup.
+    ResumeFromCheckpoint,
+    /// The latest checkpoint was loaded from storage, or none was found.
+    LoadedCheckpoint {
+        maybe_checkpoint: Option<Checkpoint>,
+    },
     /// Dump state for debugging purposes.
     #[from]
     DumpState(DumpConsensusStateRequest),
+    /// Perform a debugging or recovery action.
+    #[from]
+    DiagnosticsAction(ConsensusActionRequest),
 }
 
 impl Debug for ConsensusMessage {
@@ -143,11 +260,49 @@ impl Debug for ConsensusMessage {
             ConsensusMessage::Protocol { era_id, payload: _ } => {
                 write!(f, "Protocol {{ era_id: {:?}, .. }}", era_id)
             }
+            ConsensusMessage::SignedProtocol {
+                era_id, public_key, ..
+            } => write!(
+                f,
+                "SignedProtocol {{ era_id: {:?}, public_key: {:?}, .. }}",
+                era_id, public_key
+            ),
+            ConsensusMessage::ProtocolBatch { era_id, payloads } => write!(
+                f,
+                "ProtocolBatch {{ era_id: {:?}, payloads: {} }}",
+                era_id,
+                payloads.len()
+            ),
             ConsensusMessage::EvidenceRequest { era_id, pub_key } => f
                 .debug_struct("EvidenceRequest")
                 .field("era_id", era_id)
                 .field("pub_key", pub_key)
                 .finish(),
+            ConsensusMessage::Ping { era_id, nonce } => f
+                .debug_struct("Ping")
+                .field("era_id", era_id)
+                .field("nonce", nonce)
+                .finish(),
+            ConsensusMessage::Pong { era_id, nonce } => f
+                .debug_struct("Pong")
+                .field("era_id", era_id)
+                .field("nonce", nonce)
+                .finish(),
+            ConsensusMessage::BlockSignaturesRequest { era_id, block_hash } => f
+                .debug_struct("BlockSignaturesRequest")
+                .field("era_id", era_id)
+                .field("block_hash", block_hash)
+                .finish(),
+            ConsensusMessage::BlockSignaturesResponse {
+                era_id,
+                block_hash,
+                signatures,
+            } => f
+                .debug_struct("BlockSignaturesResponse")
+                .field("era_id", era_id)
+                .field("block_hash", block_hash)
+                .field("signatures", signatures)
+                .finish(),
         }
     }
 }
@@ -156,13 +311,67 @@ impl Display for ConsensusMessage {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             ConsensusMessage::Protocol { era_id, payload } => {
-                write!(f, "protocol message {:10} in {}", HexFmt(payload), era_id)
+                write!(
+                    f,
+                    "protocol message {} in {}",
+                    RedactedPayload(payload),
+                    era_id
+                )
             }
+            ConsensusMessage::SignedProtocol {
+                era_id,
+                payload,
+                public_key,
+                ..
+            } => write!(
+                f,
+                "protocol message {} in {} signed by {}",
+                RedactedPayload(payload),
+                era_id,
+                public_key
+            ),
+            ConsensusMessage::ProtocolBatch { era_id, payloads } => write!(
+                f,
+                "batch of {} protocol messages in {}",
+                payloads.len(),
+                era_id
+            ),
             ConsensusMessage::EvidenceRequest { era_id, pub_key } => write!(
                 f,
                 "request for evidence of fault by {} in {} or earlier",
                 pub_key, era_id,
             ),
+            ConsensusMessage::Ping { era_id, nonce } => {
+                write!(f, "latency probe {} in {}", nonce, era_id)
+            }
+            ConsensusMessage::Pong { era_id, nonce } => {
+                write!(f, "response to latency probe {} in {}", nonce, era_id)
+            }
+            ConsensusMessage::BlockSignaturesRequest { era_id, block_hash } => write!(
+                f,
+                "request for signatures of block {} in {}",
+                block_hash, era_id
+            ),
+            ConsensusMessage::BlockSignaturesResponse {
+                era_id,
+                block_hash,
+                signatures: Some(signatures),
+            } => write!(
+                f,
+                "{} signatures of block {} in {}",
+                signatures.proofs.len(),
+                block_hash,
+                era_id
+            ),
+            ConsensusMessage::BlockSignaturesResponse {
+                era_id,
+                block_hash,
+                signatures: None,
+            } => write!(
+                f,
+                "no signatures of block {} in {} available",
+                block_hash, era_id
+            ),
         }
     }
 }
@@ -232,10 +441,48 @@ impl Display for Event {
                 "New era should be created; switch blocks: {:?}",
                 switch_blocks
             ),
+            Event::SwitchBlocksUnavailable {
+                era_id,
+                switch_block_era_id,
+            } => write!(
+                f,
+                "switch block in {} needed for {} is not in storage",
+                switch_block_era_id, era_id
+            ),
+            Event::RetrySwitchBlocks { era_id } => {
+                write!(f, "request switch blocks for {} again", era_id)
+            }
+            Event::FlushMessageBatch { era_id } => {
+                write!(f, "send batched messages in {}", era_id)
+            }
+            Event::CoalescedTimers { fire_at } => {
+                write!(f, "handle coalesced timers firing at {}", fire_at)
+            }
+            Event::HandleQueuedMessages => write!(f, "handle queued messages"),
+            Event::Failed(error) => write!(f, "consensus failed: {}", error),
             Event::GotUpgradeActivationPoint(activation_point) => {
                 write!(f, "new upgrade activation point: {:?}", activation_point)
             }
+            Event::LoadedEvidence {
+                era_id, pub_key, ..
+            } => write!(f, "loaded evidence against {} in {}", pub_key, era_id),
+            Event::LoadedGossipIndex { era_id, keys } => {
+                write!(
+                    f,
+                    "loaded gossip index of {} with {} entries",
+                    era_id,
+                    keys.len()
+                )
+            }
+            Event::ResumeFromCheckpoint => write!(f, "resume from checkpoint"),
+            Event::LoadedCheckpoint {
+                maybe_checkpoint: Some(checkpoint),
+            } => write!(f, "loaded {}", checkpoint),
+            Event::LoadedCheckpoint {
+                maybe_checkpoint: None,
+            } => write!(f, "no checkpoint found"),
             Event::DumpState(req) => Display::fmt(req, f),
+            Event::DiagnosticsAction(req) => Display::fmt(req, f),
         }
     }
 }
@@ -252,6 +499,7 @@ pub(crate) trait ReactorEventT:
     + From<ConsensusAnnouncement>
     + From<BlockValidationRequest>
     + From<StorageRequest>
+    + From<StateStoreRequest>
     + From<ContractRuntimeRequest>
     + From<ChainspecLoaderRequest>
     + From<BlocklistAnnouncement>
@@ -269,6 +517,7 @@ impl<REv> ReactorEventT for REv where
         + From<ConsensusAnnouncement>
         + From<BlockValidationRequest>
         + From<StorageRequest>
+        + From<StateStoreRequest>
         + From<ContractRuntimeRequest>
         + From<ChainspecLoaderRequest>
         + From<BlocklistAnnouncement>
@@ -298,7 +547,7 @@ where
                 self.handle_action(effect_builder, rng, era_id, action_id)
             }
             Event::Incoming(ConsensusMessageIncoming { sender, message }) => {
-                self.handle_message(effect_builder, rng, sender, message)
+                self.handle_incoming(effect_builder, rng, sender, message)
             }
             Event::NewBlockPayload(new_block_payload) => {
                 self.handle_new_block_payload(effect_builder, rng, new_block_payload)
@@ -308,7 +557,7 @@ where
                 header_hash: _,
             } => self.handle_block_added(effect_builder, *header),
             Event::ResolveValidity(resolve_validity) => {
-                self.resolve_validity(effect_builder, rng, resolve_validity)
+                self.handle_validation_result(effect_builder, rng, resolve_validity)
             }
             Event::DeactivateEra {
                 era_id,
@@ -318,9 +567,34 @@ where
             Event::CreateNewEra { switch_blocks } => {
                 self.create_new_era_effects(effect_builder, rng, &switch_blocks)
             }
+            Event::SwitchBlocksUnavailable {
+                era_id,
+                switch_block_era_id,
+            } => self.handle_switch_blocks_unavailable(effect_builder, era_id, switch_block_era_id),
+            Event::RetrySwitchBlocks { era_id } => {
+                self.handle_retry_switch_blocks(effect_builder, era_id)
+            }
+            Event::FlushMessageBatch { era_id } => self.flush_message_batch(effect_builder, era_id),
+            Event::CoalescedTimers { fire_at } => {
+                self.handle_coalesced_timers(effect_builder, rng, fire_at)
+            }
+            Event::HandleQueuedMessages => self.handle_queued_messages(effect_builder, rng),
+            Event::Failed(error) => fatal!(effect_builder, "{}", error).ignore(),
             Event::GotUpgradeActivationPoint(activation_point) => {
                 self.got_upgrade_activation_point(activation_point)
             }
+            Event::LoadedEvidence {
+                era_id,
+                pub_key,
+                evidence,
+            } => self.handle_loaded_evidence(era_id, pub_key, evidence),
+            Event::LoadedGossipIndex { era_id, keys } => {
+                self.handle_loaded_gossip_index(era_id, keys)
+            }
+            Event::ResumeFromCheckpoint => self.resume_from_checkpoint(effect_builder),
+            Event::LoadedCheckpoint { maybe_checkpoint } => {
+                self.handle_loaded_checkpoint(effect_builder, rng, maybe_checkpoint)
+            }
             Event::ConsensusRequest(ConsensusRequest::Status(responder)) => self.status(responder),
             Event::ConsensusRequest(ConsensusRequest::ValidatorChanges(responder)) => {
                 let validator_changes = self.get_validator_changes();
@@ -333,22 +607,64 @@ where
                 // operator a chance to find out why their node is busy.
                 info!(era_id=%requested_era.value(), was_latest=era_id.is_none(), "dumping era via diagnostics port");
 
-                let era_dump_result = self
-                    .open_eras()
-                    .get(&requested_era)
-                    .ok_or_else(|| {
-                        Cow::Owned(format!(
-                            "could not dump consensus, {} not found",
-                            requested_era
-                        ))
-                    })
-                    .and_then(|era| EraDump::dump_era(era, requested_era));
+                let era_dump_result = EraDump::dump_era(self, requested_era);
 
                 match era_dump_result {
                     Ok(dump) => req.answer(Ok(&dump)).ignore(),
                     Err(err) => req.answer(Err(err)).ignore(),
                 }
             }
+            Event::DiagnosticsAction(ConsensusActionRequest { action, responder }) => {
+                info!(%action, "performing consensus action via diagnostics port");
+                match action {
+                    ConsensusAction::ReplayMessageLog(path) => {
+                        match self.replay_message_log(effect_builder, rng, &path) {
+                            Ok(mut effects) => {
+                                let msg = format!("replayed {}", path.display());
+                                effects.extend(responder.respond(Ok(msg)).ignore());
+                                effects
+                            }
+                            Err(err) => {
+                                let reason =
+                                    format!("failed to replay {}: {}", path.display(), err);
+                                responder.respond(Err(Cow::Owned(reason))).ignore()
+                            }
+                        }
+                    }
+                    ConsensusAction::ForceEraSync(era_id) => {
+                        let era_id = era_id.unwrap_or_else(|| self.current_era());
+                        if !self.open_eras().contains_key(&era_id) {
+                            let reason = format!("cannot force sync, {} is not open", era_id);
+                            return responder.respond(Err(Cow::Owned(reason))).ignore();
+                        }
+                        let mut effects = self.force_era_sync(effect_builder, rng, era_id);
+                        let msg = format!("requested missing units and evidence in {}", era_id);
+                        effects.extend(responder.respond(Ok(msg)).ignore());
+                        effects
+                    }
+                    ConsensusAction::Status => {
+                        let eras = self
+                            .active_eras()
+                            .into_iter()
+                            .map(|(era_id, count)| format!("{} ({} validators)", era_id, count))
+                            .join(", ");
+                        let peers = self.peers_by_reputation().into_iter().join(", ");
+                        let latency = match self.last_equivocation_detection_latency() {
+                            Some(latency) => format!("{:?}", latency),
+                            None => "none".to_string(),
+                        };
+                        let msg = format!(
+                            "current era: {}, eras in memory: {}\npeers by reputation: {}\n\
+                             last equivocation detection latency: {}",
+                            self.current_era(),
+                            eras,
+                            peers,
+                            latency
+                        );
+                        responder.respond(Ok(msg)).ignore()
+                    }
+                }
+            }
         }
     }
 }
@@ -1,9 +1,13 @@
-use std::{path::Path, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use datasize::DataSize;
+use num::rational::Ratio;
 use serde::Deserialize;
 
-use casper_types::{PublicKey, SecretKey};
+use casper_types::{PublicKey, SecretKey, TimeDiff};
 
 use crate::{
     components::consensus::{
@@ -20,19 +24,239 @@ use crate::{
 pub(crate) struct Config {
     /// Path to secret key file.
     pub(crate) secret_key_path: External,
+    /// Whether this node takes part in consensus as a validator or only follows it.
+    #[serde(default)]
+    pub(crate) participation: Participation,
+    /// The consensus protocol that runs in each era.
+    #[serde(default)]
+    pub(crate) protocol: ProtocolKind,
     /// Highway-specific node configuration.
     pub(crate) highway: HighwayConfig,
+    /// The maximum number of consensus messages for future eras that are kept until the era is
+    /// initialized. Further messages are dropped.
+    #[serde(default = "default_max_buffered_future_messages")]
+    pub(crate) max_buffered_future_messages: usize,
+    /// If the current era has not finalized a block for this long, it is announced as stalled.
+    #[serde(default)]
+    pub(crate) era_stall_timeout: Option<TimeDiff>,
+    /// The maximum serialized size of a proposed block payload, in bytes. Larger proposals are
+    /// rejected as invalid without being validated.
+    #[serde(default = "default_max_block_payload_size")]
+    pub(crate) max_block_payload_size: u64,
+    /// The maximum size of an incoming consensus message's payload, in bytes. Larger messages are
+    /// dropped without being deserialized. This must exceed `max_block_payload_size`, since units
+    /// can contain a block payload.
+    #[serde(default = "default_max_message_size")]
+    pub(crate) max_message_size: usize,
+    /// The number of past eras whose protocol state is kept in memory in addition to the current
+    /// era. Older eras are retired. The eras that are needed to validate evidence are always kept,
+    /// so values below that number have no effect.
+    #[serde(default = "default_retained_past_eras")]
+    pub(crate) retained_past_eras: u64,
+    /// If `true`, a new era is only initialized once its switch block contains validators with a
+    /// nonzero total weight. Until then, its messages are buffered and the switch blocks are
+    /// requested again periodically.
+    #[serde(default)]
+    pub(crate) strict_era_transition: bool,
+    /// If set, a latency probe is sent to all peers at this interval, and the round-trip times
+    /// are recorded in the metrics.
+    #[serde(default)]
+    pub(crate) latency_probe_interval: Option<TimeDiff>,
+    /// If set, outgoing protocol messages are collected for this long, and sent to peers together
+    /// in a single batch.
+    #[serde(default)]
+    pub(crate) message_batch_window: Option<TimeDiff>,
+    /// The maximum number of protocol messages in a batch. A full batch is sent immediately, and
+    /// larger incoming batches are dropped.
+    #[serde(default = "default_max_message_batch_size")]
+    pub(crate) max_message_batch_size: usize,
+    /// If set, incoming protocol messages are queued and handled in the background, and at most
+    /// this many are kept in the queue. If it is full, messages for past eras and duplicates are
+    /// dropped first.
+    #[serde(default)]
+    pub(crate) max_queued_messages: Option<usize>,
+    /// Fault tolerance thresholds for individual eras, overriding the chainspec's finality
+    /// threshold fraction, e.g. to be more conservative during a known membership change.
+    #[serde(default)]
+    pub(crate) finality_threshold_overrides: Vec<FinalityThresholdOverride>,
+    /// If `true`, outgoing protocol messages in eras in which we are a validator are signed, so
+    /// that peers can detect messages that were altered or spoofed in transit. Since a batch can't
+    /// be signed, batched messages in such eras are sent individually.
+    #[serde(default)]
+    pub(crate) sign_messages: bool,
+    /// If `true`, incoming protocol messages that aren't signed, including batches, are dropped,
+    /// except for evidence, which is self-authenticating. This should only be enabled once all
+    /// validators sign their messages.
+    #[serde(default)]
+    pub(crate) require_signed_messages: bool,
+    /// If set, protocol timers are rounded up to the next multiple of this window, and all timers
+    /// of all eras that fall into the same window are handled together in a single pass.
+    #[serde(default)]
+    pub(crate) timer_coalescing_window: Option<TimeDiff>,
+    /// If set, at most this many eras, including the current one, are kept in memory. If there
+    /// are more, the oldest eras that are not needed to validate evidence are retired first,
+    /// even if they are within `retained_past_eras`. Must cover at least the eras that are needed
+    /// to validate evidence.
+    #[serde(default)]
+    pub(crate) max_tracked_eras: Option<usize>,
+    /// If set, we stop proposing blocks in an era after this many of our own consecutive
+    /// proposals failed validation, e.g. because of a local bug or invalid deploys, instead of
+    /// repeatedly proposing invalid blocks.
+    #[serde(default)]
+    pub(crate) max_failed_proposals: Option<u32>,
+    /// A bonded validator that hasn't sent any unit for longer than this is considered inactive.
+    /// Unlike an equivocation, this is a liveness fault, and doesn't make the validator faulty.
+    #[serde(default = "default_inactivity_grace_period")]
+    pub(crate) inactivity_grace_period: TimeDiff,
+    /// If set, all incoming consensus messages are appended to this file, with the time they were
+    /// received, so that they can be replayed offline to debug a stuck era. A relative path is
+    /// resolved against the storage directory.
+    #[serde(default)]
+    pub(crate) message_log_path: Option<PathBuf>,
+    /// The maximum size of the message log in bytes. Once it is reached, the log is moved to a
+    /// file with the suffix `.1`, replacing the previous one, and a new log is started.
+    #[serde(default = "default_max_message_log_size")]
+    pub(crate) max_message_log_size: u64,
+    /// If set, at most this many proposed blocks are validated at the same time. Further blocks
+    /// wait until a validation completes, and are validated in the order in which they arrived.
+    #[serde(default)]
+    pub(crate) max_concurrent_validations: Option<usize>,
+    /// If `false`, we never broadcast requests for evidence we are missing, e.g. against a
+    /// validator accused in a proposed block, and wait for peers to send it on their own instead.
+    /// Evidence requests from peers are still answered.
+    #[serde(default = "default_broadcast_evidence_requests")]
+    pub(crate) broadcast_evidence_requests: bool,
+    /// If set, at most this many evidence requests from peers are answered per second. Further
+    /// requests are dropped, since answering them can be expensive if the evidence is large.
+    #[serde(default)]
+    pub(crate) max_evidence_requests_per_second: Option<u32>,
+    /// If `true`, every dropped or ignored incoming message is logged at debug level, with the
+    /// reason and sender. The metrics count dropped messages either way.
+    #[serde(default = "default_log_dropped_messages")]
+    pub(crate) log_dropped_messages: bool,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
             secret_key_path: External::Missing,
+            participation: Participation::default(),
+            protocol: ProtocolKind::default(),
             highway: HighwayConfig::default(),
+            max_buffered_future_messages: default_max_buffered_future_messages(),
+            era_stall_timeout: None,
+            max_block_payload_size: default_max_block_payload_size(),
+            max_message_size: default_max_message_size(),
+            retained_past_eras: default_retained_past_eras(),
+            strict_era_transition: false,
+            latency_probe_interval: None,
+            message_batch_window: None,
+            max_message_batch_size: default_max_message_batch_size(),
+            max_queued_messages: None,
+            finality_threshold_overrides: Vec::new(),
+            sign_messages: false,
+            require_signed_messages: false,
+            timer_coalescing_window: None,
+            max_tracked_eras: None,
+            max_failed_proposals: None,
+            inactivity_grace_period: default_inactivity_grace_period(),
+            message_log_path: None,
+            max_message_log_size: default_max_message_log_size(),
+            max_concurrent_validations: None,
+            broadcast_evidence_requests: default_broadcast_evidence_requests(),
+            max_evidence_requests_per_second: None,
+            log_dropped_messages: default_log_dropped_messages(),
         }
     }
 }
 
+/// A finality threshold fraction that applies to a single era instead of the chainspec's.
+#[derive(Copy, Clone, DataSize, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct FinalityThresholdOverride {
+    /// The era in which the threshold applies.
+    pub(crate) era_id: EraId,
+    /// The fault tolerance threshold, as a fraction of the total weight.
+    #[data_size(skip)]
+    pub(crate) fraction: Ratio<u64>,
+}
+
+impl FinalityThresholdOverride {
+    /// Returns `true` if the fraction is in the range (0, 1).
+    pub(crate) fn is_valid(&self) -> bool {
+        self.fraction > Ratio::new(0, 1) && self.fraction < Ratio::new(1, 1)
+    }
+}
+
+/// How a node takes part in consensus.
+#[derive(Copy, Clone, DataSize, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Participation {
+    /// The node creates and signs units and finality signatures in eras in which it is bonded.
+    Validator,
+    /// The node only follows consensus and never signs anything, even if it is bonded.
+    Observer,
+}
+
+impl Default for Participation {
+    fn default() -> Self {
+        Participation::Validator
+    }
+}
+
+/// A consensus protocol implementation.
+#[derive(Copy, Clone, DataSize, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ProtocolKind {
+    /// The Highway protocol.
+    Highway,
+    /// A deterministic mock that only records what it receives, for tests.
+    #[cfg(test)]
+    Mock,
+}
+
+impl Default for ProtocolKind {
+    fn default() -> Self {
+        ProtocolKind::Highway
+    }
+}
+
+fn default_max_buffered_future_messages() -> usize {
+    1_000
+}
+
+fn default_max_block_payload_size() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_max_message_size() -> usize {
+    16 * 1024 * 1024
+}
+
+fn default_retained_past_eras() -> u64 {
+    PAST_OPEN_ERAS
+}
+
+fn default_max_message_batch_size() -> usize {
+    50
+}
+
+fn default_inactivity_grace_period() -> TimeDiff {
+    TimeDiff::from_seconds(120)
+}
+
+fn default_max_message_log_size() -> u64 {
+    1024 * 1024 * 1024
+}
+
+fn default_broadcast_evidence_requests() -> bool {
+    true
+}
+
+fn default_log_dropped_messages() -> bool {
+    true
+}
+
 impl Config {
     /// Loads the secret key from the configuration file and derives the public key.
     #[allow(clippy::type_complexity)]
@@ -44,6 +268,26 @@ impl Config {
         let public_key: PublicKey = PublicKey::from(secret_signing_key.as_ref());
         Ok((secret_signing_key, public_key))
     }
+
+    /// Returns `true` if `max_tracked_eras` is unset or covers at least the current era and the
+    /// past eras that are needed to validate evidence.
+    pub(crate) fn has_valid_max_tracked_eras(&self) -> bool {
+        self.max_tracked_eras
+            .map_or(true, |max_eras| max_eras as u64 > PAST_OPEN_ERAS)
+    }
+
+    /// Returns the finality threshold fraction for the given era: the override, if there is one,
+    /// and otherwise the given default.
+    pub(crate) fn finality_threshold_fraction(
+        &self,
+        era_id: EraId,
+        default: Ratio<u64>,
+    ) -> Ratio<u64> {
+        self.finality_threshold_overrides
+            .iter()
+            .find(|ftt_override| ftt_override.era_id == era_id)
+            .map_or(default, |ftt_override| ftt_override.fraction)
+    }
 }
 
 pub trait ChainspecConsensusExt {
@@ -213,12 +213,16 @@ pub(crate) enum ProtocolOutcome<C: Context> {
         sender: NodeId,
         proposed_block: ProposedBlock<C>,
     },
-    /// New direct evidence was added against the given validator.
-    NewEvidence(C::ValidatorId),
+    /// New direct evidence was added against the given validator, whose fault was committed at
+    /// the given time.
+    NewEvidence(C::ValidatorId, Timestamp),
     /// Send evidence about the validator from an earlier era to the peer.
     SendEvidence(NodeId, C::ValidatorId),
     /// We've detected an equivocation our own node has made.
     WeAreFaulty,
+    /// Our next unit would have conflicted with one we signed earlier, e.g. before a restart on
+    /// stale state. It was not signed, but the node must not continue as a validator.
+    WouldEquivocate,
     /// We've received a unit from a doppelganger.
     DoppelgangerDetected,
     /// Too many faulty validators. The protocol's fault tolerance threshold has been exceeded and
@@ -246,6 +250,15 @@ pub(crate) trait ConsensusProtocol<C: Context>: Send {
         now: Timestamp,
     ) -> ProtocolOutcomes<C>;
 
+    /// Returns `true` if the serialized message can directly advance finality, so that it should
+    /// be handled without any queueing delay. This must be cheap, and only depend on the message.
+    fn is_urgent(&self, msg: &[u8]) -> bool;
+
+    /// Returns `true` if the serialized message is evidence of a fault. Evidence is
+    /// self-authenticating, so it is accepted from any peer even if signed messages are required.
+    /// This must be cheap, and only depend on the message.
+    fn is_evidence(&self, msg: &[u8]) -> bool;
+
     /// Current instance of consensus protocol is latest era.
     fn handle_is_current(&self, now: Timestamp) -> ProtocolOutcomes<C>;
 
@@ -282,15 +295,42 @@ pub(crate) trait ConsensusProtocol<C: Context>: Send {
     /// Clears this instance and keeps only the information necessary to validate evidence.
     fn set_evidence_only(&mut self);
 
+    /// Returns whether we have received any protocol state and are not waiting for missing
+    /// dependencies.
+    fn is_synced(&self) -> bool;
+
     /// Returns whether the validator `vid` is known to be faulty.
     fn has_evidence(&self, vid: &C::ValidatorId) -> bool;
 
+    /// Returns the timestamp of the latest unit we have from the validator `vid`, or `None` if we
+    /// have none or the validator is known to be faulty.
+    fn latest_unit_timestamp(&self, vid: &C::ValidatorId) -> Option<Timestamp>;
+
     /// Marks the validator `vid` as faulty, based on evidence from a different instance.
     fn mark_faulty(&mut self, vid: &C::ValidatorId);
 
     /// Sends evidence for a faulty of validator `vid` to the `sender` of the request.
     fn request_evidence(&self, sender: NodeId, vid: &C::ValidatorId) -> ProtocolOutcomes<C>;
 
+    /// Returns the evidence against validator `vid` as a serialized message, that any instance of
+    /// the protocol in this era would accept, or `None` if we have no evidence.
+    fn evidence_message(&self, vid: &C::ValidatorId) -> Option<Vec<u8>>;
+
+    /// Returns the validator who is expected to propose a block at the given timestamp.
+    fn leader(&self, timestamp: Timestamp) -> Option<&C::ValidatorId>;
+
+    /// Returns the validator who is expected to propose the next block after `now`, and the time
+    /// of that proposal, or `None` if no more blocks are expected in this era.
+    fn next_leader(&self, now: Timestamp) -> Option<(&C::ValidatorId, Timestamp)>;
+
+    /// Returns the leader of every slot from the start of the era until its minimum end time, in
+    /// order, or `None` if this instance only keeps evidence and no longer runs the protocol.
+    fn leader_schedule(&self) -> Option<Vec<&C::ValidatorId>>;
+
+    /// Requests everything we know we are missing from peers, and the latest state from a random
+    /// peer. This doesn't change the protocol state, so it is safe to call repeatedly.
+    fn request_missing_units(&self) -> ProtocolOutcomes<C>;
+
     /// Sets the pause status: While paused we don't create consensus messages other than pings.
     fn set_paused(&mut self, paused: bool);
 
@@ -5,11 +5,23 @@
 //! it assumes is the concept of era/epoch and that each era runs separate consensus instance.
 //! Most importantly, it doesn't care about what messages it's forwarding.
 
+mod checkpoint;
+mod clock;
 pub(super) mod debug;
 mod era;
+mod era_buffer;
+mod gossip_index;
+mod message_drop_reason;
+mod message_log;
+mod message_queue;
+mod peer_reputation;
+#[cfg(test)]
+mod tests;
+mod validator_set_anomaly;
 
 use std::{
-    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
     convert::TryInto,
     fmt::{self, Debug, Formatter},
     fs, io,
@@ -22,6 +34,7 @@ use anyhow::Error;
 use datasize::DataSize;
 use futures::FutureExt;
 use itertools::Itertools;
+use num::rational::Ratio;
 use prometheus::Registry;
 use rand::Rng;
 use tracing::{debug, error, info, trace, warn};
@@ -32,33 +45,50 @@ use casper_types::{AsymmetricType, EraId, PublicKey, SecretKey, TimeDiff, Timest
 use crate::{
     components::{
         consensus::{
+            check_sufficient_finality_signatures,
             cl_context::{ClContext, Keypair},
+            config::{Participation, ProtocolKind},
             consensus_protocol::{
-                ConsensusProtocol, EraReport, FinalizedBlock as CpFinalizedBlock, ProposedBlock,
-                ProtocolOutcome,
+                BlockContext, ConsensusProtocol, EraReport, FinalizedBlock as CpFinalizedBlock,
+                ProposedBlock, ProtocolOutcome,
             },
             metrics::Metrics,
+            protocols::highway::HighwayProtocol,
+            utils::RedactedPayload,
             validator_change::{ValidatorChange, ValidatorChanges},
-            ActionId, ChainspecConsensusExt, Config, ConsensusMessage, Event, NewBlockPayload,
-            ReactorEventT, ResolveValidity, TimerId,
+            verify_protocol_signature, ActionId, ChainspecConsensusExt, Config, ConsensusMessage,
+            Event, NewBlockPayload, ReactorEventT, ResolveValidity, TimerId,
         },
         storage::Storage,
     },
     effect::{
         announcements::ControlAnnouncement,
         requests::{BlockValidationRequest, ContractRuntimeRequest, StorageRequest},
-        EffectBuilder, EffectExt, Effects, Responder,
+        EffectBuilder, EffectExt, EffectOptionExt, Effects, Responder,
     },
     fatal,
     types::{
-        ActivationPoint, BlockHash, BlockHeader, Chainspec, Deploy, DeployHash,
-        DeployOrTransferHash, FinalitySignature, FinalizedApprovals, FinalizedBlock, NodeId,
+        ActivationPoint, BlockHash, BlockHeader, BlockPayload, BlockSignatures, Chainspec, Deploy,
+        DeployHash, DeployOrTransferHash, FinalitySignature, FinalizedApprovals, FinalizedBlock,
+        NodeId,
     },
     NodeRng,
 };
 
+pub(crate) use self::checkpoint::Checkpoint;
 pub use self::era::Era;
-use crate::components::consensus::error::CreateNewEraError;
+use self::{
+    clock::{Clock, MockClock, SystemClock},
+    era_buffer::EraBuffer,
+    message_drop_reason::MessageDropReason,
+    message_log::{read_message_log, LoggedMessage, MessageLog},
+    message_queue::{MessagePriority, MessageQueue},
+    peer_reputation::{PeerBehavior, PeerReputation},
+    validator_set_anomaly::ValidatorSetAnomaly,
+};
+use crate::components::consensus::error::{ConsensusError, CreateNewEraError};
+#[cfg(test)]
+use crate::components::consensus::protocols::mock::MockProtocol;
 
 /// The delay in milliseconds before we shutdown after the number of faulty validators exceeded the
 /// fault tolerance threshold.
@@ -73,6 +103,29 @@ const PAST_EVIDENCE_ERAS: u64 = 1;
 /// The older half is in evidence-only state, and only used to validate cited evidence.
 pub(super) const PAST_OPEN_ERAS: u64 = 2 * PAST_EVIDENCE_ERAS;
 
+/// The maximum number of block payloads that are kept until the era they belong to is initialized.
+const MAX_PENDING_BLOCK_PAYLOADS: usize = 10;
+
+/// The delay after which the switch blocks are requested again if they didn't contain any
+/// validators, in strict era transition mode.
+const STRICT_ERA_TRANSITION_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// The delay before the switch blocks are requested again after the first time they were not in
+/// storage. It doubles with every further attempt.
+const STORAGE_RETRY_BASE_DELAY: TimeDiff = TimeDiff::from_seconds(1);
+
+/// The number of times missing switch blocks are requested again before giving up.
+const MAX_STORAGE_RETRIES: u32 = 5;
+
+/// The maximum number of queued messages that are handled in response to a single event, so that
+/// a full queue does not starve other components.
+const MAX_QUEUED_MESSAGES_PER_EVENT: usize = 100;
+
+/// The number of messages we gossip in an era before its gossip index is persisted again. The
+/// indices of all open eras are also persisted whenever a new era begins. After a crash, at most
+/// this many messages are gossiped a second time.
+const GOSSIP_INDEX_SAVE_INTERVAL: usize = 100;
+
 type ConsensusConstructor = dyn Fn(
         Digest,                    // the era's unique instance ID
         BTreeMap<PublicKey, U512>, // validator weights
@@ -81,6 +134,7 @@ type ConsensusConstructor = dyn Fn(
         &HashSet<PublicKey>, // inactive validators that can't be leaders
         &Chainspec,          // the network's chainspec
         &Config,             // The consensus part of the node config.
+        Ratio<u64>,          // the finality threshold fraction
         Option<&dyn ConsensusProtocol<ClContext>>, // previous era's consensus instance
         Timestamp,           // start time for this era
         u64,                 // random seed
@@ -90,6 +144,15 @@ type ConsensusConstructor = dyn Fn(
         Vec<ProtocolOutcome<ClContext>>,
     ) + Send;
 
+/// Returns the constructor for consensus instances of the given kind.
+fn consensus_constructor(protocol: ProtocolKind) -> Box<ConsensusConstructor> {
+    match protocol {
+        ProtocolKind::Highway => Box::new(HighwayProtocol::new_boxed),
+        #[cfg(test)]
+        ProtocolKind::Mock => Box::new(MockProtocol::new_boxed),
+    }
+}
+
 #[derive(DataSize)]
 pub struct EraSupervisor {
     /// A map of consensus protocol instances.
@@ -130,6 +193,51 @@ pub struct EraSupervisor {
     next_upgrade_activation_point: Option<ActivationPoint>,
     /// The era that was current when this node joined the network.
     era_where_we_joined: EraId,
+    /// Block payloads for eras that are not initialized yet.
+    pending_block_payloads: EraBuffer<NewBlockPayload>,
+    /// Consensus messages for future eras that are not initialized yet, with their senders.
+    future_messages: EraBuffer<(NodeId, Vec<u8>)>,
+    /// The nonce and sending time of the most recent latency probe, if any.
+    latency_probe: Option<(u64, Timestamp)>,
+    /// The reputation of the peers that sent us consensus messages.
+    peer_reputation: PeerReputation,
+    /// Outgoing protocol messages that are collected to be sent together, by era.
+    message_batches: BTreeMap<EraId, Vec<Vec<u8>>>,
+    /// Protocol timers that are handled together, by the end of their coalescing window.
+    coalesced_timers: BTreeMap<Timestamp, Vec<(EraId, Timestamp, TimerId)>>,
+    /// Incoming protocol messages waiting to be handled, if queueing is enabled.
+    message_queue: Option<MessageQueue>,
+    /// The most recent era in which we announced that we caught up.
+    caught_up_era: Option<EraId>,
+    /// The era and timestamp of the most recent slot in which we announced that we are the
+    /// leader.
+    last_leader_slot: Option<(EraId, Timestamp)>,
+    /// The time between the most recently detected equivocation being committed and detected.
+    last_equivocation_detection_latency: Option<TimeDiff>,
+    /// The start of the current one-second window for limiting evidence requests, and the number
+    /// of evidence requests answered in it.
+    evidence_request_window: (Timestamp, u32),
+    /// The hashes of the key blocks that eras in the retention window were initialized from, so
+    /// that duplicate or late switch blocks don't initialize an era again.
+    initialized_key_blocks: HashMap<BlockHash, EraId>,
+    /// The most recent switch block we executed, i.e. the key block of the era we are currently
+    /// loading the switch blocks for. Used to detect stale switch blocks from storage.
+    executed_key_block: Option<BlockHeader>,
+    /// The number of times the switch blocks for an era were requested again because they were
+    /// not in storage, and when the next attempt is due, by the era to be created.
+    switch_block_retries: HashMap<EraId, (u32, Timestamp)>,
+    /// The proposed blocks that are currently being validated, if the number of concurrent
+    /// validations is limited.
+    validations_in_flight: Vec<(EraId, ProposedBlock<ClContext>)>,
+    /// The proposed blocks waiting for validation because the maximum number of concurrent
+    /// validations was reached, with their senders, in the order in which they were received.
+    pending_validations: VecDeque<(EraId, NodeId, ProposedBlock<ClContext>)>,
+    /// The log incoming messages are recorded to, if recording is enabled.
+    #[data_size(skip)]
+    message_log: Option<MessageLog>,
+    /// The source of the current time.
+    #[data_size(skip)]
+    clock: Box<dyn Clock>,
 }
 
 impl Debug for EraSupervisor {
@@ -153,7 +261,6 @@ impl EraSupervisor {
         latest_block_header: &BlockHeader,
         next_upgrade_activation_point: Option<ActivationPoint>,
         registry: &Registry,
-        new_consensus: Box<ConsensusConstructor>,
         storage: &Storage,
         rng: &mut NodeRng,
     ) -> Result<(Self, Effects<Event>), Error> {
@@ -165,6 +272,83 @@ impl EraSupervisor {
                 chainspec.activation_era()
             );
         }
+        if let Some(invalid) = config
+            .finality_threshold_overrides
+            .iter()
+            .find(|ftt_override| !ftt_override.is_valid())
+        {
+            return Err(anyhow::Error::msg(format!(
+                "finality threshold override for {} is not in the range (0, 1): {}",
+                invalid.era_id, invalid.fraction
+            )));
+        }
+        if config.max_concurrent_validations == Some(0) {
+            return Err(anyhow::Error::msg(
+                "max_concurrent_validations must be at least 1",
+            ));
+        }
+        if !config.has_valid_max_tracked_eras() {
+            return Err(anyhow::Error::msg(format!(
+                "max_tracked_eras must be greater than {}: {:?}",
+                PAST_OPEN_ERAS, config.max_tracked_eras
+            )));
+        }
+
+        // Collect the information needed to initialize all open eras.
+        //
+        // We need to initialize current_era, current_era - 1 and (evidence-only) current_era - 2.
+        // To initialize an era, all switch blocks between its booking block and its key block are
+        // required. The booking block for era N is in N - auction_delay - 1, and the key block in
+        // N - 1. So we need all switch blocks between:
+        // (including) current_era - 2 - auction_delay - 1 and (excluding) current_era.
+        // However, we never use any block from before the last activation point.
+        //
+        // Example: If auction_delay is 1, to initialize era N we need the switch blocks from era N
+        // and N - 1. If current_era is 10, we will initialize eras 10, 9 and 8. So we need the
+        // switch blocks from eras 9, 8, 7 and 6.
+        let earliest_open_era = chainspec.earliest_open_era(current_era);
+        let earliest_era = chainspec.earliest_switch_block_needed(earliest_open_era);
+        let mut switch_blocks = Vec::new();
+        for era_id in (earliest_era.value()..current_era.value()).map(EraId::from) {
+            let switch_block = storage
+                .read_switch_block_header_by_era_id(era_id)?
+                .ok_or_else(|| anyhow::Error::msg(format!("No such switch block in {}", era_id)))?;
+            switch_blocks.push(switch_block);
+        }
+
+        Ok(Self::from_switch_blocks(
+            current_era,
+            storage_dir,
+            secret_signing_key,
+            public_signing_key,
+            config,
+            effect_builder,
+            chainspec,
+            latest_block_header,
+            next_upgrade_activation_point,
+            registry,
+            &switch_blocks,
+            rng,
+        ))
+    }
+
+    /// Creates a new `EraSupervisor`, starting in the indicated current era, and initializes the
+    /// open eras from the given switch blocks.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn from_switch_blocks<REv: ReactorEventT>(
+        current_era: EraId,
+        storage_dir: &Path,
+        secret_signing_key: Arc<SecretKey>,
+        public_signing_key: PublicKey,
+        config: Config,
+        effect_builder: EffectBuilder<REv>,
+        chainspec: Arc<Chainspec>,
+        latest_block_header: &BlockHeader,
+        next_upgrade_activation_point: Option<ActivationPoint>,
+        registry: &Registry,
+        switch_blocks: &[BlockHeader],
+        rng: &mut NodeRng,
+    ) -> (Self, Effects<Event>) {
         let unit_files_folder = storage_dir.join("unit_files");
         info!(our_id = %public_signing_key, "EraSupervisor pubkey",);
         let metrics =
@@ -172,6 +356,19 @@ impl EraSupervisor {
         #[allow(clippy::integer_arithmetic)] // Block height should never reach u64::MAX.
         let next_height = latest_block_header.height() + 1;
 
+        let max_buffered_future_messages = config.max_buffered_future_messages;
+        let message_queue = config.max_queued_messages.map(MessageQueue::new);
+        let new_consensus = consensus_constructor(config.protocol);
+        let message_log = config.message_log_path.as_ref().and_then(|path| {
+            let path = storage_dir.join(path);
+            match MessageLog::open(&path, config.max_message_log_size) {
+                Ok(message_log) => Some(message_log),
+                Err(err) => {
+                    error!(?path, %err, "failed to open consensus message log");
+                    None
+                }
+            }
+        });
         let mut era_supervisor = Self {
             open_eras: Default::default(),
             secret_signing_key,
@@ -186,32 +383,26 @@ impl EraSupervisor {
             next_upgrade_activation_point,
             next_executed_height: next_height,
             era_where_we_joined: current_era,
+            pending_block_payloads: EraBuffer::new(MAX_PENDING_BLOCK_PAYLOADS),
+            future_messages: EraBuffer::new(max_buffered_future_messages),
+            latency_probe: None,
+            peer_reputation: PeerReputation::default(),
+            message_batches: BTreeMap::new(),
+            coalesced_timers: BTreeMap::new(),
+            message_queue,
+            caught_up_era: None,
+            last_leader_slot: None,
+            last_equivocation_detection_latency: None,
+            evidence_request_window: (Timestamp::zero(), 0),
+            initialized_key_blocks: HashMap::new(),
+            executed_key_block: None,
+            switch_block_retries: HashMap::new(),
+            validations_in_flight: Vec::new(),
+            pending_validations: VecDeque::new(),
+            message_log,
+            clock: Box::new(SystemClock),
         };
 
-        // Collect the information needed to initialize all open eras.
-        //
-        // We need to initialize current_era, current_era - 1 and (evidence-only) current_era - 2.
-        // To initialize an era, all switch blocks between its booking block and its key block are
-        // required. The booking block for era N is in N - auction_delay - 1, and the key block in
-        // N - 1. So we need all switch blocks between:
-        // (including) current_era - 2 - auction_delay - 1 and (excluding) current_era.
-        // However, we never use any block from before the last activation point.
-        //
-        // Example: If auction_delay is 1, to initialize era N we need the switch blocks from era N
-        // and N - 1. If current_era is 10, we will initialize eras 10, 9 and 8. So we need the
-        // switch blocks from eras 9, 8, 7 and 6.
-        let earliest_open_era = era_supervisor.chainspec.earliest_open_era(current_era);
-        let earliest_era = era_supervisor
-            .chainspec
-            .earliest_switch_block_needed(earliest_open_era);
-        let mut switch_blocks = Vec::new();
-        for era_id in (earliest_era.value()..current_era.value()).map(EraId::from) {
-            let switch_block = storage
-                .read_switch_block_header_by_era_id(era_id)?
-                .ok_or_else(|| anyhow::Error::msg(format!("No such switch block in {}", era_id)))?;
-            switch_blocks.push(switch_block);
-        }
-
         // The create_new_era method initializes the era that the slice's last block is the key
         // block for. We want to initialize the three latest eras, so we have to pass in the whole
         // slice for the current era, and omit one or two elements for the other two. We never
@@ -232,8 +423,10 @@ impl EraSupervisor {
                 &switch_blocks[..i],
             ));
         }
+        effects.extend(era_supervisor.load_evidence(effect_builder));
+        effects.extend(era_supervisor.load_gossip_indices(effect_builder));
 
-        Ok((era_supervisor, effects))
+        (era_supervisor, effects)
     }
 
     /// Returns the merkle tree hash activation from the chainspec.
@@ -338,9 +531,101 @@ impl EraSupervisor {
         rng: &mut NodeRng,
         switch_blocks: &[BlockHeader],
     ) -> Effects<Event> {
+        if let Some(expected) = self.stale_key_block(switch_blocks) {
+            let chainspec = self.chainspec.clone();
+            let era_id = expected.era_id().successor();
+            return async move {
+                let result = get_switch_blocks(chainspec, effect_builder, era_id).await;
+                result.map(|mut switch_blocks| {
+                    switch_blocks.pop();
+                    switch_blocks.push(expected);
+                    switch_blocks
+                })
+            }
+            .event(new_era_event);
+        }
+        let key_block_hash = switch_blocks
+            .last()
+            .map(|key_block| key_block.hash(self.verifiable_chunked_hash_activation()));
+        if let Some(era_id) =
+            key_block_hash.and_then(|hash| self.initialized_key_blocks.get(&hash).copied())
+        {
+            info!(
+                era = era_id.value(),
+                "era was already initialized from this switch block; ignoring it"
+            );
+            return Effects::new();
+        }
         match self.create_new_era(switch_blocks) {
             Ok((era_id, outcomes)) => {
-                self.handle_consensus_outcomes(effect_builder, rng, era_id, outcomes)
+                self.switch_block_retries.remove(&era_id);
+                if let Some(hash) = key_block_hash {
+                    self.initialized_key_blocks.insert(hash, era_id);
+                }
+                let mut effects = self.retire_old_eras(effect_builder);
+                effects.extend(self.save_gossip_indices(effect_builder));
+                effects.extend(self.handle_consensus_outcomes(
+                    effect_builder,
+                    rng,
+                    era_id,
+                    outcomes,
+                ));
+                let future_messages = self.future_messages.take(era_id);
+                self.metrics
+                    .buffered_future_messages
+                    .set(self.future_messages.len() as i64);
+                for (sender, payload) in future_messages {
+                    debug!(era = era_id.value(), %sender, "replaying buffered consensus message");
+                    effects.extend(self.handle_message(
+                        effect_builder,
+                        rng,
+                        sender,
+                        ConsensusMessage::Protocol { era_id, payload },
+                    ));
+                }
+                for new_block_payload in self.pending_block_payloads.take(era_id) {
+                    debug!(era = era_id.value(), "replaying pending block payload");
+                    effects.extend(self.handle_new_block_payload(
+                        effect_builder,
+                        rng,
+                        new_block_payload,
+                    ));
+                }
+                effects
+            }
+            Err(CreateNewEraError::NoValidators { era_id }) => {
+                warn!(
+                    era = era_id.value(),
+                    "no validator weight in switch block; requesting it again later"
+                );
+                let chainspec = self.chainspec.clone();
+                async move {
+                    effect_builder
+                        .set_timeout(STRICT_ERA_TRANSITION_RETRY_DELAY)
+                        .await;
+                    get_switch_blocks(chainspec, effect_builder, era_id).await
+                }
+                .event(new_era_event)
+            }
+            Err(CreateNewEraError::ZeroValidatorWeight {
+                era_id,
+                validator_count,
+            }) => {
+                error!(
+                    era = era_id.value(),
+                    validator_count, "no validator weight in switch block; the era can't run"
+                );
+                let mut effects = effect_builder.announce_no_validator_weight(era_id).ignore();
+                effects.extend(
+                    fatal!(
+                        effect_builder,
+                        "cannot create {} with {} validators and zero total weight",
+                        era_id,
+                        validator_count,
+                    )
+                    .ignore(),
+                );
+                effects
             }
             Err(err) => fatal!(
                 effect_builder,
@@ -351,6 +636,85 @@ impl EraSupervisor {
         }
     }
 
+    /// Requests the switch blocks needed to create `era_id` again after a backoff, or fails if
+    /// they were requested too often already.
+    pub(super) fn handle_switch_blocks_unavailable<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        era_id: EraId,
+        switch_block_era_id: EraId,
+    ) -> Effects<Event> {
+        let attempts = self
+            .switch_block_retries
+            .get(&era_id)
+            .map_or(0, |(attempts, _)| *attempts);
+        if attempts >= MAX_STORAGE_RETRIES {
+            self.switch_block_retries.remove(&era_id);
+            let error = ConsensusError::StorageUnavailable {
+                era_id,
+                switch_block_era_id,
+                attempts,
+            };
+            return fatal!(effect_builder, "{}", error).ignore();
+        }
+        let delay = STORAGE_RETRY_BASE_DELAY.saturating_mul(2u64.saturating_pow(attempts));
+        let retry_at = self.clock.now().saturating_add(delay);
+        self.switch_block_retries
+            .insert(era_id, (attempts.saturating_add(1), retry_at));
+        warn!(
+            era = era_id.value(),
+            switch_block_era = switch_block_era_id.value(),
+            %delay,
+            "switch block not in storage; requesting it again later"
+        );
+        effect_builder
+            .set_timeout(delay.into())
+            .event(move |_| Event::RetrySwitchBlocks { era_id })
+    }
+
+    /// Requests the switch blocks needed to create `era_id` again, unless the era was created in
+    /// the meantime. If the backoff hasn't passed yet according to our clock, waits until it has.
+    pub(super) fn handle_retry_switch_blocks<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        era_id: EraId,
+    ) -> Effects<Event> {
+        let retry_at = match self.switch_block_retries.get(&era_id) {
+            Some((_, retry_at)) => *retry_at,
+            None => return Effects::new(),
+        };
+        let now = self.clock.now();
+        if now < retry_at {
+            return effect_builder
+                .set_timeout(retry_at.saturating_diff(now).into())
+                .event(move |_| Event::RetrySwitchBlocks { era_id });
+        }
+        get_switch_blocks(self.chainspec.clone(), effect_builder, era_id).event(new_era_event)
+    }
+
+    /// Returns the switch block we executed, if the given key block is from the same era but
+    /// differs from it, e.g. because storage returned an outdated header during a fast catch-up.
+    ///
+    /// The validators of the new era must be read from the block we executed, so in that case
+    /// the switch blocks need to be requested again.
+    fn stale_key_block(&self, switch_blocks: &[BlockHeader]) -> Option<BlockHeader> {
+        let expected = self.executed_key_block.as_ref()?;
+        let key_block = switch_blocks.last()?;
+        let activation = self.verifiable_chunked_hash_activation();
+        if key_block.era_id() != expected.era_id()
+            || key_block.hash(activation) == expected.hash(activation)
+        {
+            return None;
+        }
+        warn!(
+            era = key_block.era_id().value(),
+            stale_state_root_hash = %key_block.state_root_hash(),
+            expected_state_root_hash = %expected.state_root_hash(),
+            "key block from storage is stale; requesting switch blocks again"
+        );
+        Some(expected.clone())
+    }
+
     /// Initializes a new era. The switch blocks must contain the most recent `auction_delay + 1`
     /// ones, in order, but at most as far back as to the last activation point.
     fn create_new_era(
@@ -360,7 +724,10 @@ impl EraSupervisor {
         let key_block = switch_blocks
             .last()
             .ok_or(CreateNewEraError::AttemptedToCreateEraWithNoSwitchBlocks)?;
-        let era_id = key_block.era_id().successor();
+        let era_id = key_block
+            .era_id()
+            .checked_successor()
+            .ok_or(CreateNewEraError::MaxEraReached)?;
         let era_end = key_block.era_end().ok_or_else(|| {
             CreateNewEraError::LastBlockHeaderNotASwitchBlock {
                 era_id,
@@ -393,10 +760,31 @@ impl EraSupervisor {
             warn!(era = era_id.value(), "era already exists");
             return Ok((era_id, vec![]));
         }
-        if self.current_era > era_id.saturating_add(PAST_OPEN_ERAS) {
+        if self.config.strict_era_transition && validators.values().all(U512::is_zero) {
+            return Err(CreateNewEraError::NoValidators { era_id });
+        }
+        if self.current_era > era_id.saturating_add(self.retained_past_eras()) {
             warn!(era = era_id.value(), "trying to create obsolete era");
             return Ok((era_id, vec![]));
         }
+        // Without any weight, no threshold can be reached and no leader can be selected.
+        if validators.values().all(U512::is_zero) {
+            return Err(CreateNewEraError::ZeroValidatorWeight {
+                era_id,
+                validator_count: validators.len(),
+            });
+        }
+        // An inconsistent report indicates corrupt data. It doesn't prevent the era from running,
+        // but it needs to be investigated.
+        let ending_era_validators = self.open_eras.get(&key_block.era_id()).map(Era::validators);
+        for anomaly in ValidatorSetAnomaly::find(report, ending_era_validators) {
+            error!(
+                era = era_id.value(),
+                key_block_era = key_block.era_id().value(),
+                %anomaly,
+                "inconsistent validator set in switch block"
+            );
+        }
 
         // Compute the seed for the PRNG from the booking block hash and the accumulated seed.
         let auction_delay = self.chainspec.core_config.auction_delay as usize;
@@ -432,7 +820,7 @@ impl EraSupervisor {
         let chainspec_hash = self.chainspec.hash();
         let key_block_hash = key_block.hash(self.verifiable_chunked_hash_activation());
         let instance_id = instance_id(chainspec_hash, era_id, key_block_hash);
-        let now = Timestamp::now();
+        let now = self.clock.now();
 
         info!(
             ?validators,
@@ -456,6 +844,11 @@ impl EraSupervisor {
             .cloned()
             .collect();
 
+        let ftt = self.finality_threshold_fraction(era_id);
+        if ftt != self.chainspec.highway_config.finality_threshold_fraction {
+            info!(era = era_id.value(), %ftt, "using overridden finality threshold fraction");
+        }
+
         // Create and insert the new era instance.
         let (consensus, mut outcomes) = (self.new_consensus)(
             instance_id,
@@ -464,6 +857,7 @@ impl EraSupervisor {
             &inactive,
             self.chainspec.as_ref(),
             &self.config,
+            ftt,
             maybe_prev_era.map(|prev_era| &*prev_era.consensus),
             start_time,
             seed,
@@ -493,13 +887,17 @@ impl EraSupervisor {
             self.metrics.current_era.set(era_id.value() as i64);
             self.next_block_height = self.next_block_height.max(start_height);
             outcomes.extend(self.era_mut(era_id).consensus.handle_is_current(now));
+            let participation = self.config.participation;
             if !self.era(era_id).validators().contains_key(&our_id) {
                 info!(era = era_id.value(), %our_id, "not voting; not a validator");
+            } else if participation == Participation::Observer {
+                info!(era = era_id.value(), %our_id, "not voting; running as an observer");
             } else {
                 info!(era = era_id.value(), %our_id, "start voting");
                 let secret = Keypair::new(self.secret_signing_key.clone(), our_id.clone());
                 let unit_hash_file = self.unit_file(&instance_id);
-                outcomes.extend(self.era_mut(era_id).consensus.activate_validator(
+                outcomes.extend(self.era_mut(era_id).activate_validator(
+                    participation,
                     our_id,
                     secret,
                     now,
@@ -530,23 +928,78 @@ impl EraSupervisor {
                 trace!(era = evidence_only_era_id.value(), "clearing unbonded era");
                 era.consensus.set_evidence_only();
             }
+        }
+        self.peer_reputation.prune(self.clock.now());
 
-            // Remove the era that has become obsolete now: We keep only three in memory.
-            if let Some(obsolete_era_id) = evidence_only_era_id.checked_sub(1) {
-                if let Some(era) = self.open_eras.remove(&obsolete_era_id) {
-                    trace!(era = obsolete_era_id.value(), "removing obsolete era");
-                    match fs::remove_file(self.unit_file(era.consensus.instance_id())) {
-                        Ok(_) => {}
-                        Err(err) => match err.kind() {
-                            io::ErrorKind::NotFound => {}
-                            err => warn!(?err, "could not delete unit hash file"),
-                        },
-                    }
+        Ok((era_id, outcomes))
+    }
+
+    /// Returns the number of past eras that are kept in memory in addition to the current one.
+    fn retained_past_eras(&self) -> u64 {
+        self.config.retained_past_eras.max(PAST_OPEN_ERAS)
+    }
+
+    /// Removes all eras that are older than the retention window from memory. If more than
+    /// `max_tracked_eras` remain, the oldest ones that are not needed to validate evidence are
+    /// removed, too. The evidence and gossip indices persisted for the retired eras are deleted.
+    fn retire_old_eras<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event> {
+        let earliest_retained_era = self.current_era.saturating_sub(self.retained_past_eras());
+        self.initialized_key_blocks
+            .retain(|_, era_id| *era_id >= earliest_retained_era);
+        let mut retired_era_ids: Vec<EraId> = self
+            .open_eras
+            .keys()
+            .filter(|era_id| **era_id < earliest_retained_era)
+            .copied()
+            .collect();
+        if let Some(max_eras) = self.config.max_tracked_eras {
+            let excess = self
+                .open_eras
+                .len()
+                .saturating_sub(retired_era_ids.len())
+                .saturating_sub(max_eras);
+            let earliest_bonded_era = self.current_era.saturating_sub(PAST_OPEN_ERAS);
+            let mut candidates: Vec<EraId> = self
+                .open_eras
+                .keys()
+                .filter(|era_id| {
+                    **era_id >= earliest_retained_era && **era_id < earliest_bonded_era
+                })
+                .copied()
+                .collect();
+            // The open eras are not ordered, but the oldest ones must be retired first.
+            candidates.sort();
+            retired_era_ids.extend(candidates.into_iter().take(excess));
+        }
+        let mut effects = Effects::new();
+        for era_id in retired_era_ids {
+            if let Some(era) = self.open_eras.remove(&era_id) {
+                debug!(era = era_id.value(), "retiring era");
+                match fs::remove_file(self.unit_file(era.consensus.instance_id())) {
+                    Ok(_) => {}
+                    Err(err) => match err.kind() {
+                        io::ErrorKind::NotFound => {}
+                        err => warn!(?err, "could not delete unit hash file"),
+                    },
+                }
+                for pub_key in era.validators().keys() {
+                    effects.extend(
+                        effect_builder
+                            .delete_state(evidence_key(era_id, pub_key))
+                            .ignore(),
+                    );
                 }
+                effects.extend(
+                    effect_builder
+                        .delete_state(gossip_index_key(era_id))
+                        .ignore(),
+                );
             }
         }
-
-        Ok((era_id, outcomes))
+        effects
     }
 
     /// Returns the path to the era's unit file.
@@ -583,68 +1036,1115 @@ impl EraSupervisor {
             }
             Some(era) => {
                 let outcomes = f(&mut *era.consensus, rng);
-                self.handle_consensus_outcomes(effect_builder, rng, era_id, outcomes)
+                let mut effects =
+                    self.handle_consensus_outcomes(effect_builder, rng, era_id, outcomes);
+                effects.extend(self.check_caught_up(effect_builder, era_id));
+                effects
+            }
+        }
+    }
+
+    /// Announces that we caught up if the era is the current one and it is synced, unless we
+    /// already did so for that era.
+    fn check_caught_up<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        era_id: EraId,
+    ) -> Effects<Event> {
+        if era_id != self.current_era
+            || self.caught_up_era >= Some(era_id)
+            || !self
+                .open_eras
+                .get(&era_id)
+                .map_or(false, |era| era.consensus.is_synced())
+        {
+            return Effects::new();
+        }
+        self.caught_up_era = Some(era_id);
+        info!(era = era_id.value(), "caught up with the current era");
+        effect_builder.announce_caught_up(era_id).ignore()
+    }
+
+    /// Announces that we are the leader in the slot of the given block context, unless that was
+    /// already announced for this slot.
+    fn announce_leader_slot<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        era_id: EraId,
+        block_context: &BlockContext<ClContext>,
+    ) -> Effects<Event> {
+        let slot = (era_id, block_context.timestamp());
+        if self.last_leader_slot == Some(slot) {
+            return Effects::new();
+        }
+        self.last_leader_slot = Some(slot);
+        effect_builder
+            .announce_we_are_leader(era_id, block_context.clone())
+            .ignore()
+    }
+
+    pub(super) fn handle_timer<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+        era_id: EraId,
+        timestamp: Timestamp,
+        timer_id: TimerId,
+    ) -> Effects<Event> {
+        let mut effects = self.delegate_to_era(effect_builder, rng, era_id, move |consensus, _| {
+            consensus.handle_timer(timestamp, timer_id)
+        });
+        effects.extend(self.check_era_stalled(effect_builder, era_id, timestamp));
+        effects.extend(self.probe_latency(effect_builder, rng, era_id));
+        effects
+    }
+
+    /// Sends a latency probe to all peers if the era is the current one and the configured
+    /// interval has passed since the last probe.
+    fn probe_latency<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+        era_id: EraId,
+    ) -> Effects<Event> {
+        let interval = match self.config.latency_probe_interval {
+            Some(interval) if era_id == self.current_era => interval,
+            _ => return Effects::new(),
+        };
+        let now = self.clock.now();
+        if let Some((_, sent)) = self.latency_probe {
+            if now.saturating_diff(sent) < interval {
+                return Effects::new();
             }
         }
+        let nonce = rng.gen();
+        self.latency_probe = Some((nonce, now));
+        let message = ConsensusMessage::Ping { era_id, nonce };
+        effect_builder.broadcast_message(message.into()).ignore()
+    }
+
+    /// Announces the era as stalled if it is the current one and it has not finalized a block
+    /// for longer than the configured timeout.
+    fn check_era_stalled<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        era_id: EraId,
+        now: Timestamp,
+    ) -> Effects<Event> {
+        let stall_timeout = match self.config.era_stall_timeout {
+            Some(stall_timeout) if era_id == self.current_era => stall_timeout,
+            _ => return Effects::new(),
+        };
+        let since = match self
+            .open_eras
+            .get_mut(&era_id)
+            .and_then(|era| era.check_stalled(now, stall_timeout))
+        {
+            Some(since) => since,
+            None => return Effects::new(),
+        };
+        warn!(
+            era = era_id.value(),
+            %since,
+            "era has not finalized a block for longer than {}",
+            stall_timeout
+        );
+        effect_builder.announce_era_stalled(era_id, since).ignore()
+    }
+
+    pub(super) fn handle_action<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+        era_id: EraId,
+        action_id: ActionId,
+    ) -> Effects<Event> {
+        let now = self.clock.now();
+        self.delegate_to_era(effect_builder, rng, era_id, move |consensus, _| {
+            consensus.handle_action(action_id, now)
+        })
+    }
+
+    /// Handles a message received from a peer, after recording it in the message log if that is
+    /// enabled.
+    pub(super) fn handle_incoming<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+        sender: NodeId,
+        msg: ConsensusMessage,
+    ) -> Effects<Event> {
+        let msg = if self.config.require_signed_messages {
+            match self.drop_unsigned(sender, msg) {
+                Some(msg) => msg,
+                None => return Effects::new(),
+            }
+        } else {
+            msg
+        };
+        if let Some(message_log) = &mut self.message_log {
+            let entry = LoggedMessage {
+                timestamp: self.clock.now(),
+                sender,
+                message: msg,
+            };
+            if let Err(err) = message_log.record(&entry) {
+                error!(%err, "failed to record consensus message; disabling the message log");
+                self.message_log = None;
+            }
+            return self.handle_message(effect_builder, rng, sender, entry.message);
+        }
+        self.handle_message(effect_builder, rng, sender, msg)
+    }
+
+    /// Drops unsigned protocol messages, except for evidence: that is self-authenticating, and
+    /// it needs to reach observers and non-validators, too, e.g. in replies to evidence requests.
+    /// Returns the remaining message, if any.
+    fn drop_unsigned(&self, sender: NodeId, msg: ConsensusMessage) -> Option<ConsensusMessage> {
+        let is_evidence = |era_id: &EraId, payload: &[u8]| {
+            self.open_eras
+                .get(era_id)
+                .map_or(false, |era| era.consensus.is_evidence(payload))
+        };
+        match msg {
+            ConsensusMessage::Protocol { era_id, payload } => {
+                if is_evidence(&era_id, &payload) {
+                    return Some(ConsensusMessage::Protocol { era_id, payload });
+                }
+                debug!(
+                    era = era_id.value(),
+                    %sender,
+                    "unsigned consensus protocol message; dropping it"
+                );
+                self.record_dropped_message(era_id, sender, MessageDropReason::Unsigned);
+                None
+            }
+            ConsensusMessage::ProtocolBatch { era_id, payloads }
+                if payloads.len() <= self.config.max_message_batch_size =>
+            {
+                let count = payloads.len();
+                let payloads: Vec<_> = payloads
+                    .into_iter()
+                    .filter(|payload| is_evidence(&era_id, payload))
+                    .collect();
+                if payloads.len() < count {
+                    debug!(
+                        era = era_id.value(),
+                        %sender,
+                        dropped = count - payloads.len(),
+                        "unsigned consensus protocol messages in batch; dropping them"
+                    );
+                    for _ in payloads.len()..count {
+                        self.record_dropped_message(era_id, sender, MessageDropReason::Unsigned);
+                    }
+                }
+                if payloads.is_empty() {
+                    return None;
+                }
+                Some(ConsensusMessage::ProtocolBatch { era_id, payloads })
+            }
+            msg => Some(msg),
+        }
+    }
+
+    /// Feeds all messages from the message log at `path` into this supervisor, in the order in
+    /// which they were recorded, e.g. to reproduce a stuck era offline in a node that was started
+    /// from a copy of the stuck node's storage, without any peers. This is triggered via the
+    /// diagnostics port.
+    ///
+    /// While each message is handled, the clock shows the time at which it was originally
+    /// received. The returned effects need to be executed by the caller. Replayed messages are not
+    /// recorded again.
+    pub(crate) fn replay_message_log<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+        path: &Path,
+    ) -> Result<Effects<Event>, bincode::Error> {
+        let entries = read_message_log(path)?;
+        let clock = MockClock::default();
+        let previous_clock = std::mem::replace(&mut self.clock, Box::new(clock.clone()));
+        let mut effects = Effects::new();
+        for entry in entries {
+            clock.set(entry.timestamp);
+            effects.extend(self.handle_message(effect_builder, rng, entry.sender, entry.message));
+        }
+        self.clock = previous_clock;
+        Ok(effects)
+    }
+
+    /// Handles a message from a peer.
+    ///
+    /// Several eras can be open at the same time, e.g. the current one and its predecessors, which
+    /// still accept evidence. Protocol messages are routed strictly by their era ID: they are
+    /// handled by that era's consensus instance, buffered if the era is in the future and not
+    /// initialized yet, and dropped otherwise. They are never passed to a different era. Batches
+    /// with more than `max_message_batch_size` messages are dropped; smaller ones are unpacked,
+    /// and each of their messages is handled individually. If queueing is enabled, protocol
+    /// messages are queued and handled later, by priority, except for urgent ones, like
+    /// units in the current era, which are handled immediately.
+    /// Evidence requests are answered by the given era and the ones before it that are still
+    /// open, since evidence from those eras is relevant for the requested one.
+    ///
+    /// Every message that is dropped or ignored is logged at debug level and counted in the
+    /// metrics, together with the reason.
+    pub(super) fn handle_message<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+        sender: NodeId,
+        msg: ConsensusMessage,
+    ) -> Effects<Event> {
+        match msg {
+            ConsensusMessage::Protocol { era_id, payload } => {
+                // If the era is already unbonded, only accept new evidence, because still-bonded
+                // eras could depend on that.
+                trace!(era = era_id.value(), "received a consensus message");
+                if self.drop_if_oversized(era_id, sender, &payload) {
+                    return Effects::new();
+                }
+                if era_id > self.current_era && !self.open_eras.contains_key(&era_id) {
+                    return self.buffer_future_message(era_id, sender, payload);
+                }
+                if era_id < self.current_era && !self.open_eras.contains_key(&era_id) {
+                    self.record_dropped_message(era_id, sender, MessageDropReason::AncientEra);
+                    return Effects::new();
+                }
+                if self.message_queue.is_some() {
+                    let priority = self.message_priority(era_id, &payload);
+                    if priority != MessagePriority::Urgent {
+                        return self.queue_message(
+                            effect_builder,
+                            priority,
+                            era_id,
+                            sender,
+                            payload,
+                        );
+                    }
+                }
+                self.handle_protocol_message(effect_builder, rng, era_id, sender, payload)
+            }
+            ConsensusMessage::SignedProtocol {
+                era_id,
+                payload,
+                public_key,
+                signature,
+            } => {
+                // The cheap checks come first, so that verifying signatures doesn't cost us more
+                // than the sender.
+                if self.drop_if_oversized(era_id, sender, &payload) {
+                    return Effects::new();
+                }
+                // If the era isn't open, we can't tell whether the signer is a validator in it.
+                if !self
+                    .open_eras
+                    .get(&era_id)
+                    .map_or(false, |era| era.validators().contains_key(&public_key))
+                {
+                    info!(
+                        era = era_id.value(),
+                        %sender,
+                        %public_key,
+                        "consensus message signed by a non-validator or for an era that isn't \
+                         open; dropping it"
+                    );
+                    self.record_dropped_message(
+                        era_id,
+                        sender,
+                        MessageDropReason::NonValidatorSender,
+                    );
+                    return Effects::new();
+                }
+                if let Err(error) =
+                    verify_protocol_signature(era_id, &payload, &public_key, &signature)
+                {
+                    warn!(
+                        era = era_id.value(),
+                        %sender,
+                        %public_key,
+                        %error,
+                        "invalid consensus message signature; dropping it"
+                    );
+                    self.peer_reputation
+                        .record(sender, PeerBehavior::Invalid, self.clock.now());
+                    self.record_dropped_message(era_id, sender, MessageDropReason::BadSignature);
+                    return Effects::new();
+                }
+                let message = ConsensusMessage::Protocol { era_id, payload };
+                self.handle_message(effect_builder, rng, sender, message)
+            }
+            ConsensusMessage::ProtocolBatch { era_id, payloads } => {
+                if payloads.len() > self.config.max_message_batch_size {
+                    info!(
+                        era = era_id.value(),
+                        %sender,
+                        count = payloads.len(),
+                        max_count = self.config.max_message_batch_size,
+                        "consensus message batch is too large; dropping it"
+                    );
+                    self.metrics.oversized_messages.inc();
+                    self.peer_reputation
+                        .record(sender, PeerBehavior::Oversized, self.clock.now());
+                    self.record_dropped_message(era_id, sender, MessageDropReason::TooLarge);
+                    return Effects::new();
+                }
+                payloads
+                    .into_iter()
+                    .flat_map(|payload| {
+                        let message = ConsensusMessage::Protocol { era_id, payload };
+                        self.handle_message(effect_builder, rng, sender, message)
+                    })
+                    .collect()
+            }
+            ConsensusMessage::EvidenceRequest { era_id, pub_key } => {
+                if era_id.saturating_add(PAST_EVIDENCE_ERAS) < self.current_era
+                    || !self.open_eras.contains_key(&era_id)
+                {
+                    self.record_dropped_message(era_id, sender, MessageDropReason::AncientEra);
+                    return Effects::new();
+                }
+                if !self.count_evidence_request() {
+                    self.record_dropped_message(
+                        era_id,
+                        sender,
+                        MessageDropReason::EvidenceRateLimited,
+                    );
+                    return Effects::new();
+                }
+                self.iter_past(era_id, PAST_EVIDENCE_ERAS)
+                    .flat_map(|e_id| match self.persisted_evidence(e_id, &pub_key) {
+                        Some(payload) => {
+                            let message = self.protocol_message(e_id, payload);
+                            effect_builder.send_message(sender, message.into()).ignore()
+                        }
+                        None => self.delegate_to_era(effect_builder, rng, e_id, |consensus, _| {
+                            consensus.request_evidence(sender, &pub_key)
+                        }),
+                    })
+                    .collect()
+            }
+            ConsensusMessage::Ping { era_id, nonce } => {
+                trace!(era = era_id.value(), %sender, nonce, "answering latency probe");
+                let message = ConsensusMessage::Pong { era_id, nonce };
+                effect_builder.send_message(sender, message.into()).ignore()
+            }
+            ConsensusMessage::BlockSignaturesRequest { era_id, block_hash } => {
+                trace!(
+                    era = era_id.value(),
+                    %sender,
+                    %block_hash,
+                    "answering block signatures request"
+                );
+                async move {
+                    // Signatures from a different era are not the ones the peer asked for.
+                    let signatures = effect_builder
+                        .get_signatures_from_storage(block_hash)
+                        .await
+                        .filter(|signatures| signatures.era_id == era_id);
+                    let message = ConsensusMessage::BlockSignaturesResponse {
+                        era_id,
+                        block_hash,
+                        signatures,
+                    };
+                    effect_builder.send_message(sender, message.into()).await
+                }
+                .ignore()
+            }
+            ConsensusMessage::BlockSignaturesResponse {
+                era_id,
+                block_hash,
+                signatures,
+            } => {
+                self.handle_block_signatures(effect_builder, sender, era_id, block_hash, signatures)
+            }
+            ConsensusMessage::Pong { era_id, nonce } => {
+                match self.latency_probe {
+                    Some((probe_nonce, sent)) if probe_nonce == nonce => {
+                        let latency = self.clock.now().saturating_diff(sent);
+                        debug!(
+                            era = era_id.value(),
+                            %sender,
+                            %latency,
+                            "received latency probe response"
+                        );
+                        self.metrics.record_peer_latency(&sender, latency);
+                    }
+                    _ => trace!(
+                        era = era_id.value(),
+                        %sender,
+                        nonce,
+                        "ignoring unexpected latency probe response"
+                    ),
+                }
+                Effects::new()
+            }
+        }
+    }
+
+    /// Adds an outgoing protocol message to the era's batch. The batch is sent once it is full, or
+    /// once the batching window has passed since its first message was added.
+    fn batch_message<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        era_id: EraId,
+        payload: Vec<u8>,
+        window: TimeDiff,
+    ) -> Effects<Event> {
+        let batch = self.message_batches.entry(era_id).or_default();
+        batch.push(payload);
+        if batch.len() >= self.config.max_message_batch_size {
+            self.flush_message_batch(effect_builder, era_id)
+        } else if batch.len() == 1 {
+            effect_builder
+                .set_timeout(window.into())
+                .event(move |_| Event::FlushMessageBatch { era_id })
+        } else {
+            Effects::new()
+        }
+    }
+
+    /// Sends all batched outgoing protocol messages for the given era to all peers.
+    ///
+    /// A timer scheduled for a batch that was already sent because it was full can send the next
+    /// batch a bit early, which is harmless.
+    pub(super) fn flush_message_batch<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        era_id: EraId,
+    ) -> Effects<Event> {
+        let mut payloads = self.message_batches.remove(&era_id).unwrap_or_default();
+        if payloads.len() > 1 && self.signs_messages(era_id) {
+            // A batch can't be signed, so signed messages are sent individually.
+            return payloads
+                .into_iter()
+                .flat_map(|payload| {
+                    let message = self.protocol_message(era_id, payload);
+                    effect_builder.broadcast_message(message.into()).ignore()
+                })
+                .collect();
+        }
+        let message = match payloads.len() {
+            0 => return Effects::new(),
+            1 => self.protocol_message(era_id, payloads.remove(0)),
+            _ => ConsensusMessage::ProtocolBatch { era_id, payloads },
+        };
+        effect_builder.broadcast_message(message.into()).ignore()
+    }
+
+    /// Adds a protocol timer to the coalescing window it falls into, i.e. it is rounded up to the
+    /// next multiple of the window. Only the first timer in a window schedules a timeout.
+    fn schedule_coalesced_timer<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        era_id: EraId,
+        timestamp: Timestamp,
+        timer_id: TimerId,
+        window: TimeDiff,
+    ) -> Effects<Event> {
+        let millis = timestamp.millis();
+        let fire_at = match millis.checked_rem(window.millis()) {
+            Some(0) | None => timestamp,
+            Some(rem) => {
+                Timestamp::from(millis.saturating_add(window.millis().saturating_sub(rem)))
+            }
+        };
+        let timers = self.coalesced_timers.entry(fire_at).or_default();
+        timers.push((era_id, timestamp, timer_id));
+        if timers.len() > 1 {
+            return Effects::new();
+        }
+        let timediff = fire_at.saturating_diff(self.clock.now());
+        effect_builder
+            .set_timeout(timediff.into())
+            .event(move |_| Event::CoalescedTimers { fire_at })
+    }
+
+    /// Handles all protocol timers in the coalescing window ending at `fire_at`, in one pass.
+    pub(super) fn handle_coalesced_timers<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+        fire_at: Timestamp,
+    ) -> Effects<Event> {
+        let timers = self.coalesced_timers.remove(&fire_at).unwrap_or_default();
+        let mut effects = Effects::new();
+        for (era_id, timestamp, timer_id) in timers {
+            effects.extend(self.handle_timer(effect_builder, rng, era_id, timestamp, timer_id));
+        }
+        effects
+    }
+
+    /// Returns whether our protocol messages in the given era are signed, i.e. whether that is
+    /// configured and we are a validator in the era.
+    fn signs_messages(&self, era_id: EraId) -> bool {
+        self.config.sign_messages
+            && self.config.participation == Participation::Validator
+            && self.open_eras.get(&era_id).map_or(false, |era| {
+                era.validators().contains_key(&self.public_signing_key)
+            })
+    }
+
+    /// Returns a protocol message with the given payload. It is signed if that is configured and
+    /// we are a validator in the era.
+    fn protocol_message(&self, era_id: EraId, payload: Vec<u8>) -> ConsensusMessage {
+        if self.signs_messages(era_id) {
+            ConsensusMessage::new_signed_protocol(
+                era_id,
+                payload,
+                &self.secret_signing_key,
+                self.public_signing_key.clone(),
+            )
+        } else {
+            ConsensusMessage::Protocol { era_id, payload }
+        }
+    }
+
+    /// Asks all peers for evidence that the validator with the given public key equivocated in
+    /// the given era or earlier.
+    pub(super) fn request_evidence<REv: ReactorEventT>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+        era_id: EraId,
+        pub_key: PublicKey,
+    ) -> Effects<Event> {
+        if !self.config.broadcast_evidence_requests {
+            debug!(
+                era = era_id.value(),
+                %pub_key,
+                "not broadcasting evidence request; disabled in config"
+            );
+            return Effects::new();
+        }
+        let message = ConsensusMessage::EvidenceRequest { era_id, pub_key };
+        effect_builder.broadcast_message(message.into()).ignore()
+    }
+
+    /// Stores the evidence against `pub_key` in the given era, so that we can still send it to
+    /// peers after a restart.
+    fn persist_evidence<REv: ReactorEventT>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+        era_id: EraId,
+        pub_key: &PublicKey,
+    ) -> Effects<Event> {
+        let evidence = match self.open_eras.get(&era_id) {
+            Some(era) => era.consensus.evidence_message(pub_key),
+            None => None,
+        };
+        match evidence {
+            Some(evidence) => effect_builder
+                .save_state(evidence_key(era_id, pub_key), evidence)
+                .ignore(),
+            None => Effects::new(),
+        }
+    }
+
+    /// Loads the stored evidence against the validators of all open eras.
+    ///
+    /// This is only needed on startup: evidence is stored when it is first added to an era, so
+    /// eras created later have nothing stored yet.
+    fn load_evidence<REv: ReactorEventT>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event> {
+        let mut effects = Effects::new();
+        for (era_id, era) in &self.open_eras {
+            for pub_key in era.validators().keys() {
+                let era_id = *era_id;
+                let pub_key = pub_key.clone();
+                effects.extend(
+                    effect_builder
+                        .load_state::<Vec<u8>>(evidence_key(era_id, &pub_key))
+                        .map_some(move |evidence| Event::LoadedEvidence {
+                            era_id,
+                            pub_key,
+                            evidence,
+                        }),
+                );
+            }
+        }
+        effects
+    }
+
+    /// Keeps evidence that was loaded from storage, to answer evidence requests with it.
+    pub(super) fn handle_loaded_evidence(
+        &mut self,
+        era_id: EraId,
+        pub_key: PublicKey,
+        evidence: Vec<u8>,
+    ) -> Effects<Event> {
+        match self.open_eras.get_mut(&era_id) {
+            Some(era) => {
+                debug!(era = era_id.value(), %pub_key, "loaded evidence from storage");
+                era.persisted_evidence.insert(pub_key, evidence);
+            }
+            None => debug!(
+                era = era_id.value(),
+                %pub_key,
+                "ignoring loaded evidence for an era that is not open"
+            ),
+        }
+        Effects::new()
+    }
+
+    /// Adds the message to the era's gossip index, and persists the index every
+    /// `GOSSIP_INDEX_SAVE_INTERVAL` messages. Returns `None` if the message was gossiped before,
+    /// and should not be sent again.
+    fn record_gossip<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        era_id: EraId,
+        payload: &[u8],
+    ) -> Option<Effects<Event>> {
+        let era = match self.open_eras.get_mut(&era_id) {
+            Some(era) => era,
+            None => return Some(Effects::new()),
+        };
+        if !era.gossiped.insert(payload) {
+            return None;
+        }
+        match era.gossiped.take_unsaved(GOSSIP_INDEX_SAVE_INTERVAL) {
+            Some(keys) => Some(
+                effect_builder
+                    .save_state(gossip_index_key(era_id), keys)
+                    .ignore(),
+            ),
+            None => Some(Effects::new()),
+        }
+    }
+
+    /// Persists the gossip indices of all open eras that changed since they were last persisted.
+    fn save_gossip_indices<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event> {
+        let mut effects = Effects::new();
+        for (era_id, era) in &mut self.open_eras {
+            if let Some(keys) = era.gossiped.take_unsaved(1) {
+                effects.extend(
+                    effect_builder
+                        .save_state(gossip_index_key(*era_id), keys)
+                        .ignore(),
+                );
+            }
+        }
+        effects
+    }
+
+    /// Loads the stored gossip indices of all open eras.
+    ///
+    /// This is only needed on startup, like `load_evidence`.
+    fn load_gossip_indices<REv: ReactorEventT>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event> {
+        let mut effects = Effects::new();
+        for era_id in self.open_eras.keys().copied() {
+            effects.extend(
+                effect_builder
+                    .load_state::<Vec<u64>>(gossip_index_key(era_id))
+                    .map_some(move |keys| Event::LoadedGossipIndex { era_id, keys }),
+            );
+        }
+        effects
+    }
+
+    /// Adds a gossip index that was loaded from storage to the era's index, so that the messages
+    /// we gossiped before a restart are not gossiped again.
+    pub(super) fn handle_loaded_gossip_index(
+        &mut self,
+        era_id: EraId,
+        keys: Vec<u64>,
+    ) -> Effects<Event> {
+        match self.open_eras.get_mut(&era_id) {
+            Some(era) => {
+                debug!(
+                    era = era_id.value(),
+                    count = keys.len(),
+                    "loaded gossip index from storage"
+                );
+                era.gossiped.extend(keys);
+            }
+            None => debug!(
+                era = era_id.value(),
+                "ignoring loaded gossip index for an era that is not open"
+            ),
+        }
+        Effects::new()
+    }
+
+    /// Returns the evidence against `pub_key` that was loaded from storage, unless the protocol
+    /// instance of the given era has the evidence itself.
+    fn persisted_evidence(&self, era_id: EraId, pub_key: &PublicKey) -> Option<Vec<u8>> {
+        let era = self.open_eras.get(&era_id)?;
+        if era.consensus.has_evidence(pub_key) {
+            return None;
+        }
+        era.persisted_evidence.get(pub_key).cloned()
+    }
+
+    /// Requests the units and evidence we are missing in the given era from peers again.
+    ///
+    /// This is a recovery tool for a stalled node. It doesn't change the era's state, so it is
+    /// safe to call repeatedly.
+    pub(super) fn force_era_sync<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+        era_id: EraId,
+    ) -> Effects<Event> {
+        let missing_evidence = match self.open_eras.get(&era_id) {
+            Some(era) => era.missing_evidence(),
+            None => {
+                info!(
+                    era = era_id.value(),
+                    "cannot force sync of era that is not open"
+                );
+                return Effects::new();
+            }
+        };
+        info!(
+            era = era_id.value(),
+            missing_evidence = missing_evidence.len(),
+            "forcing sync of era"
+        );
+        let mut effects = Effects::new();
+        for pub_key in missing_evidence {
+            if !self.has_evidence(era_id, pub_key.clone()) {
+                effects.extend(self.request_evidence(effect_builder, era_id, pub_key));
+            }
+        }
+        effects.extend(
+            self.delegate_to_era(effect_builder, rng, era_id, |consensus, _| {
+                consensus.request_missing_units()
+            }),
+        );
+        effects
+    }
+
+    /// Returns the checkpoint of the current state.
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            era_id: self.current_era,
+            next_block_height: self.next_block_height,
+            next_executed_height: self.next_executed_height,
+        }
+    }
+
+    /// Saves the checkpoint of the current state, replacing the previous one.
+    fn save_checkpoint<REv: ReactorEventT>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event> {
+        effect_builder
+            .save_state(Checkpoint::key(), self.checkpoint())
+            .ignore()
+    }
+
+    /// Loads the latest checkpoint, to resume from it.
+    ///
+    /// This should be called once, right after the supervisor was created on startup.
+    pub(super) fn resume_from_checkpoint<REv: ReactorEventT>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event> {
+        effect_builder
+            .load_state::<Checkpoint>(Checkpoint::key())
+            .event(|maybe_checkpoint| Event::LoadedCheckpoint { maybe_checkpoint })
+    }
+
+    /// Resumes from the loaded checkpoint, if it belongs to the current era: Restores the block
+    /// heights and announces our presence to peers by requesting the era's latest state.
+    ///
+    /// Without a checkpoint, or with one from a different era, the supervisor keeps the state it
+    /// was initialized with from storage.
+    pub(super) fn handle_loaded_checkpoint<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+        maybe_checkpoint: Option<Checkpoint>,
+    ) -> Effects<Event> {
+        let checkpoint = match maybe_checkpoint {
+            Some(checkpoint) => checkpoint,
+            None => {
+                info!("no consensus checkpoint found; starting from the latest switch block");
+                return Effects::new();
+            }
+        };
+        if checkpoint.era_id != self.current_era {
+            warn!(
+                %checkpoint,
+                current_era = self.current_era.value(),
+                "consensus checkpoint is not from the current era; not resuming from it"
+            );
+            return Effects::new();
+        }
+        info!(%checkpoint, "resuming from consensus checkpoint");
+        self.next_block_height = self.next_block_height.max(checkpoint.next_block_height);
+        self.next_executed_height = self
+            .next_executed_height
+            .max(checkpoint.next_executed_height);
+        self.update_consensus_pause();
+        self.force_era_sync(effect_builder, rng, checkpoint.era_id)
+    }
+
+    /// Handles the finality signatures a peer sent in response to a `BlockSignaturesRequest`.
+    ///
+    /// If they are valid and their total weight exceeds the era's fault tolerance threshold, they
+    /// are stored.
+    fn handle_block_signatures<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        sender: NodeId,
+        era_id: EraId,
+        block_hash: BlockHash,
+        signatures: Option<BlockSignatures>,
+    ) -> Effects<Event> {
+        let signatures = match signatures {
+            Some(signatures) => signatures,
+            None => {
+                debug!(era = era_id.value(), %sender, %block_hash, "peer has no block signatures");
+                return Effects::new();
+            }
+        };
+        if signatures.era_id != era_id || signatures.block_hash != block_hash {
+            info!(
+                era = era_id.value(),
+                %sender,
+                %block_hash,
+                %signatures,
+                "received signatures of a different block than requested; disconnecting"
+            );
+            return self.disconnect(effect_builder, sender);
+        }
+        let validators = match self.open_eras.get(&era_id) {
+            Some(era) => era.validators(),
+            None => {
+                debug!(
+                    era = era_id.value(),
+                    %sender,
+                    %block_hash,
+                    "era is not in memory; ignoring block signatures"
+                );
+                return Effects::new();
+            }
+        };
+        let ftt = self.finality_threshold_fraction(era_id);
+        if let Err(error) = check_sufficient_finality_signatures(validators, ftt, &signatures) {
+            debug!(%error, %sender, %signatures, "ignoring insufficient block signatures");
+            return Effects::new();
+        }
+        if let Err(error) = signatures.verify() {
+            info!(%error, %sender, %signatures, "received invalid block signatures; disconnecting");
+            return self.disconnect(effect_builder, sender);
+        }
+        effect_builder
+            .put_signatures_to_storage(signatures)
+            .ignore()
+    }
+
+    /// Keeps a message for a future era until that era is initialized, unless too many messages
+    /// are buffered already.
+    fn buffer_future_message(
+        &mut self,
+        era_id: EraId,
+        sender: NodeId,
+        payload: Vec<u8>,
+    ) -> Effects<Event> {
+        match self.future_messages.try_push(era_id, (sender, payload)) {
+            Ok(()) => {
+                self.record_dropped_message(era_id, sender, MessageDropReason::FutureEraBuffered);
+                self.metrics
+                    .buffered_future_messages
+                    .set(self.future_messages.len() as i64);
+            }
+            Err((_, payload)) => {
+                info!(
+                    era = era_id.value(),
+                    %sender,
+                    payload = %RedactedPayload(&payload),
+                    "too many buffered messages for future eras; dropping message"
+                );
+                self.metrics.dropped_future_messages.inc();
+                self.record_dropped_message(era_id, sender, MessageDropReason::RateLimited);
+            }
+        }
+        Effects::new()
+    }
+
+    /// Classifies an incoming protocol message: messages for past eras have a low priority, and
+    /// the ones for the current era that can advance finality are urgent.
+    fn message_priority(&self, era_id: EraId, payload: &[u8]) -> MessagePriority {
+        if era_id < self.current_era {
+            return MessagePriority::Low;
+        }
+        let is_urgent = era_id == self.current_era
+            && self
+                .open_eras
+                .get(&era_id)
+                .map_or(false, |era| era.consensus.is_urgent(payload));
+        if is_urgent {
+            MessagePriority::Urgent
+        } else {
+            MessagePriority::High
+        }
     }
 
-    pub(super) fn handle_timer<REv: ReactorEventT>(
+    /// Adds an incoming protocol message to the queue. If the queue is full, messages for past
+    /// eras and duplicates are dropped first.
+    fn queue_message<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
-        rng: &mut NodeRng,
+        priority: MessagePriority,
         era_id: EraId,
-        timestamp: Timestamp,
-        timer_id: TimerId,
+        sender: NodeId,
+        payload: Vec<u8>,
     ) -> Effects<Event> {
-        self.delegate_to_era(effect_builder, rng, era_id, move |consensus, _| {
-            consensus.handle_timer(timestamp, timer_id)
-        })
+        let queue = match self.message_queue.as_mut() {
+            Some(queue) => queue,
+            None => return Effects::new(),
+        };
+        let was_empty = queue.is_empty();
+        let dropped = queue.push(priority, era_id, sender, payload);
+        let is_first = was_empty && !queue.is_empty();
+        if let Some(reason) = dropped {
+            self.metrics.load_shed.inc();
+            self.record_dropped_message(era_id, sender, reason);
+        }
+        if is_first {
+            effect_builder
+                .immediately()
+                .event(|()| Event::HandleQueuedMessages)
+        } else {
+            Effects::new()
+        }
     }
 
-    pub(super) fn handle_action<REv: ReactorEventT>(
+    /// Handles the next queued protocol messages, the ones with the highest priority first. If
+    /// there are more, another event is scheduled to handle them.
+    pub(super) fn handle_queued_messages<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
         rng: &mut NodeRng,
-        era_id: EraId,
-        action_id: ActionId,
     ) -> Effects<Event> {
-        self.delegate_to_era(effect_builder, rng, era_id, move |consensus, _| {
-            consensus.handle_action(action_id, Timestamp::now())
-        })
+        let mut effects = Effects::new();
+        for _ in 0..MAX_QUEUED_MESSAGES_PER_EVENT {
+            let (era_id, sender, payload) =
+                match self.message_queue.as_mut().and_then(MessageQueue::pop) {
+                    Some(message) => message,
+                    None => return effects,
+                };
+            effects.extend(self.handle_protocol_message(
+                effect_builder,
+                rng,
+                era_id,
+                sender,
+                payload,
+            ));
+        }
+        if self
+            .message_queue
+            .as_ref()
+            .map_or(false, |queue| !queue.is_empty())
+        {
+            effects.extend(
+                effect_builder
+                    .immediately()
+                    .event(|()| Event::HandleQueuedMessages),
+            );
+        }
+        effects
     }
 
-    pub(super) fn handle_message<REv: ReactorEventT>(
+    /// Passes a protocol message to its era's consensus instance, and updates the sender's
+    /// reputation based on the outcome.
+    fn handle_protocol_message<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
         rng: &mut NodeRng,
+        era_id: EraId,
         sender: NodeId,
-        msg: ConsensusMessage,
+        payload: Vec<u8>,
     ) -> Effects<Event> {
-        match msg {
-            ConsensusMessage::Protocol { era_id, payload } => {
-                // If the era is already unbonded, only accept new evidence, because still-bonded
-                // eras could depend on that.
-                trace!(era = era_id.value(), "received a consensus message");
-                self.delegate_to_era(effect_builder, rng, era_id, move |consensus, rng| {
-                    consensus.handle_message(rng, sender, payload, Timestamp::now())
-                })
-            }
-            ConsensusMessage::EvidenceRequest { era_id, pub_key } => {
-                if era_id.saturating_add(PAST_EVIDENCE_ERAS) < self.current_era
-                    || !self.open_eras.contains_key(&era_id)
-                {
-                    trace!(era = era_id.value(), "not handling message; era too old");
-                    return Effects::new();
-                }
-                self.iter_past(era_id, PAST_EVIDENCE_ERAS)
-                    .flat_map(|e_id| {
-                        self.delegate_to_era(effect_builder, rng, e_id, |consensus, _| {
-                            consensus.request_evidence(sender, &pub_key)
-                        })
-                    })
-                    .collect()
-            }
+        let now = self.clock.now();
+
+        let mut behavior = None;
+        let effects = self.delegate_to_era(effect_builder, rng, era_id, |consensus, rng| {
+            let outcomes = consensus.handle_message(rng, sender, payload, now);
+            let is_invalid = outcomes.iter().any(|outcome| {
+                matches!(
+                    outcome,
+                    ProtocolOutcome::InvalidIncomingMessage(..) | ProtocolOutcome::Disconnect(_)
+                )
+            });
+            // Invalid messages are penalized when disconnecting from the sender.
+            behavior = if outcomes.is_empty() {
+                Some(PeerBehavior::Duplicate)
+            } else if is_invalid {
+                None
+            } else {
+                Some(PeerBehavior::Useful)
+            };
+            outcomes
+        });
+        if let Some(behavior) = behavior {
+            self.peer_reputation.record(sender, behavior, now);
+        }
+        if behavior == Some(PeerBehavior::Duplicate) {
+            self.record_dropped_message(era_id, sender, MessageDropReason::Duplicate);
         }
+        effects
+    }
+
+    /// Counts an evidence request we are about to answer. Returns `false` if the configured
+    /// maximum number of evidence requests per second has already been answered, in which case
+    /// the request should be dropped.
+    fn count_evidence_request(&mut self) -> bool {
+        let max_requests = match self.config.max_evidence_requests_per_second {
+            Some(max_requests) => max_requests,
+            None => return true,
+        };
+        let now = self.clock.now();
+        let (window_start, count) = &mut self.evidence_request_window;
+        if now.saturating_diff(*window_start) >= TimeDiff::from_seconds(1) {
+            *window_start = now;
+            *count = 0;
+        }
+        if *count >= max_requests {
+            return false;
+        }
+        *count = count.saturating_add(1);
+        true
+    }
+
+    /// Returns `true` and records the drop if the payload exceeds `max_message_size`.
+    fn drop_if_oversized(&mut self, era_id: EraId, sender: NodeId, payload: &[u8]) -> bool {
+        if payload.len() <= self.config.max_message_size {
+            return false;
+        }
+        info!(
+            era = era_id.value(),
+            %sender,
+            size = payload.len(),
+            max_size = self.config.max_message_size,
+            "consensus message is too large; dropping it"
+        );
+        self.metrics.oversized_messages.inc();
+        self.peer_reputation
+            .record(sender, PeerBehavior::Oversized, self.clock.now());
+        self.record_dropped_message(era_id, sender, MessageDropReason::TooLarge);
+        true
+    }
+
+    /// Logs that an incoming message was dropped or ignored, and counts it in the metrics.
+    fn record_dropped_message(&self, era_id: EraId, sender: NodeId, reason: MessageDropReason) {
+        if self.config.log_dropped_messages {
+            debug!(era = era_id.value(), %sender, %reason, "dropping consensus message");
+        }
+        self.metrics
+            .dropped_messages
+            .with_label_values(&[reason.label()])
+            .inc();
     }
 
     pub(super) fn handle_new_block_payload<REv: ReactorEventT>(
@@ -653,6 +2153,23 @@ impl EraSupervisor {
         rng: &mut NodeRng,
         new_block_payload: NewBlockPayload,
     ) -> Effects<Event> {
+        let era_id = new_block_payload.era_id;
+        if era_id > self.current_era && !self.open_eras.contains_key(&era_id) {
+            debug!(
+                era = era_id.value(),
+                "buffering block payload for uninitialized era"
+            );
+            if let Some((dropped_era_id, _)) =
+                self.pending_block_payloads.push(era_id, new_block_payload)
+            {
+                warn!(
+                    era = dropped_era_id.value(),
+                    pending = self.pending_block_payloads.len(),
+                    "too many pending block payloads; dropping the oldest one"
+                );
+            }
+            return Effects::new();
+        }
         let NewBlockPayload {
             era_id,
             block_payload,
@@ -664,12 +2181,77 @@ impl EraSupervisor {
             warn!(era = era_id.value(), "new block payload in outdated era");
             return Effects::new();
         }
+        if let Some(size) = self.oversized_block_payload_size(&block_payload) {
+            error!(
+                era = era_id.value(),
+                size,
+                max_size = self.config.max_block_payload_size,
+                "new block payload is too large; not proposing it"
+            );
+            self.metrics.oversized_block_payloads.inc();
+            return self.record_failed_proposal(effect_builder, era_id);
+        }
+        let timestamp = block_context.timestamp();
+        let leader = self.era(era_id).consensus.leader(timestamp);
+        if leader != Some(&self.public_signing_key) {
+            error!(
+                era = era_id.value(),
+                %timestamp,
+                ?leader,
+                "new block payload for a slot in which we are not the leader; not proposing it"
+            );
+            self.metrics.non_leader_proposals.inc();
+            return Effects::new();
+        }
         let proposed_block = ProposedBlock::new(block_payload, block_context);
+        if let Some(deploy_hash) = proposed_block.contains_replay() {
+            error!(
+                era = era_id.value(),
+                %deploy_hash,
+                "new block payload contains a replayed deploy; not proposing it"
+            );
+            return self.record_failed_proposal(effect_builder, era_id);
+        }
+        let era = self.era_mut(era_id);
+        if era.proposals_halted {
+            debug!(era = era_id.value(), "proposals are halted; not proposing");
+            return Effects::new();
+        }
+        era.failed_proposals = 0;
+        let now = self.clock.now();
         self.delegate_to_era(effect_builder, rng, era_id, move |consensus, _| {
-            consensus.propose(proposed_block, Timestamp::now())
+            consensus.propose(proposed_block, now)
         })
     }
 
+    /// Counts a failed proposal of ours in the given era. If the configured maximum number of
+    /// consecutive failures is reached, we stop proposing in that era and announce it.
+    fn record_failed_proposal<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        era_id: EraId,
+    ) -> Effects<Event> {
+        let max_failed_proposals = match self.config.max_failed_proposals {
+            Some(max_failed_proposals) => max_failed_proposals,
+            None => return Effects::new(),
+        };
+        let era = self.era_mut(era_id);
+        era.failed_proposals = era.failed_proposals.saturating_add(1);
+        if era.proposals_halted || era.failed_proposals < max_failed_proposals {
+            return Effects::new();
+        }
+        era.proposals_halted = true;
+        let failures = era.failed_proposals;
+        error!(
+            era = era_id.value(),
+            failures,
+            "our proposals repeatedly failed validation; not proposing in this era anymore"
+        );
+        effect_builder
+            .announce_proposals_halted(era_id, failures)
+            .ignore()
+    }
+
     pub(super) fn handle_block_added<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
@@ -679,7 +2261,9 @@ impl EraSupervisor {
         let our_sk = self.secret_signing_key.clone();
         let era_id = block_header.era_id();
         self.executed_block(&block_header);
-        let mut effects = if self.is_validator_in(&our_pk, era_id) {
+        let mut effects = if self.config.participation == Participation::Validator
+            && self.is_validator_in(&our_pk, era_id)
+        {
             effect_builder
                 .announce_created_finality_signature(FinalitySignature::new(
                     block_header.hash(self.verifiable_chunked_hash_activation()),
@@ -691,6 +2275,7 @@ impl EraSupervisor {
         } else {
             Effects::new()
         };
+        effects.extend(self.save_checkpoint(effect_builder));
         if era_id < self.current_era {
             trace!(era = era_id.value(), "executed block in old era");
             return effects;
@@ -698,7 +2283,9 @@ impl EraSupervisor {
         if block_header.is_switch_block() {
             if let Some(era) = self.open_eras.get_mut(&era_id) {
                 // This was the era's last block. Schedule deactivating this era.
-                let delay = Timestamp::now()
+                let delay = self
+                    .clock
+                    .now()
                     .saturating_diff(block_header.timestamp())
                     .into();
                 let faulty_num = era.consensus.validators_with_evidence().len();
@@ -713,9 +2300,23 @@ impl EraSupervisor {
             }
             // If it's not the last block before an upgrade, initialize the next era.
             if !self.should_upgrade_after(&era_id) {
-                let new_era_id = era_id.successor();
+                let new_era_id = match era_id.checked_successor() {
+                    Some(new_era_id) => new_era_id,
+                    None => {
+                        effects.extend(
+                            fatal!(
+                                effect_builder,
+                                "finalized the last block of the maximum era {}",
+                                era_id
+                            )
+                            .ignore(),
+                        );
+                        return effects;
+                    }
+                };
+                self.executed_key_block = Some(block_header.clone());
                 let effect = get_switch_blocks(self.chainspec.clone(), effect_builder, new_era_id)
-                    .event(move |switch_blocks| Event::CreateNewEra { switch_blocks });
+                    .event(new_era_event);
                 effects.extend(effect);
             }
         }
@@ -750,6 +2351,30 @@ impl EraSupervisor {
         }
     }
 
+    /// Handles the result of a proposed block's validation, and starts validating the next waiting
+    /// blocks if that frees up capacity.
+    pub(super) fn handle_validation_result<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+        rng: &mut NodeRng,
+        resolve_validity: ResolveValidity,
+    ) -> Effects<Event> {
+        let in_flight = (
+            resolve_validity.era_id,
+            resolve_validity.proposed_block.clone(),
+        );
+        if let Some(index) = self
+            .validations_in_flight
+            .iter()
+            .position(|validation| *validation == in_flight)
+        {
+            self.validations_in_flight.swap_remove(index);
+        }
+        let mut effects = self.resolve_validity(effect_builder, rng, resolve_validity);
+        effects.extend(self.start_pending_validations(effect_builder));
+        effects
+    }
+
     pub(super) fn resolve_validity<REv: ReactorEventT>(
         &mut self,
         effect_builder: EffectBuilder<REv>,
@@ -762,6 +2387,16 @@ impl EraSupervisor {
             proposed_block,
             valid,
         } = resolve_validity;
+        if let Some(era) = self.open_eras.get_mut(&era_id) {
+            if !era.mark_resolved(&proposed_block) {
+                debug!(
+                    peer_id = %sender,
+                    era = %era_id.value(),
+                    "ignoring duplicate validity resolution for proposed block"
+                );
+                return Effects::new();
+            }
+        }
         self.metrics.proposed_block();
         let mut effects = Effects::new();
         if !valid {
@@ -777,9 +2412,10 @@ impl EraSupervisor {
             .get_mut(&era_id)
             .map_or(false, |era| era.resolve_validity(&proposed_block, valid))
         {
+            let now = self.clock.now();
             effects.extend(
                 self.delegate_to_era(effect_builder, rng, era_id, |consensus, _| {
-                    consensus.resolve_validity(proposed_block, valid, Timestamp::now())
+                    consensus.resolve_validity(proposed_block, valid, now)
                 }),
             );
         }
@@ -802,6 +2438,53 @@ impl EraSupervisor {
             .collect()
     }
 
+    /// Starts validating waiting proposed blocks, in order, until the maximum number of concurrent
+    /// validations is reached. Blocks whose era is outdated by now are dropped.
+    fn start_pending_validations<REv: ReactorEventT>(
+        &mut self,
+        effect_builder: EffectBuilder<REv>,
+    ) -> Effects<Event> {
+        let mut effects = Effects::new();
+        while self
+            .config
+            .max_concurrent_validations
+            .map_or(true, |max| self.validations_in_flight.len() < max)
+        {
+            let (era_id, sender, proposed_block) = match self.pending_validations.pop_front() {
+                Some(pending) => pending,
+                None => break,
+            };
+            if era_id.saturating_add(PAST_EVIDENCE_ERAS) < self.current_era
+                || !self.open_eras.contains_key(&era_id)
+            {
+                continue; // Outdated era; we don't need the value anymore.
+            }
+            if self.config.max_concurrent_validations.is_some() {
+                self.validations_in_flight
+                    .push((era_id, proposed_block.clone()));
+            }
+            effects.extend(
+                async move {
+                    check_deploys_for_replay_in_previous_eras_and_validate_block(
+                        effect_builder,
+                        era_id,
+                        sender,
+                        proposed_block,
+                    )
+                    .await
+                }
+                .event(std::convert::identity),
+            );
+        }
+        effects
+    }
+
+    /// Returns the serialized size of the block payload if it exceeds the configured maximum.
+    fn oversized_block_payload_size(&self, block_payload: &BlockPayload) -> Option<u64> {
+        let size = bincode::serialized_size(block_payload).unwrap_or(u64::MAX);
+        (size > self.config.max_block_payload_size).then(|| size)
+    }
+
     /// Returns `true` if any of the most recent eras has evidence against the validator with key
     /// `pub_key`.
     fn has_evidence(&self, era_id: EraId, pub_key: PublicKey) -> bool {
@@ -828,12 +2511,14 @@ impl EraSupervisor {
         consensus_result: ProtocolOutcome<ClContext>,
     ) -> Effects<Event> {
         match consensus_result {
-            ProtocolOutcome::InvalidIncomingMessage(_, sender, error) => {
+            ProtocolOutcome::InvalidIncomingMessage(msg, sender, error) => {
                 warn!(
                     %sender,
                     %error,
+                    payload = %RedactedPayload(&msg),
                     "invalid incoming message to consensus instance; disconnecting from the sender"
                 );
+                self.metrics.invalid_messages.inc();
                 self.disconnect(effect_builder, sender)
             }
             ProtocolOutcome::Disconnect(sender) => {
@@ -844,16 +2529,28 @@ impl EraSupervisor {
                 self.disconnect(effect_builder, sender)
             }
             ProtocolOutcome::CreatedGossipMessage(payload) => {
-                let message = ConsensusMessage::Protocol { era_id, payload };
+                let mut effects = match self.record_gossip(effect_builder, era_id, &payload) {
+                    Some(effects) => effects,
+                    None => {
+                        debug!(era = era_id.value(), "not gossiping a message again");
+                        return Effects::new();
+                    }
+                };
+                if let Some(window) = self.config.message_batch_window {
+                    effects.extend(self.batch_message(effect_builder, era_id, payload, window));
+                    return effects;
+                }
+                let message = self.protocol_message(era_id, payload);
                 // TODO: we'll want to gossip instead of broadcast here
-                effect_builder.broadcast_message(message.into()).ignore()
+                effects.extend(effect_builder.broadcast_message(message.into()).ignore());
+                effects
             }
             ProtocolOutcome::CreatedTargetedMessage(payload, to) => {
-                let message = ConsensusMessage::Protocol { era_id, payload };
+                let message = self.protocol_message(era_id, payload);
                 effect_builder.send_message(to, message.into()).ignore()
             }
             ProtocolOutcome::CreatedMessageToRandomPeer(payload) => {
-                let message = ConsensusMessage::Protocol { era_id, payload };
+                let message = self.protocol_message(era_id, payload);
 
                 async move {
                     let peers = effect_builder.get_fully_connected_peers().await;
@@ -864,7 +2561,16 @@ impl EraSupervisor {
                 .ignore()
             }
             ProtocolOutcome::ScheduleTimer(timestamp, timer_id) => {
-                let timediff = timestamp.saturating_diff(Timestamp::now());
+                if let Some(window) = self.config.timer_coalescing_window {
+                    return self.schedule_coalesced_timer(
+                        effect_builder,
+                        era_id,
+                        timestamp,
+                        timer_id,
+                        window,
+                    );
+                }
+                let timediff = timestamp.saturating_diff(self.clock.now());
                 effect_builder
                     .set_timeout(timediff.into())
                     .event(move |_| Event::Timer {
@@ -877,6 +2583,13 @@ impl EraSupervisor {
                 .immediately()
                 .event(move |()| Event::Action { era_id, action_id }),
             ProtocolOutcome::CreateNewBlock(block_context) => {
+                if self.era(era_id).proposals_halted {
+                    debug!(
+                        era = era_id.value(),
+                        "proposals are halted; not requesting a block payload"
+                    );
+                    return Effects::new();
+                }
                 let accusations = self
                     .iter_past(era_id, PAST_EVIDENCE_ERAS)
                     .flat_map(|e_id| self.era(e_id).consensus.validators_with_evidence())
@@ -884,20 +2597,24 @@ impl EraSupervisor {
                     .filter(|pub_key| !self.era(era_id).faulty.contains(pub_key))
                     .cloned()
                     .collect();
-                effect_builder
-                    .request_block_payload(
-                        block_context.clone(),
-                        self.next_block_height,
-                        accusations,
-                        rng.gen(),
-                    )
-                    .event(move |block_payload| {
-                        Event::NewBlockPayload(NewBlockPayload {
-                            era_id,
-                            block_payload,
-                            block_context,
-                        })
-                    })
+                let mut effects = self.announce_leader_slot(effect_builder, era_id, &block_context);
+                effects.extend(
+                    effect_builder
+                        .request_block_payload(
+                            block_context.clone(),
+                            self.next_block_height,
+                            accusations,
+                            rng.gen(),
+                        )
+                        .event(move |block_payload| {
+                            Event::NewBlockPayload(NewBlockPayload {
+                                era_id,
+                                block_payload,
+                                block_context,
+                            })
+                        }),
+                );
+                effects
             }
             ProtocolOutcome::FinalizedBlock(CpFinalizedBlock {
                 value,
@@ -912,6 +2629,9 @@ impl EraSupervisor {
                     return Effects::new();
                 }
                 let era = self.open_eras.get_mut(&era_id).unwrap();
+                let now = self.clock.now();
+                era.record_progress(now);
+                era.record_finalization(era.start_height + relative_height, now);
                 era.add_accusations(&equivocators);
                 era.add_accusations(value.accusations());
                 // If this is the era's last block, it contains rewards. Everyone who is accused in
@@ -979,6 +2699,25 @@ impl EraSupervisor {
                     .collect();
                 self.era_mut(era_id)
                     .add_block(proposed_block.clone(), missing_evidence.clone());
+                if let Some(size) = self.oversized_block_payload_size(proposed_block.value()) {
+                    info!(
+                        %sender,
+                        size,
+                        max_size = self.config.max_block_payload_size,
+                        "proposed block payload is too large"
+                    );
+                    self.metrics.oversized_block_payloads.inc();
+                    return self.resolve_validity(
+                        effect_builder,
+                        rng,
+                        ResolveValidity {
+                            era_id,
+                            sender,
+                            proposed_block,
+                            valid: false,
+                        },
+                    );
+                }
                 if let Some(deploy_hash) = proposed_block.contains_replay() {
                     info!(%sender, %deploy_hash, "block contains a replayed deploy");
                     return self.resolve_validity(
@@ -994,28 +2733,22 @@ impl EraSupervisor {
                 }
                 let mut effects = Effects::new();
                 for pub_key in missing_evidence {
-                    let msg = ConsensusMessage::EvidenceRequest { era_id, pub_key };
-                    effects.extend(effect_builder.send_message(sender, msg.into()).ignore());
+                    effects.extend(self.request_evidence(effect_builder, era_id, pub_key));
                 }
-                effects.extend(
-                    async move {
-                        check_deploys_for_replay_in_previous_eras_and_validate_block(
-                            effect_builder,
-                            era_id,
-                            sender,
-                            proposed_block,
-                        )
-                        .await
-                    }
-                    .event(std::convert::identity),
-                );
+                self.pending_validations
+                    .push_back((era_id, sender, proposed_block));
+                effects.extend(self.start_pending_validations(effect_builder));
                 effects
             }
-            ProtocolOutcome::NewEvidence(pub_key) => {
-                info!(%pub_key, era = era_id.value(), "validator equivocated");
+            ProtocolOutcome::NewEvidence(pub_key, timestamp) => {
+                let latency = self.clock.now().saturating_diff(timestamp);
+                info!(%pub_key, era = era_id.value(), %latency, "validator equivocated");
+                self.metrics.record_equivocation_detection_latency(latency);
+                self.last_equivocation_detection_latency = Some(latency);
                 let mut effects = effect_builder
-                    .announce_fault_event(era_id, pub_key.clone(), Timestamp::now())
+                    .announce_fault_event(era_id, pub_key.clone(), self.clock.now())
                     .ignore();
+                effects.extend(self.persist_evidence(effect_builder, era_id, &pub_key));
                 for e_id in self.iter_future(era_id, PAST_EVIDENCE_ERAS) {
                     let proposed_blocks = if let Some(era) = self.open_eras.get_mut(&e_id) {
                         era.resolve_evidence_and_mark_faulty(&pub_key)
@@ -1023,13 +2756,12 @@ impl EraSupervisor {
                         continue;
                     };
                     for proposed_block in proposed_blocks {
+                        let now = self.clock.now();
                         effects.extend(self.delegate_to_era(
                             effect_builder,
                             rng,
                             e_id,
-                            |consensus, _| {
-                                consensus.resolve_validity(proposed_block, true, Timestamp::now())
-                            },
+                            |consensus, _| consensus.resolve_validity(proposed_block, true, now),
                         ));
                     }
                 }
@@ -1045,6 +2777,11 @@ impl EraSupervisor {
                 .collect(),
             ProtocolOutcome::WeAreFaulty => Default::default(),
             ProtocolOutcome::DoppelgangerDetected => Default::default(),
+            ProtocolOutcome::WouldEquivocate => fatal!(
+                effect_builder,
+                "refusing to sign a unit that conflicts with our own last unit"
+            )
+            .ignore(),
             ProtocolOutcome::FttExceeded => effect_builder
                 .set_timeout(Duration::from_millis(FTT_EXCEEDED_SHUTDOWN_DELAY_MILLIS))
                 .then(move |_| fatal!(effect_builder, "too many faulty validators"))
@@ -1086,10 +2823,12 @@ impl EraSupervisor {
     }
 
     fn disconnect<REv: ReactorEventT>(
-        &self,
+        &mut self,
         effect_builder: EffectBuilder<REv>,
         sender: NodeId,
     ) -> Effects<Event> {
+        self.peer_reputation
+            .record(sender, PeerBehavior::Invalid, self.clock.now());
         effect_builder
             .announce_disconnect_from_peer(sender)
             .ignore()
@@ -1111,6 +2850,127 @@ impl EraSupervisor {
     pub(crate) fn current_era(&self) -> EraId {
         self.current_era
     }
+
+    /// Returns the validators that are known to have equivocated in the given era, sorted.
+    ///
+    /// The result is empty both if no faults were detected and if the era is not in memory.
+    pub(crate) fn faulty_validators(&self, era_id: EraId) -> Vec<PublicKey> {
+        self.open_eras
+            .get(&era_id)
+            .map(Era::faulty_validators)
+            .unwrap_or_default()
+    }
+
+    /// Returns the fault tolerance threshold of the given era, as a fraction of the total weight:
+    /// the configured override for that era, if any, and otherwise the chainspec's.
+    pub(crate) fn finality_threshold_fraction(&self, era_id: EraId) -> Ratio<u64> {
+        self.config.finality_threshold_fraction(
+            era_id,
+            self.chainspec.highway_config.finality_threshold_fraction,
+        )
+    }
+
+    /// Returns whether the given era can still finalize blocks, i.e. whether the total weight of
+    /// its faulty validators is below the fault tolerance threshold. Returns `false` if the era is
+    /// not in memory.
+    pub(crate) fn can_finalize(&self, era_id: EraId) -> bool {
+        let ftt = self.finality_threshold_fraction(era_id);
+        self.open_eras
+            .get(&era_id)
+            .map_or(false, |era| era.can_finalize(ftt))
+    }
+
+    /// Returns the heights of the most recently finalized blocks in the given era, with the times
+    /// they were finalized, in order. Returns an empty slice if the era is not in memory.
+    pub(crate) fn finalization_times(&self, era_id: EraId) -> &[(u64, Timestamp)] {
+        self.open_eras
+            .get(&era_id)
+            .map(|era| era.finalization_times.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Returns the peers that sent us consensus messages, the ones with the best reputation first.
+    ///
+    /// Peers that sent useful messages rank higher than ones that sent invalid, oversized or
+    /// redundant ones.
+    pub(crate) fn peers_by_reputation(&self) -> Vec<NodeId> {
+        self.peer_reputation.ranked_peers(self.clock.now())
+    }
+
+    /// Returns the validator weights in the given era, or `None` if the era is not in memory.
+    pub(crate) fn validator_weights(&self, era_id: EraId) -> Option<&BTreeMap<PublicKey, U512>> {
+        self.open_eras.get(&era_id).map(Era::validators)
+    }
+
+    /// Returns the eras that are in memory, with the number of validators in each, ordered by era
+    /// ID.
+    pub(crate) fn active_eras(&self) -> Vec<(EraId, usize)> {
+        self.open_eras
+            .iter()
+            .map(|(era_id, era)| (*era_id, era.validators().len()))
+            .sorted()
+            .collect()
+    }
+
+    /// Returns the validators in the given era that are not known to be faulty, but haven't sent
+    /// any unit for longer than the inactivity grace period, or since the era started. Returns an
+    /// empty list if the era is not in memory.
+    ///
+    /// Unlike equivocators, inactive validators may just be briefly offline, so they are not
+    /// accused of anything.
+    pub(crate) fn inactive_validators(&self, era_id: EraId) -> Vec<PublicKey> {
+        let era = match self.open_eras.get(&era_id) {
+            Some(era) => era,
+            None => return Vec::new(),
+        };
+        let now = self.clock.now();
+        era.validators()
+            .iter()
+            .filter(|(pub_key, weight)| {
+                !weight.is_zero()
+                    && !era.faulty.contains(*pub_key)
+                    && !era.consensus.has_evidence(pub_key)
+            })
+            .map(|(pub_key, _)| pub_key)
+            .filter(|pub_key| {
+                let last_activity = era
+                    .consensus
+                    .latest_unit_timestamp(pub_key)
+                    .map_or(era.start_time, |timestamp| timestamp.max(era.start_time));
+                now.saturating_diff(last_activity) > self.config.inactivity_grace_period
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the time between the most recently detected equivocation being committed and this
+    /// node detecting it, or `None` if no equivocation was detected yet.
+    pub(crate) fn last_equivocation_detection_latency(&self) -> Option<Duration> {
+        self.last_equivocation_detection_latency.map(Duration::from)
+    }
+
+    /// Returns the validator who is expected to propose the next block in the given era, and
+    /// when. Returns `None` if the era is not the current one, or if it has ended.
+    pub(crate) fn next_leader(&self, era_id: EraId) -> Option<(PublicKey, Timestamp)> {
+        if era_id != self.current_era {
+            return None;
+        }
+        let era = self.open_eras.get(&era_id)?;
+        let (leader, timestamp) = era.consensus.next_leader(self.clock.now())?;
+        Some((leader.clone(), timestamp))
+    }
+
+    /// Returns the validators who are expected to propose blocks in the given era, one for each
+    /// slot from the era's start until its minimum end time, in order. Returns `None` if the era
+    /// is not in memory, or if it only keeps evidence.
+    ///
+    /// The leaders are selected deterministically, so every node computes the same schedule: it
+    /// can be used to audit the fairness of the leader selection, and to predict the proposers.
+    pub(crate) fn leader_schedule(&self, era_id: EraId) -> Option<Vec<PublicKey>> {
+        let era = self.open_eras.get(&era_id)?;
+        let schedule = era.consensus.leader_schedule()?;
+        Some(schedule.into_iter().cloned().collect())
+    }
 }
 
 #[cfg(test)]
@@ -1119,17 +2979,41 @@ impl EraSupervisor {
     pub(crate) fn public_key(&self) -> &PublicKey {
         &self.public_signing_key
     }
+
+    /// Replaces the source of the current time, e.g. with a `MockClock`.
+    pub(crate) fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Checks that the buffers for messages and block payloads don't exceed their capacities.
+    pub(crate) fn assert_buffers_bounded(&self) {
+        assert!(self.future_messages.len() <= self.config.max_buffered_future_messages);
+        assert!(self.pending_block_payloads.len() <= MAX_PENDING_BLOCK_PAYLOADS);
+        if let (Some(queue), Some(capacity)) =
+            (&self.message_queue, self.config.max_queued_messages)
+        {
+            assert!(queue.len() <= capacity);
+        }
+        for batch in self.message_batches.values() {
+            assert!(batch.len() <= self.config.max_message_batch_size);
+        }
+        if let Some(max) = self.config.max_concurrent_validations {
+            assert!(self.validations_in_flight.len() <= max);
+        }
+    }
 }
 
 /// Returns all switch blocks needed to initialize `era_id`.
 ///
 /// Those are the booking block, i.e. the switch block in `era_id - auction_delay - 1`,
 /// the key block, i.e. the switch block in `era_id - 1`, and all switch blocks in between.
+///
+/// Returns an error if any of them is missing from storage.
 async fn get_switch_blocks<REv>(
     chainspec: Arc<Chainspec>,
     effect_builder: EffectBuilder<REv>,
     era_id: EraId,
-) -> Vec<BlockHeader>
+) -> Result<Vec<BlockHeader>, ConsensusError>
 where
     REv: From<StorageRequest>,
 {
@@ -1147,11 +3031,30 @@ where
                     ?switch_block_era_id,
                     "switch block header era must exist to initialize era"
                 );
-                panic!("switch block header not found in storage");
+                return Err(ConsensusError::ValidatorsUnavailable {
+                    era_id,
+                    switch_block_era_id,
+                });
             }
         }
     }
-    switch_blocks
+    Ok(switch_blocks)
+}
+
+/// Returns the event to create a new era from the given switch blocks, or to handle the missing
+/// switch block that prevented loading them.
+fn new_era_event(result: Result<Vec<BlockHeader>, ConsensusError>) -> Event {
+    match result {
+        Ok(switch_blocks) => Event::CreateNewEra { switch_blocks },
+        Err(ConsensusError::ValidatorsUnavailable {
+            era_id,
+            switch_block_era_id,
+        }) => Event::SwitchBlocksUnavailable {
+            era_id,
+            switch_block_era_id,
+        },
+        Err(error) => Event::Failed(error),
+    }
 }
 
 async fn get_deploys_or_transfers<REv>(
@@ -1241,6 +3144,22 @@ fn instance_id(chainspec_hash: Digest, era_id: EraId, key_block_hash: BlockHash)
     )
 }
 
+/// Returns the state store key under which the evidence against `pub_key` in the given era is
+/// persisted.
+fn evidence_key(era_id: EraId, pub_key: &PublicKey) -> Cow<'static, [u8]> {
+    format!("consensus evidence {} {}", era_id.value(), pub_key.to_hex())
+        .into_bytes()
+        .into()
+}
+
+/// Returns the state store key under which the index of the messages we gossiped in the given era
+/// is persisted.
+fn gossip_index_key(era_id: EraId) -> Cow<'static, [u8]> {
+    format!("consensus gossip index {}", era_id.value())
+        .into_bytes()
+        .into()
+}
+
 /// Checks that a [BlockPayload] does not have deploys we have already included in blocks in
 /// previous eras. This is done by repeatedly querying storage for deploy metadata. When metadata is
 /// found storage is queried again to get the era id for the included deploy. That era id must *not*
@@ -0,0 +1,45 @@
+//! The part of the era supervisor's state that is persisted, to resume from after a restart.
+
+use std::{
+    borrow::Cow,
+    fmt::{self, Display, Formatter},
+};
+
+use datasize::DataSize;
+use serde::{Deserialize, Serialize};
+
+use casper_types::EraId;
+
+/// The state store key under which the latest checkpoint is saved.
+const CHECKPOINT_KEY: &[u8] = b"consensus checkpoint";
+
+/// The era supervisor's progress, as of the last executed block.
+///
+/// The open eras themselves are always initialized from the switch blocks in storage. The
+/// checkpoint only tells a restarted node which era it was in, and how far it had gotten.
+#[derive(Clone, Copy, DataSize, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Checkpoint {
+    /// The current era.
+    pub(crate) era_id: EraId,
+    /// The height of the next block to be finalized.
+    pub(crate) next_block_height: u64,
+    /// The height of the next block to be executed.
+    pub(crate) next_executed_height: u64,
+}
+
+impl Checkpoint {
+    /// Returns the state store key under which the latest checkpoint is saved.
+    pub(super) fn key() -> Cow<'static, [u8]> {
+        Cow::Borrowed(CHECKPOINT_KEY)
+    }
+}
+
+impl Display for Checkpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "checkpoint in {}, next block height {}, next executed height {}",
+            self.era_id, self.next_block_height, self.next_executed_height
+        )
+    }
+}
@@ -0,0 +1,64 @@
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+#[cfg(test)]
+use casper_types::TimeDiff;
+use casper_types::Timestamp;
+
+/// A source of the current time.
+pub(crate) trait Clock: Debug + Send {
+    /// Returns the current time.
+    fn now(&self) -> Timestamp;
+}
+
+/// The system's wall clock.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Timestamp {
+        Timestamp::now()
+    }
+}
+
+/// A clock that only changes when it is explicitly set or advanced, e.g. in tests or to replay
+/// recorded messages.
+///
+/// Clones share the same time, so a test can keep a clone to control the clock it passed to the
+/// component under test.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MockClock {
+    millis: Arc<AtomicU64>,
+}
+
+impl MockClock {
+    /// Creates a clock showing the given time.
+    #[cfg(test)]
+    pub(crate) fn new(now: Timestamp) -> Self {
+        MockClock {
+            millis: Arc::new(AtomicU64::new(now.millis())),
+        }
+    }
+
+    /// Moves the clock forward by the given duration.
+    #[cfg(test)]
+    pub(crate) fn advance(&self, duration: TimeDiff) {
+        self.millis.fetch_add(duration.millis(), Ordering::SeqCst);
+    }
+
+    /// Sets the clock to the given time.
+    pub(crate) fn set(&self, now: Timestamp) {
+        self.millis.store(now.millis(), Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Timestamp {
+        Timestamp::from(self.millis.load(Ordering::SeqCst))
+    }
+}
@@ -11,7 +11,7 @@ use serde::Serialize;
 
 use crate::components::consensus::{highway_core::State, ClContext, HighwayProtocol};
 
-use super::Era;
+use super::EraSupervisor;
 
 /// Debug dump of era used for serialization.
 #[derive(Debug, Serialize)]
@@ -32,8 +32,20 @@ pub(crate) struct EraDump<'a> {
     pub(crate) cannot_propose: &'a HashSet<PublicKey>,
     /// Accusations collected in this era so far.
     pub(crate) accusations: &'a HashSet<PublicKey>,
+    /// Validators known to have equivocated in this era, including accused ones.
+    pub(crate) faulty_validators: Vec<PublicKey>,
     /// The validator weights.
     pub(crate) validators: &'a BTreeMap<PublicKey, U512>,
+    /// Whether the validators not known to be faulty still have enough weight to finalize blocks.
+    pub(crate) can_finalize: bool,
+    /// The heights of the most recently finalized blocks, with the times they were finalized.
+    pub(crate) finalization_times: &'a [(u64, Timestamp)],
+    /// The validator expected to propose the next block, and when, if this is the current era.
+    pub(crate) next_leader: Option<(PublicKey, Timestamp)>,
+    /// Validators that have been silent for longer than the inactivity grace period.
+    pub(crate) inactive_validators: Vec<PublicKey>,
+    /// The validators expected to propose blocks in this era, one for each slot, in order.
+    pub(crate) leader_schedule: Option<Vec<PublicKey>>,
 
     /// The state of the highway instance associated with the era.
     pub(crate) highway_state: &'a State<ClContext>,
@@ -46,8 +58,15 @@ impl<'a> Display for EraDump<'a> {
 }
 
 impl<'a> EraDump<'a> {
-    /// Creates a new `EraDump` from a given era.
-    pub(crate) fn dump_era(era: &'a Era, era_id: EraId) -> Result<Self, Cow<'static, str>> {
+    /// Creates a new `EraDump` from the given era of the supervisor.
+    pub(crate) fn dump_era(
+        supervisor: &'a EraSupervisor,
+        era_id: EraId,
+    ) -> Result<Self, Cow<'static, str>> {
+        let validators = supervisor
+            .validator_weights(era_id)
+            .ok_or_else(|| Cow::Owned(format!("could not dump consensus, {} not found", era_id)))?;
+        let era = &supervisor.open_eras()[&era_id];
         let highway = era
             .consensus
             .as_any()
@@ -63,7 +82,13 @@ impl<'a> EraDump<'a> {
             faulty: &era.faulty,
             cannot_propose: &era.cannot_propose,
             accusations: &era.accusations,
-            validators: &era.validators,
+            faulty_validators: supervisor.faulty_validators(era_id),
+            validators,
+            can_finalize: supervisor.can_finalize(era_id),
+            finalization_times: supervisor.finalization_times(era_id),
+            next_leader: supervisor.next_leader(era_id),
+            inactive_validators: supervisor.inactive_validators(era_id),
+            leader_schedule: supervisor.leader_schedule(era_id),
             highway_state: highway.highway().state(),
         })
     }
@@ -1,18 +1,22 @@
 use std::{
-    collections::{BTreeMap, HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     env,
+    path::PathBuf,
 };
 
 use datasize::DataSize;
 use itertools::Itertools;
+use num::rational::Ratio;
 use once_cell::sync::Lazy;
 use tracing::{debug, warn};
 
-use casper_types::{PublicKey, Timestamp, U512};
+use casper_types::{PublicKey, TimeDiff, Timestamp, U512};
 
+use super::gossip_index::GossipIndex;
 use crate::components::consensus::{
-    cl_context::ClContext,
-    consensus_protocol::{ConsensusProtocol, ProposedBlock},
+    cl_context::{ClContext, Keypair},
+    config::Participation,
+    consensus_protocol::{ConsensusProtocol, ProposedBlock, ProtocolOutcomes},
     protocols::highway::HighwayProtocol,
 };
 
@@ -21,6 +25,9 @@ const CASPER_ENABLE_DETAILED_CONSENSUS_METRICS_ENV_VAR: &str =
 static CASPER_ENABLE_DETAILED_CONSENSUS_METRICS: Lazy<bool> =
     Lazy::new(|| env::var(CASPER_ENABLE_DETAILED_CONSENSUS_METRICS_ENV_VAR).is_ok());
 
+/// The maximum number of finalization times that are kept per era. Older ones are dropped.
+const MAX_FINALIZATION_TIMES: usize = 1_000;
+
 /// A proposed block waiting for validation and dependencies.
 #[derive(DataSize)]
 pub struct ValidationState {
@@ -52,6 +59,8 @@ pub struct Era {
     pub(crate) start_height: u64,
     /// Pending blocks, waiting for validation and dependencies.
     pub(crate) validation_states: HashMap<ProposedBlock<ClContext>, ValidationState>,
+    /// Blocks whose validity has already been resolved since they were last added.
+    pub(crate) resolved_blocks: HashSet<ProposedBlock<ClContext>>,
     /// Validators banned in this and the next BONDED_ERAS eras, because they were faulty in the
     /// previous switch block.
     pub(crate) faulty: HashSet<PublicKey>,
@@ -61,6 +70,21 @@ pub struct Era {
     pub(crate) accusations: HashSet<PublicKey>,
     /// The validator weights.
     pub(crate) validators: BTreeMap<PublicKey, U512>,
+    /// The time when the last block in this era was finalized, or the era's start time.
+    pub(crate) last_progress: Timestamp,
+    /// Whether a stall has been reported since the last progress.
+    pub(crate) stall_reported: bool,
+    /// The heights of the most recently finalized blocks, with the times they were finalized.
+    pub(crate) finalization_times: Vec<(u64, Timestamp)>,
+    /// The number of our own consecutive block proposals that failed validation.
+    pub(crate) failed_proposals: u32,
+    /// Whether we stopped proposing blocks in this era because too many proposals failed.
+    pub(crate) proposals_halted: bool,
+    /// Serialized evidence loaded from storage after a restart, by validator. It is only used to
+    /// answer evidence requests, if the protocol instance doesn't have the evidence itself.
+    pub(crate) persisted_evidence: HashMap<PublicKey, Vec<u8>>,
+    /// The protocol messages we gossiped in this era, so we don't gossip them again.
+    pub(crate) gossiped: GossipIndex,
 }
 
 impl Era {
@@ -77,11 +101,45 @@ impl Era {
             start_time,
             start_height,
             validation_states: HashMap::new(),
+            resolved_blocks: HashSet::new(),
             faulty,
             cannot_propose,
             accusations: HashSet::new(),
             validators,
+            last_progress: start_time,
+            stall_reported: false,
+            finalization_times: Vec::new(),
+            failed_proposals: 0,
+            proposals_halted: false,
+            persisted_evidence: HashMap::new(),
+            gossiped: GossipIndex::default(),
+        }
+    }
+
+    /// Activates our validator in this era's consensus protocol, so that it starts creating and
+    /// signing units. Does nothing if we are only observing or are not one of the era's
+    /// validators.
+    pub(crate) fn activate_validator(
+        &mut self,
+        participation: Participation,
+        our_id: PublicKey,
+        secret: Keypair,
+        now: Timestamp,
+        unit_hash_file: Option<PathBuf>,
+    ) -> ProtocolOutcomes<ClContext> {
+        if participation == Participation::Observer || !self.validators.contains_key(&our_id) {
+            return vec![];
         }
+        self.consensus
+            .activate_validator(our_id, secret, now, unit_hash_file)
+    }
+
+    /// Returns the accused validators for which we are still missing evidence.
+    pub(crate) fn missing_evidence(&self) -> BTreeSet<PublicKey> {
+        self.validation_states
+            .values()
+            .flat_map(|vs| vs.missing_evidence.iter().cloned())
+            .collect()
     }
 
     /// Adds a new block, together with the accusations for which we don't have evidence yet.
@@ -90,6 +148,7 @@ impl Era {
         proposed_block: ProposedBlock<ClContext>,
         missing_evidence: Vec<PublicKey>,
     ) {
+        self.resolved_blocks.remove(&proposed_block);
         self.validation_states
             .insert(proposed_block, ValidationState::new(missing_evidence));
     }
@@ -134,6 +193,15 @@ impl Era {
         self.validation_states.remove(proposed_block).is_some()
     }
 
+    /// Records that the validity of the block has been resolved. Returns `false` if that had
+    /// already happened since the block was last added, i.e. if this is a duplicate response.
+    pub(crate) fn mark_resolved(&mut self, proposed_block: &ProposedBlock<ClContext>) -> bool {
+        if self.resolved_blocks.contains(proposed_block) {
+            return false;
+        }
+        self.resolved_blocks.insert(proposed_block.clone())
+    }
+
     /// Adds new accusations from a finalized block.
     pub(crate) fn add_accusations(&mut self, accusations: &[PublicKey]) {
         for pub_key in accusations {
@@ -148,6 +216,76 @@ impl Era {
         self.accusations.iter().cloned().sorted().collect()
     }
 
+    /// Returns all validators that are known to have equivocated in this era, either because the
+    /// consensus protocol has direct evidence against them or because they were accused in a
+    /// finalized block. The result is sorted and empty if no faults were detected.
+    pub(crate) fn faulty_validators(&self) -> Vec<PublicKey> {
+        self.consensus
+            .validators_with_evidence()
+            .into_iter()
+            .chain(&self.accusations)
+            .cloned()
+            .sorted()
+            .dedup()
+            .collect()
+    }
+
+    /// Returns whether the weight of faulty validators is still below the fault tolerance
+    /// threshold, given as a fraction of the total weight. Both validators banned at the start of
+    /// the era and those that equivocated in it count as faulty.
+    ///
+    /// Like the finality detector, this requires the faulty weight to be less than the threshold
+    /// and less than half the total weight.
+    pub(crate) fn can_finalize(&self, finality_threshold_fraction: Ratio<u64>) -> bool {
+        let faulty_validators = self.faulty_validators();
+        let is_faulty = |key: &PublicKey| {
+            self.faulty.contains(key) || faulty_validators.binary_search(key).is_ok()
+        };
+        let (total_weight, faulty_weight) = self.validators.iter().fold(
+            (U512::zero(), U512::zero()),
+            |(total, faulty), (key, weight)| {
+                let faulty = if is_faulty(key) {
+                    faulty.saturating_add(*weight)
+                } else {
+                    faulty
+                };
+                (total.saturating_add(*weight), faulty)
+            },
+        );
+        let ftt = total_weight * U512::from(*finality_threshold_fraction.numer())
+            / U512::from(*finality_threshold_fraction.denom());
+        faulty_weight < ftt && faulty_weight.saturating_mul(U512::from(2)) < total_weight
+    }
+
+    /// Records that a block was finalized in this era at the given time.
+    pub(crate) fn record_progress(&mut self, timestamp: Timestamp) {
+        self.last_progress = self.last_progress.max(timestamp);
+        self.stall_reported = false;
+    }
+
+    /// Records the time at which the block with the given height was finalized. Only the most
+    /// recent `MAX_FINALIZATION_TIMES` entries are kept.
+    pub(crate) fn record_finalization(&mut self, height: u64, timestamp: Timestamp) {
+        if self.finalization_times.len() >= MAX_FINALIZATION_TIMES {
+            self.finalization_times.remove(0);
+        }
+        self.finalization_times.push((height, timestamp));
+    }
+
+    /// Returns the time of the last progress if the era has not finalized a block for longer than
+    /// `stall_timeout`. Each stall is only reported once, until the era makes progress again.
+    pub(crate) fn check_stalled(
+        &mut self,
+        now: Timestamp,
+        stall_timeout: TimeDiff,
+    ) -> Option<Timestamp> {
+        if self.stall_reported || now.saturating_diff(self.last_progress) <= stall_timeout {
+            return None;
+        }
+        self.stall_reported = true;
+        Some(self.last_progress)
+    }
+
     /// Returns the map of validator weights.
     pub(crate) fn validators(&self) -> &BTreeMap<PublicKey, U512> {
         &self.validators
@@ -172,10 +310,18 @@ impl DataSize for Era {
             start_time,
             start_height,
             validation_states,
+            resolved_blocks,
             faulty,
             cannot_propose,
             accusations,
             validators,
+            last_progress,
+            stall_reported,
+            finalization_times,
+            failed_proposals,
+            proposals_halted,
+            persisted_evidence,
+            gossiped,
         } = self;
 
         // `DataSize` cannot be made object safe due its use of associated constants. We implement
@@ -208,9 +354,217 @@ impl DataSize for Era {
             .saturating_add(start_time.estimate_heap_size())
             .saturating_add(start_height.estimate_heap_size())
             .saturating_add(validation_states.estimate_heap_size())
+            .saturating_add(resolved_blocks.estimate_heap_size())
             .saturating_add(faulty.estimate_heap_size())
             .saturating_add(cannot_propose.estimate_heap_size())
             .saturating_add(accusations.estimate_heap_size())
             .saturating_add(validators.estimate_heap_size())
+            .saturating_add(last_progress.estimate_heap_size())
+            .saturating_add(stall_reported.estimate_heap_size())
+            .saturating_add(finalization_times.estimate_heap_size())
+            .saturating_add(failed_proposals.estimate_heap_size())
+            .saturating_add(proposals_halted.estimate_heap_size())
+            .saturating_add(persisted_evidence.estimate_heap_size())
+            .saturating_add(gossiped.estimate_heap_size())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, sync::Arc};
+
+    use casper_types::testing::TestRng;
+
+    use super::*;
+    use crate::{
+        components::consensus::{
+            cl_context::Keypair,
+            consensus_protocol::{BlockContext, ProtocolOutcome},
+            highway_core::{
+                highway::{SignedWireUnit, Vertex, WireUnit},
+                state::{tests::ALICE, Observation, Panorama},
+            },
+            protocols::highway::{
+                tests::{new_test_highway_protocol, INSTANCE_ID_DATA},
+                HighwayMessage, ACTION_ID_VERTEX, TIMER_ID_ACTIVE_VALIDATOR,
+            },
+            tests::utils::{ALICE_NODE_ID, ALICE_PUBLIC_KEY, ALICE_SECRET_KEY, BOB_PUBLIC_KEY},
+            traits::Context,
+        },
+        types::BlockPayload,
+    };
+
+    fn new_test_era(validators: Vec<(PublicKey, u64)>) -> Era {
+        let weights = validators
+            .iter()
+            .map(|(pub_key, weight)| (pub_key.clone(), U512::from(*weight)))
+            .collect();
+        let consensus = new_test_highway_protocol(validators, vec![]);
+        Era::new(
+            consensus,
+            Timestamp::zero(),
+            0,
+            HashSet::new(),
+            HashSet::new(),
+            weights,
+        )
+    }
+
+    /// Returns a serialized block created by Alice in the first round.
+    fn alice_block(random_bit: bool) -> Vec<u8> {
+        let wunit: WireUnit<ClContext> = WireUnit {
+            panorama: Panorama::from(vec![Observation::None]),
+            creator: ALICE,
+            instance_id: ClContext::hash(INSTANCE_ID_DATA),
+            value: Some(Arc::new(BlockPayload::new(
+                vec![],
+                vec![],
+                vec![],
+                random_bit,
+            ))),
+            seq_number: 0,
+            timestamp: Timestamp::zero(),
+            round_exp: 14,
+            endorsed: BTreeSet::new(),
+        };
+        let alice_keypair = Keypair::from(Arc::clone(&*ALICE_SECRET_KEY));
+        let highway_message: HighwayMessage<ClContext> = HighwayMessage::NewVertex(Vertex::Unit(
+            SignedWireUnit::new(wunit.into_hashed(), &alice_keypair),
+        ));
+        bincode::serialize(&highway_message).unwrap()
+    }
+
+    #[test]
+    fn should_have_no_faulty_validators_initially() {
+        let era = new_test_era(vec![
+            (ALICE_PUBLIC_KEY.clone(), 100),
+            (BOB_PUBLIC_KEY.clone(), 100),
+        ]);
+        assert!(era.faulty_validators().is_empty());
+    }
+
+    #[test]
+    fn should_report_equivocators_and_accused_validators() {
+        let mut rng = TestRng::new();
+        let now = Timestamp::zero();
+        let mut era = new_test_era(vec![(ALICE_PUBLIC_KEY.clone(), 100)]);
+
+        // Alice creates two different blocks in the same round: an equivocation.
+        let mut outcomes = vec![];
+        for random_bit in [false, true] {
+            outcomes.extend(era.consensus.handle_message(
+                &mut rng,
+                *ALICE_NODE_ID,
+                alice_block(random_bit),
+                now,
+            ));
+        }
+        let mut new_evidence = vec![];
+        while let Some(outcome) = outcomes.pop() {
+            match outcome {
+                ProtocolOutcome::QueueAction(ACTION_ID_VERTEX) => {
+                    outcomes.extend(era.consensus.handle_action(ACTION_ID_VERTEX, now))
+                }
+                ProtocolOutcome::NewEvidence(pub_key, _) => new_evidence.push(pub_key),
+                _ => (),
+            }
+        }
+        assert_eq!(new_evidence, vec![ALICE_PUBLIC_KEY.clone()]);
+        assert_eq!(era.faulty_validators(), vec![ALICE_PUBLIC_KEY.clone()]);
+
+        // Validators accused in a finalized block are included, too.
+        era.add_accusations(&[BOB_PUBLIC_KEY.clone(), ALICE_PUBLIC_KEY.clone()]);
+        let mut expected = vec![ALICE_PUBLIC_KEY.clone(), BOB_PUBLIC_KEY.clone()];
+        expected.sort();
+        assert_eq!(era.faulty_validators(), expected);
+    }
+
+    #[test]
+    fn should_ignore_duplicate_validity_resolution() {
+        let mut era = new_test_era(vec![(ALICE_PUBLIC_KEY.clone(), 100)]);
+        let proposed_block = ProposedBlock::new(
+            Arc::new(BlockPayload::new(vec![], vec![], vec![], false)),
+            BlockContext::new(Timestamp::zero(), vec![]),
+        );
+        era.add_block(proposed_block.clone(), vec![]);
+
+        // Only the first response is processed.
+        assert!(era.mark_resolved(&proposed_block));
+        assert!(era.resolve_validity(&proposed_block, true));
+        assert!(!era.mark_resolved(&proposed_block));
+
+        // If the block is added again, it needs to be validated again.
+        era.add_block(proposed_block.clone(), vec![]);
+        assert!(era.mark_resolved(&proposed_block));
+        assert!(!era.mark_resolved(&proposed_block));
+    }
+
+    #[test]
+    fn should_report_stalled_era_once() {
+        let stall_timeout: TimeDiff = "10sec".parse().unwrap();
+        let mut era = new_test_era(vec![(ALICE_PUBLIC_KEY.clone(), 100)]);
+        let start = era.start_time;
+
+        // The era is not stalled before the timeout has passed.
+        assert_eq!(
+            era.check_stalled(start + stall_timeout, stall_timeout),
+            None
+        );
+
+        // Once it has passed without progress, the stall is reported exactly once.
+        let mut now = start;
+        let mut reports = vec![];
+        for _ in 0..5 {
+            now += "5sec".parse().unwrap();
+            reports.extend(era.check_stalled(now, stall_timeout));
+        }
+        assert_eq!(reports, vec![start]);
+
+        // After progress the era is not considered stalled, until the timeout passes again.
+        era.record_progress(now);
+        assert_eq!(era.check_stalled(now + stall_timeout, stall_timeout), None);
+        let later = now + stall_timeout + "1sec".parse().unwrap();
+        assert_eq!(era.check_stalled(later, stall_timeout), Some(now));
+        assert_eq!(era.check_stalled(later, stall_timeout), None);
+    }
+
+    #[test]
+    fn should_not_sign_anything_as_observer() {
+        let now = Timestamp::zero();
+        let alice_keypair = || Keypair::from(Arc::clone(&*ALICE_SECRET_KEY));
+
+        let mut era = new_test_era(vec![(ALICE_PUBLIC_KEY.clone(), 100)]);
+        let outcomes = era.activate_validator(
+            Participation::Observer,
+            ALICE_PUBLIC_KEY.clone(),
+            alice_keypair(),
+            now,
+            None,
+        );
+        assert!(outcomes.is_empty(), "unexpected outcomes: {:?}", outcomes);
+        assert!(!era.consensus.is_active());
+
+        // Even when the validator's timer fires, no units are created.
+        let outcomes = era
+            .consensus
+            .handle_timer(now + "1min".parse().unwrap(), TIMER_ID_ACTIVE_VALIDATOR);
+        assert!(
+            !outcomes
+                .iter()
+                .any(|outcome| matches!(outcome, ProtocolOutcome::CreatedGossipMessage(_))),
+            "unexpected outcomes: {:?}",
+            outcomes
+        );
+
+        // As a validator, the same node starts participating.
+        let mut era = new_test_era(vec![(ALICE_PUBLIC_KEY.clone(), 100)]);
+        let _ = era.activate_validator(
+            Participation::Validator,
+            ALICE_PUBLIC_KEY.clone(),
+            alice_keypair(),
+            now,
+            None,
+        );
+        assert!(era.consensus.is_active());
     }
 }
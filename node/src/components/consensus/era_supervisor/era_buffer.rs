@@ -0,0 +1,153 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use datasize::DataSize;
+
+use casper_types::EraId;
+
+/// A bounded buffer of items that arrived for eras that are not initialized yet.
+///
+/// The items are kept in order, grouped by era. Once the buffer is full, the oldest item of the
+/// earliest era is dropped to make room for a new one.
+#[derive(DataSize, Debug)]
+pub(crate) struct EraBuffer<T> {
+    /// The buffered items, by era, in the order in which they were added.
+    items: BTreeMap<EraId, VecDeque<T>>,
+    /// The total number of buffered items.
+    len: usize,
+    /// The maximum number of buffered items.
+    capacity: usize,
+}
+
+impl<T> EraBuffer<T> {
+    /// Creates a new buffer that holds at most `capacity` items.
+    pub(crate) fn new(capacity: usize) -> Self {
+        EraBuffer {
+            items: BTreeMap::new(),
+            len: 0,
+            capacity,
+        }
+    }
+
+    /// Adds an item for the given era. If the buffer was full, the oldest item of the earliest
+    /// era is dropped and returned.
+    pub(crate) fn push(&mut self, era_id: EraId, item: T) -> Option<(EraId, T)> {
+        if self.capacity == 0 {
+            return Some((era_id, item));
+        }
+        let dropped = if self.len >= self.capacity {
+            self.pop_oldest()
+        } else {
+            None
+        };
+        self.items.entry(era_id).or_default().push_back(item);
+        self.len = self.len.saturating_add(1);
+        dropped
+    }
+
+    /// Adds an item for the given era, unless the buffer is full. In that case the new item is
+    /// returned as an error and the buffer remains unchanged.
+    pub(crate) fn try_push(&mut self, era_id: EraId, item: T) -> Result<(), T> {
+        if self.len >= self.capacity {
+            return Err(item);
+        }
+        self.items.entry(era_id).or_default().push_back(item);
+        self.len = self.len.saturating_add(1);
+        Ok(())
+    }
+
+    /// Removes and returns all items buffered for the given era, in the order they were added.
+    pub(crate) fn take(&mut self, era_id: EraId) -> Vec<T> {
+        let items: Vec<T> = self.items.remove(&era_id).unwrap_or_default().into();
+        self.len = self.len.saturating_sub(items.len());
+        items
+    }
+
+    /// Returns the number of buffered items.
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns an iterator over all buffered items, by era, in the order in which they were added.
+    #[cfg(test)]
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (EraId, &T)> {
+        self.items
+            .iter()
+            .flat_map(|(era_id, queue)| queue.iter().map(move |item| (*era_id, item)))
+    }
+
+    /// Removes the oldest item of the earliest era.
+    fn pop_oldest(&mut self) -> Option<(EraId, T)> {
+        let era_id = *self.items.keys().next()?;
+        let queue = self.items.get_mut(&era_id)?;
+        let item = queue.pop_front()?;
+        if queue.is_empty() {
+            self.items.remove(&era_id);
+        }
+        self.len = self.len.saturating_sub(1);
+        Some((era_id, item))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_return_items_in_order_once_era_is_initialized() {
+        let mut buffer = EraBuffer::new(10);
+        assert_eq!(buffer.push(EraId::from(3), "a"), None);
+        assert_eq!(buffer.push(EraId::from(4), "b"), None);
+        assert_eq!(buffer.push(EraId::from(3), "c"), None);
+        assert_eq!(buffer.len(), 3);
+
+        // Nothing arrives for an era that has not been buffered.
+        assert!(buffer.take(EraId::from(2)).is_empty());
+
+        // Once the validators for era 3 are known, its items are replayed in order.
+        assert_eq!(buffer.take(EraId::from(3)), vec!["a", "c"]);
+        assert_eq!(buffer.len(), 1);
+        assert!(buffer.take(EraId::from(3)).is_empty());
+        assert_eq!(buffer.take(EraId::from(4)), vec!["b"]);
+        assert_eq!(buffer.len(), 0);
+    }
+
+    #[test]
+    fn should_drop_oldest_items_past_capacity() {
+        let mut buffer = EraBuffer::new(2);
+        assert_eq!(buffer.push(EraId::from(5), 1), None);
+        assert_eq!(buffer.push(EraId::from(4), 2), None);
+        assert_eq!(buffer.push(EraId::from(5), 3), Some((EraId::from(4), 2)));
+        assert_eq!(buffer.push(EraId::from(5), 4), Some((EraId::from(5), 1)));
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.take(EraId::from(5)), vec![3, 4]);
+    }
+
+    #[test]
+    fn should_reject_new_items_when_full() {
+        let mut buffer = EraBuffer::new(3);
+        for i in 0..3 {
+            assert_eq!(buffer.try_push(EraId::from(7), i), Ok(()));
+        }
+        assert_eq!(buffer.len(), 3);
+
+        // Further items are dropped and handed back, while the buffered ones are retained.
+        let dropped: Vec<_> = (3..6)
+            .filter_map(|i| buffer.try_push(EraId::from(8), i).err())
+            .collect();
+        assert_eq!(dropped, vec![3, 4, 5]);
+        assert_eq!(buffer.len(), 3);
+        assert!(buffer.take(EraId::from(8)).is_empty());
+        assert_eq!(buffer.take(EraId::from(7)), vec![0, 1, 2]);
+
+        // Once there is room again, new items are accepted.
+        assert_eq!(buffer.try_push(EraId::from(8), 6), Ok(()));
+    }
+
+    #[test]
+    fn should_not_buffer_anything_with_zero_capacity() {
+        let mut buffer = EraBuffer::new(0);
+        assert_eq!(buffer.push(EraId::from(1), 1), Some((EraId::from(1), 1)));
+        assert_eq!(buffer.try_push(EraId::from(1), 2), Err(2));
+        assert_eq!(buffer.len(), 0);
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::{HashSet, VecDeque};
+
+use datasize::DataSize;
+
+use casper_hashing::Digest;
+
+/// The maximum number of gossiped messages that are remembered per era. Once it is reached, the
+/// oldest ones are forgotten.
+const MAX_GOSSIP_INDEX_SIZE: usize = 10_000;
+
+/// A compact index of the protocol messages we gossiped in an era, so that we don't gossip them
+/// again, e.g. after a restart.
+///
+/// Only the first eight bytes of each message's hash are kept. A collision would just mean that
+/// we don't gossip a message that the other validators will still receive via synchronization.
+#[derive(DataSize, Debug)]
+pub(crate) struct GossipIndex {
+    /// The keys of the gossiped messages.
+    keys: HashSet<u64>,
+    /// The same keys, in the order in which they were added.
+    order: VecDeque<u64>,
+    /// The maximum number of keys.
+    capacity: usize,
+    /// The number of keys that were added since the index was last persisted.
+    unsaved: usize,
+}
+
+impl Default for GossipIndex {
+    fn default() -> Self {
+        GossipIndex::new(MAX_GOSSIP_INDEX_SIZE)
+    }
+}
+
+impl GossipIndex {
+    /// Creates a new index that holds at most `capacity` keys.
+    pub(crate) fn new(capacity: usize) -> Self {
+        GossipIndex {
+            keys: HashSet::new(),
+            order: VecDeque::new(),
+            capacity,
+            unsaved: 0,
+        }
+    }
+
+    /// Adds the message to the index. Returns `false` if it was already in it.
+    pub(crate) fn insert(&mut self, payload: &[u8]) -> bool {
+        let inserted = self.insert_key(Self::key(payload));
+        if inserted {
+            self.unsaved += 1;
+        }
+        inserted
+    }
+
+    /// Adds the keys of a persisted index, e.g. after a restart.
+    pub(crate) fn extend(&mut self, keys: Vec<u64>) {
+        for key in keys {
+            self.insert_key(key);
+        }
+    }
+
+    /// Returns the keys, in the order in which they were added, to be persisted.
+    pub(crate) fn keys(&self) -> Vec<u64> {
+        self.order.iter().copied().collect()
+    }
+
+    /// Returns the keys to be persisted if at least `min_unsaved` of them, and at least one, were
+    /// added since the last time, and marks the index as saved.
+    pub(crate) fn take_unsaved(&mut self, min_unsaved: usize) -> Option<Vec<u64>> {
+        if self.unsaved == 0 || self.unsaved < min_unsaved {
+            return None;
+        }
+        self.unsaved = 0;
+        Some(self.keys())
+    }
+
+    fn insert_key(&mut self, key: u64) -> bool {
+        if self.capacity == 0 || !self.keys.insert(key) {
+            return false;
+        }
+        self.order.push_back(key);
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.keys.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    /// Returns the compact key of the given message.
+    fn key(payload: &[u8]) -> u64 {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&Digest::hash(payload).value()[..8]);
+        u64::from_le_bytes(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_only_insert_messages_once() {
+        let mut index = GossipIndex::new(10);
+        assert!(index.insert(b"unit 1"));
+        assert!(!index.insert(b"unit 1"));
+        assert!(index.insert(b"unit 2"));
+        assert_eq!(index.keys().len(), 2);
+    }
+
+    #[test]
+    fn should_forget_oldest_messages_when_full() {
+        let mut index = GossipIndex::new(2);
+        assert!(index.insert(b"unit 1"));
+        assert!(index.insert(b"unit 2"));
+        assert!(index.insert(b"unit 3"));
+        assert_eq!(index.keys().len(), 2);
+        assert!(!index.insert(b"unit 3"));
+        assert!(index.insert(b"unit 1"));
+    }
+
+    #[test]
+    fn should_only_save_after_enough_new_messages() {
+        let mut index = GossipIndex::new(10);
+        assert_eq!(index.take_unsaved(1), None);
+        index.insert(b"unit 1");
+        assert_eq!(index.take_unsaved(2), None);
+        index.insert(b"unit 1");
+        assert_eq!(index.take_unsaved(2), None);
+        index.insert(b"unit 2");
+        assert_eq!(index.take_unsaved(2).map(|keys| keys.len()), Some(2));
+        assert_eq!(index.take_unsaved(1), None);
+    }
+
+    #[test]
+    fn should_restore_persisted_keys() {
+        let mut index = GossipIndex::new(10);
+        index.insert(b"unit 1");
+        let mut restored = GossipIndex::new(10);
+        restored.extend(index.keys());
+        assert!(!restored.insert(b"unit 1"));
+        assert!(restored.insert(b"unit 2"));
+    }
+}
@@ -0,0 +1,49 @@
+use std::fmt::{self, Display, Formatter};
+
+/// The reason why an incoming consensus message was dropped or ignored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MessageDropReason {
+    /// The message belongs to an era that is not kept in memory anymore.
+    AncientEra,
+    /// The message belongs to a future era that is not initialized yet. It is buffered until the
+    /// era starts.
+    FutureEraBuffered,
+    /// The message didn't add anything to our protocol state.
+    Duplicate,
+    /// The message was dropped because too many messages were buffered or queued.
+    RateLimited,
+    /// The evidence request was dropped because too many evidence requests were answered in the
+    /// last second.
+    EvidenceRateLimited,
+    /// The message exceeded the maximum size.
+    TooLarge,
+    /// The message must come from a validator, but the sender isn't one.
+    NonValidatorSender,
+    /// The message's signature is invalid, e.g. because it was altered in transit.
+    BadSignature,
+    /// The protocol message wasn't signed, but signatures are required.
+    Unsigned,
+}
+
+impl MessageDropReason {
+    /// Returns the name of the reason, as used in metric labels.
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            MessageDropReason::AncientEra => "ancient_era",
+            MessageDropReason::FutureEraBuffered => "future_era_buffered",
+            MessageDropReason::Duplicate => "duplicate",
+            MessageDropReason::RateLimited => "rate_limited",
+            MessageDropReason::EvidenceRateLimited => "evidence_rate_limited",
+            MessageDropReason::TooLarge => "too_large",
+            MessageDropReason::NonValidatorSender => "non_validator_sender",
+            MessageDropReason::BadSignature => "bad_signature",
+            MessageDropReason::Unsigned => "unsigned",
+        }
+    }
+}
+
+impl Display for MessageDropReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
@@ -0,0 +1,140 @@
+//! A log of the incoming consensus messages, for reproducing stuck eras offline.
+
+use std::{
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use casper_types::{TimeDiff, Timestamp};
+
+use crate::{components::consensus::ConsensusMessage, types::NodeId};
+
+/// The maximum time for which recorded entries are kept in the buffer before they are written to
+/// the file.
+const FLUSH_INTERVAL: TimeDiff = TimeDiff::from_seconds(1);
+
+/// An incoming consensus message, with its sender and the time we received it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct LoggedMessage {
+    pub(crate) timestamp: Timestamp,
+    pub(crate) sender: NodeId,
+    pub(crate) message: ConsensusMessage,
+}
+
+/// A file to which incoming consensus messages are appended.
+///
+/// Each entry is a bincode-serialized `LoggedMessage`. Entries are buffered, and the buffer is
+/// flushed once an entry is recorded more than `FLUSH_INTERVAL` after the last flush, so if the
+/// node is killed, only the last few entries are lost. Once the file would exceed the maximum
+/// size, it is rotated: it replaces the file with the suffix `.1`, and a new one is started.
+#[derive(Debug)]
+pub(crate) struct MessageLog {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    size: u64,
+    max_size: u64,
+    last_flush: Timestamp,
+}
+
+impl MessageLog {
+    /// Opens the log file, creating it and its parent directory if they don't exist. New entries
+    /// are appended to the existing ones.
+    pub(crate) fn open(path: &Path, max_size: u64) -> Result<Self, bincode::Error> {
+        if let Some(parent_directory) = path.parent() {
+            fs::create_dir_all(parent_directory)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(MessageLog {
+            writer: BufWriter::new(file),
+            path: path.to_path_buf(),
+            size,
+            max_size,
+            last_flush: Timestamp::zero(),
+        })
+    }
+
+    /// Appends the entry to the log, rotating the file first if it would grow too large.
+    pub(crate) fn record(&mut self, entry: &LoggedMessage) -> Result<(), bincode::Error> {
+        let bytes = bincode::serialize(entry)?;
+        let len = bytes.len() as u64;
+        if self.size > 0 && self.size.saturating_add(len) > self.max_size {
+            self.rotate()?;
+        }
+        self.writer.write_all(&bytes)?;
+        self.size = self.size.saturating_add(len);
+        if entry.timestamp.saturating_diff(self.last_flush) >= FLUSH_INTERVAL {
+            self.writer.flush()?;
+            self.last_flush = entry.timestamp;
+        }
+        Ok(())
+    }
+
+    /// Moves the current file to the rotated path and starts a new one.
+    fn rotate(&mut self) -> Result<(), bincode::Error> {
+        self.writer.flush()?;
+        fs::rename(&self.path, rotated_path(&self.path))?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.writer = BufWriter::new(file);
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// Returns the path to which a full log file is moved: the same path with the suffix `.1`.
+pub(crate) fn rotated_path(path: &Path) -> PathBuf {
+    let mut rotated = OsString::from(path.as_os_str());
+    rotated.push(".1");
+    PathBuf::from(rotated)
+}
+
+/// Reads all entries from a message log file, in the order in which they were recorded.
+pub(crate) fn read_message_log(path: &Path) -> Result<Vec<LoggedMessage>, bincode::Error> {
+    let bytes = fs::read(path)?;
+    let mut reader = bytes.as_slice();
+    let mut entries = Vec::new();
+    while !reader.is_empty() {
+        entries.push(bincode::deserialize_from(&mut reader)?);
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::{testing::TestRng, EraId};
+
+    use super::*;
+
+    #[test]
+    fn should_rotate_full_log() {
+        let mut rng = TestRng::new();
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("messages.bin");
+        let entry = |payload: Vec<u8>, rng: &mut TestRng| LoggedMessage {
+            timestamp: Timestamp::zero(),
+            sender: NodeId::random(rng),
+            message: ConsensusMessage::Protocol {
+                era_id: EraId::from(1),
+                payload,
+            },
+        };
+        let first = entry(vec![1], &mut rng);
+        let second = entry(vec![2], &mut rng);
+        let max_size = bincode::serialize(&first).unwrap().len() as u64;
+
+        let mut log = MessageLog::open(&path, max_size).unwrap();
+        log.record(&first).unwrap();
+        log.record(&second).unwrap();
+        drop(log);
+
+        assert_eq!(read_message_log(&rotated_path(&path)).unwrap(), vec![first]);
+        assert_eq!(read_message_log(&path).unwrap(), vec![second]);
+    }
+}
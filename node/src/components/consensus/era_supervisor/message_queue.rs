@@ -0,0 +1,165 @@
+use std::collections::{HashSet, VecDeque};
+
+use datasize::DataSize;
+
+use casper_hashing::Digest;
+use casper_types::EraId;
+
+use super::message_drop_reason::MessageDropReason;
+use crate::types::NodeId;
+
+/// How urgently a queued message needs to be handled.
+#[derive(Clone, Copy, DataSize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum MessagePriority {
+    /// Messages that are unlikely to advance finality, e.g. for past eras.
+    Low,
+    /// Other messages for the current era, e.g. pings and evidence.
+    High,
+    /// Messages that can directly advance finality in the current era, i.e. units. These are
+    /// handled immediately instead of being queued.
+    Urgent,
+}
+
+/// A message waiting to be handled.
+#[derive(DataSize, Debug)]
+struct QueuedMessage {
+    era_id: EraId,
+    sender: NodeId,
+    payload: Vec<u8>,
+    hash: Digest,
+}
+
+/// A bounded queue of incoming protocol messages.
+///
+/// High-priority messages are handled first. Once the queue is full, a new message displaces the
+/// oldest low-priority one, or is dropped if there is none. A message whose payload is already
+/// queued is always dropped.
+#[derive(DataSize, Debug)]
+pub(crate) struct MessageQueue {
+    /// The queued low-priority messages, in the order in which they arrived.
+    low: VecDeque<QueuedMessage>,
+    /// The queued high-priority messages, in the order in which they arrived.
+    high: VecDeque<QueuedMessage>,
+    /// The hashes of all queued payloads.
+    hashes: HashSet<Digest>,
+    /// The maximum number of queued messages.
+    capacity: usize,
+}
+
+impl MessageQueue {
+    /// Creates a new queue that holds at most `capacity` messages.
+    pub(crate) fn new(capacity: usize) -> Self {
+        MessageQueue {
+            low: VecDeque::new(),
+            high: VecDeque::new(),
+            hashes: HashSet::new(),
+            capacity,
+        }
+    }
+
+    /// Adds a message to the queue. Returns the reason if a message was dropped: either the new
+    /// one, or a queued one with a lower priority.
+    pub(crate) fn push(
+        &mut self,
+        priority: MessagePriority,
+        era_id: EraId,
+        sender: NodeId,
+        payload: Vec<u8>,
+    ) -> Option<MessageDropReason> {
+        let hash = Digest::hash(&payload);
+        if self.hashes.contains(&hash) {
+            return Some(MessageDropReason::Duplicate);
+        }
+        let mut dropped = None;
+        if self.len() >= self.capacity {
+            if priority == MessagePriority::Low || self.low.is_empty() {
+                return Some(MessageDropReason::RateLimited);
+            }
+            if let Some(message) = self.low.pop_front() {
+                self.hashes.remove(&message.hash);
+                dropped = Some(MessageDropReason::RateLimited);
+            }
+        }
+        self.hashes.insert(hash);
+        let message = QueuedMessage {
+            era_id,
+            sender,
+            payload,
+            hash,
+        };
+        match priority {
+            MessagePriority::Low => self.low.push_back(message),
+            MessagePriority::High | MessagePriority::Urgent => self.high.push_back(message),
+        }
+        dropped
+    }
+
+    /// Removes and returns the next message to handle: the oldest one with the highest priority.
+    pub(crate) fn pop(&mut self) -> Option<(EraId, NodeId, Vec<u8>)> {
+        let message = self.high.pop_front().or_else(|| self.low.pop_front())?;
+        self.hashes.remove(&message.hash);
+        Some((message.era_id, message.sender, message.payload))
+    }
+
+    /// Returns the number of queued messages.
+    pub(crate) fn len(&self) -> usize {
+        self.low.len().saturating_add(self.high.len())
+    }
+
+    /// Returns `true` if no messages are queued.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.low.is_empty() && self.high.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::testing::TestRng;
+
+    use super::*;
+
+    #[test]
+    fn should_shed_low_priority_messages_first() {
+        let mut rng = TestRng::new();
+        let sender = NodeId::random(&mut rng);
+        let (old, current) = (EraId::new(1), EraId::new(2));
+        let mut queue = MessageQueue::new(2);
+
+        assert_eq!(queue.push(MessagePriority::Low, old, sender, vec![1]), None);
+        assert_eq!(
+            queue.push(MessagePriority::High, current, sender, vec![2]),
+            None
+        );
+        assert_eq!(queue.pop(), Some((current, sender, vec![2])));
+        // Duplicates are dropped even if there is room.
+        assert_eq!(
+            queue.push(MessagePriority::High, current, sender, vec![1]),
+            Some(MessageDropReason::Duplicate)
+        );
+        assert_eq!(
+            queue.push(MessagePriority::High, current, sender, vec![3]),
+            None
+        );
+
+        // The queue is full: A low-priority message is dropped, a high-priority one displaces the
+        // oldest low-priority message.
+        assert_eq!(
+            queue.push(MessagePriority::Low, old, sender, vec![4]),
+            Some(MessageDropReason::RateLimited)
+        );
+        assert_eq!(
+            queue.push(MessagePriority::High, current, sender, vec![5]),
+            Some(MessageDropReason::RateLimited)
+        );
+        // Now there are only high-priority messages, so the new one is dropped.
+        assert_eq!(
+            queue.push(MessagePriority::High, current, sender, vec![6]),
+            Some(MessageDropReason::RateLimited)
+        );
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pop(), Some((current, sender, vec![3])));
+        assert_eq!(queue.pop(), Some((current, sender, vec![5])));
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,151 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use datasize::DataSize;
+use tracing::debug;
+
+use casper_types::{TimeDiff, Timestamp};
+
+use crate::types::NodeId;
+
+/// The time after which a peer's score has decayed to half its value.
+const HALF_LIFE: TimeDiff = TimeDiff::from_seconds(10 * 60);
+
+/// The score below which a peer is considered unreliable.
+const LOW_REPUTATION_THRESHOLD: f64 = -50.0;
+
+/// Something a peer did that affects its reputation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PeerBehavior {
+    /// The peer sent a message that advanced our protocol state, e.g. a new unit.
+    Useful,
+    /// The peer sent a message we already had, or that was irrelevant.
+    Duplicate,
+    /// The peer sent a message exceeding the maximum size.
+    Oversized,
+    /// The peer sent invalid content, e.g. an invalid unit or proposed block.
+    Invalid,
+}
+
+impl PeerBehavior {
+    /// Returns the change to the peer's score.
+    fn score(self) -> f64 {
+        match self {
+            PeerBehavior::Useful => 10.0,
+            PeerBehavior::Duplicate => -1.0,
+            PeerBehavior::Oversized => -25.0,
+            PeerBehavior::Invalid => -100.0,
+        }
+    }
+}
+
+/// Reputation scores of the peers that sent us consensus messages.
+///
+/// Scores rise with useful messages and fall with invalid or redundant ones. They decay towards
+/// zero over time, so that old behavior is eventually forgotten.
+#[derive(DataSize, Debug, Default)]
+pub(crate) struct PeerReputation {
+    /// Each peer's score, and the time it was last updated.
+    scores: HashMap<NodeId, (f64, Timestamp)>,
+}
+
+impl PeerReputation {
+    /// Updates the peer's score with the given behavior.
+    pub(crate) fn record(&mut self, peer: NodeId, behavior: PeerBehavior, now: Timestamp) {
+        let score = self.score(&peer, now) + behavior.score();
+        self.scores.insert(peer, (score, now));
+        if self.is_low(&peer, now) {
+            debug!(%peer, score, ?behavior, "peer has a low reputation");
+        }
+    }
+
+    /// Returns the peer's current score. Unknown peers have a score of zero.
+    pub(crate) fn score(&self, peer: &NodeId, now: Timestamp) -> f64 {
+        self.scores.get(peer).map_or(0.0, |(score, updated)| {
+            decay(*score, now.saturating_diff(*updated))
+        })
+    }
+
+    /// Returns `true` if the peer's score is below the threshold for reliable peers.
+    pub(crate) fn is_low(&self, peer: &NodeId, now: Timestamp) -> bool {
+        self.score(peer, now) < LOW_REPUTATION_THRESHOLD
+    }
+
+    /// Returns all known peers, the ones with the highest scores first.
+    pub(crate) fn ranked_peers(&self, now: Timestamp) -> Vec<NodeId> {
+        let mut peers: Vec<(f64, NodeId)> = self
+            .scores
+            .keys()
+            .map(|peer| (self.score(peer, now), *peer))
+            .collect();
+        // Scores are never NaN, so they are totally ordered.
+        peers.sort_by(|(score0, _), (score1, _)| {
+            score1.partial_cmp(score0).unwrap_or(Ordering::Equal)
+        });
+        peers.into_iter().map(|(_, peer)| peer).collect()
+    }
+
+    /// Forgets peers whose scores have decayed to almost zero.
+    pub(crate) fn prune(&mut self, now: Timestamp) {
+        self.scores.retain(|_, (score, updated)| {
+            decay(*score, now.saturating_diff(*updated)).abs() >= 1.0
+        });
+    }
+}
+
+/// Returns the score after the given time has elapsed.
+fn decay(score: f64, elapsed: TimeDiff) -> f64 {
+    let half_lives = elapsed.millis() as f64 / HALF_LIFE.millis() as f64;
+    score * 0.5f64.powf(half_lives)
+}
+
+#[cfg(test)]
+mod tests {
+    use casper_types::testing::TestRng;
+
+    use super::*;
+
+    #[test]
+    fn should_rank_peers_by_behavior() {
+        let mut rng = TestRng::new();
+        let (good, bad, unknown) = (
+            NodeId::random(&mut rng),
+            NodeId::random(&mut rng),
+            NodeId::random(&mut rng),
+        );
+        let now = Timestamp::from(100_000);
+        let mut reputation = PeerReputation::default();
+        reputation.record(good, PeerBehavior::Useful, now);
+        reputation.record(good, PeerBehavior::Duplicate, now);
+        reputation.record(bad, PeerBehavior::Oversized, now);
+        reputation.record(bad, PeerBehavior::Invalid, now);
+
+        assert_eq!(reputation.score(&good, now), 9.0);
+        assert_eq!(reputation.score(&bad, now), -125.0);
+        assert_eq!(reputation.score(&unknown, now), 0.0);
+        assert!(!reputation.is_low(&good, now));
+        assert!(reputation.is_low(&bad, now));
+        assert_eq!(reputation.ranked_peers(now), vec![good, bad]);
+    }
+
+    #[test]
+    fn should_decay_scores() {
+        let mut rng = TestRng::new();
+        let peer = NodeId::random(&mut rng);
+        let now = Timestamp::from(100_000);
+        let mut reputation = PeerReputation::default();
+        reputation.record(peer, PeerBehavior::Invalid, now);
+        assert!(reputation.is_low(&peer, now));
+
+        // After one half-life, the score is halved, and the peer is not considered unreliable.
+        let later = now + HALF_LIFE;
+        assert_eq!(reputation.score(&peer, later), -50.0);
+        assert!(!reputation.is_low(&peer, later));
+
+        // Eventually the peer is forgotten.
+        reputation.prune(later);
+        assert_eq!(reputation.ranked_peers(later), vec![peer]);
+        let much_later = now + HALF_LIFE * 10;
+        reputation.prune(much_later);
+        assert!(reputation.ranked_peers(much_later).is_empty());
+    }
+}
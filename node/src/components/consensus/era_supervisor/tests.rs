@@ -0,0 +1,3217 @@
+use futures::future;
+use serde::{Deserialize, Serialize};
+
+use super::{clock::MockClock, *};
+use crate::{
+    components::consensus::{
+        config::FinalityThresholdOverride,
+        highway_core::{
+            highway::{Ping, SignedWireUnit, Vertex, WireUnit},
+            state::{self, Panorama},
+        },
+        protocols::highway::{HighwayMessage, TIMER_ID_REQUEST_STATE},
+        tests::{
+            harness::{run_effects, CapturedEffects, TestEvent},
+            utils::{
+                new_test_chainspec, switch_block_header, ALICE_PUBLIC_KEY, ALICE_SECRET_KEY,
+                BOB_PUBLIC_KEY, CAROL_PUBLIC_KEY,
+            },
+        },
+        BlockContext, HighwayProtocol,
+    },
+    effect::{
+        announcements::{BlocklistAnnouncement, ConsensusAnnouncement},
+        requests::{NetworkInfoRequest, NetworkRequest, StateStoreRequest, StorageRequest},
+    },
+    protocol::Message,
+    testing::ComponentHarness,
+    types::{BlockHash, BlockPayload, BlockSignatures, FinalitySignature},
+};
+
+/// Returns the validator weights as a map.
+fn weights(validators: &[(PublicKey, u64)]) -> BTreeMap<PublicKey, U512> {
+    validators
+        .iter()
+        .map(|(pub_key, weight)| (pub_key.clone(), U512::from(*weight)))
+        .collect()
+}
+
+/// The state of an `EraSupervisor` that determines how it reacts to further events.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Snapshot {
+    /// The current era.
+    current_era: EraId,
+    /// The switch blocks the open eras were created from, in order.
+    switch_blocks: Vec<BlockHeader>,
+    /// The validator weights of each open era.
+    validators: BTreeMap<EraId, BTreeMap<PublicKey, U512>>,
+    /// The height of the next block to be finalized.
+    next_block_height: u64,
+    /// The height of the next block to be executed.
+    next_executed_height: u64,
+    /// The buffered messages for future eras, with their senders.
+    future_messages: Vec<(EraId, NodeId, Vec<u8>)>,
+}
+
+/// An `EraSupervisor` running as Alice, with the means to execute its effects.
+struct TestSupervisor {
+    harness: ComponentHarness<TestEvent>,
+    supervisor: EraSupervisor,
+    /// The switch blocks the supervisor's eras were created from, in order.
+    switch_blocks: Vec<BlockHeader>,
+}
+
+impl TestSupervisor {
+    /// Creates a supervisor in era 1, with the given validators.
+    fn new(validators: Vec<(PublicKey, u64)>, config: Config) -> Self {
+        let chainspec = new_test_chainspec(validators.clone());
+        let switch_block = switch_block_header(&chainspec, EraId::from(0), weights(&validators));
+        Self::with_switch_blocks(Arc::new(chainspec), config, vec![switch_block])
+    }
+
+    /// Creates a supervisor whose eras are initialized from the given switch blocks. The current
+    /// era is the one following the last switch block.
+    fn with_switch_blocks(
+        chainspec: Arc<Chainspec>,
+        config: Config,
+        switch_blocks: Vec<BlockHeader>,
+    ) -> Self {
+        let mut harness = ComponentHarness::default();
+        let supervisor = new_supervisor(&mut harness, chainspec, config, &switch_blocks);
+        TestSupervisor {
+            harness,
+            supervisor,
+            switch_blocks,
+        }
+    }
+
+    /// Replaces the supervisor with a new one in era 1, with the given validators, as if the node
+    /// was restarted. The harness is kept, including its temporary directory and its random number
+    /// generator: There can only be one `TestRng` per thread.
+    fn restart(&mut self, validators: Vec<(PublicKey, u64)>, config: Config) {
+        let chainspec = new_test_chainspec(validators.clone());
+        let switch_block = switch_block_header(&chainspec, EraId::from(0), weights(&validators));
+        self.restart_with_switch_blocks(Arc::new(chainspec), config, vec![switch_block]);
+    }
+
+    /// Replaces the supervisor with a new one whose eras are initialized from the given switch
+    /// blocks, keeping the harness.
+    fn restart_with_switch_blocks(
+        &mut self,
+        chainspec: Arc<Chainspec>,
+        config: Config,
+        switch_blocks: Vec<BlockHeader>,
+    ) {
+        self.supervisor = new_supervisor(&mut self.harness, chainspec, config, &switch_blocks);
+        self.switch_blocks = switch_blocks;
+    }
+
+    /// Captures the supervisor's state.
+    fn snapshot(&self) -> Snapshot {
+        let supervisor = &self.supervisor;
+        Snapshot {
+            current_era: supervisor.current_era,
+            switch_blocks: self.switch_blocks.clone(),
+            validators: supervisor
+                .open_eras
+                .iter()
+                .map(|(era_id, era)| (*era_id, era.validators().clone()))
+                .collect(),
+            next_block_height: supervisor.next_block_height,
+            next_executed_height: supervisor.next_executed_height,
+            future_messages: supervisor
+                .future_messages
+                .iter()
+                .map(|(era_id, (sender, payload))| (era_id, *sender, payload.clone()))
+                .collect(),
+        }
+    }
+
+    /// Replaces the supervisor with a new one in the state captured by the snapshot, keeping the
+    /// harness.
+    fn restore(&mut self, snapshot: &Snapshot, chainspec: Arc<Chainspec>, config: Config) {
+        self.restart_with_switch_blocks(chainspec, config, snapshot.switch_blocks.clone());
+        let supervisor = &mut self.supervisor;
+        assert_eq!(supervisor.current_era, snapshot.current_era);
+        supervisor.next_block_height = snapshot.next_block_height;
+        supervisor.next_executed_height = snapshot.next_executed_height;
+        for (era_id, sender, payload) in &snapshot.future_messages {
+            supervisor
+                .future_messages
+                .try_push(*era_id, (*sender, payload.clone()))
+                .expect("too many buffered messages");
+        }
+    }
+
+    /// Passes a new switch block to the supervisor, to initialize the next era.
+    fn add_switch_block(&mut self, switch_block: BlockHeader) -> Effects<Event> {
+        self.switch_blocks.push(switch_block);
+        self.supervisor.create_new_era_effects(
+            self.harness.effect_builder,
+            &mut self.harness.rng,
+            &self.switch_blocks,
+        )
+    }
+
+    /// Finishes the current era with a switch block, and initializes the next era with the given
+    /// validators.
+    fn advance_era(&mut self, validators: &[(PublicKey, u64)]) -> Vec<TestEvent> {
+        let switch_block = switch_block_header(
+            &self.supervisor.chainspec,
+            self.supervisor.current_era,
+            weights(validators),
+        );
+        let effects = self.add_switch_block(switch_block);
+        self.run(effects)
+    }
+
+    /// Runs the effects until they complete or wait for a response, and returns all events they
+    /// produced or scheduled on the queue.
+    fn run(&mut self, effects: Effects<Event>) -> Vec<TestEvent> {
+        run_effects(&mut self.harness, effects)
+    }
+
+    /// Handles the scheduled actions among the events, and the ones they schedule in turn, until
+    /// there are none left.
+    fn run_actions(&mut self, mut events: Vec<TestEvent>) {
+        while !events.is_empty() {
+            let mut effects = Effects::new();
+            for event in events {
+                if let TestEvent::Consensus(Event::Action { era_id, action_id }) = event {
+                    effects.extend(self.supervisor.handle_action(
+                        self.harness.effect_builder,
+                        &mut self.harness.rng,
+                        era_id,
+                        action_id,
+                    ));
+                }
+            }
+            events = self.run(effects);
+        }
+    }
+}
+
+/// Creates an `EraSupervisor` running as Alice, whose eras are initialized from the given switch
+/// blocks.
+fn new_supervisor(
+    harness: &mut ComponentHarness<TestEvent>,
+    chainspec: Arc<Chainspec>,
+    config: Config,
+    switch_blocks: &[BlockHeader],
+) -> EraSupervisor {
+    let latest_block_header = switch_blocks.last().expect("missing switch block");
+    let (supervisor, _) = EraSupervisor::from_switch_blocks(
+        latest_block_header.era_id().successor(),
+        harness.tmp.path(),
+        Arc::clone(&*ALICE_SECRET_KEY),
+        ALICE_PUBLIC_KEY.clone(),
+        config,
+        harness.effect_builder,
+        chainspec,
+        latest_block_header,
+        None,
+        &Registry::new(),
+        switch_blocks,
+        &mut harness.rng,
+    );
+    supervisor
+}
+
+#[test]
+fn should_broadcast_evidence_request() {
+    let mut test = TestSupervisor::new(
+        vec![
+            (ALICE_PUBLIC_KEY.clone(), 100),
+            (BOB_PUBLIC_KEY.clone(), 100),
+        ],
+        Config::default(),
+    );
+    let era_id = EraId::from(1);
+    let effects = test.supervisor.request_evidence(
+        test.harness.effect_builder,
+        era_id,
+        BOB_PUBLIC_KEY.clone(),
+    );
+    let events = test.run(effects);
+    match &*events {
+        [TestEvent::Network(NetworkRequest::Broadcast { payload, .. })] => match &**payload {
+            Message::Consensus(ConsensusMessage::EvidenceRequest {
+                era_id: requested_era_id,
+                pub_key,
+            }) => {
+                assert_eq!(*requested_era_id, era_id);
+                assert_eq!(*pub_key, *BOB_PUBLIC_KEY);
+            }
+            message => panic!("unexpected message: {:?}", message),
+        },
+        events => panic!("unexpected events: {:?}", events),
+    }
+}
+
+/// Restarts the supervisor with the given setting, and returns the number of evidence requests
+/// that are broadcast when a block accusing Bob is proposed.
+fn evidence_requests_for_accusation(
+    test: &mut TestSupervisor,
+    broadcast_evidence_requests: bool,
+) -> usize {
+    test.restart(
+        vec![
+            (ALICE_PUBLIC_KEY.clone(), 100),
+            (BOB_PUBLIC_KEY.clone(), 100),
+        ],
+        Config {
+            broadcast_evidence_requests,
+            ..Config::default()
+        },
+    );
+    let sender = NodeId::random(&mut test.harness.rng);
+    let block_payload = BlockPayload::new(vec![], vec![], vec![BOB_PUBLIC_KEY.clone()], false);
+    validate_proposed_block(test, sender, block_payload)
+        .iter()
+        .filter(|event| match event {
+            TestEvent::Network(NetworkRequest::Broadcast { payload, .. }) => matches!(
+                **payload,
+                Message::Consensus(ConsensusMessage::EvidenceRequest { .. })
+            ),
+            _ => false,
+        })
+        .count()
+}
+
+#[test]
+fn should_only_broadcast_evidence_requests_if_enabled() {
+    let mut test = TestSupervisor::new(vec![(ALICE_PUBLIC_KEY.clone(), 100)], Config::default());
+    assert_eq!(evidence_requests_for_accusation(&mut test, true), 1);
+    assert_eq!(evidence_requests_for_accusation(&mut test, false), 0);
+}
+
+#[test]
+fn should_roundtrip_latency_probe_messages() {
+    let messages = vec![
+        ConsensusMessage::Ping {
+            era_id: EraId::from(3),
+            nonce: 17,
+        },
+        ConsensusMessage::Pong {
+            era_id: EraId::from(3),
+            nonce: u64::MAX,
+        },
+    ];
+    for message in messages {
+        let bytes = bincode::serialize(&message).expect("should serialize");
+        let decoded: ConsensusMessage = bincode::deserialize(&bytes).expect("should deserialize");
+        assert_eq!(decoded, message);
+    }
+}
+
+#[test]
+fn should_roundtrip_message_batch() {
+    let message = ConsensusMessage::ProtocolBatch {
+        era_id: EraId::from(3),
+        payloads: vec![vec![1, 2, 3], vec![], vec![4]],
+    };
+    let bytes = bincode::serialize(&message).expect("should serialize");
+    let decoded: ConsensusMessage = bincode::deserialize(&bytes).expect("should deserialize");
+    assert_eq!(decoded, message);
+}
+
+#[test]
+fn should_handle_each_message_in_batch() {
+    let mut test = TestSupervisor::new(
+        vec![
+            (ALICE_PUBLIC_KEY.clone(), 100),
+            (BOB_PUBLIC_KEY.clone(), 100),
+        ],
+        Config::default(),
+    );
+    let era_id = EraId::from(1);
+    let sender = NodeId::random(&mut test.harness.rng);
+    let ping = bob_ping(&test, era_id);
+    let batch = ConsensusMessage::ProtocolBatch {
+        era_id,
+        payloads: vec![ping.clone(), ping, vec![0xff]],
+    };
+    let effects = test.supervisor.handle_message(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        sender,
+        batch,
+    );
+    let captured: CapturedEffects = test.run(effects).into_iter().collect();
+
+    // The invalid message gets the sender disconnected, but the others are handled as usual: The
+    // new unit is useful, and its copy is a duplicate.
+    assert!(matches!(
+        &*captured.blocklist,
+        [BlocklistAnnouncement::OffenseCommitted(peer)] if **peer == sender
+    ));
+    let score = test
+        .supervisor
+        .peer_reputation
+        .score(&sender, Timestamp::now());
+    assert!((score - (10.0 - 1.0 - 100.0)).abs() < 0.1);
+}
+
+#[test]
+fn should_drop_batch_with_too_many_messages() {
+    let config = Config {
+        max_message_batch_size: 2,
+        ..Config::default()
+    };
+    let mut test = TestSupervisor::new(
+        vec![
+            (ALICE_PUBLIC_KEY.clone(), 100),
+            (BOB_PUBLIC_KEY.clone(), 100),
+        ],
+        config,
+    );
+    let era_id = EraId::from(1);
+    let sender = NodeId::random(&mut test.harness.rng);
+    let handle_batch = |test: &mut TestSupervisor, payloads| {
+        let batch = ConsensusMessage::ProtocolBatch { era_id, payloads };
+        let effects = test.supervisor.handle_message(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            sender,
+            batch,
+        );
+        let captured: CapturedEffects = test.run(effects).into_iter().collect();
+        captured.blocklist.len()
+    };
+
+    // The invalid messages in a batch that is too large are not even looked at.
+    assert_eq!(handle_batch(&mut test, vec![vec![0xff]; 3]), 0);
+    assert_eq!(test.supervisor.metrics.oversized_messages.get(), 1);
+
+    // In a batch of the maximum size, each of them gets the sender disconnected.
+    assert_eq!(handle_batch(&mut test, vec![vec![0xff]; 2]), 2);
+    assert_eq!(test.supervisor.metrics.oversized_messages.get(), 1);
+}
+
+#[test]
+fn should_send_batched_messages_together() {
+    let config = Config {
+        message_batch_window: Some(TimeDiff::from_seconds(1)),
+        max_message_batch_size: 3,
+        ..Config::default()
+    };
+    let mut test = TestSupervisor::new(vec![(ALICE_PUBLIC_KEY.clone(), 100)], config);
+    let era_id = EraId::from(1);
+    // Initializing the era already batched Alice's first ping. Send it, to start with an empty
+    // batch.
+    let effects = test
+        .supervisor
+        .flush_message_batch(test.harness.effect_builder, era_id);
+    test.run(effects);
+    let mut gossip = |payload| {
+        let effects = test.supervisor.handle_consensus_outcome(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            era_id,
+            ProtocolOutcome::CreatedGossipMessage(payload),
+        );
+        let captured: CapturedEffects = test.run(effects).into_iter().collect();
+        captured.network
+    };
+
+    // Nothing is sent until the batch is full.
+    assert!(gossip(vec![1]).is_empty());
+    assert!(gossip(vec![2]).is_empty());
+    match &*gossip(vec![3]) {
+        [NetworkRequest::Broadcast { payload, .. }] => match &**payload {
+            Message::Consensus(message) => assert_eq!(
+                *message,
+                ConsensusMessage::ProtocolBatch {
+                    era_id,
+                    payloads: vec![vec![1], vec![2], vec![3]],
+                }
+            ),
+            message => panic!("unexpected message: {:?}", message),
+        },
+        requests => panic!("unexpected requests: {:?}", requests),
+    }
+
+    // Once the window has passed, an incomplete batch is sent, too. A single message is sent on
+    // its own.
+    assert!(gossip(vec![4]).is_empty());
+    let effects = test
+        .supervisor
+        .flush_message_batch(test.harness.effect_builder, era_id);
+    let captured: CapturedEffects = test.run(effects).into_iter().collect();
+    match &*captured.network {
+        [NetworkRequest::Broadcast { payload, .. }] => match &**payload {
+            Message::Consensus(message) => assert_eq!(
+                *message,
+                ConsensusMessage::Protocol {
+                    era_id,
+                    payload: vec![4],
+                }
+            ),
+            message => panic!("unexpected message: {:?}", message),
+        },
+        requests => panic!("unexpected requests: {:?}", requests),
+    }
+}
+
+#[test]
+fn should_coalesce_timers_in_the_same_window() {
+    let config = Config {
+        timer_coalescing_window: Some(TimeDiff::from_seconds(1)),
+        ..Config::default()
+    };
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let mut test = TestSupervisor::new(validators.clone(), config);
+    test.advance_era(&validators);
+    test.supervisor
+        .set_clock(Box::new(MockClock::new(Timestamp::from(10_000))));
+    let mut schedule = |era_id: u64, millis: u64| {
+        test.supervisor
+            .handle_consensus_outcome(
+                test.harness.effect_builder,
+                &mut test.harness.rng,
+                EraId::from(era_id),
+                ProtocolOutcome::ScheduleTimer(Timestamp::from(millis), TIMER_ID_REQUEST_STATE),
+            )
+            .len()
+    };
+
+    // Only the first timer in each window schedules a timeout, regardless of the era.
+    assert_eq!(schedule(1, 10_100), 1);
+    assert_eq!(schedule(2, 10_500), 0);
+    assert_eq!(schedule(1, 11_000), 0);
+    assert_eq!(schedule(2, 11_001), 1);
+
+    // All three timers in the first window are handled in a single pass: Each one requests the
+    // latest state from a random peer.
+    let effects = test.supervisor.handle_coalesced_timers(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        Timestamp::from(11_000),
+    );
+    let captured: CapturedEffects = test.run(effects).into_iter().collect();
+    assert_eq!(captured.network_info.len(), 3);
+    assert!(captured
+        .network_info
+        .iter()
+        .all(|request| matches!(request, NetworkInfoRequest::FullyConnectedPeers { .. })));
+    assert!(!test
+        .supervisor
+        .coalesced_timers
+        .contains_key(&Timestamp::from(11_000)));
+    assert_eq!(
+        test.supervisor.coalesced_timers[&Timestamp::from(12_000)].len(),
+        1
+    );
+}
+
+#[test]
+fn should_answer_ping_and_record_latency() {
+    let mut test = TestSupervisor::new(
+        vec![
+            (ALICE_PUBLIC_KEY.clone(), 100),
+            (BOB_PUBLIC_KEY.clone(), 100),
+        ],
+        Config::default(),
+    );
+    let era_id = EraId::from(1);
+    let sender = NodeId::random(&mut test.harness.rng);
+    let ping = ConsensusMessage::Ping { era_id, nonce: 42 };
+    let effects = test.supervisor.handle_message(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        sender,
+        ping,
+    );
+    match &*test.run(effects) {
+        [TestEvent::Network(NetworkRequest::SendMessage { dest, payload, .. })] => {
+            assert_eq!(**dest, sender);
+            match &**payload {
+                Message::Consensus(message) => {
+                    assert_eq!(*message, ConsensusMessage::Pong { era_id, nonce: 42 })
+                }
+                message => panic!("unexpected message: {:?}", message),
+            }
+        }
+        events => panic!("unexpected events: {:?}", events),
+    }
+
+    // A matching pong records the round-trip time; others are ignored.
+    let sent = Timestamp::now().saturating_sub(TimeDiff::from(50));
+    test.supervisor.latency_probe = Some((7, sent));
+    let mut handle_pong = |nonce| {
+        let pong = ConsensusMessage::Pong { era_id, nonce };
+        let effects = test.supervisor.handle_message(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            sender,
+            pong,
+        );
+        assert!(test.run(effects).is_empty());
+        test.supervisor
+            .metrics
+            .peer_latency
+            .with_label_values(&[&sender.to_string()])
+            .get()
+    };
+    assert_eq!(handle_pong(6), 0);
+    assert!(handle_pong(7) >= 50);
+}
+
+#[test]
+fn should_capture_effects_of_timer() {
+    let mut test = TestSupervisor::new(
+        vec![
+            (ALICE_PUBLIC_KEY.clone(), 100),
+            (BOB_PUBLIC_KEY.clone(), 100),
+        ],
+        Config::default(),
+    );
+    let effects = test.supervisor.handle_timer(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        EraId::from(1),
+        Timestamp::now(),
+        TIMER_ID_REQUEST_STATE,
+    );
+    let captured: CapturedEffects = test.run(effects).into_iter().collect();
+
+    // The era asks for the connected peers, to request the latest state from one of them. The
+    // next request is scheduled for later.
+    assert!(matches!(
+        &*captured.network_info,
+        [NetworkInfoRequest::FullyConnectedPeers { .. }]
+    ));
+    assert!(captured.network.is_empty());
+    assert!(captured.consensus.is_empty());
+    assert!(captured.announcements.is_empty());
+    assert!(captured.blocklist.is_empty());
+    assert!(captured.control.is_empty());
+}
+
+#[test]
+fn should_probe_latency_when_mock_clock_reaches_interval() {
+    let config = Config {
+        latency_probe_interval: Some(TimeDiff::from_seconds(10)),
+        ..Config::default()
+    };
+    let mut test = TestSupervisor::new(vec![(ALICE_PUBLIC_KEY.clone(), 100)], config);
+    let clock = MockClock::new(Timestamp::from(1_000_000));
+    test.supervisor.set_clock(Box::new(clock.clone()));
+    let mut sends_probe = |now| {
+        let effects = test.supervisor.handle_timer(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            EraId::from(1),
+            now,
+            TIMER_ID_REQUEST_STATE,
+        );
+        let captured: CapturedEffects = test.run(effects).into_iter().collect();
+        captured.network.iter().any(|request| {
+            matches!(
+                request,
+                NetworkRequest::Broadcast { payload, .. }
+                    if matches!(**payload, Message::Consensus(ConsensusMessage::Ping { .. }))
+            )
+        })
+    };
+
+    // The first timer sends a probe. The next one is due exactly one interval later.
+    assert!(sends_probe(clock.now()));
+    clock.advance(TimeDiff::from_seconds(10) - TimeDiff::from(1));
+    assert!(!sends_probe(clock.now()));
+    clock.advance(TimeDiff::from(1));
+    assert!(sends_probe(clock.now()));
+    assert!(!sends_probe(clock.now()));
+}
+
+#[test]
+fn should_behave_identically_after_restoring_snapshot() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let mut original = TestSupervisor::new(validators, Config::default());
+
+    // An invalid message for era 2 is buffered until that era is initialized.
+    let sender = NodeId::random(&mut original.harness.rng);
+    let message = ConsensusMessage::Protocol {
+        era_id: EraId::from(2),
+        payload: vec![1, 2, 3],
+    };
+    let effects = original.supervisor.handle_message(
+        original.harness.effect_builder,
+        &mut original.harness.rng,
+        sender,
+        message,
+    );
+    assert!(effects.is_empty());
+
+    let snapshot = original.snapshot();
+    assert_eq!(snapshot.current_era, EraId::from(1));
+    assert_eq!(snapshot.future_messages.len(), 1);
+    let bytes = bincode::serialize(&snapshot).expect("failed to serialize snapshot");
+    let deserialized: Snapshot =
+        bincode::deserialize(&bytes).expect("failed to deserialize snapshot");
+    assert_eq!(deserialized, snapshot);
+
+    // Era 2 is initialized and the buffered message is replayed, disconnecting from its sender.
+    let switch_block = switch_block_header(
+        &original.supervisor.chainspec,
+        EraId::from(1),
+        weights(&[(BOB_PUBLIC_KEY.clone(), 100)]),
+    );
+    let effects = original.add_switch_block(switch_block.clone());
+    let original_events = original.run(effects);
+    assert!(original_events.iter().any(|event| matches!(
+        event,
+        TestEvent::Blocklist(BlocklistAnnouncement::OffenseCommitted(peer)) if **peer == sender
+    )));
+    let original_snapshot = original.snapshot();
+
+    // A supervisor restored from the snapshot does exactly the same.
+    let mut restored = original;
+    let chainspec = Arc::clone(&restored.supervisor.chainspec);
+    let config = restored.supervisor.config.clone();
+    restored.restore(&deserialized, chainspec, config);
+    assert_eq!(restored.snapshot(), snapshot);
+    let effects = restored.add_switch_block(switch_block);
+    let restored_events = restored.run(effects);
+
+    // The persisted gossip indices contain our own pings, which depend on the wall clock.
+    let without_state_store = |events: Vec<TestEvent>| {
+        let events: Vec<_> = events
+            .into_iter()
+            .filter(|event| !matches!(event, TestEvent::StateStore(_)))
+            .collect();
+        format!("{:?}", events)
+    };
+    assert_eq!(
+        without_state_store(original_events),
+        without_state_store(restored_events)
+    );
+    assert_eq!(restored.snapshot(), original_snapshot);
+}
+
+/// Passes a proposed block from `sender` in era 1 to the supervisor for validation, and returns
+/// the resulting events.
+fn validate_proposed_block(
+    test: &mut TestSupervisor,
+    sender: NodeId,
+    block_payload: BlockPayload,
+) -> Vec<TestEvent> {
+    let block_context = BlockContext::new(Timestamp::now(), vec![]);
+    let outcome = ProtocolOutcome::ValidateConsensusValue {
+        sender,
+        proposed_block: ProposedBlock::new(Arc::new(block_payload), block_context),
+    };
+    let effects = test.supervisor.handle_consensus_outcome(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        EraId::from(1),
+        outcome,
+    );
+    test.run(effects)
+}
+
+#[test]
+fn should_reject_oversized_block_payload_before_validation() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let block_payload = BlockPayload::new(vec![], vec![], vec![], false);
+    let size = bincode::serialized_size(&block_payload).unwrap();
+
+    // A block payload of the maximum size is sent for validation.
+    let config = Config {
+        max_block_payload_size: size,
+        ..Default::default()
+    };
+    let mut test = TestSupervisor::new(validators.clone(), config);
+    let sender = NodeId::random(&mut test.harness.rng);
+    let events = validate_proposed_block(&mut test, sender, block_payload.clone());
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, TestEvent::BlockValidation(_))));
+    assert_eq!(test.supervisor.metrics.oversized_block_payloads.get(), 0);
+
+    // A larger one is rejected as invalid without being validated.
+    let config = Config {
+        max_block_payload_size: size.saturating_sub(1),
+        ..Default::default()
+    };
+    test.restart(validators, config);
+    let events = validate_proposed_block(&mut test, sender, block_payload);
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, TestEvent::BlockValidation(_))));
+    assert!(events.iter().any(|event| matches!(
+        event,
+        TestEvent::Blocklist(BlocklistAnnouncement::OffenseCommitted(peer)) if **peer == sender
+    )));
+    assert_eq!(test.supervisor.metrics.oversized_block_payloads.get(), 1);
+}
+
+#[test]
+fn should_not_create_era_after_maximum_era() {
+    let validators = vec![(ALICE_PUBLIC_KEY.clone(), 100)];
+    let mut test = TestSupervisor::new(validators.clone(), Config::default());
+    let switch_block =
+        switch_block_header(&test.supervisor.chainspec, EraId::MAX, weights(&validators));
+    let result = test.supervisor.create_new_era(&[switch_block]);
+    assert!(matches!(result, Err(CreateNewEraError::MaxEraReached)));
+}
+
+#[test]
+fn should_report_missing_switch_block_as_error() {
+    let validators = vec![(ALICE_PUBLIC_KEY.clone(), 100)];
+    let harness = ComponentHarness::<TestEvent>::default();
+    let chainspec = Arc::new(new_test_chainspec(validators));
+    let era_id = EraId::from(5);
+    let missing_era_id = chainspec.earliest_switch_block_needed(era_id);
+
+    // Storage doesn't have the first switch block that is needed to initialize the era.
+    let get_switch_blocks = get_switch_blocks(chainspec, harness.effect_builder, era_id);
+    let respond_from_storage = async {
+        let ((_ancestor, event), _) = harness.scheduler.pop().await;
+        match event {
+            TestEvent::Storage(StorageRequest::GetSwitchBlockHeaderAtEraId {
+                era_id,
+                responder,
+            }) => {
+                assert_eq!(era_id, missing_era_id);
+                responder.respond(None).await
+            }
+            event => panic!("unexpected event: {:?}", event),
+        }
+    };
+    let (result, ()) = harness
+        .runtime
+        .block_on(future::join(get_switch_blocks, respond_from_storage));
+
+    let expected_error = ConsensusError::ValidatorsUnavailable {
+        era_id,
+        switch_block_era_id: missing_era_id,
+    };
+    assert_eq!(result, Err(expected_error));
+    assert!(matches!(
+        new_era_event(result),
+        Event::SwitchBlocksUnavailable {
+            era_id: unavailable_era_id,
+            switch_block_era_id,
+        } if unavailable_era_id == era_id && switch_block_era_id == missing_era_id
+    ));
+}
+
+#[test]
+fn should_record_finalization_times() {
+    let mut test = TestSupervisor::new(vec![(ALICE_PUBLIC_KEY.clone(), 100)], Config::default());
+    let era_id = EraId::from(1);
+    let start_height = test.supervisor.era(era_id).start_height;
+    let before = Timestamp::now();
+    for relative_height in 0..2 {
+        let finalized_block = CpFinalizedBlock {
+            value: Arc::new(BlockPayload::new(vec![], vec![], vec![], false)),
+            timestamp: Timestamp::now(),
+            relative_height,
+            equivocators: vec![],
+            terminal_block_data: None,
+            proposer: ALICE_PUBLIC_KEY.clone(),
+        };
+        test.supervisor.handle_consensus_outcome(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            era_id,
+            ProtocolOutcome::FinalizedBlock(finalized_block),
+        );
+    }
+    let after = Timestamp::now();
+
+    match test.supervisor.finalization_times(era_id) {
+        [(height0, time0), (height1, time1)] => {
+            assert_eq!(*height0, start_height);
+            assert_eq!(*height1, start_height + 1);
+            assert!(before <= *time0 && time0 <= time1 && *time1 <= after);
+        }
+        times => panic!("unexpected finalization times: {:?}", times),
+    }
+    assert!(test
+        .supervisor
+        .finalization_times(EraId::from(2))
+        .is_empty());
+}
+
+#[test]
+fn should_drop_oversized_message() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let config = Config {
+        max_message_size: 3,
+        ..Default::default()
+    };
+    let mut test = TestSupervisor::new(validators, config);
+    let sender = NodeId::random(&mut test.harness.rng);
+    let send = |test: &mut TestSupervisor, payload: Vec<u8>| {
+        let message = ConsensusMessage::Protocol {
+            era_id: EraId::from(1),
+            payload,
+        };
+        let effects = test.supervisor.handle_message(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            sender,
+            message,
+        );
+        test.run(effects)
+    };
+
+    // An invalid message within the limit reaches the Highway instance, which rejects it.
+    let events = send(&mut test, vec![1, 2, 3]);
+    assert!(events.iter().any(|event| matches!(
+        event,
+        TestEvent::Blocklist(BlocklistAnnouncement::OffenseCommitted(peer)) if **peer == sender
+    )));
+    assert_eq!(test.supervisor.metrics.oversized_messages.get(), 0);
+
+    // A larger one is dropped before it is deserialized.
+    let events = send(&mut test, vec![1, 2, 3, 4]);
+    assert!(events.is_empty());
+    assert_eq!(test.supervisor.metrics.oversized_messages.get(), 1);
+}
+
+#[test]
+fn should_return_validator_weights_of_open_eras() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let mut test = TestSupervisor::new(validators.clone(), Config::default());
+    let era_2_validators = vec![(BOB_PUBLIC_KEY.clone(), 200)];
+    let switch_block = switch_block_header(
+        &test.supervisor.chainspec,
+        EraId::from(1),
+        weights(&era_2_validators),
+    );
+    let effects = test.add_switch_block(switch_block);
+    test.run(effects);
+
+    assert_eq!(
+        test.supervisor.validator_weights(EraId::from(1)),
+        Some(&weights(&validators))
+    );
+    assert_eq!(
+        test.supervisor.validator_weights(EraId::from(2)),
+        Some(&weights(&era_2_validators))
+    );
+    // The activation era is never initialized, and era 3 doesn't exist yet.
+    assert_eq!(test.supervisor.validator_weights(EraId::from(0)), None);
+    assert_eq!(test.supervisor.validator_weights(EraId::from(3)), None);
+}
+
+#[test]
+fn should_list_active_eras_with_validator_counts() {
+    let mut test = TestSupervisor::new(
+        vec![
+            (ALICE_PUBLIC_KEY.clone(), 100),
+            (BOB_PUBLIC_KEY.clone(), 100),
+        ],
+        Config::default(),
+    );
+    assert_eq!(test.supervisor.active_eras(), vec![(EraId::from(1), 2)]);
+
+    test.advance_era(&[(BOB_PUBLIC_KEY.clone(), 200)]);
+    assert_eq!(
+        test.supervisor.active_eras(),
+        vec![(EraId::from(1), 2), (EraId::from(2), 1)]
+    );
+}
+
+/// Restarts the supervisor with the given validators, and returns whether era 1 can finalize with
+/// the ones marked as faulty.
+fn can_finalize_with_faults(
+    test: &mut TestSupervisor,
+    validators: Vec<(PublicKey, u64)>,
+    faulty: &[PublicKey],
+) -> bool {
+    test.restart(validators, Config::default());
+    let era = test
+        .supervisor
+        .open_eras
+        .get_mut(&EraId::from(1))
+        .expect("era 1 should be open");
+    era.accusations.extend(faulty.iter().cloned());
+    test.supervisor.can_finalize(EraId::from(1))
+}
+
+#[test]
+fn should_detect_whether_era_can_finalize() {
+    // Eras that are not in memory can't finalize anything.
+    let mut test = TestSupervisor::new(vec![(ALICE_PUBLIC_KEY.clone(), 100)], Config::default());
+    assert!(!test.supervisor.can_finalize(EraId::from(2)));
+
+    // The local chainspec has a fault tolerance threshold of 1/3.
+    let healthy = vec![
+        (ALICE_PUBLIC_KEY.clone(), 200),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    assert!(can_finalize_with_faults(&mut test, healthy.clone(), &[]));
+
+    // Bob's 100 is just below the threshold of 303 / 3 = 101.
+    let below_threshold = vec![
+        (ALICE_PUBLIC_KEY.clone(), 203),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    assert!(can_finalize_with_faults(
+        &mut test,
+        below_threshold,
+        &[BOB_PUBLIC_KEY.clone()]
+    ));
+
+    // Bob's 100 is exactly at the threshold of 300 / 3 = 100.
+    assert!(!can_finalize_with_faults(
+        &mut test,
+        healthy.clone(),
+        &[BOB_PUBLIC_KEY.clone()]
+    ));
+
+    // Alice's 200 is past the threshold.
+    assert!(!can_finalize_with_faults(
+        &mut test,
+        healthy,
+        &[ALICE_PUBLIC_KEY.clone()]
+    ));
+}
+
+/// Sends an invalid message in the given era, and returns whether it reached the era's Highway
+/// instance, i.e. whether the sender was disconnected.
+fn reaches_era(test: &mut TestSupervisor, era_id: EraId) -> bool {
+    let payload = vec![era_id.value() as u8];
+    is_rejected(test, era_id, payload)
+}
+
+/// Sends the payload as a message in the given era, and returns whether it was rejected as invalid,
+/// i.e. whether the sender was disconnected. The actions it schedules are handled afterwards.
+fn is_rejected(test: &mut TestSupervisor, era_id: EraId, payload: Vec<u8>) -> bool {
+    let sender = NodeId::random(&mut test.harness.rng);
+    let message = ConsensusMessage::Protocol { era_id, payload };
+    let effects = test.supervisor.handle_message(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        sender,
+        message,
+    );
+    let events = test.run(effects);
+    let rejected = events.iter().any(|event| {
+        matches!(
+            event,
+            TestEvent::Blocklist(BlocklistAnnouncement::OffenseCommitted(_))
+        )
+    });
+    test.run_actions(events);
+    rejected
+}
+
+/// Returns the Highway instance of the given era.
+fn highway(test: &TestSupervisor, era_id: EraId) -> &HighwayProtocol<ClContext> {
+    test.supervisor.open_eras[&era_id]
+        .consensus
+        .as_any()
+        .downcast_ref::<HighwayProtocol<ClContext>>()
+        .expect("era should use Highway")
+}
+
+/// Returns a serialized ping by Bob, signed for the consensus instance of the given era.
+fn bob_ping(test: &TestSupervisor, era_id: EraId) -> Vec<u8> {
+    bob_ping_at(test, era_id, Timestamp::now())
+}
+
+/// Returns a serialized ping by Bob with the given timestamp, signed for the consensus instance of
+/// the given era.
+fn bob_ping_at(test: &TestSupervisor, era_id: EraId, timestamp: Timestamp) -> Vec<u8> {
+    let highway = highway(test, era_id);
+    let creator = highway
+        .highway()
+        .validators()
+        .get_index(&*BOB_PUBLIC_KEY)
+        .expect("Bob should be a validator");
+    let secret = Keypair::from(Arc::new(
+        SecretKey::ed25519_from_bytes([1; SecretKey::ED25519_LENGTH]).unwrap(),
+    ));
+    let instance_id = *highway.instance_id();
+    let ping = Ping::<ClContext>::new(creator, timestamp, instance_id, &secret);
+    HighwayMessage::NewVertex(Vertex::Ping(ping)).serialize()
+}
+
+/// Returns whether the given era's Highway instance has added Bob's ping with the timestamp.
+fn has_bob_ping(test: &TestSupervisor, era_id: EraId, timestamp: Timestamp) -> bool {
+    let highway = highway(test, era_id).highway();
+    let creator = highway
+        .validators()
+        .get_index(&*BOB_PUBLIC_KEY)
+        .expect("Bob should be a validator");
+    highway.state().has_ping(creator, timestamp)
+}
+
+#[test]
+fn should_route_messages_to_their_own_era() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let mut test = TestSupervisor::new(validators.clone(), Config::default());
+    test.advance_era(&validators);
+    let (era_1, era_2) = (EraId::from(1), EraId::from(2));
+    assert_eq!(test.supervisor.current_era, era_2);
+    assert!(test.supervisor.open_eras.contains_key(&era_1));
+
+    // Both eras are active, and each one adds the pings signed for it. The ping times are
+    // initialized with the eras' start, so the pings need to be later than that.
+    let clock = MockClock::new(Timestamp::now() + TimeDiff::from_seconds(1));
+    test.supervisor.set_clock(Box::new(clock.clone()));
+    let timestamp = clock.now();
+    let ping_1 = bob_ping_at(&test, era_1, timestamp);
+    assert!(!is_rejected(&mut test, era_1, ping_1));
+    assert!(has_bob_ping(&test, era_1, timestamp));
+    assert!(!has_bob_ping(&test, era_2, timestamp));
+
+    clock.advance(TimeDiff::from(1));
+    let timestamp = clock.now();
+    let ping_2 = bob_ping_at(&test, era_2, timestamp);
+    assert!(!is_rejected(&mut test, era_2, ping_2));
+    assert!(has_bob_ping(&test, era_2, timestamp));
+    assert!(!has_bob_ping(&test, era_1, timestamp));
+
+    // Messages are not passed on to the other era: A ping sent in the wrong era is rejected and
+    // isn't added to either instance.
+    clock.advance(TimeDiff::from(1));
+    let timestamp = clock.now();
+    let ping_1 = bob_ping_at(&test, era_1, timestamp);
+    let ping_2 = bob_ping_at(&test, era_2, timestamp);
+    assert!(is_rejected(&mut test, era_1, ping_2));
+    assert!(is_rejected(&mut test, era_2, ping_1));
+    assert!(!has_bob_ping(&test, era_1, timestamp));
+    assert!(!has_bob_ping(&test, era_2, timestamp));
+}
+
+#[test]
+fn should_rate_peers_by_the_messages_they_send() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let mut test = TestSupervisor::new(validators, Config::default());
+    let era_id = EraId::from(1);
+    let honest = NodeId::random(&mut test.harness.rng);
+    let malicious = NodeId::random(&mut test.harness.rng);
+    let ping = bob_ping(&test, era_id);
+    let mut send = |sender, payload| {
+        let message = ConsensusMessage::Protocol { era_id, payload };
+        let effects = test.supervisor.handle_message(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            sender,
+            message,
+        );
+        test.run(effects);
+    };
+
+    // A new unit followed by a redundant copy of it is still a net benefit.
+    send(honest, ping.clone());
+    send(honest, ping);
+    send(malicious, vec![0xff]);
+
+    let now = Timestamp::now();
+    let reputation = &test.supervisor.peer_reputation;
+    assert!(reputation.score(&honest, now) > 0.0);
+    assert!(!reputation.is_low(&honest, now));
+    assert!(reputation.is_low(&malicious, now));
+    assert_eq!(
+        test.supervisor.peers_by_reputation(),
+        vec![honest, malicious]
+    );
+}
+
+#[test]
+fn should_reject_corrupt_payload() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let mut test = TestSupervisor::new(validators, Config::default());
+    let era_id = EraId::from(1);
+    let sender = NodeId::random(&mut test.harness.rng);
+
+    // A truncated unit fails to deserialize.
+    let mut payload = bob_ping(&test, era_id);
+    payload.truncate(payload.len() / 2);
+    let message = ConsensusMessage::Protocol { era_id, payload };
+    let effects = test.supervisor.handle_message(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        sender,
+        message,
+    );
+    let captured: CapturedEffects = test.run(effects).into_iter().collect();
+
+    assert!(matches!(
+        &*captured.blocklist,
+        [BlocklistAnnouncement::OffenseCommitted(peer)] if **peer == sender
+    ));
+    assert!(captured.control.is_empty());
+    assert_eq!(test.supervisor.metrics.invalid_messages.get(), 1);
+    assert!(test
+        .supervisor
+        .peer_reputation
+        .is_low(&sender, Timestamp::now()));
+}
+
+#[test]
+fn should_retire_eras_outside_retention_window() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let mut test = TestSupervisor::new(validators.clone(), Config::default());
+    test.advance_era(&validators);
+    test.advance_era(&validators);
+    assert_eq!(test.supervisor.current_era, EraId::from(3));
+    assert!(test.supervisor.open_eras.contains_key(&EraId::from(1)));
+    assert!(reaches_era(&mut test, EraId::from(1)));
+
+    // Era 1 is more than two eras in the past now, and messages for it are ignored. The evidence
+    // persisted for it is deleted.
+    let captured: CapturedEffects = test.advance_era(&validators).into_iter().collect();
+    assert_eq!(test.supervisor.current_era, EraId::from(4));
+    for (pub_key, _) in &validators {
+        let deleted_key = evidence_key(EraId::from(1), pub_key);
+        assert!(captured.state_store.iter().any(|request| matches!(
+            request,
+            StateStoreRequest::Delete { key, .. } if *key == deleted_key
+        )));
+    }
+    assert!(captured.state_store.iter().any(|request| matches!(
+        request,
+        StateStoreRequest::Delete { key, .. } if *key == gossip_index_key(EraId::from(1))
+    )));
+    assert!(!test.supervisor.open_eras.contains_key(&EraId::from(1)));
+    assert!(!reaches_era(&mut test, EraId::from(1)));
+    assert!(reaches_era(&mut test, EraId::from(2)));
+
+    // With a longer retention window, era 1 is kept.
+    let config = Config {
+        retained_past_eras: 3,
+        ..Default::default()
+    };
+    test.restart(validators.clone(), config);
+    for _ in 0..3 {
+        test.advance_era(&validators);
+    }
+    assert_eq!(test.supervisor.current_era, EraId::from(4));
+    assert!(reaches_era(&mut test, EraId::from(1)));
+    test.advance_era(&validators);
+    assert!(!test.supervisor.open_eras.contains_key(&EraId::from(1)));
+    assert!(!reaches_era(&mut test, EraId::from(1)));
+}
+
+#[test]
+fn should_retire_oldest_eras_beyond_max_tracked_eras() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    assert!(Config::default().has_valid_max_tracked_eras());
+    let too_small = Config {
+        max_tracked_eras: Some(2),
+        ..Default::default()
+    };
+    assert!(!too_small.has_valid_max_tracked_eras());
+
+    let config = Config {
+        retained_past_eras: 5,
+        max_tracked_eras: Some(4),
+        ..Default::default()
+    };
+    assert!(config.has_valid_max_tracked_eras());
+    let mut test = TestSupervisor::new(validators.clone(), config);
+    for _ in 0..3 {
+        test.advance_era(&validators);
+    }
+    assert_eq!(test.supervisor.current_era, EraId::from(4));
+    assert_eq!(test.supervisor.open_eras.len(), 4);
+
+    // Era 1 is within the retention window, but it is the oldest one beyond the eras needed to
+    // validate evidence, so it is retired once the cap is exceeded.
+    test.advance_era(&validators);
+    assert_eq!(test.supervisor.current_era, EraId::from(5));
+    let mut open_era_ids: Vec<u64> = test
+        .supervisor
+        .open_eras
+        .keys()
+        .map(|era_id| era_id.value())
+        .collect();
+    open_era_ids.sort_unstable();
+    assert_eq!(open_era_ids, vec![2, 3, 4, 5]);
+
+    let sender = NodeId::random(&mut test.harness.rng);
+    let message = ConsensusMessage::Protocol {
+        era_id: EraId::from(1),
+        payload: vec![1],
+    };
+    let effects = test.supervisor.handle_message(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        sender,
+        message,
+    );
+    assert!(test.run(effects).is_empty());
+    let ancient_dropped = test
+        .supervisor
+        .metrics
+        .dropped_messages
+        .with_label_values(&[MessageDropReason::AncientEra.label()])
+        .get();
+    assert_eq!(ancient_dropped, 1);
+}
+
+#[test]
+fn should_wait_for_validators_in_strict_mode() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let config = Config {
+        strict_era_transition: true,
+        ..Default::default()
+    };
+    let mut test = TestSupervisor::new(validators.clone(), config);
+
+    // A switch block without validators doesn't start the next era.
+    let events = test.advance_era(&[]);
+    assert!(events.is_empty());
+    assert_eq!(test.supervisor.current_era, EraId::from(1));
+    assert!(!test.supervisor.open_eras.contains_key(&EraId::from(2)));
+
+    // Meanwhile, messages for the next era are buffered.
+    let sender = NodeId::random(&mut test.harness.rng);
+    let message = ConsensusMessage::Protocol {
+        era_id: EraId::from(2),
+        payload: vec![1, 2, 3],
+    };
+    let effects = test.supervisor.handle_message(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        sender,
+        message,
+    );
+    assert!(test.run(effects).is_empty());
+    assert_eq!(test.supervisor.future_messages.len(), 1);
+
+    // Once the validators are known, the era starts and the buffered message is handled.
+    test.switch_blocks.pop();
+    let events = test.advance_era(&validators);
+    assert_eq!(test.supervisor.current_era, EraId::from(2));
+    assert_eq!(test.supervisor.future_messages.len(), 0);
+    assert!(events.iter().any(|event| matches!(
+        event,
+        TestEvent::Blocklist(BlocklistAnnouncement::OffenseCommitted(peer)) if **peer == sender
+    )));
+}
+
+#[test]
+fn should_request_switch_blocks_again_if_key_block_is_stale() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let mut test = TestSupervisor::new(validators.clone(), Config::default());
+    let booking_block = test.switch_blocks[0].clone();
+    let key_block = switch_block_header(
+        &test.supervisor.chainspec,
+        EraId::from(1),
+        weights(&validators),
+    );
+    let effects = test
+        .supervisor
+        .handle_block_added(test.harness.effect_builder, key_block.clone());
+    test.run(effects);
+
+    // Storage returns an outdated switch block for era 1, with a different validator set.
+    let stale_key_block = switch_block_header(
+        &test.supervisor.chainspec,
+        EraId::from(1),
+        weights(&[(ALICE_PUBLIC_KEY.clone(), 100)]),
+    );
+    let effects = test.supervisor.create_new_era_effects(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        &[booking_block.clone(), stale_key_block.clone()],
+    );
+    assert!(!test.supervisor.open_eras.contains_key(&EraId::from(2)));
+
+    // The switch blocks are requested again, and the one we executed is used as the key block.
+    let scheduler = test.harness.scheduler;
+    let stored = vec![booking_block.clone(), stale_key_block];
+    let respond = async {
+        for stored_block in stored {
+            let ((_ancestor, event), _) = scheduler.pop().await;
+            match event {
+                TestEvent::Storage(StorageRequest::GetSwitchBlockHeaderAtEraId {
+                    era_id,
+                    responder,
+                }) => {
+                    assert_eq!(era_id, stored_block.era_id());
+                    responder.respond(Some(stored_block)).await
+                }
+                event => panic!("unexpected event: {:?}", event),
+            }
+        }
+    };
+    let (events, ()) = test
+        .harness
+        .runtime
+        .block_on(future::join(future::join_all(effects), respond));
+    let switch_blocks = match events.into_iter().flatten().collect::<Vec<_>>().as_slice() {
+        [Event::CreateNewEra { switch_blocks }] => switch_blocks.clone(),
+        events => panic!("unexpected events: {:?}", events),
+    };
+    assert_eq!(switch_blocks, vec![booking_block, key_block]);
+
+    let effects = test.supervisor.create_new_era_effects(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        &switch_blocks,
+    );
+    test.run(effects);
+    assert_eq!(
+        test.supervisor.open_eras[&EraId::from(2)].validators(),
+        &weights(&validators)
+    );
+}
+
+/// Reports to the supervisor that the switch block in era 0, needed to create era 2, is not in
+/// storage, and returns the captured effects.
+fn report_switch_blocks_unavailable(test: &mut TestSupervisor) -> CapturedEffects {
+    let effects = test.supervisor.handle_switch_blocks_unavailable(
+        test.harness.effect_builder,
+        EraId::from(2),
+        EraId::from(0),
+    );
+    test.run(effects).into_iter().collect()
+}
+
+#[test]
+fn should_request_unavailable_switch_blocks_again_after_backoff() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let mut test = TestSupervisor::new(validators.clone(), Config::default());
+    let clock = MockClock::new(Timestamp::from(1_000_000));
+    test.supervisor.set_clock(Box::new(clock.clone()));
+    let stored = vec![
+        test.switch_blocks[0].clone(),
+        switch_block_header(
+            &test.supervisor.chainspec,
+            EraId::from(1),
+            weights(&validators),
+        ),
+    ];
+
+    // Storage doesn't have the switch blocks yet. That is not fatal.
+    let captured = report_switch_blocks_unavailable(&mut test);
+    assert!(captured.control.is_empty());
+
+    // They are not requested again before the backoff has passed.
+    let effects = test
+        .supervisor
+        .handle_retry_switch_blocks(test.harness.effect_builder, EraId::from(2));
+    let captured: CapturedEffects = test.run(effects).into_iter().collect();
+    assert!(captured.storage.is_empty());
+
+    // Afterwards they are, and once storage has them, the era is created.
+    clock.advance(STORAGE_RETRY_BASE_DELAY);
+    let effects = test
+        .supervisor
+        .handle_retry_switch_blocks(test.harness.effect_builder, EraId::from(2));
+    let scheduler = test.harness.scheduler;
+    let respond = async {
+        for stored_block in stored.clone() {
+            let ((_ancestor, event), _) = scheduler.pop().await;
+            match event {
+                TestEvent::Storage(StorageRequest::GetSwitchBlockHeaderAtEraId {
+                    era_id,
+                    responder,
+                }) => {
+                    assert_eq!(era_id, stored_block.era_id());
+                    responder.respond(Some(stored_block)).await
+                }
+                event => panic!("unexpected event: {:?}", event),
+            }
+        }
+    };
+    let (events, ()) = test
+        .harness
+        .runtime
+        .block_on(future::join(future::join_all(effects), respond));
+    let switch_blocks = match events.into_iter().flatten().collect::<Vec<_>>().as_slice() {
+        [Event::CreateNewEra { switch_blocks }] => switch_blocks.clone(),
+        events => panic!("unexpected events: {:?}", events),
+    };
+    assert_eq!(switch_blocks, stored);
+    let effects = test.supervisor.create_new_era_effects(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        &switch_blocks,
+    );
+    test.run(effects);
+    assert!(test.supervisor.open_eras.contains_key(&EraId::from(2)));
+    assert!(test.supervisor.switch_block_retries.is_empty());
+}
+
+#[test]
+fn should_fail_if_switch_blocks_remain_unavailable() {
+    let mut test = TestSupervisor::new(vec![(ALICE_PUBLIC_KEY.clone(), 100)], Config::default());
+    let clock = MockClock::new(Timestamp::from(1_000_000));
+    test.supervisor.set_clock(Box::new(clock.clone()));
+
+    // The backoff doubles with every attempt.
+    for attempt in 0..MAX_STORAGE_RETRIES {
+        let captured = report_switch_blocks_unavailable(&mut test);
+        assert!(captured.control.is_empty());
+        let delay = STORAGE_RETRY_BASE_DELAY.saturating_mul(2u64.pow(attempt));
+        assert_eq!(
+            test.supervisor.switch_block_retries[&EraId::from(2)],
+            (attempt + 1, clock.now() + delay)
+        );
+    }
+
+    let captured = report_switch_blocks_unavailable(&mut test);
+    assert!(matches!(
+        &*captured.control,
+        [ControlAnnouncement::FatalError { .. }]
+    ));
+}
+
+/// Returns whether advancing from era 1 with the given validators fails with a fatal error and an
+/// announcement that era 2 has no validator weight.
+fn fails_without_validator_weight(next_validators: &[(PublicKey, u64)]) -> bool {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let mut test = TestSupervisor::new(validators, Config::default());
+    let captured: CapturedEffects = test.advance_era(next_validators).into_iter().collect();
+    !test.supervisor.open_eras.contains_key(&EraId::from(2))
+        && matches!(
+            &*captured.announcements,
+            [ConsensusAnnouncement::NoValidatorWeight { era_id }] if *era_id == EraId::from(2)
+        )
+        && matches!(&*captured.control, [ControlAnnouncement::FatalError { .. }])
+}
+
+#[test]
+fn should_fail_on_empty_validator_set() {
+    assert!(fails_without_validator_weight(&[]));
+}
+
+#[test]
+fn should_fail_on_validator_set_with_zero_weight() {
+    assert!(fails_without_validator_weight(&[
+        (ALICE_PUBLIC_KEY.clone(), 0),
+        (BOB_PUBLIC_KEY.clone(), 0),
+    ]));
+}
+
+#[test]
+fn should_shed_low_priority_messages_under_load() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let config = Config {
+        max_queued_messages: Some(3),
+        ..Config::default()
+    };
+    let mut test = TestSupervisor::new(validators.clone(), config);
+    test.advance_era(&validators);
+    let (old_era_id, era_id) = (EraId::from(1), EraId::from(2));
+    let spammer = NodeId::random(&mut test.harness.rng);
+    let honest = NodeId::random(&mut test.harness.rng);
+    let ping = bob_ping(&test, era_id);
+    let mut send = |sender, era_id, payload| {
+        let message = ConsensusMessage::Protocol { era_id, payload };
+        let effects = test.supervisor.handle_message(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            sender,
+            message,
+        );
+        let captured: CapturedEffects = test.run(effects).into_iter().collect();
+        captured.consensus
+    };
+
+    // Only the first message schedules handling the queue.
+    let events = send(spammer, old_era_id, vec![1]);
+    assert!(matches!(&*events, [Event::HandleQueuedMessages]));
+    assert!(send(spammer, old_era_id, vec![2]).is_empty());
+    assert!(send(spammer, old_era_id, vec![3]).is_empty());
+
+    // The queue is full of messages for a past era, so a new message for the current era displaces
+    // one of them.
+    assert!(send(honest, era_id, ping).is_empty());
+    assert_eq!(test.supervisor.metrics.load_shed.get(), 1);
+
+    // The current era's unit is still handled.
+    let effects = test
+        .supervisor
+        .handle_queued_messages(test.harness.effect_builder, &mut test.harness.rng);
+    test.run(effects);
+    assert!(test.supervisor.message_queue.as_ref().unwrap().is_empty());
+    let score = test
+        .supervisor
+        .peer_reputation
+        .score(&honest, Timestamp::now());
+    assert!(score > 0.0);
+}
+
+#[test]
+fn should_use_finality_threshold_override() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 200),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let ftt_override = FinalityThresholdOverride {
+        era_id: EraId::from(2),
+        fraction: Ratio::new(2, 5),
+    };
+    assert!(ftt_override.is_valid());
+    assert!(!FinalityThresholdOverride {
+        fraction: Ratio::new(1, 1),
+        ..ftt_override
+    }
+    .is_valid());
+    assert!(!FinalityThresholdOverride {
+        fraction: Ratio::new(0, 1),
+        ..ftt_override
+    }
+    .is_valid());
+
+    let config = Config {
+        finality_threshold_overrides: vec![ftt_override],
+        ..Config::default()
+    };
+    let mut test = TestSupervisor::new(validators.clone(), config);
+    test.advance_era(&validators);
+
+    // Era 1 uses the chainspec's threshold of 1/3, era 2 the override.
+    assert_eq!(
+        test.supervisor.finality_threshold_fraction(EraId::from(1)),
+        Ratio::new(1, 3)
+    );
+    assert_eq!(
+        test.supervisor.finality_threshold_fraction(EraId::from(2)),
+        Ratio::new(2, 5)
+    );
+
+    // Bob's 100 is exactly at the default threshold of 300 / 3 = 100, but below the overridden one
+    // of 300 * 2 / 5 = 120.
+    for era_id in &[EraId::from(1), EraId::from(2)] {
+        let era = test.supervisor.open_eras.get_mut(era_id).unwrap();
+        era.accusations.insert(BOB_PUBLIC_KEY.clone());
+    }
+    assert!(!test.supervisor.can_finalize(EraId::from(1)));
+    assert!(test.supervisor.can_finalize(EraId::from(2)));
+}
+
+/// Returns Alice's finality signatures of the given block.
+fn alice_block_signatures(era_id: EraId, block_hash: BlockHash) -> BlockSignatures {
+    let signature = FinalitySignature::new(
+        block_hash,
+        era_id,
+        &ALICE_SECRET_KEY,
+        ALICE_PUBLIC_KEY.clone(),
+    );
+    let mut signatures = BlockSignatures::new(block_hash, era_id);
+    signatures.insert_proof(signature.public_key, signature.signature);
+    signatures
+}
+
+/// Sends a request for the signatures of the given block to the supervisor, answers its storage
+/// request with `stored`, and returns the response it sends to the requesting peer.
+fn request_block_signatures(
+    test: &mut TestSupervisor,
+    era_id: EraId,
+    block_hash: BlockHash,
+    stored: Option<BlockSignatures>,
+) -> ConsensusMessage {
+    let sender = NodeId::random(&mut test.harness.rng);
+    let request = ConsensusMessage::BlockSignaturesRequest { era_id, block_hash };
+    let effects = test.supervisor.handle_message(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        sender,
+        request,
+    );
+    let scheduler = test.harness.scheduler;
+    let respond = async {
+        let ((_ancestor, event), _) = scheduler.pop().await;
+        match event {
+            TestEvent::Storage(StorageRequest::GetBlockSignatures {
+                block_hash: requested_block_hash,
+                responder,
+            }) => {
+                assert_eq!(requested_block_hash, block_hash);
+                responder.respond(stored).await
+            }
+            event => panic!("unexpected event: {:?}", event),
+        }
+        let ((_ancestor, event), _) = scheduler.pop().await;
+        match event {
+            TestEvent::Network(NetworkRequest::SendMessage {
+                dest,
+                payload,
+                responder,
+                ..
+            }) => {
+                assert_eq!(*dest, sender);
+                responder.respond(()).await;
+                match *payload {
+                    Message::Consensus(message) => message,
+                    message => panic!("unexpected message: {:?}", message),
+                }
+            }
+            event => panic!("unexpected event: {:?}", event),
+        }
+    };
+    let (_, response) = test
+        .harness
+        .runtime
+        .block_on(future::join(future::join_all(effects), respond));
+    response
+}
+
+#[test]
+fn should_roundtrip_block_signatures_messages() {
+    let era_id = EraId::from(3);
+    let block_hash = BlockHash::new(Digest::hash(&[1, 2, 3]));
+    let messages = vec![
+        ConsensusMessage::BlockSignaturesRequest { era_id, block_hash },
+        ConsensusMessage::BlockSignaturesResponse {
+            era_id,
+            block_hash,
+            signatures: Some(alice_block_signatures(era_id, block_hash)),
+        },
+        ConsensusMessage::BlockSignaturesResponse {
+            era_id,
+            block_hash,
+            signatures: None,
+        },
+    ];
+    for message in messages {
+        let bytes = bincode::serialize(&message).expect("should serialize");
+        let decoded: ConsensusMessage = bincode::deserialize(&bytes).expect("should deserialize");
+        assert_eq!(decoded, message);
+    }
+}
+
+#[test]
+fn should_answer_block_signatures_request() {
+    let mut test = TestSupervisor::new(vec![(ALICE_PUBLIC_KEY.clone(), 100)], Config::default());
+    let era_id = EraId::from(1);
+    let known = BlockHash::new(Digest::hash(&[1]));
+    let unknown = BlockHash::new(Digest::hash(&[2]));
+    let signatures = alice_block_signatures(era_id, known);
+
+    let response = request_block_signatures(&mut test, era_id, known, Some(signatures.clone()));
+    assert_eq!(
+        response,
+        ConsensusMessage::BlockSignaturesResponse {
+            era_id,
+            block_hash: known,
+            signatures: Some(signatures),
+        }
+    );
+
+    let response = request_block_signatures(&mut test, era_id, unknown, None);
+    assert_eq!(
+        response,
+        ConsensusMessage::BlockSignaturesResponse {
+            era_id,
+            block_hash: unknown,
+            signatures: None,
+        }
+    );
+}
+
+#[test]
+fn should_store_valid_block_signatures() {
+    let mut test = TestSupervisor::new(vec![(ALICE_PUBLIC_KEY.clone(), 100)], Config::default());
+    let era_id = EraId::from(1);
+    let block_hash = BlockHash::new(Digest::hash(&[1]));
+    let sender = NodeId::random(&mut test.harness.rng);
+    let mut receive = |signatures: BlockSignatures| {
+        let response = ConsensusMessage::BlockSignaturesResponse {
+            era_id,
+            block_hash,
+            signatures: Some(signatures),
+        };
+        let effects = test.supervisor.handle_message(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            sender,
+            response,
+        );
+        let captured: CapturedEffects = test.run(effects).into_iter().collect();
+        captured
+    };
+
+    // Signatures of a different block get the sender disconnected.
+    let other_block_hash = BlockHash::new(Digest::hash(&[2]));
+    let captured = receive(alice_block_signatures(era_id, other_block_hash));
+    assert!(captured.storage.is_empty());
+    assert_eq!(captured.blocklist.len(), 1);
+
+    // Valid signatures are stored.
+    let signatures = alice_block_signatures(era_id, block_hash);
+    let captured = receive(signatures.clone());
+    match &*captured.storage {
+        [StorageRequest::PutBlockSignatures {
+            signatures: stored, ..
+        }] => assert_eq!(*stored, signatures),
+        requests => panic!("unexpected requests: {:?}", requests),
+    }
+}
+
+#[test]
+fn should_record_why_messages_are_dropped() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let config = Config {
+        max_message_size: 1_000,
+        max_buffered_future_messages: 1,
+        ..Config::default()
+    };
+    let mut test = TestSupervisor::new(validators.clone(), config);
+    let sender = NodeId::random(&mut test.harness.rng);
+    let ping = bob_ping(&test, EraId::from(1));
+    let send = |test: &mut TestSupervisor, era_id: u64, payload: Vec<u8>| {
+        let message = ConsensusMessage::Protocol {
+            era_id: EraId::from(era_id),
+            payload,
+        };
+        let effects = test.supervisor.handle_message(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            sender,
+            message,
+        );
+        test.run(effects);
+    };
+    let dropped = |test: &TestSupervisor, reason: MessageDropReason| {
+        test.supervisor
+            .metrics
+            .dropped_messages
+            .with_label_values(&[reason.label()])
+            .get()
+    };
+
+    send(&mut test, 1, ping.clone());
+    assert_eq!(dropped(&test, MessageDropReason::Duplicate), 0);
+    send(&mut test, 1, ping);
+    assert_eq!(dropped(&test, MessageDropReason::Duplicate), 1);
+
+    send(&mut test, 1, vec![0; 1_001]);
+    assert_eq!(dropped(&test, MessageDropReason::TooLarge), 1);
+
+    // The first message for a future era is buffered, the second exceeds the buffer's capacity.
+    send(&mut test, 100, vec![1]);
+    assert_eq!(dropped(&test, MessageDropReason::FutureEraBuffered), 1);
+    send(&mut test, 100, vec![2]);
+    assert_eq!(dropped(&test, MessageDropReason::RateLimited), 1);
+
+    // Once era 1 is retired, its messages are ignored.
+    for _ in 0..3 {
+        test.advance_era(&validators);
+    }
+    assert!(!test.supervisor.open_eras.contains_key(&EraId::from(1)));
+    send(&mut test, 1, vec![3]);
+    assert_eq!(dropped(&test, MessageDropReason::AncientEra), 1);
+}
+
+#[test]
+fn should_request_missing_units_and_evidence_on_forced_sync() {
+    let mut test = TestSupervisor::new(
+        vec![
+            (ALICE_PUBLIC_KEY.clone(), 100),
+            (BOB_PUBLIC_KEY.clone(), 100),
+        ],
+        Config::default(),
+    );
+    let era_id = EraId::from(1);
+    // A proposed block accuses Bob, but we don't have evidence against him yet.
+    let block_payload = BlockPayload::new(vec![], vec![], vec![BOB_PUBLIC_KEY.clone()], false);
+    let block_context = BlockContext::new(Timestamp::now(), vec![]);
+    let proposed_block = ProposedBlock::new(Arc::new(block_payload), block_context);
+    test.supervisor
+        .era_mut(era_id)
+        .add_block(proposed_block, vec![BOB_PUBLIC_KEY.clone()]);
+
+    // Forcing a sync is safe to repeat: it asks for the evidence and the latest state every time.
+    for _ in 0..2 {
+        let effects = test.supervisor.force_era_sync(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            era_id,
+        );
+        let captured: CapturedEffects = test.run(effects).into_iter().collect();
+        match &*captured.network {
+            [NetworkRequest::Broadcast { payload, .. }] => match &**payload {
+                Message::Consensus(ConsensusMessage::EvidenceRequest {
+                    era_id: requested_era_id,
+                    pub_key,
+                }) => {
+                    assert_eq!(*requested_era_id, era_id);
+                    assert_eq!(*pub_key, *BOB_PUBLIC_KEY);
+                }
+                message => panic!("unexpected message: {:?}", message),
+            },
+            requests => panic!("unexpected requests: {:?}", requests),
+        }
+        assert!(matches!(
+            &*captured.network_info,
+            [NetworkInfoRequest::FullyConnectedPeers { .. }]
+        ));
+    }
+
+    // Eras that are not open can't be synced.
+    let effects = test.supervisor.force_era_sync(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        EraId::from(5),
+    );
+    assert!(test.run(effects).is_empty());
+}
+
+#[test]
+fn should_only_propose_in_leader_slots() {
+    let mut test = TestSupervisor::new(
+        vec![
+            (ALICE_PUBLIC_KEY.clone(), 100),
+            (BOB_PUBLIC_KEY.clone(), 100),
+        ],
+        Config::default(),
+    );
+    let era_id = EraId::from(1);
+    let start_time = test.supervisor.era(era_id).start_time;
+    let is_alice_slot = |timestamp: Timestamp| {
+        test.supervisor.era(era_id).consensus.leader(timestamp) == Some(&*ALICE_PUBLIC_KEY)
+    };
+    let mut slots = std::iter::successors(Some(start_time), |t| Some(*t + TimeDiff::from(1_000)));
+    let alice_slot = slots.clone().find(|t| is_alice_slot(*t)).unwrap();
+    let bob_slot = slots.find(|t| !is_alice_slot(*t)).unwrap();
+    let propose = |test: &mut TestSupervisor, timestamp: Timestamp| {
+        let new_block_payload = NewBlockPayload {
+            era_id,
+            block_payload: Arc::new(BlockPayload::new(vec![], vec![], vec![], false)),
+            block_context: BlockContext::new(timestamp, vec![]),
+        };
+        let effects = test.supervisor.handle_new_block_payload(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            new_block_payload,
+        );
+        test.run(effects);
+    };
+
+    // Alice is the leader in her own slot, so her proposal is passed on to the protocol.
+    propose(&mut test, alice_slot);
+    assert_eq!(test.supervisor.metrics.non_leader_proposals.get(), 0);
+
+    // In Bob's slot, Alice's proposal is dropped and counted.
+    propose(&mut test, bob_slot);
+    assert_eq!(test.supervisor.metrics.non_leader_proposals.get(), 1);
+}
+
+#[test]
+fn should_announce_leader_slot_once() {
+    let mut test = TestSupervisor::new(
+        vec![
+            (ALICE_PUBLIC_KEY.clone(), 100),
+            (BOB_PUBLIC_KEY.clone(), 100),
+        ],
+        Config::default(),
+    );
+    let era_id = EraId::from(1);
+    let start_time = test.supervisor.era(era_id).start_time;
+    let mut create_new_block = |timestamp: Timestamp| {
+        let block_context = BlockContext::new(timestamp, vec![]);
+        let effects = test.supervisor.handle_consensus_outcome(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            era_id,
+            ProtocolOutcome::CreateNewBlock(block_context),
+        );
+        let captured: CapturedEffects = test.run(effects).into_iter().collect();
+        // A block payload is requested every time.
+        assert_eq!(captured.block_proposer.len(), 1);
+        captured.announcements
+    };
+
+    // The first request for a block in a slot is announced, with the era and block context.
+    match &*create_new_block(start_time) {
+        [ConsensusAnnouncement::WeAreLeader {
+            era_id: announced_era_id,
+            block_context,
+        }] => {
+            assert_eq!(*announced_era_id, era_id);
+            assert_eq!(*block_context, BlockContext::new(start_time, vec![]));
+        }
+        announcements => panic!("unexpected announcements: {:?}", announcements),
+    }
+
+    // Further requests in the same slot are not; the next slot is announced again.
+    assert!(create_new_block(start_time).is_empty());
+    let next_slot = start_time + TimeDiff::from(1_000);
+    assert_eq!(create_new_block(next_slot).len(), 1);
+}
+
+/// Returns a serialized proposal by Bob with an empty payload, for the consensus instance of the
+/// given era, and its timestamp: the first round after the era's start in which Bob is the leader.
+fn bob_proposal(test: &TestSupervisor, era_id: EraId) -> (Vec<u8>, Timestamp) {
+    let highway = test.supervisor.open_eras[&era_id]
+        .consensus
+        .as_any()
+        .downcast_ref::<HighwayProtocol<ClContext>>()
+        .expect("era should use Highway");
+    let state = highway.highway().state();
+    let creator = highway
+        .highway()
+        .validators()
+        .get_index(&*BOB_PUBLIC_KEY)
+        .expect("Bob should be a validator");
+    let round_exp = state.params().min_round_exp();
+    let round_len = state.params().min_round_length();
+    let first_round = state::round_id(state.params().start_timestamp(), round_exp) + round_len;
+    let timestamp = std::iter::successors(Some(first_round), |t| Some(*t + round_len))
+        .find(|t| state.leader(*t) == creator)
+        .expect("Bob should be a leader");
+    let wunit = WireUnit::<ClContext> {
+        panorama: Panorama::new(state.validator_count()),
+        creator,
+        instance_id: *highway.instance_id(),
+        value: Some(Arc::new(BlockPayload::new(vec![], vec![], vec![], false))),
+        seq_number: 0,
+        timestamp,
+        round_exp,
+        endorsed: BTreeSet::new(),
+    };
+    let secret = Keypair::from(Arc::new(
+        SecretKey::ed25519_from_bytes([1; SecretKey::ED25519_LENGTH]).unwrap(),
+    ));
+    let swunit = SignedWireUnit::new(wunit.into_hashed(), &secret);
+    let payload = HighwayMessage::NewVertex(Vertex::Unit(swunit)).serialize();
+    (payload, timestamp)
+}
+
+#[test]
+fn should_announce_catching_up_once() {
+    let mut test = TestSupervisor::new(
+        vec![
+            (ALICE_PUBLIC_KEY.clone(), 100),
+            (BOB_PUBLIC_KEY.clone(), 100),
+        ],
+        Config::default(),
+    );
+    let era_id = EraId::from(1);
+    let (payload, timestamp) = bob_proposal(&test, era_id);
+    let clock = MockClock::new(timestamp + TimeDiff::from(1));
+    test.supervisor.set_clock(Box::new(clock));
+    let caught_up = |events: &[TestEvent]| {
+        events
+            .iter()
+            .filter(|event| {
+                matches!(
+                    event,
+                    TestEvent::ConsensusAnnouncement(ConsensusAnnouncement::CaughtUp { era_id })
+                        if *era_id == EraId::from(1)
+                )
+            })
+            .count()
+    };
+
+    // Before we receive any units, we haven't caught up yet.
+    let effects =
+        test.supervisor
+            .force_era_sync(test.harness.effect_builder, &mut test.harness.rng, era_id);
+    assert_eq!(caught_up(&test.run(effects)), 0);
+
+    // Bob's proposal is the only unit so far. Once it is added, we are synced.
+    let sender = NodeId::random(&mut test.harness.rng);
+    let message = ConsensusMessage::Protocol { era_id, payload };
+    let effects = test.supervisor.handle_message(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        sender,
+        message,
+    );
+    let mut events = test.run(effects);
+    let mut announcements: usize = 0;
+    while !events.is_empty() {
+        announcements = announcements.saturating_add(caught_up(&events));
+        let mut effects = Effects::new();
+        for event in events {
+            if let TestEvent::Consensus(Event::Action { era_id, action_id }) = event {
+                effects.extend(test.supervisor.handle_action(
+                    test.harness.effect_builder,
+                    &mut test.harness.rng,
+                    era_id,
+                    action_id,
+                ));
+            }
+        }
+        events = test.run(effects);
+    }
+    assert_eq!(announcements, 1);
+    assert!(test.supervisor.open_eras[&era_id].consensus.is_synced());
+
+    // Further activity in the same era doesn't announce it again.
+    let effects =
+        test.supervisor
+            .force_era_sync(test.harness.effect_builder, &mut test.harness.rng, era_id);
+    assert_eq!(caught_up(&test.run(effects)), 0);
+}
+
+#[test]
+fn should_verify_message_signatures() {
+    let mut test = TestSupervisor::new(
+        vec![
+            (ALICE_PUBLIC_KEY.clone(), 100),
+            (BOB_PUBLIC_KEY.clone(), 100),
+        ],
+        Config {
+            sign_messages: true,
+            ..Config::default()
+        },
+    );
+    let era_id = EraId::from(1);
+    let bob_secret = SecretKey::ed25519_from_bytes([1; SecretKey::ED25519_LENGTH]).unwrap();
+    let sender = NodeId::random(&mut test.harness.rng);
+    let send = |test: &mut TestSupervisor, message: ConsensusMessage| {
+        let effects = test.supervisor.handle_message(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            sender,
+            message,
+        );
+        test.run(effects);
+    };
+    let dropped = |test: &TestSupervisor, reason: MessageDropReason| {
+        test.supervisor
+            .metrics
+            .dropped_messages
+            .with_label_values(&[reason.label()])
+            .get()
+    };
+
+    // A correctly signed message is passed on to the era: sending it again is a duplicate.
+    let ping = bob_ping(&test, era_id);
+    let message = ConsensusMessage::new_signed_protocol(
+        era_id,
+        ping.clone(),
+        &bob_secret,
+        BOB_PUBLIC_KEY.clone(),
+    );
+    send(&mut test, message.clone());
+    assert_eq!(dropped(&test, MessageDropReason::Duplicate), 0);
+    send(&mut test, message.clone());
+    assert_eq!(dropped(&test, MessageDropReason::Duplicate), 1);
+
+    // A message whose payload was altered in transit is dropped.
+    let tampered = match message {
+        ConsensusMessage::SignedProtocol {
+            era_id,
+            mut payload,
+            public_key,
+            signature,
+        } => {
+            if let Some(byte) = payload.last_mut() {
+                *byte ^= 1;
+            }
+            ConsensusMessage::SignedProtocol {
+                era_id,
+                payload,
+                public_key,
+                signature,
+            }
+        }
+        message => panic!("unexpected message: {:?}", message),
+    };
+    send(&mut test, tampered);
+    assert_eq!(dropped(&test, MessageDropReason::BadSignature), 1);
+    assert_eq!(dropped(&test, MessageDropReason::Duplicate), 1);
+
+    // A message signed by someone who isn't a validator in the era is dropped.
+    let other_secret = SecretKey::random(&mut test.harness.rng);
+    let other_message = ConsensusMessage::new_signed_protocol(
+        era_id,
+        ping,
+        &other_secret,
+        PublicKey::from(&other_secret),
+    );
+    send(&mut test, other_message);
+    assert_eq!(dropped(&test, MessageDropReason::NonValidatorSender), 1);
+
+    // Since Alice is a validator, her own outgoing messages are signed.
+    match test.supervisor.protocol_message(era_id, vec![1, 2, 3]) {
+        ConsensusMessage::SignedProtocol {
+            era_id: signed_era_id,
+            payload,
+            public_key,
+            signature,
+        } => {
+            assert_eq!(signed_era_id, era_id);
+            assert_eq!(public_key, *ALICE_PUBLIC_KEY);
+            assert!(verify_protocol_signature(era_id, &payload, &public_key, &signature).is_ok());
+        }
+        message => panic!("unexpected message: {:?}", message),
+    }
+}
+
+#[test]
+fn should_drop_unsigned_messages_if_signatures_are_required() {
+    let mut test = TestSupervisor::new(
+        vec![
+            (ALICE_PUBLIC_KEY.clone(), 100),
+            (BOB_PUBLIC_KEY.clone(), 100),
+        ],
+        Config {
+            sign_messages: true,
+            require_signed_messages: true,
+            message_batch_window: Some(TimeDiff::from_seconds(1)),
+            max_message_batch_size: 2,
+            ..Config::default()
+        },
+    );
+    let era_id = EraId::from(1);
+    let bob_secret = SecretKey::ed25519_from_bytes([1; SecretKey::ED25519_LENGTH]).unwrap();
+    let sender = NodeId::random(&mut test.harness.rng);
+    let send = |test: &mut TestSupervisor, message: ConsensusMessage| {
+        let effects = test.supervisor.handle_incoming(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            sender,
+            message,
+        );
+        test.run(effects);
+    };
+    let dropped = |test: &TestSupervisor, reason: MessageDropReason| {
+        test.supervisor
+            .metrics
+            .dropped_messages
+            .with_label_values(&[reason.label()])
+            .get()
+    };
+
+    // Unsigned messages and batches are dropped.
+    let ping = bob_ping(&test, era_id);
+    send(
+        &mut test,
+        ConsensusMessage::Protocol {
+            era_id,
+            payload: ping.clone(),
+        },
+    );
+    assert_eq!(dropped(&test, MessageDropReason::Unsigned), 1);
+    send(
+        &mut test,
+        ConsensusMessage::ProtocolBatch {
+            era_id,
+            payloads: vec![ping.clone()],
+        },
+    );
+    assert_eq!(dropped(&test, MessageDropReason::Unsigned), 2);
+
+    // Evidence is accepted without a signature, also in a batch with other messages.
+    let evidence = vec![0, 0, 0, 0, 1, 0, 0, 0, 0];
+    send(
+        &mut test,
+        ConsensusMessage::Protocol {
+            era_id,
+            payload: evidence.clone(),
+        },
+    );
+    assert_eq!(dropped(&test, MessageDropReason::Unsigned), 2);
+    send(
+        &mut test,
+        ConsensusMessage::ProtocolBatch {
+            era_id,
+            payloads: vec![evidence, ping.clone()],
+        },
+    );
+    assert_eq!(dropped(&test, MessageDropReason::Unsigned), 3);
+
+    // A signed message is passed on to the era: sending it again is a duplicate.
+    let message = ConsensusMessage::new_signed_protocol(
+        era_id,
+        ping.clone(),
+        &bob_secret,
+        BOB_PUBLIC_KEY.clone(),
+    );
+    send(&mut test, message.clone());
+    assert_eq!(dropped(&test, MessageDropReason::Duplicate), 0);
+    send(&mut test, message);
+    assert_eq!(dropped(&test, MessageDropReason::Duplicate), 1);
+    assert_eq!(dropped(&test, MessageDropReason::Unsigned), 3);
+
+    // An oversized message is dropped before its signature is verified.
+    let oversized = match ConsensusMessage::new_signed_protocol(
+        era_id,
+        vec![0],
+        &bob_secret,
+        BOB_PUBLIC_KEY.clone(),
+    ) {
+        ConsensusMessage::SignedProtocol {
+            era_id,
+            public_key,
+            signature,
+            ..
+        } => ConsensusMessage::SignedProtocol {
+            era_id,
+            payload: vec![0; test.supervisor.config.max_message_size + 1],
+            public_key,
+            signature,
+        },
+        message => panic!("unexpected message: {:?}", message),
+    };
+    send(&mut test, oversized);
+    assert_eq!(dropped(&test, MessageDropReason::TooLarge), 1);
+    assert_eq!(dropped(&test, MessageDropReason::BadSignature), 0);
+
+    // A signed message for an era that isn't open is dropped, since its signer can't be checked.
+    let future_era_id = EraId::from(5);
+    let future_message = ConsensusMessage::new_signed_protocol(
+        future_era_id,
+        ping,
+        &bob_secret,
+        BOB_PUBLIC_KEY.clone(),
+    );
+    send(&mut test, future_message);
+    assert_eq!(dropped(&test, MessageDropReason::NonValidatorSender), 1);
+
+    // Our own batched messages are sent individually, so that they can be signed. Flush the batch
+    // with our initial ping first.
+    let effects = test
+        .supervisor
+        .flush_message_batch(test.harness.effect_builder, era_id);
+    test.run(effects);
+    let mut gossip = |payload| {
+        let effects = test.supervisor.handle_consensus_outcome(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            era_id,
+            ProtocolOutcome::CreatedGossipMessage(payload),
+        );
+        let captured: CapturedEffects = test.run(effects).into_iter().collect();
+        captured.network
+    };
+    assert!(gossip(vec![1]).is_empty());
+    let sent = gossip(vec![2]);
+    assert_eq!(sent.len(), 2);
+    for request in &*sent {
+        match request {
+            NetworkRequest::Broadcast { payload, .. } => match &**payload {
+                Message::Consensus(ConsensusMessage::SignedProtocol { public_key, .. }) => {
+                    assert_eq!(*public_key, *ALICE_PUBLIC_KEY)
+                }
+                message => panic!("unexpected message: {:?}", message),
+            },
+            request => panic!("unexpected request: {:?}", request),
+        }
+    }
+}
+
+#[test]
+fn should_use_the_configured_protocol() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let era_id = EraId::from(1);
+
+    // Highway is the default.
+    let mut test = TestSupervisor::new(validators.clone(), Config::default());
+    let consensus = test.supervisor.open_eras[&era_id].consensus.as_any();
+    assert!(consensus.is::<HighwayProtocol<ClContext>>());
+
+    let config = Config {
+        protocol: ProtocolKind::Mock,
+        ..Config::default()
+    };
+    test.restart(validators, config);
+    let sender = NodeId::random(&mut test.harness.rng);
+    let message = ConsensusMessage::Protocol {
+        era_id,
+        payload: vec![1, 2, 3],
+    };
+    let effects = test.supervisor.handle_message(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        sender,
+        message,
+    );
+    test.run(effects);
+    let mock = test.supervisor.open_eras[&era_id]
+        .consensus
+        .as_any()
+        .downcast_ref::<MockProtocol<ClContext>>()
+        .expect("era should use the mock protocol");
+    assert_eq!(mock.messages, vec![(sender, vec![1, 2, 3])]);
+    assert!(mock.is_active());
+}
+
+#[test]
+fn should_return_next_leader_and_time() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let config = Config {
+        protocol: ProtocolKind::Mock,
+        ..Config::default()
+    };
+    let mut test = TestSupervisor::new(validators.clone(), config);
+    let clock = MockClock::new(Timestamp::from(10_500));
+    test.supervisor.set_clock(Box::new(clock.clone()));
+
+    // The mock protocol's leaders take turns in one-second slots, in the order of their keys.
+    let mut leaders = vec![ALICE_PUBLIC_KEY.clone(), BOB_PUBLIC_KEY.clone()];
+    leaders.sort();
+    let era_id = EraId::from(1);
+    assert_eq!(
+        test.supervisor.next_leader(era_id),
+        Some((leaders[1].clone(), Timestamp::from(11_000)))
+    );
+
+    // At the start of a slot, the next one is returned.
+    clock.advance(TimeDiff::from(500));
+    assert_eq!(
+        test.supervisor.next_leader(era_id),
+        Some((leaders[0].clone(), Timestamp::from(12_000)))
+    );
+
+    // Unknown and past eras have no next leader.
+    assert_eq!(test.supervisor.next_leader(EraId::from(2)), None);
+    test.advance_era(&validators);
+    assert_eq!(test.supervisor.next_leader(era_id), None);
+    assert!(test.supervisor.next_leader(EraId::from(2)).is_some());
+}
+
+#[test]
+fn should_expose_leader_schedule() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+        (CAROL_PUBLIC_KEY.clone(), 100),
+    ];
+    let mut chainspec = new_test_chainspec(validators.clone());
+    chainspec.core_config.era_duration = TimeDiff::from_seconds(5);
+    let switch_block = switch_block_header(&chainspec, EraId::from(0), weights(&validators));
+    let config = Config {
+        protocol: ProtocolKind::Mock,
+        ..Config::default()
+    };
+    let mut test =
+        TestSupervisor::with_switch_blocks(Arc::new(chainspec), config, vec![switch_block]);
+
+    // The mock protocol's leaders take turns in one-second slots, in the order of their keys, so
+    // the five slots of the era cycle through them.
+    let mut leaders = vec![
+        ALICE_PUBLIC_KEY.clone(),
+        BOB_PUBLIC_KEY.clone(),
+        CAROL_PUBLIC_KEY.clone(),
+    ];
+    leaders.sort();
+    let era_id = EraId::from(1);
+    let schedule = test
+        .supervisor
+        .leader_schedule(era_id)
+        .expect("era should have a schedule");
+    let first = leaders
+        .iter()
+        .position(|leader| *leader == schedule[0])
+        .unwrap();
+    let expected: Vec<PublicKey> = leaders
+        .iter()
+        .cycle()
+        .skip(first)
+        .take(5)
+        .cloned()
+        .collect();
+    assert_eq!(schedule, expected);
+
+    // Unknown eras and eras that only keep evidence have no schedule.
+    assert_eq!(test.supervisor.leader_schedule(EraId::from(2)), None);
+    test.supervisor
+        .open_eras
+        .get_mut(&era_id)
+        .unwrap()
+        .consensus
+        .set_evidence_only();
+    assert_eq!(test.supervisor.leader_schedule(era_id), None);
+}
+
+#[test]
+fn should_halt_proposals_after_repeated_failures() {
+    let block_payload = BlockPayload::new(vec![], vec![], vec![], false);
+    let config = Config {
+        protocol: ProtocolKind::Mock,
+        max_block_payload_size: bincode::serialized_size(&block_payload).unwrap(),
+        max_failed_proposals: Some(2),
+        ..Config::default()
+    };
+    let mut test = TestSupervisor::new(
+        vec![
+            (ALICE_PUBLIC_KEY.clone(), 100),
+            (BOB_PUBLIC_KEY.clone(), 100),
+        ],
+        config,
+    );
+    let era_id = EraId::from(1);
+    let start_time = test.supervisor.era(era_id).start_time;
+    let alice_slot = std::iter::successors(Some(start_time), |t| Some(*t + TimeDiff::from(1_000)))
+        .find(|t| test.supervisor.era(era_id).consensus.leader(*t) == Some(&*ALICE_PUBLIC_KEY))
+        .unwrap();
+    // An accusation makes the payload exceed the maximum size, so that it fails validation.
+    let oversized = BlockPayload::new(vec![], vec![], vec![BOB_PUBLIC_KEY.clone()], false);
+    let propose = |test: &mut TestSupervisor, block_payload: &BlockPayload| {
+        let new_block_payload = NewBlockPayload {
+            era_id,
+            block_payload: Arc::new(block_payload.clone()),
+            block_context: BlockContext::new(alice_slot, vec![]),
+        };
+        let effects = test.supervisor.handle_new_block_payload(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            new_block_payload,
+        );
+        let captured: CapturedEffects = test.run(effects).into_iter().collect();
+        captured.announcements
+    };
+    let proposal_count = |test: &TestSupervisor| {
+        test.supervisor.open_eras[&era_id]
+            .consensus
+            .as_any()
+            .downcast_ref::<MockProtocol<ClContext>>()
+            .expect("era should use the mock protocol")
+            .proposals
+            .len()
+    };
+
+    // A successful proposal resets the count of consecutive failures.
+    assert!(propose(&mut test, &oversized).is_empty());
+    assert!(propose(&mut test, &block_payload).is_empty());
+    assert_eq!(proposal_count(&test), 1);
+    assert!(propose(&mut test, &oversized).is_empty());
+
+    // The second consecutive failure trips the breaker.
+    match &*propose(&mut test, &oversized) {
+        [ConsensusAnnouncement::ProposalsHalted {
+            era_id: halted_era_id,
+            failures: 2,
+        }] => assert_eq!(*halted_era_id, era_id),
+        announcements => panic!("unexpected announcements: {:?}", announcements),
+    }
+
+    // Valid payloads are not proposed anymore, and no new ones are requested.
+    assert!(propose(&mut test, &block_payload).is_empty());
+    assert_eq!(proposal_count(&test), 1);
+    let effects = test.supervisor.handle_consensus_outcome(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        era_id,
+        ProtocolOutcome::CreateNewBlock(BlockContext::new(alice_slot, vec![])),
+    );
+    let captured: CapturedEffects = test.run(effects).into_iter().collect();
+    assert!(captured.block_proposer.is_empty());
+    assert!(captured.announcements.is_empty());
+}
+
+#[test]
+fn should_record_equivocation_detection_latency() {
+    let mut test = TestSupervisor::new(
+        vec![
+            (ALICE_PUBLIC_KEY.clone(), 100),
+            (BOB_PUBLIC_KEY.clone(), 100),
+        ],
+        Config::default(),
+    );
+    let committed = Timestamp::from(100_000);
+    let clock = MockClock::new(committed);
+    test.supervisor.set_clock(Box::new(clock.clone()));
+    assert_eq!(test.supervisor.last_equivocation_detection_latency(), None);
+
+    // Bob's equivocation is detected 1.5 seconds after he committed it.
+    clock.advance(TimeDiff::from(1_500));
+    let effects = test.supervisor.handle_consensus_outcome(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        EraId::from(1),
+        ProtocolOutcome::NewEvidence(BOB_PUBLIC_KEY.clone(), committed),
+    );
+    test.run(effects);
+    assert_eq!(
+        test.supervisor.last_equivocation_detection_latency(),
+        Some(Duration::from_millis(1_500))
+    );
+    let histogram = &test.supervisor.metrics.equivocation_detection_latency;
+    assert_eq!(histogram.get_sample_count(), 1);
+    assert_eq!(histogram.get_sample_sum() as u64, 1_500);
+}
+
+#[test]
+fn should_answer_evidence_request_with_persisted_evidence() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let config = Config {
+        protocol: ProtocolKind::Mock,
+        sign_messages: true,
+        ..Config::default()
+    };
+    let era_id = EraId::from(1);
+
+    // When Bob's equivocation is detected, the evidence is stored.
+    let mut test = TestSupervisor::new(validators.clone(), config.clone());
+    let era = test.supervisor.open_eras.get_mut(&era_id).unwrap();
+    era.consensus.mark_faulty(&BOB_PUBLIC_KEY);
+    let evidence = era.consensus.evidence_message(&BOB_PUBLIC_KEY).unwrap();
+    let effects = test.supervisor.handle_consensus_outcome(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        era_id,
+        ProtocolOutcome::NewEvidence(BOB_PUBLIC_KEY.clone(), Timestamp::zero()),
+    );
+    let captured: CapturedEffects = test.run(effects).into_iter().collect();
+    let (stored_key, stored_data) = match &*captured.state_store {
+        [StateStoreRequest::Save { key, data, .. }] => (key.clone(), data.clone()),
+        requests => panic!("unexpected state store requests: {:?}", requests),
+    };
+
+    // After a restart, the evidence is loaded again.
+    test.restart(validators, config);
+    let effects = test.supervisor.load_evidence(test.harness.effect_builder);
+    let load_count = effects.len();
+    let scheduler = test.harness.scheduler;
+    let respond = async {
+        for _ in 0..load_count {
+            let ((_ancestor, event), _) = scheduler.pop().await;
+            match event {
+                TestEvent::StateStore(StateStoreRequest::Load { key, responder }) => {
+                    let data = (key == stored_key).then(|| stored_data.clone());
+                    responder.respond(data).await
+                }
+                event => panic!("unexpected event: {:?}", event),
+            }
+        }
+    };
+    let (outputs, ()) = test
+        .harness
+        .runtime
+        .block_on(future::join(future::join_all(effects), respond));
+    for event in outputs.into_iter().flatten() {
+        match event {
+            Event::LoadedEvidence {
+                era_id,
+                pub_key,
+                evidence,
+            } => {
+                test.supervisor
+                    .handle_loaded_evidence(era_id, pub_key, evidence);
+            }
+            event => panic!("unexpected event: {}", event),
+        }
+    }
+
+    // The protocol instance doesn't know about the equivocation, but the loaded evidence is sent
+    // to peers who request it, signed like any other protocol message.
+    assert!(!test.supervisor.open_eras[&era_id]
+        .consensus
+        .has_evidence(&BOB_PUBLIC_KEY));
+    let sender = NodeId::random(&mut test.harness.rng);
+    let request = ConsensusMessage::EvidenceRequest {
+        era_id,
+        pub_key: BOB_PUBLIC_KEY.clone(),
+    };
+    let effects = test.supervisor.handle_message(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        sender,
+        request,
+    );
+    let captured: CapturedEffects = test.run(effects).into_iter().collect();
+    match &*captured.network {
+        [NetworkRequest::SendMessage { dest, payload, .. }] => {
+            assert_eq!(**dest, sender);
+            match &**payload {
+                Message::Consensus(ConsensusMessage::SignedProtocol {
+                    era_id: signed_era_id,
+                    payload,
+                    public_key,
+                    signature,
+                }) => {
+                    assert_eq!(*signed_era_id, era_id);
+                    assert_eq!(*payload, evidence);
+                    assert_eq!(*public_key, *ALICE_PUBLIC_KEY);
+                    assert!(
+                        verify_protocol_signature(era_id, payload, public_key, signature).is_ok()
+                    );
+                }
+                message => panic!("unexpected message: {:?}", message),
+            }
+        }
+        requests => panic!("unexpected network requests: {:?}", requests),
+    }
+}
+
+/// Sends a request for evidence against Bob in era 1 to the supervisor, and returns the number of
+/// messages it sends back.
+fn request_evidence_against_bob(test: &mut TestSupervisor, sender: NodeId) -> usize {
+    let request = ConsensusMessage::EvidenceRequest {
+        era_id: EraId::from(1),
+        pub_key: BOB_PUBLIC_KEY.clone(),
+    };
+    let effects = test.supervisor.handle_message(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        sender,
+        request,
+    );
+    let captured: CapturedEffects = test.run(effects).into_iter().collect();
+    captured
+        .network
+        .iter()
+        .filter(|request| matches!(request, NetworkRequest::SendMessage { .. }))
+        .count()
+}
+
+#[test]
+fn should_drop_evidence_requests_beyond_rate_limit() {
+    let config = Config {
+        protocol: ProtocolKind::Mock,
+        max_evidence_requests_per_second: Some(2),
+        ..Config::default()
+    };
+    let mut test = TestSupervisor::new(
+        vec![
+            (ALICE_PUBLIC_KEY.clone(), 100),
+            (BOB_PUBLIC_KEY.clone(), 100),
+        ],
+        config,
+    );
+    let clock = MockClock::new(Timestamp::from(100_000));
+    test.supervisor.set_clock(Box::new(clock.clone()));
+    test.supervisor
+        .handle_loaded_evidence(EraId::from(1), BOB_PUBLIC_KEY.clone(), vec![1, 2, 3]);
+    let sender = NodeId::random(&mut test.harness.rng);
+
+    // Of a burst of five requests, only the first two are answered.
+    let answered: usize = (0..5)
+        .map(|_| request_evidence_against_bob(&mut test, sender))
+        .sum();
+    assert_eq!(answered, 2);
+    let dropped = test
+        .supervisor
+        .metrics
+        .dropped_messages
+        .with_label_values(&[MessageDropReason::EvidenceRateLimited.label()])
+        .get();
+    assert_eq!(dropped, 3);
+
+    // A second later, requests are answered again.
+    clock.advance(TimeDiff::from_seconds(1));
+    assert_eq!(request_evidence_against_bob(&mut test, sender), 1);
+}
+
+/// Makes the supervisor resume from a checkpoint, answers its state store request with
+/// `stored`, and returns the events produced by handling the loaded checkpoint.
+fn resume_from_checkpoint(test: &mut TestSupervisor, stored: Option<Checkpoint>) -> Vec<TestEvent> {
+    let effects = test
+        .supervisor
+        .resume_from_checkpoint(test.harness.effect_builder);
+    let scheduler = test.harness.scheduler;
+    let respond = async {
+        let ((_ancestor, event), _) = scheduler.pop().await;
+        match event {
+            TestEvent::StateStore(StateStoreRequest::Load { key, responder }) => {
+                assert_eq!(key, Checkpoint::key());
+                let data = stored.map(|checkpoint| bincode::serialize(&checkpoint).unwrap());
+                responder.respond(data).await
+            }
+            event => panic!("unexpected event: {:?}", event),
+        }
+    };
+    let (outputs, ()) = test
+        .harness
+        .runtime
+        .block_on(future::join(future::join_all(effects), respond));
+    let mut effects = Effects::new();
+    for event in outputs.into_iter().flatten() {
+        match event {
+            Event::LoadedCheckpoint { maybe_checkpoint } => {
+                assert_eq!(maybe_checkpoint, stored);
+                effects.extend(test.supervisor.handle_loaded_checkpoint(
+                    test.harness.effect_builder,
+                    &mut test.harness.rng,
+                    maybe_checkpoint,
+                ));
+            }
+            event => panic!("unexpected event: {}", event),
+        }
+    }
+    test.run(effects)
+}
+
+#[test]
+fn should_resume_from_checkpoint() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let era_id = EraId::from(1);
+
+    // Without a checkpoint, nothing happens.
+    let mut test = TestSupervisor::new(validators.clone(), Config::default());
+    assert!(resume_from_checkpoint(&mut test, None).is_empty());
+
+    // A checkpoint from another era is ignored.
+    let initial = test.supervisor.checkpoint();
+    let stale = Checkpoint {
+        era_id: EraId::from(0),
+        next_block_height: 7,
+        next_executed_height: 7,
+    };
+    assert!(resume_from_checkpoint(&mut test, Some(stale)).is_empty());
+    assert_eq!(test.supervisor.checkpoint(), initial);
+
+    // A checkpoint from the current era restores the state, and we ask a peer for the latest
+    // state, which also tells it that we are back.
+    let checkpoint = Checkpoint {
+        era_id,
+        next_block_height: 7,
+        next_executed_height: 6,
+    };
+    test.restart(validators, Config::default());
+    let captured: CapturedEffects = resume_from_checkpoint(&mut test, Some(checkpoint))
+        .into_iter()
+        .collect();
+    assert_eq!(test.supervisor.current_era, era_id);
+    assert_eq!(test.supervisor.checkpoint(), checkpoint);
+    assert!(matches!(
+        &*captured.network_info,
+        [NetworkInfoRequest::FullyConnectedPeers { .. }]
+    ));
+}
+
+#[test]
+fn should_initialize_era_only_once_per_switch_block() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let config = Config {
+        protocol: ProtocolKind::Mock,
+        retained_past_eras: 5,
+        max_tracked_eras: Some(3),
+        ..Config::default()
+    };
+    let mut test = TestSupervisor::new(validators.clone(), config);
+    for _ in 0..3 {
+        test.advance_era(&validators);
+    }
+    let era_id = EraId::from(4);
+    assert_eq!(test.supervisor.current_era, era_id);
+    let sender = NodeId::random(&mut test.harness.rng);
+    let message = ConsensusMessage::Protocol {
+        era_id,
+        payload: vec![1],
+    };
+    let effects = test.supervisor.handle_message(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        sender,
+        message,
+    );
+    test.run(effects);
+    let message_count = |test: &TestSupervisor| {
+        test.supervisor.open_eras[&era_id]
+            .consensus
+            .as_any()
+            .downcast_ref::<MockProtocol<ClContext>>()
+            .expect("era should use the mock protocol")
+            .messages
+            .len()
+    };
+    assert_eq!(message_count(&test), 1);
+
+    // The same switch blocks again don't initialize the era again, which would lose its state.
+    let switch_blocks = test.switch_blocks.clone();
+    let effects = test.supervisor.create_new_era_effects(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        &switch_blocks,
+    );
+    assert!(test.run(effects).is_empty());
+    assert_eq!(message_count(&test), 1);
+
+    // Era 1 was retired to stay within the maximum number of eras. A late response with its key
+    // block doesn't bring it back.
+    assert!(!test.supervisor.open_eras.contains_key(&EraId::from(1)));
+    let effects = test.supervisor.create_new_era_effects(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        &switch_blocks[..1],
+    );
+    assert!(test.run(effects).is_empty());
+    assert!(!test.supervisor.open_eras.contains_key(&EraId::from(1)));
+}
+
+#[test]
+fn should_report_validators_silent_beyond_grace_period() {
+    let grace = TimeDiff::from_seconds(600);
+    let mut test = TestSupervisor::new(
+        vec![
+            (ALICE_PUBLIC_KEY.clone(), 100),
+            (BOB_PUBLIC_KEY.clone(), 100),
+            (CAROL_PUBLIC_KEY.clone(), 100),
+        ],
+        Config {
+            inactivity_grace_period: grace,
+            ..Config::default()
+        },
+    );
+    let era_id = EraId::from(1);
+    let (payload, timestamp) = bob_proposal(&test, era_id);
+    let clock = MockClock::new(timestamp + TimeDiff::from(1));
+    test.supervisor.set_clock(Box::new(clock.clone()));
+
+    // Bob sends a unit; Carol stays silent.
+    let sender = NodeId::random(&mut test.harness.rng);
+    let message = ConsensusMessage::Protocol { era_id, payload };
+    let effects = test.supervisor.handle_message(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        sender,
+        message,
+    );
+    let events = test.run(effects);
+    test.run_actions(events);
+    assert_eq!(
+        test.supervisor.open_eras[&era_id]
+            .consensus
+            .latest_unit_timestamp(&*BOB_PUBLIC_KEY),
+        Some(timestamp)
+    );
+    let era_start = test.supervisor.open_eras[&era_id].start_time;
+
+    // Within the grace period, nobody is inactive.
+    assert!(test.supervisor.inactive_validators(era_id).is_empty());
+
+    // Once it has passed since the era started, Carol is inactive, but Bob's unit is recent enough.
+    clock.advance((era_start + grace).saturating_diff(timestamp));
+    assert_eq!(
+        test.supervisor.inactive_validators(era_id),
+        vec![CAROL_PUBLIC_KEY.clone()]
+    );
+
+    // Once it has passed since Bob's unit, he is inactive, too. Neither is considered faulty.
+    clock.advance(timestamp.saturating_diff(era_start));
+    let mut inactive = test.supervisor.inactive_validators(era_id);
+    inactive.sort();
+    let mut expected = vec![BOB_PUBLIC_KEY.clone(), CAROL_PUBLIC_KEY.clone()];
+    expected.sort();
+    assert_eq!(inactive, expected);
+    assert!(test.supervisor.open_eras[&era_id].faulty.is_empty());
+}
+
+#[test]
+fn should_reproduce_era_state_by_replaying_message_log() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let mut test = TestSupervisor::new(
+        validators,
+        Config {
+            message_log_path: Some("consensus_messages.bin".into()),
+            ..Config::default()
+        },
+    );
+    let era_id = EraId::from(1);
+    let (payload, timestamp) = bob_proposal(&test, era_id);
+    let clock = MockClock::new(timestamp + TimeDiff::from(1));
+    test.supervisor.set_clock(Box::new(clock.clone()));
+
+    // Record Bob's proposal, a ping, and a message for a future era, which is buffered.
+    let sender = NodeId::random(&mut test.harness.rng);
+    let messages = vec![
+        ConsensusMessage::Protocol { era_id, payload },
+        ConsensusMessage::Protocol {
+            era_id,
+            payload: bob_ping(&test, era_id),
+        },
+        ConsensusMessage::Protocol {
+            era_id: EraId::from(3),
+            payload: vec![1, 2, 3],
+        },
+    ];
+    for message in messages {
+        clock.advance(TimeDiff::from(10));
+        let effects = test.supervisor.handle_incoming(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            sender,
+            message,
+        );
+        let events = test.run(effects);
+        test.run_actions(events);
+    }
+
+    let snapshot = test.snapshot();
+    let latest_unit_timestamp = |test: &TestSupervisor| {
+        test.supervisor.open_eras[&era_id]
+            .consensus
+            .latest_unit_timestamp(&*BOB_PUBLIC_KEY)
+    };
+    assert_eq!(latest_unit_timestamp(&test), Some(timestamp));
+
+    // Replay the log into a fresh supervisor, initialized from the same switch blocks. Replacing
+    // the original one flushes its log.
+    let chainspec = Arc::clone(&test.supervisor.chainspec);
+    let switch_blocks = test.switch_blocks.clone();
+    test.restart_with_switch_blocks(chainspec, Config::default(), switch_blocks);
+    test.supervisor.set_clock(Box::new(clock));
+    let path = test.harness.tmp.path().join("consensus_messages.bin");
+    let effects = test
+        .supervisor
+        .replay_message_log(test.harness.effect_builder, &mut test.harness.rng, &path)
+        .expect("failed to replay message log");
+    let events = test.run(effects);
+    test.run_actions(events);
+
+    assert_eq!(test.snapshot(), snapshot);
+    assert_eq!(test.supervisor.future_messages.len(), 1);
+    assert_eq!(latest_unit_timestamp(&test), Some(timestamp));
+}
+
+#[test]
+fn should_limit_concurrent_validations() {
+    let validators = vec![
+        (ALICE_PUBLIC_KEY.clone(), 100),
+        (BOB_PUBLIC_KEY.clone(), 100),
+    ];
+    let mut test = TestSupervisor::new(
+        validators.clone(),
+        Config {
+            max_concurrent_validations: Some(2),
+            ..Config::default()
+        },
+    );
+    test.advance_era(&validators);
+    let sender = NodeId::random(&mut test.harness.rng);
+    let proposed_block = |millis: u64| {
+        let block_payload = BlockPayload::new(vec![], vec![], vec![], false);
+        let block_context = BlockContext::new(Timestamp::from(millis), vec![]);
+        ProposedBlock::new(Arc::new(block_payload), block_context)
+    };
+    let validation_requests = |events: &[TestEvent]| {
+        events
+            .iter()
+            .filter(|event| matches!(event, TestEvent::BlockValidation(_)))
+            .count()
+    };
+
+    // Independent blocks from both open eras arrive; only two are validated at a time.
+    let blocks = vec![
+        (EraId::from(1), proposed_block(1)),
+        (EraId::from(2), proposed_block(2)),
+        (EraId::from(2), proposed_block(3)),
+    ];
+    let mut requests = 0_usize;
+    for (era_id, proposed_block) in blocks.clone() {
+        let outcome = ProtocolOutcome::ValidateConsensusValue {
+            sender,
+            proposed_block,
+        };
+        let effects = test.supervisor.handle_consensus_outcome(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            era_id,
+            outcome,
+        );
+        requests = validation_requests(&test.run(effects)).saturating_add(requests);
+    }
+    assert_eq!(requests, 2);
+    test.supervisor.assert_buffers_bounded();
+
+    // Once a validation completes, the waiting block is validated.
+    let (era_id, proposed_block) = blocks[0].clone();
+    let resolve_validity = ResolveValidity {
+        era_id,
+        sender,
+        proposed_block,
+        valid: true,
+    };
+    let effects = test.supervisor.handle_validation_result(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        resolve_validity,
+    );
+    assert_eq!(validation_requests(&test.run(effects)), 1);
+    test.supervisor.assert_buffers_bounded();
+    assert!(test.supervisor.pending_validations.is_empty());
+}
+
+#[test]
+fn should_handle_units_ahead_of_queued_messages() {
+    let mut test = TestSupervisor::new(
+        vec![
+            (ALICE_PUBLIC_KEY.clone(), 100),
+            (BOB_PUBLIC_KEY.clone(), 100),
+        ],
+        Config {
+            max_queued_messages: Some(10),
+            ..Config::default()
+        },
+    );
+    let era_id = EraId::from(1);
+    let (unit, timestamp) = bob_proposal(&test, era_id);
+    test.supervisor
+        .set_clock(Box::new(MockClock::new(timestamp + TimeDiff::from(1))));
+    let ping = bob_ping(&test, era_id);
+    let sender = NodeId::random(&mut test.harness.rng);
+    let send = |test: &mut TestSupervisor, payload: Vec<u8>| {
+        let message = ConsensusMessage::Protocol { era_id, payload };
+        let effects = test.supervisor.handle_message(
+            test.harness.effect_builder,
+            &mut test.harness.rng,
+            sender,
+            message,
+        );
+        test.run(effects)
+    };
+
+    // The ping is queued.
+    send(&mut test, ping);
+    let queue_len = |test: &TestSupervisor| test.supervisor.message_queue.as_ref().unwrap().len();
+    assert_eq!(queue_len(&test), 1);
+    assert!(!test.supervisor.open_eras[&era_id]
+        .consensus
+        .has_received_messages());
+
+    // Bob's unit can advance finality, so it is handled right away, before the queued ping.
+    send(&mut test, unit);
+    assert_eq!(queue_len(&test), 1);
+    assert!(test.supervisor.open_eras[&era_id]
+        .consensus
+        .has_received_messages());
+}
+
+/// Passes a gossip message to the supervisor as a protocol outcome in era 1, and returns the
+/// captured effects.
+fn gossip_in_era_1(test: &mut TestSupervisor, payload: Vec<u8>) -> CapturedEffects {
+    let effects = test.supervisor.handle_consensus_outcome(
+        test.harness.effect_builder,
+        &mut test.harness.rng,
+        EraId::from(1),
+        ProtocolOutcome::CreatedGossipMessage(payload),
+    );
+    test.run(effects).into_iter().collect()
+}
+
+#[test]
+fn should_not_gossip_message_again_after_restart() {
+    let validators = vec![(ALICE_PUBLIC_KEY.clone(), 100)];
+    let config = Config {
+        protocol: ProtocolKind::Mock,
+        ..Config::default()
+    };
+
+    // The gossiped message is sent, and the era's gossip index is stored once enough messages
+    // were gossiped.
+    let mut test = TestSupervisor::new(validators.clone(), config.clone());
+    let captured = gossip_in_era_1(&mut test, vec![1, 2, 3]);
+    assert!(matches!(
+        &*captured.network,
+        [NetworkRequest::Broadcast { .. }]
+    ));
+    assert!(captured.state_store.is_empty());
+    for i in 1..GOSSIP_INDEX_SAVE_INTERVAL - 1 {
+        let captured = gossip_in_era_1(&mut test, i.to_le_bytes().to_vec());
+        assert!(captured.state_store.is_empty());
+    }
+    let captured = gossip_in_era_1(&mut test, vec![]);
+    let (stored_key, stored_data) = match &*captured.state_store {
+        [StateStoreRequest::Save { key, data, .. }] => (key.clone(), data.clone()),
+        requests => panic!("unexpected state store requests: {:?}", requests),
+    };
+
+    // After a restart, the gossip index is loaded again.
+    test.restart(validators, config);
+    let effects = test
+        .supervisor
+        .load_gossip_indices(test.harness.effect_builder);
+    let load_count = effects.len();
+    let scheduler = test.harness.scheduler;
+    let respond = async {
+        for _ in 0..load_count {
+            let ((_ancestor, event), _) = scheduler.pop().await;
+            match event {
+                TestEvent::StateStore(StateStoreRequest::Load { key, responder }) => {
+                    let data = (key == stored_key).then(|| stored_data.clone());
+                    responder.respond(data).await
+                }
+                event => panic!("unexpected event: {:?}", event),
+            }
+        }
+    };
+    let (outputs, ()) = test
+        .harness
+        .runtime
+        .block_on(future::join(future::join_all(effects), respond));
+    for event in outputs.into_iter().flatten() {
+        match event {
+            Event::LoadedGossipIndex { era_id, keys } => {
+                test.supervisor.handle_loaded_gossip_index(era_id, keys);
+            }
+            event => panic!("unexpected event: {}", event),
+        }
+    }
+
+    // The message that was gossiped before the restart is not sent again, but new ones are.
+    let captured = gossip_in_era_1(&mut test, vec![1, 2, 3]);
+    assert!(captured.network.is_empty());
+    assert!(captured.state_store.is_empty());
+    let captured = gossip_in_era_1(&mut test, vec![4, 5, 6]);
+    assert!(matches!(
+        &*captured.network,
+        [NetworkRequest::Broadcast { .. }]
+    ));
+
+    // Indices with unsaved messages are stored when requested, e.g. when a new era begins.
+    let effects = test
+        .supervisor
+        .save_gossip_indices(test.harness.effect_builder);
+    let captured: CapturedEffects = test.run(effects).into_iter().collect();
+    assert!(matches!(
+        &*captured.state_store,
+        [StateStoreRequest::Save { key, .. }] if *key == stored_key
+    ));
+}
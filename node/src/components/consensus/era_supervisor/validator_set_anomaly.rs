@@ -0,0 +1,134 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display, Formatter},
+};
+
+use casper_types::{PublicKey, U512};
+
+use crate::components::consensus::consensus_protocol::EraReport;
+
+/// An inconsistency between a switch block's era report and the validator set of the era it ends.
+///
+/// The validator weights themselves are a map, so they can't contain duplicate keys, and an era
+/// without any weight is rejected when it is created.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ValidatorSetAnomaly {
+    /// A validator is listed more than once as an equivocator.
+    DuplicateEquivocator(PublicKey),
+    /// A validator is listed more than once as inactive.
+    DuplicateInactiveValidator(PublicKey),
+    /// The report mentions a validator that is not in the ending era's validator set.
+    UnknownValidator(PublicKey),
+}
+
+impl ValidatorSetAnomaly {
+    /// Returns all anomalies in the era report. Unknown validators are only detected if the
+    /// ending era's validator weights are given.
+    pub(crate) fn find(
+        report: &EraReport<PublicKey>,
+        ending_era_validators: Option<&BTreeMap<PublicKey, U512>>,
+    ) -> Vec<ValidatorSetAnomaly> {
+        let mut anomalies = Vec::new();
+        let mut seen = BTreeSet::new();
+        for pub_key in &report.equivocators {
+            if !seen.insert(pub_key) {
+                anomalies.push(ValidatorSetAnomaly::DuplicateEquivocator(pub_key.clone()));
+            }
+        }
+        let mut seen = BTreeSet::new();
+        for pub_key in &report.inactive_validators {
+            if !seen.insert(pub_key) {
+                anomalies.push(ValidatorSetAnomaly::DuplicateInactiveValidator(
+                    pub_key.clone(),
+                ));
+            }
+        }
+        if let Some(validators) = ending_era_validators {
+            let mentioned: BTreeSet<&PublicKey> = report
+                .equivocators
+                .iter()
+                .chain(&report.inactive_validators)
+                .chain(report.rewards.keys())
+                .collect();
+            anomalies.extend(
+                mentioned
+                    .into_iter()
+                    .filter(|pub_key| !validators.contains_key(*pub_key))
+                    .map(|pub_key| ValidatorSetAnomaly::UnknownValidator(pub_key.clone())),
+            );
+        }
+        anomalies
+    }
+}
+
+impl Display for ValidatorSetAnomaly {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidatorSetAnomaly::DuplicateEquivocator(pub_key) => {
+                write!(f, "equivocator {} is listed more than once", pub_key)
+            }
+            ValidatorSetAnomaly::DuplicateInactiveValidator(pub_key) => {
+                write!(f, "inactive validator {} is listed more than once", pub_key)
+            }
+            ValidatorSetAnomaly::UnknownValidator(pub_key) => {
+                write!(f, "{} is not a validator in the ending era", pub_key)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::consensus::tests::utils::{ALICE_PUBLIC_KEY, BOB_PUBLIC_KEY};
+
+    fn validators() -> BTreeMap<PublicKey, U512> {
+        vec![
+            (ALICE_PUBLIC_KEY.clone(), U512::from(100)),
+            (BOB_PUBLIC_KEY.clone(), U512::from(100)),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn should_accept_consistent_report() {
+        let report = EraReport {
+            equivocators: vec![BOB_PUBLIC_KEY.clone()],
+            rewards: vec![(ALICE_PUBLIC_KEY.clone(), 1_000)]
+                .into_iter()
+                .collect(),
+            inactive_validators: vec![ALICE_PUBLIC_KEY.clone()],
+        };
+        assert!(ValidatorSetAnomaly::find(&report, Some(&validators())).is_empty());
+    }
+
+    #[test]
+    fn should_detect_duplicate_keys() {
+        let report = EraReport {
+            equivocators: vec![BOB_PUBLIC_KEY.clone(), BOB_PUBLIC_KEY.clone()],
+            rewards: BTreeMap::new(),
+            inactive_validators: vec![ALICE_PUBLIC_KEY.clone(), ALICE_PUBLIC_KEY.clone()],
+        };
+        assert_eq!(
+            ValidatorSetAnomaly::find(&report, None),
+            vec![
+                ValidatorSetAnomaly::DuplicateEquivocator(BOB_PUBLIC_KEY.clone()),
+                ValidatorSetAnomaly::DuplicateInactiveValidator(ALICE_PUBLIC_KEY.clone()),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_detect_unknown_validators() {
+        let report = EraReport {
+            equivocators: vec![],
+            rewards: vec![(PublicKey::System, 1_000)].into_iter().collect(),
+            inactive_validators: vec![],
+        };
+        assert_eq!(
+            ValidatorSetAnomaly::find(&report, Some(&validators())),
+            vec![ValidatorSetAnomaly::UnknownValidator(PublicKey::System)]
+        );
+    }
+}
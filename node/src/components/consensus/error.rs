@@ -1,5 +1,6 @@
 use std::collections::BTreeMap;
 
+use datasize::DataSize;
 use num::rational::Ratio;
 use thiserror::Error;
 
@@ -56,10 +57,43 @@ pub enum FinalitySignatureError {
     },
 }
 
+/// An error that prevents the consensus component from continuing, and is reported as fatal.
+#[derive(Error, DataSize, Debug, Clone, PartialEq, Eq)]
+pub enum ConsensusError {
+    #[error(
+        "The switch block in {switch_block_era_id} needed to get the validators of {era_id} is \
+         not in storage."
+    )]
+    ValidatorsUnavailable {
+        era_id: EraId,
+        switch_block_era_id: EraId,
+    },
+    #[error(
+        "The switch block in {switch_block_era_id} needed to get the validators of {era_id} is \
+         still not in storage after {attempts} attempts."
+    )]
+    StorageUnavailable {
+        era_id: EraId,
+        switch_block_era_id: EraId,
+        attempts: u32,
+    },
+}
+
 #[derive(Error, Debug)]
 pub enum CreateNewEraError {
     #[error("Attempted to create era with no switch blocks.")]
     AttemptedToCreateEraWithNoSwitchBlocks,
+    #[error("Attempted to create an era after the maximum era {}.", EraId::MAX)]
+    MaxEraReached,
+    #[error("Attempted to create {era_id} without any validator weight.")]
+    NoValidators { era_id: EraId },
+    #[error(
+        "Attempted to create {era_id} with {validator_count} validators and zero total weight."
+    )]
+    ZeroValidatorWeight {
+        era_id: EraId,
+        validator_count: usize,
+    },
     #[error("Attempted to create {era_id} with non-switch block {last_block_header:?}.")]
     LastBlockHeaderNotASwitchBlock {
         era_id: EraId,
@@ -40,6 +40,16 @@ pub(crate) enum Effect<C: Context> {
     ///
     /// When this is returned, the validator automatically deactivates.
     WeAreFaulty(Fault<C>),
+    /// The next unit would conflict with the last unit this validator signed before a restart,
+    /// i.e. the protocol state is behind what the unit file says we already signed.
+    ///
+    /// When this is returned, the validator automatically deactivates.
+    WouldEquivocate {
+        /// The sequence number of the unit we were about to create.
+        seq_number: u64,
+        /// The sequence number of the last unit we signed.
+        own_last_seq_number: u64,
+    },
 }
 
 /// A validator that actively participates in consensus by creating new vertices.
@@ -75,6 +85,9 @@ where
     unit_file: Option<PathBuf>,
     /// The last known unit created by us.
     own_last_unit: Option<SignedWireUnit<C>>,
+    /// The sequence number of the last unit we signed, as persisted in the unit file. Any new unit
+    /// must have a higher one; otherwise it would conflict with a unit we already signed.
+    own_last_seq_number: Option<u64>,
     /// The target fault tolerance threshold. The validator pauses (i.e. doesn't create new units)
     /// if not enough validators are online to finalize values at this FTT.
     target_ftt: Weight,
@@ -116,6 +129,9 @@ impl<C: Context> ActiveValidator<C> {
             })
             .ok()
             .flatten();
+        let own_last_seq_number = own_last_unit
+            .as_ref()
+            .map(|swunit| swunit.wire_unit().seq_number);
         let mut av = ActiveValidator {
             vidx,
             secret,
@@ -124,6 +140,7 @@ impl<C: Context> ActiveValidator<C> {
             next_proposal: None,
             unit_file,
             own_last_unit,
+            own_last_seq_number,
             target_ftt,
             paused: false,
         };
@@ -197,16 +214,14 @@ impl<C: Context> ActiveValidator<C> {
                 return effects;
             } else if timestamp == r_id + self.witness_offset(r_len) {
                 let panorama = self.panorama_at(state, timestamp);
-                if let Some(witness_unit) =
-                    self.new_unit(panorama, timestamp, None, state, instance_id)
-                {
+                if let Some(effect) = self.new_unit(panorama, timestamp, None, state, instance_id) {
                     if self
                         .latest_unit(state)
                         .map_or(true, |latest_unit| latest_unit.round_id() != r_id)
                     {
                         info!(round_id = %r_id, "sending witness in round with no proposal");
                     }
-                    effects.push(Effect::NewVertex(ValidVertex(Vertex::Unit(witness_unit))));
+                    effects.push(effect);
                     return effects;
                 }
             }
@@ -258,12 +273,7 @@ impl<C: Context> ActiveValidator<C> {
         if self.should_send_confirmation(uhash, now, state) {
             let panorama = state.confirmation_panorama(self.vidx, uhash);
             if panorama.has_correct() {
-                if let Some(confirmation_unit) =
-                    self.new_unit(panorama, now, None, state, instance_id)
-                {
-                    let vv = ValidVertex(Vertex::Unit(confirmation_unit));
-                    effects.push(Effect::NewVertex(vv));
-                }
+                effects.extend(self.new_unit(panorama, now, None, state, instance_id));
             }
         };
         if self.should_endorse(uhash, state) {
@@ -314,9 +324,7 @@ impl<C: Context> ActiveValidator<C> {
         let maybe_parent_hash = state.fork_choice(&panorama);
         // If the parent is a terminal block, just create a unit without a new block.
         if maybe_parent_hash.map_or(false, |hash| state.is_terminal_block(hash)) {
-            return self
-                .new_unit(panorama, timestamp, None, state, instance_id)
-                .map(|proposal_unit| Effect::NewVertex(ValidVertex(Vertex::Unit(proposal_unit))));
+            return self.new_unit(panorama, timestamp, None, state, instance_id);
         }
         // Otherwise we need to request a new consensus value to propose.
         let ancestor_values = match maybe_parent_hash {
@@ -359,7 +367,6 @@ impl<C: Context> ActiveValidator<C> {
             return vec![];
         }
         self.new_unit(panorama, timestamp, Some(value), state, instance_id)
-            .map(|proposal_unit| Effect::NewVertex(ValidVertex(Vertex::Unit(proposal_unit))))
             .into_iter()
             .collect()
     }
@@ -408,9 +415,11 @@ impl<C: Context> ActiveValidator<C> {
         true
     }
 
-    /// Returns a new unit with the given data, and the correct sequence number.
+    /// Returns a `NewVertex` effect with a new unit with the given data, and the correct sequence
+    /// number.
     ///
-    /// Returns `None` if it's not possible to create a valid unit with the given panorama.
+    /// Returns `None` if it's not possible to create a valid unit with the given panorama, and
+    /// `WouldEquivocate` if the unit would conflict with the last unit we signed.
     fn new_unit(
         &mut self,
         panorama: Panorama<C>,
@@ -418,7 +427,7 @@ impl<C: Context> ActiveValidator<C> {
         value: Option<C::ConsensusValue>,
         state: &State<C>,
         instance_id: C::InstanceId,
-    ) -> Option<SignedWireUnit<C>> {
+    ) -> Option<Effect<C>> {
         if value.is_none() && !panorama.has_correct() {
             return None; // Wait for the first proposal before creating a unit without a value.
         }
@@ -446,6 +455,19 @@ impl<C: Context> ActiveValidator<C> {
             return None;
         }
         let seq_number = panorama.next_seq_num(state, self.vidx);
+        if let Some(own_last_seq_number) = self.own_last_seq_number {
+            if seq_number <= own_last_seq_number {
+                error!(
+                    seq_number,
+                    own_last_seq_number,
+                    "new unit would conflict with our own last unit; canceling unit creation"
+                );
+                return Some(Effect::WouldEquivocate {
+                    seq_number,
+                    own_last_seq_number,
+                });
+            }
+        }
         let endorsed = state.seen_endorsed(&panorama);
         let hwunit = WireUnit {
             panorama,
@@ -465,7 +487,8 @@ impl<C: Context> ActiveValidator<C> {
                 self.unit_file, err
             )
         });
-        Some(swunit)
+        self.own_last_seq_number = Some(seq_number);
+        Some(Effect::NewVertex(ValidVertex(Vertex::Unit(swunit))))
     }
 
     /// Returns a `ScheduleTimer` effect for the next time we need to be called.
@@ -1078,6 +1101,60 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn refuses_to_sign_conflicting_unit_after_restart() -> Result<(), AddUnitError<TestContext>> {
+        let instance_id = TEST_INSTANCE_ID;
+        let mut state = State::new_test(&[Weight(3)], 0);
+        let a0 = add_unit!(state, ALICE, 0xB0; N)?;
+        let a1 = add_unit!(state, ALICE, None; a0)?;
+        let a2 = {
+            let a2 = add_unit!(state, ALICE, None; a1)?;
+            state.wire_unit(&a2, instance_id).unwrap()
+        };
+
+        let tmp_dir = tempdir().unwrap();
+        let unit_file = Some(tmp_dir.path().join(format!("unit_{:?}.dat", instance_id)));
+        write_last_unit(&unit_file, a2.clone()).expect("storing unit should succeed");
+
+        // Alice restarts on stale state that only contains `a0` and `a1`. Her own last unit is
+        // handed over to the protocol state, but never actually makes it there.
+        let mut stale_state = State::new_test(&[Weight(3)], 0);
+        for hash in &[a0, a1] {
+            stale_state.add_unit(state.wire_unit(hash, instance_id).unwrap())?;
+        }
+        let (mut alice, _init_effects) = ActiveValidator::new(
+            ALICE,
+            TestSecret(ALICE.0),
+            410.into(),
+            410.into(),
+            &stale_state,
+            unit_file.clone(),
+            Weight(2),
+            TEST_INSTANCE_ID,
+        );
+        assert_eq!(alice.take_own_last_unit(), Some(a2.clone()));
+
+        let bctx = match &*alice.handle_timer(416.into(), &stale_state, instance_id) {
+            [Eff::ScheduleTimer(_), Eff::RequestNewBlock(bctx)] => bctx.clone(),
+            effects => panic!("unexpected effects {:?}", effects),
+        };
+
+        // The new unit would have the same sequence number as `a2`, so it must not be signed.
+        let effects = alice.propose(0xC0FFEE, bctx, &stale_state, instance_id);
+        assert_eq!(
+            effects,
+            vec![Eff::WouldEquivocate {
+                seq_number: 2,
+                own_last_seq_number: 2,
+            }]
+        );
+        let stored_unit: SignedWireUnit<TestContext> =
+            read_last_unit(unit_file.unwrap()).expect("reading unit should succeed");
+        assert_eq!(stored_unit, a2, "the stored unit must not be overwritten");
+
+        Ok(())
+    }
+
     // Triggers new proposal by `validator` and verifies that it's empty – no block was proposed.
     // Captures the next witness timer and calls the `validator` with that to return the timer for
     // the next proposal.
@@ -5,6 +5,8 @@ use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use casper_types::Timestamp;
+
 use super::validators::ValidatorIndex;
 use crate::components::consensus::{
     highway_core::{
@@ -77,6 +79,16 @@ impl<C: Context> Evidence<C> {
         }
     }
 
+    /// Returns the time at which the fault was committed, i.e. the timestamp of the later of the
+    /// conflicting units. Endorsements are not timestamped, so for those this is a lower bound.
+    pub(crate) fn timestamp(&self) -> Timestamp {
+        match self {
+            Evidence::Equivocation(unit1, unit2) | Evidence::Endorsements { unit1, unit2, .. } => {
+                unit1.wire_unit().timestamp.max(unit2.wire_unit().timestamp)
+            }
+        }
+    }
+
     /// Validates the evidence and returns `Ok(())` if it is valid.
     /// "Validation" can mean different things for different type of evidence.
     ///
@@ -550,7 +550,9 @@ impl<C: Context> Highway<C> {
                 Effect::NewVertex(vv) => {
                     result.extend(self.add_valid_vertex(vv.clone(), timestamp))
                 }
-                Effect::WeAreFaulty(_) => self.deactivate_validator(),
+                Effect::WeAreFaulty(_) | Effect::WouldEquivocate { .. } => {
+                    self.deactivate_validator()
+                }
                 Effect::ScheduleTimer(_) | Effect::RequestNewBlock(_) => (),
             }
         }
@@ -116,6 +116,13 @@ impl From<Effect<TestContext>> for HighwayMessage {
             Effect::ScheduleTimer(t) => HighwayMessage::Timer(t),
             Effect::RequestNewBlock(block_context) => HighwayMessage::RequestBlock(block_context),
             Effect::WeAreFaulty(fault) => HighwayMessage::WeAreFaulty(Box::new(fault)),
+            Effect::WouldEquivocate {
+                seq_number,
+                own_last_seq_number,
+            } => panic!(
+                "validator would equivocate unexpectedly: seq_number {} <= {}",
+                seq_number, own_last_seq_number
+            ),
         }
     }
 }
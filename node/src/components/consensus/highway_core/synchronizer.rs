@@ -105,6 +105,14 @@ impl<C: Context> PendingVertices<C> {
         self.0.retain(|pvv, _| pvv.inner().is_evidence());
     }
 
+    /// Returns the peers that sent any of the vertices.
+    fn senders(&self) -> HashSet<NodeId> {
+        self.0
+            .values()
+            .flat_map(|time_by_sender| time_by_sender.keys().copied())
+            .collect()
+    }
+
     /// Returns number of unique vertices pending in the queue.
     pub(crate) fn len(&self) -> u64 {
         self.0.len() as u64
@@ -471,6 +479,34 @@ impl<C: Context + 'static> Synchronizer<C> {
         missing_dependency
     }
 
+    /// Requests every missing dependency that is neither downloaded nor being added, from all
+    /// peers that sent a vertex depending on it.
+    ///
+    /// Unlike `pop_vertex_to_add`, this ignores the limit on the number of peers a dependency is
+    /// requested from, and doesn't record the requests.
+    pub(crate) fn request_missing_dependencies(&self) -> ProtocolOutcomes<C> {
+        let mut rng = thread_rng();
+        let is_downloaded = |dep: &Dependency<C>| {
+            self.vertices_no_deps.contains_dependency(dep)
+                || self
+                    .vertices_awaiting_deps
+                    .values()
+                    .chain(self.vertices_to_be_added_later.values())
+                    .any(|pvs| pvs.contains_dependency(dep))
+        };
+        self.vertices_awaiting_deps
+            .iter()
+            .filter(|(dep, _)| !is_downloaded(*dep))
+            .flat_map(|(dep, pvs)| pvs.senders().into_iter().map(move |sender| (dep, sender)))
+            .map(|(dep, sender)| {
+                let uuid = rng.next_u64();
+                debug!(?uuid, dependency = ?dep, %sender, "re-requesting dependency");
+                let ser_msg = HighwayMessage::RequestDependency(uuid, dep.clone()).serialize();
+                ProtocolOutcome::CreatedTargetedMessage(ser_msg, sender)
+            })
+            .collect()
+    }
+
     /// Adds a vertex with a known missing dependency to the queue.
     fn add_missing_dependency(&mut self, dep: Dependency<C>, pv: PendingVertex<C>) {
         self.vertices_awaiting_deps.entry(dep).or_default().push(pv)
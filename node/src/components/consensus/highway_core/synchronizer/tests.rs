@@ -351,6 +351,61 @@ fn transitive_proposal_dependency() {
     }
 }
 
+#[test]
+fn request_missing_dependencies_again() {
+    let params = test_params(0);
+    let mut state = State::new(WEIGHTS, params.clone(), vec![], vec![]);
+    let util_highway =
+        Highway::<TestContext>::new(TEST_INSTANCE_ID, test_validators(), params.clone());
+
+    // Carol   c0 — c1 — c2
+    let c0 = add_unit!(state, CAROL, 0x00, 4u8, 0xA; N, N, N).unwrap();
+    let c1 = add_unit!(state, CAROL, 0x0A, 4u8, None; N, N, c0).unwrap();
+    let c2 = add_unit!(state, CAROL, 0x1A, 4u8, None; N, N, c1).unwrap();
+
+    // Returns the WireUnit with the specified hash.
+    let unit = |hash: u64| Vertex::Unit(state.wire_unit(&hash, TEST_INSTANCE_ID).unwrap());
+    // Returns the PreValidatedVertex with the specified hash.
+    let pvv = |hash: u64| util_highway.pre_validate_vertex(unit(hash)).unwrap();
+
+    let peer0 = NodeId::from([0; 64]);
+    let peer1 = NodeId::from([1; 64]);
+
+    let max_requests_for_vertex = 1;
+    let mut sync = Synchronizer::<TestContext>::new(WEIGHTS.len(), TEST_INSTANCE_ID);
+    let highway = Highway::<TestContext>::new(TEST_INSTANCE_ID, test_validators(), params);
+    let now = 0x20.into();
+
+    // Both peers send `c2`, but its dependency `c1` is only requested from one of them.
+    let _ = sync.schedule_add_vertex(peer0, pvv(c2), now);
+    let _ = sync.schedule_add_vertex(peer1, pvv(c2), now);
+    let (pv, outcomes) =
+        sync.pop_vertex_to_add(&highway, &Default::default(), max_requests_for_vertex);
+    assert!(pv.is_none());
+    assert_eq!(outcomes.len(), 1, "unexpected outcomes: {:?}", outcomes);
+
+    // Re-requesting asks both peers, and can be repeated.
+    for _ in 0..2 {
+        let outcomes = sync.request_missing_dependencies();
+        assert_eq!(outcomes.len(), 2, "unexpected outcomes: {:?}", outcomes);
+        let peers: BTreeSet<NodeId> = outcomes
+            .iter()
+            .map(|outcome| match outcome {
+                ProtocolOutcome::CreatedTargetedMessage(_, peer) => {
+                    assert_targeted_message(outcome, peer, Dependency::Unit(c1));
+                    *peer
+                }
+                outcome => panic!("unexpected outcome: {:?}", outcome),
+            })
+            .collect();
+        assert_eq!(peers, vec![peer0, peer1].into_iter().collect());
+    }
+
+    // Once `c1` is downloaded, it isn't requested anymore, even though it can't be added yet.
+    let _ = sync.schedule_add_vertex(peer0, pvv(c1), now);
+    assert!(sync.request_missing_dependencies().is_empty());
+}
+
 fn unwrap_single<T: Debug>(vec: Vec<T>) -> T {
     assert_eq!(
         vec.len(),
@@ -1,8 +1,13 @@
-use prometheus::{Gauge, IntGauge, Registry};
+use prometheus::{
+    Gauge, Histogram, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+};
 
-use casper_types::Timestamp;
+use casper_types::{TimeDiff, Timestamp};
 
-use crate::{types::FinalizedBlock, unregister_metric};
+use crate::{
+    types::{FinalizedBlock, NodeId},
+    unregister_metric, utils,
+};
 
 /// Network metrics to track Consensus
 #[derive(Debug)]
@@ -17,6 +22,28 @@ pub(super) struct Metrics {
     time_of_last_finalized_block: IntGauge,
     /// The Current era.
     pub(super) current_era: IntGauge,
+    /// Number of consensus messages for future eras that are currently buffered.
+    pub(super) buffered_future_messages: IntGauge,
+    /// Number of consensus messages for future eras that were dropped because the buffer was full.
+    pub(super) dropped_future_messages: IntCounter,
+    /// Number of proposed block payloads that were rejected because they were too large.
+    pub(super) oversized_block_payloads: IntCounter,
+    /// Number of incoming consensus messages that were dropped because they were too large.
+    pub(super) oversized_messages: IntCounter,
+    /// Number of incoming consensus messages that were rejected as invalid, e.g. because they
+    /// failed to deserialize.
+    pub(super) invalid_messages: IntCounter,
+    /// Number of incoming consensus messages that were dropped because the queue was full.
+    pub(super) load_shed: IntCounter,
+    /// Number of incoming consensus messages that were dropped or ignored, by reason.
+    pub(super) dropped_messages: IntCounterVec,
+    /// Number of new block payloads that were not proposed because we were not the leader.
+    pub(super) non_leader_proposals: IntCounter,
+    /// The most recently measured round-trip time of a latency probe, in milliseconds, by peer.
+    pub(super) peer_latency: IntGaugeVec,
+    /// The time between an equivocation being committed and this node detecting it, in
+    /// milliseconds.
+    pub(super) equivocation_detection_latency: Histogram,
     /// registry component.
     registry: Registry,
 }
@@ -38,17 +65,84 @@ impl Metrics {
             "timestamp of the most recently finalized block",
         )?;
         let current_era = IntGauge::new("current_era", "the current era")?;
+        let buffered_future_messages = IntGauge::new(
+            "buffered_future_messages",
+            "the number of consensus messages for future eras that are currently buffered",
+        )?;
+        let dropped_future_messages = IntCounter::new(
+            "dropped_future_messages",
+            "the number of consensus messages for future eras dropped because the buffer was full",
+        )?;
+        let oversized_block_payloads = IntCounter::new(
+            "oversized_block_payloads",
+            "the number of proposed block payloads rejected because they exceeded the maximum size",
+        )?;
+        let oversized_messages = IntCounter::new(
+            "oversized_consensus_messages",
+            "the number of incoming consensus messages dropped because they exceeded the maximum size",
+        )?;
+        let invalid_messages = IntCounter::new(
+            "invalid_consensus_messages",
+            "the number of incoming consensus messages rejected as invalid",
+        )?;
+        let load_shed = IntCounter::new(
+            "consensus_load_shed",
+            "the number of incoming consensus messages dropped because the queue was full",
+        )?;
+        let dropped_messages = IntCounterVec::new(
+            Opts::new(
+                "consensus_dropped_messages",
+                "the number of incoming consensus messages that were dropped or ignored, by reason",
+            ),
+            &["reason"],
+        )?;
+        let non_leader_proposals = IntCounter::new(
+            "consensus_non_leader_proposals",
+            "the number of new block payloads not proposed because this node was not the leader",
+        )?;
+        let peer_latency = IntGaugeVec::new(
+            Opts::new(
+                "consensus_peer_latency",
+                "the most recently measured round-trip time of a consensus latency probe, in milliseconds",
+            ),
+            &["peer"],
+        )?;
+        let equivocation_detection_latency = utils::register_histogram_metric(
+            registry,
+            "consensus_equivocation_detection_latency",
+            "the time between an equivocation being committed and detected, in milliseconds",
+            prometheus::exponential_buckets(100.0, 2.0, 12)?,
+        )?;
         registry.register(Box::new(finalization_time.clone()))?;
         registry.register(Box::new(finalized_block_count.clone()))?;
         registry.register(Box::new(current_era.clone()))?;
         registry.register(Box::new(time_of_last_proposed_block.clone()))?;
         registry.register(Box::new(time_of_last_finalized_block.clone()))?;
+        registry.register(Box::new(buffered_future_messages.clone()))?;
+        registry.register(Box::new(dropped_future_messages.clone()))?;
+        registry.register(Box::new(oversized_block_payloads.clone()))?;
+        registry.register(Box::new(oversized_messages.clone()))?;
+        registry.register(Box::new(invalid_messages.clone()))?;
+        registry.register(Box::new(load_shed.clone()))?;
+        registry.register(Box::new(dropped_messages.clone()))?;
+        registry.register(Box::new(non_leader_proposals.clone()))?;
+        registry.register(Box::new(peer_latency.clone()))?;
         Ok(Metrics {
             finalization_time,
             finalized_block_count,
             time_of_last_proposed_block,
             time_of_last_finalized_block,
             current_era,
+            buffered_future_messages,
+            dropped_future_messages,
+            oversized_block_payloads,
+            oversized_messages,
+            invalid_messages,
+            load_shed,
+            dropped_messages,
+            non_leader_proposals,
+            peer_latency,
+            equivocation_detection_latency,
             registry: registry.clone(),
         })
     }
@@ -63,6 +157,19 @@ impl Metrics {
             .set(finalized_block.height() as i64);
     }
 
+    /// Records the round-trip time of a latency probe to the given peer.
+    pub(super) fn record_peer_latency(&self, peer: &NodeId, latency: TimeDiff) {
+        self.peer_latency
+            .with_label_values(&[&peer.to_string()])
+            .set(latency.millis() as i64);
+    }
+
+    /// Records the time between an equivocation being committed and this node detecting it.
+    pub(super) fn record_equivocation_detection_latency(&self, latency: TimeDiff) {
+        self.equivocation_detection_latency
+            .observe(latency.millis() as f64);
+    }
+
     /// Updates the metrics and records a newly proposed block.
     pub(super) fn proposed_block(&mut self) {
         self.time_of_last_proposed_block
@@ -77,5 +184,15 @@ impl Drop for Metrics {
         unregister_metric!(self.registry, self.current_era);
         unregister_metric!(self.registry, self.time_of_last_finalized_block);
         unregister_metric!(self.registry, self.time_of_last_proposed_block);
+        unregister_metric!(self.registry, self.buffered_future_messages);
+        unregister_metric!(self.registry, self.dropped_future_messages);
+        unregister_metric!(self.registry, self.oversized_block_payloads);
+        unregister_metric!(self.registry, self.oversized_messages);
+        unregister_metric!(self.registry, self.invalid_messages);
+        unregister_metric!(self.registry, self.load_shed);
+        unregister_metric!(self.registry, self.dropped_messages);
+        unregister_metric!(self.registry, self.non_leader_proposals);
+        unregister_metric!(self.registry, self.peer_latency);
+        unregister_metric!(self.registry, self.equivocation_detection_latency);
     }
 }
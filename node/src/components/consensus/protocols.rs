@@ -1 +1,3 @@
 pub(crate) mod highway;
+#[cfg(test)]
+pub(crate) mod mock;
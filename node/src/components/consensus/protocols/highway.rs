@@ -1,8 +1,9 @@
 pub(crate) mod config;
 mod participation;
 mod round_success_meter;
+mod seen_messages;
 #[cfg(test)]
-mod tests;
+pub(crate) mod tests;
 
 use std::{
     any::Any,
@@ -14,6 +15,7 @@ use std::{
 
 use datasize::DataSize;
 use itertools::Itertools;
+use num::rational::Ratio;
 use num_traits::AsPrimitive;
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
@@ -45,14 +47,17 @@ use crate::{
     NodeRng,
 };
 
-use self::round_success_meter::RoundSuccessMeter;
+use self::{round_success_meter::RoundSuccessMeter, seen_messages::SeenMessages};
 
 /// Never allow more than this many units in a piece of evidence for conflicting endorsements,
 /// even if eras are longer than this.
 const MAX_ENDORSEMENT_EVIDENCE_LIMIT: u64 = 10_000;
 
+/// The maximum number of recently received vertex messages we remember, to drop duplicates.
+const MAX_SEEN_MESSAGES: usize = 10_000;
+
 /// The timer for creating new units, as a validator actively participating in consensus.
-const TIMER_ID_ACTIVE_VALIDATOR: TimerId = TimerId(0);
+pub(crate) const TIMER_ID_ACTIVE_VALIDATOR: TimerId = TimerId(0);
 /// The timer for adding a vertex with a future timestamp.
 const TIMER_ID_VERTEX_WITH_FUTURE_TIMESTAMP: TimerId = TimerId(1);
 /// The timer for purging expired pending vertices from the queues.
@@ -64,7 +69,14 @@ const TIMER_ID_STANDSTILL_ALERT: TimerId = TimerId(4);
 /// The timer for logging synchronizer queue size.
 const TIMER_ID_SYNCHRONIZER_LOG: TimerId = TimerId(5);
 /// The timer to request the latest state from a random peer.
-const TIMER_ID_REQUEST_STATE: TimerId = TimerId(6);
+pub(crate) const TIMER_ID_REQUEST_STATE: TimerId = TimerId(6);
+
+/// The serialized prefix of a `HighwayMessage::NewVertex(Vertex::Unit(_))`: bincode encodes both
+/// enum variant indices as four-byte integers, and both are the first variants.
+const UNIT_MESSAGE_PREFIX: [u8; 8] = [0; 8];
+
+/// The serialized prefix of a `HighwayMessage::NewVertex(Vertex::Evidence(_))`.
+const EVIDENCE_MESSAGE_PREFIX: [u8; 8] = [0, 0, 0, 0, 1, 0, 0, 0];
 
 /// The action of adding a vertex from the `vertices_to_be_added` queue.
 pub(crate) const ACTION_ID_VERTEX: ActionId = ActionId(0);
@@ -82,6 +94,8 @@ where
     round_success_meter: RoundSuccessMeter<C>,
     synchronizer: Synchronizer<C>,
     pvv_cache: HashMap<Dependency<C>, PreValidatedVertex<C>>,
+    /// The hashes of recently received vertex messages.
+    seen_messages: SeenMessages<C>,
     evidence_only: bool,
     /// The panorama snapshot. This is updated periodically, and if it does not change for too
     /// long, an alert is raised.
@@ -99,6 +113,7 @@ impl<C: Context + 'static> HighwayProtocol<C> {
         inactive: &HashSet<C::ValidatorId>,
         chainspec: &Chainspec,
         config: &Config,
+        ftt_fraction: Ratio<u64>,
         prev_cp: Option<&dyn ConsensusProtocol<C>>,
         era_start_time: Timestamp,
         seed: u64,
@@ -134,7 +149,6 @@ impl<C: Context + 'static> HighwayProtocol<C> {
         let highway_config = &chainspec.highway_config;
 
         let total_weight = u128::from(validators.total_weight());
-        let ftt_fraction = highway_config.finality_threshold_fraction;
         assert!(
             ftt_fraction < 1.into(),
             "finality threshold must be less than 100%"
@@ -200,6 +214,7 @@ impl<C: Context + 'static> HighwayProtocol<C> {
             round_success_meter,
             synchronizer: Synchronizer::new(validators_count, instance_id),
             pvv_cache: Default::default(),
+            seen_messages: SeenMessages::new(MAX_SEEN_MESSAGES),
             evidence_only: false,
             last_panorama,
             config: config.highway.clone(),
@@ -268,6 +283,17 @@ impl<C: Context + 'static> HighwayProtocol<C> {
                 error!("this validator is faulty: {:?}", fault);
                 vec![ProtocolOutcome::WeAreFaulty]
             }
+            AvEffect::WouldEquivocate {
+                seq_number,
+                own_last_seq_number,
+            } => {
+                error!(
+                    seq_number,
+                    own_last_seq_number,
+                    "this validator's state is behind its own last unit; refusing to sign"
+                );
+                vec![ProtocolOutcome::WouldEquivocate]
+            }
         }
     }
 
@@ -280,7 +306,7 @@ impl<C: Context + 'static> HighwayProtocol<C> {
                 .id(ev.perpetrator())
                 .expect("validator not found") // We already validated this vertex.
                 .clone();
-            outcomes.push(ProtocolOutcome::NewEvidence(v_id));
+            outcomes.push(ProtocolOutcome::NewEvidence(v_id, ev.timestamp()));
         }
         let msg = HighwayMessage::NewVertex(vv.into());
         outcomes.push(ProtocolOutcome::CreatedGossipMessage(msg.serialize()));
@@ -734,6 +760,12 @@ where
         msg: Vec<u8>,
         now: Timestamp,
     ) -> ProtocolOutcomes<C> {
+        // Peers gossip the same vertices many times: Drop exact duplicates before deserializing.
+        let msg_hash = <C as Context>::hash(&msg);
+        if self.seen_messages.contains(&msg_hash) {
+            trace!("received a duplicate vertex");
+            return vec![];
+        }
         match bincode::deserialize(msg.as_slice()) {
             Err(err) => vec![ProtocolOutcome::InvalidIncomingMessage(
                 msg,
@@ -743,6 +775,7 @@ where
             Ok(HighwayMessage::NewVertex(v))
                 if self.highway.has_vertex(&v) || (self.evidence_only && !v.is_evidence()) =>
             {
+                self.seen_messages.insert(msg_hash, now);
                 trace!(
                     has_vertex = self.highway.has_vertex(&v),
                     is_evidence = v.is_evidence(),
@@ -752,6 +785,7 @@ where
                 vec![]
             }
             Ok(HighwayMessage::NewVertex(v)) => {
+                self.seen_messages.insert(msg_hash, now);
                 let v_id = v.id();
                 // If we already have that vertex, do not process it.
                 if self.highway.has_dependency(&v_id) {
@@ -913,6 +947,7 @@ where
             TIMER_ID_PURGE_VERTICES => {
                 let oldest = now.saturating_sub(self.config.pending_vertex_timeout);
                 self.synchronizer.purge_vertices(oldest);
+                self.seen_messages.purge(oldest);
                 self.pvv_cache.clear();
                 let next_time = now + self.config.pending_vertex_timeout;
                 vec![ProtocolOutcome::ScheduleTimer(next_time, timer_id)]
@@ -941,6 +976,15 @@ where
         }
     }
 
+    fn is_urgent(&self, msg: &[u8]) -> bool {
+        // Only units can make blocks final. Checking the prefix avoids deserializing the message.
+        !self.evidence_only && msg.starts_with(&UNIT_MESSAGE_PREFIX)
+    }
+
+    fn is_evidence(&self, msg: &[u8]) -> bool {
+        msg.starts_with(&EVIDENCE_MESSAGE_PREFIX)
+    }
+
     fn handle_is_current(&self, now: Timestamp) -> ProtocolOutcomes<C> {
         // Request latest protocol state of the current era.
         let mut outcomes = self.latest_state_request();
@@ -1037,30 +1081,79 @@ where
         self.highway.has_evidence(vid)
     }
 
+    fn latest_unit_timestamp(&self, vid: &C::ValidatorId) -> Option<Timestamp> {
+        let state = self.highway.state();
+        let vidx = self.highway.validators().get_index(vid)?;
+        let hash = state.panorama()[vidx].correct()?;
+        Some(state.unit(hash).timestamp)
+    }
+
     fn mark_faulty(&mut self, vid: &C::ValidatorId) {
         self.highway.mark_faulty(vid);
     }
 
     fn request_evidence(&self, sender: NodeId, vid: &C::ValidatorId) -> ProtocolOutcomes<C> {
-        self.highway
-            .validators()
-            .get_index(vid)
-            .and_then(
-                move |vidx| match self.highway.get_dependency(&Dependency::Evidence(vidx)) {
-                    GetDepOutcome::None | GetDepOutcome::Evidence(_) => None,
-                    GetDepOutcome::Vertex(vv) => {
-                        let msg = HighwayMessage::NewVertex(vv.into());
-                        Some(ProtocolOutcome::CreatedTargetedMessage(
-                            msg.serialize(),
-                            sender,
-                        ))
-                    }
-                },
-            )
+        self.evidence_message(vid)
+            .map(|msg| ProtocolOutcome::CreatedTargetedMessage(msg, sender))
             .into_iter()
             .collect()
     }
 
+    fn evidence_message(&self, vid: &C::ValidatorId) -> Option<Vec<u8>> {
+        let vidx = self.highway.validators().get_index(vid)?;
+        match self.highway.get_dependency(&Dependency::Evidence(vidx)) {
+            GetDepOutcome::None | GetDepOutcome::Evidence(_) => None,
+            GetDepOutcome::Vertex(vv) => Some(HighwayMessage::NewVertex(vv.into()).serialize()),
+        }
+    }
+
+    fn leader(&self, timestamp: Timestamp) -> Option<&C::ValidatorId> {
+        let leader = self.highway.state().leader(timestamp);
+        self.highway.validators().id(leader)
+    }
+
+    /// Returns the leader of the next round with the minimum round length: rounds of any length
+    /// start at a multiple of it, so that is the earliest time a block can be proposed.
+    fn next_leader(&self, now: Timestamp) -> Option<(&C::ValidatorId, Timestamp)> {
+        if self.evidence_only || self.finalized_switch_block() {
+            return None; // Era has ended. No further blocks are expected.
+        }
+        let min_round_exp = self.highway.state().params().min_round_exp();
+        let next_round_id =
+            state::round_id(now, min_round_exp).saturating_add(state::round_len(min_round_exp));
+        self.leader(next_round_id)
+            .map(|leader| (leader, next_round_id))
+    }
+
+    /// The slots are the rounds with the minimum round length: any round of a longer length
+    /// starts at one of them, with the same leader.
+    fn leader_schedule(&self) -> Option<Vec<&C::ValidatorId>> {
+        if self.evidence_only {
+            return None;
+        }
+        let params = self.highway.state().params();
+        let min_round_len = params.min_round_length();
+        let mut round_id = state::round_id(params.start_timestamp(), params.min_round_exp());
+        if round_id < params.start_timestamp() {
+            round_id += min_round_len;
+        }
+        let mut schedule = Vec::new();
+        while round_id < params.end_timestamp() {
+            schedule.extend(self.leader(round_id));
+            round_id += min_round_len;
+        }
+        Some(schedule)
+    }
+
+    fn request_missing_units(&self) -> ProtocolOutcomes<C> {
+        if self.evidence_only || self.finalized_switch_block() {
+            return vec![]; // Era has ended. No further progress is expected.
+        }
+        let mut outcomes = self.synchronizer.request_missing_dependencies();
+        outcomes.extend(self.latest_state_request());
+        outcomes
+    }
+
     /// Sets the pause status: While paused we don't create any new units, just pings.
     fn set_paused(&mut self, paused: bool) {
         self.highway.set_paused(paused);
@@ -1070,6 +1163,12 @@ where
         self.highway.validators_with_evidence().collect()
     }
 
+    fn is_synced(&self) -> bool {
+        !self.highway.state().is_empty()
+            && self.synchronizer.is_empty()
+            && self.pending_values.is_empty()
+    }
+
     fn has_received_messages(&self) -> bool {
         !self.highway.state().is_empty()
             || !self.synchronizer.is_empty()
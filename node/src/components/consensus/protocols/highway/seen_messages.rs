@@ -0,0 +1,100 @@
+use std::collections::{HashSet, VecDeque};
+
+use datasize::DataSize;
+
+use casper_types::Timestamp;
+
+use crate::components::consensus::traits::Context;
+
+/// The hashes of recently received messages, so that exact duplicates can be dropped before they
+/// are deserialized.
+///
+/// Entries expire after a while, so that a message can be processed again once it might have been
+/// dropped from the synchronizer's queue. At most `capacity` entries are kept; if there are more,
+/// the oldest ones are forgotten.
+#[derive(DataSize, Debug)]
+pub(crate) struct SeenMessages<C>
+where
+    C: Context,
+{
+    /// The hashes of the messages we have seen.
+    hashes: HashSet<C::Hash>,
+    /// The same hashes, with the time they were received, from oldest to newest.
+    queue: VecDeque<(Timestamp, C::Hash)>,
+    /// The maximum number of hashes we keep.
+    capacity: usize,
+}
+
+impl<C: Context> SeenMessages<C> {
+    /// Creates an empty set that holds at most `capacity` message hashes.
+    pub(crate) fn new(capacity: usize) -> Self {
+        SeenMessages {
+            hashes: HashSet::new(),
+            queue: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Returns whether a message with the given hash has been seen recently.
+    pub(crate) fn contains(&self, hash: &C::Hash) -> bool {
+        self.hashes.contains(hash)
+    }
+
+    /// Records that a message with the given hash was received at time `now`.
+    pub(crate) fn insert(&mut self, hash: C::Hash, now: Timestamp) {
+        if self.capacity == 0 || !self.hashes.insert(hash) {
+            return;
+        }
+        self.queue.push_back((now, hash));
+        while self.queue.len() > self.capacity {
+            self.pop_oldest();
+        }
+    }
+
+    /// Forgets all messages that were received before `oldest`.
+    pub(crate) fn purge(&mut self, oldest: Timestamp) {
+        while self
+            .queue
+            .front()
+            .map_or(false, |(timestamp, _)| *timestamp < oldest)
+        {
+            self.pop_oldest();
+        }
+    }
+
+    fn pop_oldest(&mut self) {
+        if let Some((_, hash)) = self.queue.pop_front() {
+            self.hashes.remove(&hash);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::components::consensus::{cl_context::ClContext, traits::Context};
+
+    use super::*;
+
+    #[test]
+    fn should_forget_old_messages() {
+        let mut seen = SeenMessages::<ClContext>::new(2);
+        let (hash0, hash1, hash2) = (
+            ClContext::hash(&[0]),
+            ClContext::hash(&[1]),
+            ClContext::hash(&[2]),
+        );
+        seen.insert(hash0, 10.into());
+        seen.insert(hash1, 20.into());
+        assert!(seen.contains(&hash0) && seen.contains(&hash1));
+
+        // Past the capacity, the oldest message is forgotten.
+        seen.insert(hash2, 30.into());
+        assert!(!seen.contains(&hash0));
+        assert!(seen.contains(&hash1) && seen.contains(&hash2));
+
+        // Messages that are too old expire.
+        seen.purge(30.into());
+        assert!(!seen.contains(&hash1));
+        assert!(seen.contains(&hash2));
+    }
+}
@@ -8,6 +8,7 @@ use crate::{
         config::Config,
         consensus_protocol::{ConsensusProtocol, ProtocolOutcome},
         highway_core::{
+            evidence::Evidence,
             highway::{SignedWireUnit, Vertex, WireUnit},
             highway_testing,
             state::{self, tests::ALICE, Observation, Panorama},
@@ -49,7 +50,7 @@ where
     state::State::new(weights, params, vec![], vec![])
 }
 
-const INSTANCE_ID_DATA: &[u8; 1] = &[123u8; 1];
+pub(crate) const INSTANCE_ID_DATA: &[u8; 1] = &[123u8; 1];
 const STANDSTILL_TIMEOUT: &str = "1min";
 
 pub(crate) fn new_test_highway_protocol<I1, I2, T>(
@@ -75,6 +76,7 @@ where
             max_execution_delay: 3,
             ..HighwayConfig::default()
         },
+        ..Default::default()
     };
     // Timestamp of the genesis era start and test start.
     let start_timestamp: Timestamp = 0.into();
@@ -85,6 +87,7 @@ where
         &None.into_iter().collect(),
         &chainspec,
         &config,
+        chainspec.highway_config.finality_threshold_fraction,
         None,
         start_timestamp,
         0,
@@ -297,3 +300,82 @@ fn detect_doppelganger() {
     }
     panic!("failed to return DoppelgangerDetected effect");
 }
+
+#[test]
+fn drop_duplicate_vertex() {
+    let mut rng = TestRng::new();
+    let creator: ValidatorIndex = ValidatorIndex(0);
+    let validators = vec![(ALICE_PUBLIC_KEY.clone(), 100)];
+    let state: State<ClContext> = new_test_state(validators.iter().map(|(_pk, w)| *w), 0);
+    let panorama: Panorama<ClContext> = Panorama::from(vec![N]);
+    let seq_number = panorama.next_seq_num(&state, creator);
+    let now = Timestamp::zero();
+    let wunit: WireUnit<ClContext> = WireUnit {
+        panorama,
+        creator,
+        instance_id: ClContext::hash(INSTANCE_ID_DATA),
+        value: Some(Arc::new(BlockPayload::new(vec![], vec![], vec![], false))),
+        seq_number,
+        timestamp: now,
+        round_exp: 14,
+        endorsed: BTreeSet::new(),
+    };
+    let alice_keypair: Keypair = Keypair::from(Arc::clone(&*ALICE_SECRET_KEY));
+    let highway_message: HighwayMessage<ClContext> = HighwayMessage::NewVertex(Vertex::Unit(
+        SignedWireUnit::new(wunit.into_hashed(), &alice_keypair),
+    ));
+    let mut highway_protocol = new_test_highway_protocol(validators, vec![]);
+    let sender = *ALICE_NODE_ID;
+    let msg = bincode::serialize(&highway_message).unwrap();
+
+    // The first copy of the vertex is processed.
+    let mut outcomes = highway_protocol.handle_message(&mut rng, sender, msg.clone(), now);
+    assert!(
+        matches!(&*outcomes, [ProtocolOutcome::QueueAction(ACTION_ID_VERTEX)]),
+        "Unexpected outcomes: {:?}",
+        outcomes
+    );
+    while let Some(outcome) = outcomes.pop() {
+        if let ProtocolOutcome::QueueAction(ACTION_ID_VERTEX) = outcome {
+            outcomes.extend(highway_protocol.handle_action(ACTION_ID_VERTEX, now))
+        }
+    }
+
+    // An identical copy is dropped without any further processing.
+    let outcomes = highway_protocol.handle_message(&mut rng, sender, msg, now);
+    assert!(outcomes.is_empty(), "Unexpected outcomes: {:?}", outcomes);
+}
+
+#[test]
+fn recognize_evidence_by_prefix() {
+    let creator: ValidatorIndex = ValidatorIndex(0);
+    let validators = vec![(ALICE_PUBLIC_KEY.clone(), 100)];
+    let alice_keypair: Keypair = Keypair::from(Arc::clone(&*ALICE_SECRET_KEY));
+    let signed_unit = |timestamp: Timestamp| {
+        let wunit: WireUnit<ClContext> = WireUnit {
+            panorama: Panorama::from(vec![N]),
+            creator,
+            instance_id: ClContext::hash(INSTANCE_ID_DATA),
+            value: Some(Arc::new(BlockPayload::new(vec![], vec![], vec![], false))),
+            seq_number: 0,
+            timestamp,
+            round_exp: 14,
+            endorsed: BTreeSet::new(),
+        };
+        SignedWireUnit::new(wunit.into_hashed(), &alice_keypair)
+    };
+    let unit0 = signed_unit(Timestamp::zero());
+    let unit1 = signed_unit(Timestamp::zero() + TimeDiff::from(1));
+    let highway_protocol = new_test_highway_protocol(validators, vec![]);
+
+    let unit_msg: HighwayMessage<ClContext> =
+        HighwayMessage::NewVertex(Vertex::Unit(unit0.clone()));
+    let unit_msg = bincode::serialize(&unit_msg).unwrap();
+    assert!(!highway_protocol.is_evidence(&unit_msg));
+
+    let evidence = Evidence::Equivocation(unit0, unit1);
+    let evidence_msg: HighwayMessage<ClContext> =
+        HighwayMessage::NewVertex(Vertex::Evidence(evidence));
+    let evidence_msg = bincode::serialize(&evidence_msg).unwrap();
+    assert!(highway_protocol.is_evidence(&evidence_msg));
+}
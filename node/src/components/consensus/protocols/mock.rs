@@ -0,0 +1,249 @@
+//! A deterministic stand-in for a consensus protocol, for tests that exercise the era supervisor
+//! without running Highway.
+
+use std::{
+    any::Any,
+    collections::{BTreeMap, HashSet},
+    path::PathBuf,
+};
+
+use num::rational::Ratio;
+
+use casper_types::{TimeDiff, Timestamp, U512};
+
+use crate::{
+    components::consensus::{
+        config::Config,
+        consensus_protocol::{ConsensusProtocol, ProposedBlock, ProtocolOutcomes},
+        traits::Context,
+        ActionId, TimerId,
+    },
+    types::{Chainspec, NodeId},
+    NodeRng,
+};
+
+/// The length of a leader slot: the validators take turns in this order.
+const SLOT_LENGTH: TimeDiff = TimeDiff::from_seconds(1);
+
+/// A consensus protocol that never produces any outcomes. It only records what it receives, so
+/// that tests can inspect it.
+pub(crate) struct MockProtocol<C: Context> {
+    instance_id: C::InstanceId,
+    /// The validators, in the order in which they are leaders.
+    validators: Vec<C::ValidatorId>,
+    /// The validators known to be faulty.
+    faulty: HashSet<C::ValidatorId>,
+    /// Our own ID, if we are an active validator.
+    our_id: Option<C::ValidatorId>,
+    /// The start time of the era.
+    start_time: Timestamp,
+    /// The minimum end time of the era.
+    end_time: Timestamp,
+    /// All received messages, with their senders.
+    pub(crate) messages: Vec<(NodeId, Vec<u8>)>,
+    /// All proposed blocks.
+    pub(crate) proposals: Vec<ProposedBlock<C>>,
+    paused: bool,
+    evidence_only: bool,
+}
+
+impl<C: Context + 'static> MockProtocol<C> {
+    /// Creates a new boxed `MockProtocol` instance, with the same signature as Highway's
+    /// constructor.
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    pub(crate) fn new_boxed(
+        instance_id: C::InstanceId,
+        validator_stakes: BTreeMap<C::ValidatorId, U512>,
+        faulty: &HashSet<C::ValidatorId>,
+        _inactive: &HashSet<C::ValidatorId>,
+        chainspec: &Chainspec,
+        _config: &Config,
+        _ftt_fraction: Ratio<u64>,
+        _prev_cp: Option<&dyn ConsensusProtocol<C>>,
+        era_start_time: Timestamp,
+        _seed: u64,
+        _now: Timestamp,
+    ) -> (Box<dyn ConsensusProtocol<C>>, ProtocolOutcomes<C>) {
+        let mock = MockProtocol {
+            instance_id,
+            validators: validator_stakes.into_iter().map(|(id, _)| id).collect(),
+            faulty: faulty.clone(),
+            our_id: None,
+            start_time: era_start_time,
+            end_time: era_start_time + chainspec.core_config.era_duration,
+            messages: Vec::new(),
+            proposals: Vec::new(),
+            paused: false,
+            evidence_only: false,
+        };
+        (Box::new(mock), vec![])
+    }
+}
+
+impl<C: Context + 'static> ConsensusProtocol<C> for MockProtocol<C> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn handle_message(
+        &mut self,
+        _rng: &mut NodeRng,
+        sender: NodeId,
+        msg: Vec<u8>,
+        _now: Timestamp,
+    ) -> ProtocolOutcomes<C> {
+        if !self.evidence_only {
+            self.messages.push((sender, msg));
+        }
+        vec![]
+    }
+
+    fn is_urgent(&self, _msg: &[u8]) -> bool {
+        false
+    }
+
+    fn is_evidence(&self, msg: &[u8]) -> bool {
+        msg.starts_with(b"evidence against ")
+    }
+
+    fn handle_is_current(&self, _now: Timestamp) -> ProtocolOutcomes<C> {
+        vec![]
+    }
+
+    fn handle_timer(&mut self, _timestamp: Timestamp, _timer_id: TimerId) -> ProtocolOutcomes<C> {
+        vec![]
+    }
+
+    fn handle_action(&mut self, _action_id: ActionId, _now: Timestamp) -> ProtocolOutcomes<C> {
+        vec![]
+    }
+
+    fn propose(
+        &mut self,
+        proposed_block: ProposedBlock<C>,
+        _now: Timestamp,
+    ) -> ProtocolOutcomes<C> {
+        if !self.paused {
+            self.proposals.push(proposed_block);
+        }
+        vec![]
+    }
+
+    fn resolve_validity(
+        &mut self,
+        _proposed_block: ProposedBlock<C>,
+        _valid: bool,
+        _now: Timestamp,
+    ) -> ProtocolOutcomes<C> {
+        vec![]
+    }
+
+    fn activate_validator(
+        &mut self,
+        our_id: C::ValidatorId,
+        _secret: C::ValidatorSecret,
+        _timestamp: Timestamp,
+        _unit_hash_file: Option<PathBuf>,
+    ) -> ProtocolOutcomes<C> {
+        self.our_id = Some(our_id);
+        vec![]
+    }
+
+    fn deactivate_validator(&mut self) {
+        self.our_id = None;
+    }
+
+    fn set_evidence_only(&mut self) {
+        self.messages.clear();
+        self.proposals.clear();
+        self.evidence_only = true;
+    }
+
+    fn is_synced(&self) -> bool {
+        !self.messages.is_empty()
+    }
+
+    fn has_evidence(&self, vid: &C::ValidatorId) -> bool {
+        self.faulty.contains(vid)
+    }
+
+    fn latest_unit_timestamp(&self, _vid: &C::ValidatorId) -> Option<Timestamp> {
+        None
+    }
+
+    fn mark_faulty(&mut self, vid: &C::ValidatorId) {
+        self.faulty.insert(vid.clone());
+    }
+
+    fn request_evidence(&self, _sender: NodeId, _vid: &C::ValidatorId) -> ProtocolOutcomes<C> {
+        vec![]
+    }
+
+    fn evidence_message(&self, vid: &C::ValidatorId) -> Option<Vec<u8>> {
+        self.faulty
+            .contains(vid)
+            .then(|| format!("evidence against {}", vid).into_bytes())
+    }
+
+    fn leader(&self, timestamp: Timestamp) -> Option<&C::ValidatorId> {
+        let slot = timestamp.millis().checked_div(SLOT_LENGTH.millis())?;
+        let index = slot.checked_rem(self.validators.len() as u64)?;
+        self.validators.get(index as usize)
+    }
+
+    fn next_leader(&self, now: Timestamp) -> Option<(&C::ValidatorId, Timestamp)> {
+        if self.evidence_only {
+            return None;
+        }
+        let slot = now.millis().checked_div(SLOT_LENGTH.millis())?;
+        let next_slot_start =
+            Timestamp::from(slot.saturating_add(1).saturating_mul(SLOT_LENGTH.millis()));
+        self.leader(next_slot_start)
+            .map(|leader| (leader, next_slot_start))
+    }
+
+    fn leader_schedule(&self) -> Option<Vec<&C::ValidatorId>> {
+        if self.evidence_only {
+            return None;
+        }
+        let first_slot = self
+            .start_time
+            .millis()
+            .checked_add(SLOT_LENGTH.millis().saturating_sub(1))?
+            .checked_div(SLOT_LENGTH.millis())?;
+        let schedule = (first_slot..)
+            .map(|slot| Timestamp::from(slot.saturating_mul(SLOT_LENGTH.millis())))
+            .take_while(|slot_start| *slot_start < self.end_time)
+            .filter_map(|slot_start| self.leader(slot_start))
+            .collect();
+        Some(schedule)
+    }
+
+    fn request_missing_units(&self) -> ProtocolOutcomes<C> {
+        vec![]
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    fn validators_with_evidence(&self) -> Vec<&C::ValidatorId> {
+        self.faulty.iter().collect()
+    }
+
+    fn has_received_messages(&self) -> bool {
+        !self.messages.is_empty()
+    }
+
+    fn is_active(&self) -> bool {
+        self.our_id.is_some()
+    }
+
+    fn instance_id(&self) -> &C::InstanceId {
+        &self.instance_id
+    }
+
+    fn next_round_length(&self) -> Option<TimeDiff> {
+        None
+    }
+}
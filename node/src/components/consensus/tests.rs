@@ -1,6 +1,12 @@
 /// Basic building blocks for the Discrete Event Simulator (DES).
 pub(crate) mod consensus_des_testing;
+/// Property tests that drive random sequences of events through the era supervisor.
+mod fuzz;
+/// A minimal reactor that captures the effects of consensus components.
+pub(crate) mod harness;
 /// Message queue.
 pub(crate) mod queue;
 /// Miscellaneous code shared among consensus tests
 pub(crate) mod utils;
+/// Golden vectors for the serialized consensus messages.
+mod wire_format;
@@ -0,0 +1,314 @@
+use std::{panic, sync::Arc, thread};
+
+use prometheus::Registry;
+use proptest::{collection::vec, prelude::*};
+
+use casper_types::{testing::TestRng, EraId, TimeDiff, Timestamp, U512};
+
+use crate::{
+    components::{
+        consensus::{
+            protocols::highway::ACTION_ID_VERTEX,
+            tests::{
+                harness::{run_effects, TestEvent},
+                utils::{
+                    new_test_chainspec, switch_block_header, ALICE_PUBLIC_KEY, ALICE_SECRET_KEY,
+                    BOB_PUBLIC_KEY,
+                },
+            },
+            BlockContext, Config, ConsensusMessage, EraSupervisor, Event, NewBlockPayload,
+            ProposedBlock, ResolveValidity, TimerId,
+        },
+        Component,
+    },
+    effect::incoming::ConsensusMessageIncoming,
+    testing::ComponentHarness,
+    types::{BlockHeader, BlockPayload, Chainspec, NodeId},
+};
+
+/// The maximum number of events handled for a single generated event, including the ones its
+/// effects produce, so that feedback loops can't stall the test.
+const MAX_EVENTS_PER_STEP: usize = 100;
+
+/// An event for the era supervisor, with its era given relative to the current one: 0 and 1 are
+/// past eras, 2 is the current era, 3 and 4 are future ones.
+#[derive(Clone, Debug)]
+enum FuzzEvent {
+    Timer {
+        era: u8,
+        offset: u64,
+        timer_id: u8,
+    },
+    Action {
+        era: u8,
+    },
+    Message {
+        era: u8,
+        sender: u8,
+        payload: Vec<u8>,
+    },
+    Ping {
+        era: u8,
+        sender: u8,
+        nonce: u64,
+    },
+    EvidenceRequest {
+        era: u8,
+        sender: u8,
+    },
+    NewBlockPayload {
+        era: u8,
+        offset: u64,
+    },
+    ResolveValidity {
+        era: u8,
+        sender: u8,
+        offset: u64,
+        valid: bool,
+    },
+    NextEra {
+        bob_weight: u64,
+    },
+}
+
+fn fuzz_event_arb() -> impl Strategy<Value = FuzzEvent> {
+    let era = 0..5u8;
+    let sender = 0..3u8;
+    let offset = 0..100_000u64;
+    prop_oneof![
+        // Highway only schedules the timers 0 to 6, and panics on any other ID.
+        (era.clone(), offset.clone(), 0..7u8).prop_map(|(era, offset, timer_id)| {
+            FuzzEvent::Timer {
+                era,
+                offset,
+                timer_id,
+            }
+        }),
+        era.clone().prop_map(|era| FuzzEvent::Action { era }),
+        (era.clone(), sender.clone(), vec(any::<u8>(), 0..64)).prop_map(
+            |(era, sender, payload)| FuzzEvent::Message {
+                era,
+                sender,
+                payload,
+            }
+        ),
+        (era.clone(), sender.clone(), any::<u64>())
+            .prop_map(|(era, sender, nonce)| FuzzEvent::Ping { era, sender, nonce }),
+        (era.clone(), sender.clone())
+            .prop_map(|(era, sender)| FuzzEvent::EvidenceRequest { era, sender }),
+        (era.clone(), offset.clone())
+            .prop_map(|(era, offset)| FuzzEvent::NewBlockPayload { era, offset }),
+        (era, sender, offset, any::<bool>()).prop_map(|(era, sender, offset, valid)| {
+            FuzzEvent::ResolveValidity {
+                era,
+                sender,
+                offset,
+                valid,
+            }
+        }),
+        (0..=100u64).prop_map(|bob_weight| FuzzEvent::NextEra { bob_weight }),
+    ]
+}
+
+/// An `EraSupervisor` running as Alice, with small buffers so that their limits are reached.
+struct FuzzTest {
+    harness: ComponentHarness<TestEvent>,
+    supervisor: EraSupervisor,
+    chainspec: Arc<Chainspec>,
+    switch_blocks: Vec<BlockHeader>,
+    senders: Vec<NodeId>,
+    start: Timestamp,
+}
+
+impl FuzzTest {
+    fn new(rng: TestRng) -> Self {
+        let validators = vec![
+            (ALICE_PUBLIC_KEY.clone(), U512::from(100)),
+            (BOB_PUBLIC_KEY.clone(), U512::from(100)),
+        ];
+        let chainspec = Arc::new(new_test_chainspec(validators.clone()));
+        let switch_block =
+            switch_block_header(&chainspec, EraId::from(0), validators.into_iter().collect());
+        let config = Config {
+            max_buffered_future_messages: 3,
+            max_queued_messages: Some(4),
+            message_batch_window: Some(TimeDiff::from_seconds(1)),
+            max_message_batch_size: 2,
+            ..Config::default()
+        };
+        let mut harness = ComponentHarness::builder().rng(rng).build();
+        let switch_blocks = vec![switch_block];
+        let (supervisor, _) = EraSupervisor::from_switch_blocks(
+            EraId::from(1),
+            harness.tmp.path(),
+            Arc::clone(&*ALICE_SECRET_KEY),
+            ALICE_PUBLIC_KEY.clone(),
+            config,
+            harness.effect_builder,
+            Arc::clone(&chainspec),
+            &switch_blocks[0],
+            None,
+            &Registry::new(),
+            &switch_blocks,
+            &mut harness.rng,
+        );
+        let senders = (0..3).map(|_| NodeId::random(&mut harness.rng)).collect();
+        FuzzTest {
+            harness,
+            supervisor,
+            chainspec,
+            switch_blocks,
+            senders,
+            start: Timestamp::now(),
+        }
+    }
+
+    /// Returns the era with the given index relative to the current era.
+    fn era_id(&self, era: u8) -> EraId {
+        let value = self.supervisor.current_era().value();
+        EraId::from(value.saturating_add(u64::from(era)).saturating_sub(2))
+    }
+
+    fn sender(&self, sender: u8) -> NodeId {
+        self.senders[sender as usize % self.senders.len()]
+    }
+
+    fn timestamp(&self, offset: u64) -> Timestamp {
+        self.start + TimeDiff::from(offset)
+    }
+
+    fn incoming(&self, sender: u8, message: ConsensusMessage) -> Event {
+        Event::Incoming(ConsensusMessageIncoming {
+            sender: self.sender(sender),
+            message,
+        })
+    }
+
+    /// Returns the supervisor event described by the fuzz event.
+    fn event(&mut self, fuzz_event: FuzzEvent) -> Event {
+        match fuzz_event {
+            FuzzEvent::Timer {
+                era,
+                offset,
+                timer_id,
+            } => Event::Timer {
+                era_id: self.era_id(era),
+                timestamp: self.timestamp(offset),
+                timer_id: TimerId(timer_id),
+            },
+            FuzzEvent::Action { era } => Event::Action {
+                era_id: self.era_id(era),
+                action_id: ACTION_ID_VERTEX,
+            },
+            FuzzEvent::Message {
+                era,
+                sender,
+                payload,
+            } => {
+                let era_id = self.era_id(era);
+                self.incoming(sender, ConsensusMessage::Protocol { era_id, payload })
+            }
+            FuzzEvent::Ping { era, sender, nonce } => {
+                let era_id = self.era_id(era);
+                self.incoming(sender, ConsensusMessage::Ping { era_id, nonce })
+            }
+            FuzzEvent::EvidenceRequest { era, sender } => {
+                let era_id = self.era_id(era);
+                let pub_key = BOB_PUBLIC_KEY.clone();
+                self.incoming(
+                    sender,
+                    ConsensusMessage::EvidenceRequest { era_id, pub_key },
+                )
+            }
+            FuzzEvent::NewBlockPayload { era, offset } => Event::NewBlockPayload(NewBlockPayload {
+                era_id: self.era_id(era),
+                block_payload: Arc::new(BlockPayload::new(vec![], vec![], vec![], false)),
+                block_context: BlockContext::new(self.timestamp(offset), vec![]),
+            }),
+            FuzzEvent::ResolveValidity {
+                era,
+                sender,
+                offset,
+                valid,
+            } => {
+                let block_payload = BlockPayload::new(vec![], vec![], vec![], false);
+                let block_context = BlockContext::new(self.timestamp(offset), vec![]);
+                Event::ResolveValidity(ResolveValidity {
+                    era_id: self.era_id(era),
+                    sender: self.sender(sender),
+                    proposed_block: ProposedBlock::new(Arc::new(block_payload), block_context),
+                    valid,
+                })
+            }
+            FuzzEvent::NextEra { bob_weight } => {
+                let validators = vec![
+                    (ALICE_PUBLIC_KEY.clone(), U512::from(100)),
+                    (BOB_PUBLIC_KEY.clone(), U512::from(bob_weight)),
+                ];
+                let current_era = self.supervisor.current_era();
+                self.switch_blocks.push(switch_block_header(
+                    &self.chainspec,
+                    current_era,
+                    validators.into_iter().collect(),
+                ));
+                Event::CreateNewEra {
+                    switch_blocks: self.switch_blocks.clone(),
+                }
+            }
+        }
+    }
+
+    /// Handles the event and all consensus events its effects produce, checking the invariants
+    /// after each one.
+    fn handle(&mut self, event: Event) {
+        let mut events = vec![event];
+        for _ in 0..MAX_EVENTS_PER_STEP {
+            let event = match events.pop() {
+                Some(event) => event,
+                None => return,
+            };
+            let era_before = self.supervisor.current_era();
+            let effects = self.supervisor.handle_event(
+                self.harness.effect_builder,
+                &mut self.harness.rng,
+                event,
+            );
+            events.extend(
+                run_effects(&mut self.harness, effects)
+                    .into_iter()
+                    .filter_map(|event| match event {
+                        TestEvent::Consensus(event) => Some(event),
+                        _ => None,
+                    }),
+            );
+            let current_era = self.supervisor.current_era();
+            assert!(current_era >= era_before, "current era went back");
+            assert!(self.supervisor.open_eras().contains_key(&current_era));
+            self.supervisor.assert_buffers_bounded();
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn should_handle_random_event_sequences(
+        seed in any::<[u8; 16]>(),
+        fuzz_events in vec(fuzz_event_arb(), 1..50),
+    ) {
+        // There can only be one `TestRng` per thread, so every case runs in a new thread, with an
+        // RNG derived from the proptest seed.
+        let result = thread::spawn(move || {
+            let mut test = FuzzTest::new(TestRng::from_seed(seed));
+            for fuzz_event in fuzz_events {
+                let event = test.event(fuzz_event);
+                test.handle(event);
+            }
+        })
+        .join();
+        if let Err(payload) = result {
+            panic::resume_unwind(payload);
+        }
+    }
+}
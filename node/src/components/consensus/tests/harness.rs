@@ -0,0 +1,141 @@
+use std::iter::FromIterator;
+
+use derive_more::From;
+use futures::FutureExt;
+
+use crate::{
+    components::consensus::Event,
+    effect::{
+        announcements::{BlocklistAnnouncement, ConsensusAnnouncement, ControlAnnouncement},
+        requests::{
+            BlockProposerRequest, BlockValidationRequest, ChainspecLoaderRequest,
+            ContractRuntimeRequest, NetworkInfoRequest, NetworkRequest, StateStoreRequest,
+            StorageRequest,
+        },
+        Effects,
+    },
+    protocol::Message,
+    reactor::ReactorEvent,
+    testing::ComponentHarness,
+};
+
+/// The events an `EraSupervisor` can schedule on the reactor's queue.
+#[derive(Debug, From)]
+pub(crate) enum TestEvent {
+    #[from]
+    Consensus(Event),
+    #[from]
+    Network(NetworkRequest<Message>),
+    #[from]
+    NetworkInfo(NetworkInfoRequest),
+    #[from]
+    BlockProposer(BlockProposerRequest),
+    #[from]
+    ConsensusAnnouncement(ConsensusAnnouncement),
+    #[from]
+    BlockValidation(BlockValidationRequest),
+    #[from]
+    Storage(StorageRequest),
+    #[from]
+    StateStore(StateStoreRequest),
+    #[from]
+    ContractRuntime(ContractRuntimeRequest),
+    #[from]
+    ChainspecLoader(ChainspecLoaderRequest),
+    #[from]
+    Blocklist(BlocklistAnnouncement),
+    #[from]
+    ControlAnnouncement(ControlAnnouncement),
+}
+
+impl ReactorEvent for TestEvent {
+    fn as_control(&self) -> Option<&ControlAnnouncement> {
+        if let Self::ControlAnnouncement(ref ctrl_ann) = self {
+            Some(ctrl_ann)
+        } else {
+            None
+        }
+    }
+
+    fn try_into_control(self) -> Option<ControlAnnouncement> {
+        if let Self::ControlAnnouncement(ctrl_ann) = self {
+            Some(ctrl_ann)
+        } else {
+            None
+        }
+    }
+}
+
+/// The events produced by consensus effects, sorted by kind.
+#[derive(Debug, Default)]
+pub(crate) struct CapturedEffects {
+    /// Events for the consensus component itself, e.g. elapsed timers.
+    pub(crate) consensus: Vec<Event>,
+    /// Messages to be sent to peers.
+    pub(crate) network: Vec<NetworkRequest<Message>>,
+    /// Requests for information about the connected peers.
+    pub(crate) network_info: Vec<NetworkInfoRequest>,
+    /// Requests for block payloads.
+    pub(crate) block_proposer: Vec<BlockProposerRequest>,
+    /// Consensus announcements, e.g. finalized blocks.
+    pub(crate) announcements: Vec<ConsensusAnnouncement>,
+    /// Requests to validate proposed blocks.
+    pub(crate) block_validation: Vec<BlockValidationRequest>,
+    /// Storage requests.
+    pub(crate) storage: Vec<StorageRequest>,
+    /// Requests to save or load the component's own state.
+    pub(crate) state_store: Vec<StateStoreRequest>,
+    /// Contract runtime requests.
+    pub(crate) contract_runtime: Vec<ContractRuntimeRequest>,
+    /// Chainspec loader requests.
+    pub(crate) chainspec_loader: Vec<ChainspecLoaderRequest>,
+    /// Peers that were reported for misbehavior.
+    pub(crate) blocklist: Vec<BlocklistAnnouncement>,
+    /// Control announcements, e.g. fatal errors.
+    pub(crate) control: Vec<ControlAnnouncement>,
+}
+
+impl FromIterator<TestEvent> for CapturedEffects {
+    fn from_iter<I: IntoIterator<Item = TestEvent>>(events: I) -> Self {
+        let mut captured = CapturedEffects::default();
+        for event in events {
+            match event {
+                TestEvent::Consensus(event) => captured.consensus.push(event),
+                TestEvent::Network(request) => captured.network.push(request),
+                TestEvent::NetworkInfo(request) => captured.network_info.push(request),
+                TestEvent::BlockProposer(request) => captured.block_proposer.push(request),
+                TestEvent::ConsensusAnnouncement(ann) => captured.announcements.push(ann),
+                TestEvent::BlockValidation(request) => captured.block_validation.push(request),
+                TestEvent::Storage(request) => captured.storage.push(request),
+                TestEvent::StateStore(request) => captured.state_store.push(request),
+                TestEvent::ContractRuntime(request) => captured.contract_runtime.push(request),
+                TestEvent::ChainspecLoader(request) => captured.chainspec_loader.push(request),
+                TestEvent::Blocklist(ann) => captured.blocklist.push(ann),
+                TestEvent::ControlAnnouncement(ann) => captured.control.push(ann),
+            }
+        }
+        captured
+    }
+}
+
+/// Runs the effects until they complete or wait for a response, and returns all events they
+/// produced or scheduled on the harness' queue.
+///
+/// Effects that are still pending afterwards, e.g. timers or requests waiting for a response, are
+/// dropped.
+pub(crate) fn run_effects(
+    harness: &mut ComponentHarness<TestEvent>,
+    effects: Effects<Event>,
+) -> Vec<TestEvent> {
+    let mut events = vec![];
+    for effect in effects {
+        if let Some(output) = harness.runtime.block_on(async { effect.now_or_never() }) {
+            events.extend(output.into_iter().map(TestEvent::Consensus));
+        }
+    }
+    while !harness.is_idle() {
+        let ((_ancestor, event), _) = harness.runtime.block_on(harness.scheduler.pop());
+        events.push(event);
+    }
+    events
+}
@@ -1,15 +1,20 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 use num::Zero;
 use once_cell::sync::Lazy;
 
-use casper_types::{system::auction::DelegationRate, Motes, PublicKey, SecretKey, Timestamp, U512};
+use casper_hashing::Digest;
+use casper_types::{
+    system::auction::DelegationRate, EraId, Motes, PublicKey, SecretKey, Timestamp, U512,
+};
 
 use crate::{
+    components::consensus::EraReport,
     tls::{KeyFingerprint, Sha512},
     types::{
         chainspec::{AccountConfig, AccountsConfig, ValidatorConfig},
-        ActivationPoint, Chainspec, ChainspecRawBytes, NodeId,
+        ActivationPoint, Block, BlockHash, BlockHeader, BlockPayload, Chainspec, ChainspecRawBytes,
+        FinalizedBlock, NodeId,
     },
     utils::Loadable,
 };
@@ -28,6 +33,10 @@ pub static BOB_PRIVATE_KEY: Lazy<SecretKey> =
     Lazy::new(|| SecretKey::ed25519_from_bytes([1; SecretKey::ED25519_LENGTH]).unwrap());
 pub static BOB_PUBLIC_KEY: Lazy<PublicKey> = Lazy::new(|| PublicKey::from(&*BOB_PRIVATE_KEY));
 
+pub static CAROL_PRIVATE_KEY: Lazy<SecretKey> =
+    Lazy::new(|| SecretKey::ed25519_from_bytes([2; SecretKey::ED25519_LENGTH]).unwrap());
+pub static CAROL_PUBLIC_KEY: Lazy<PublicKey> = Lazy::new(|| PublicKey::from(&*CAROL_PRIVATE_KEY));
+
 /// Loads the local chainspec and overrides timestamp and genesis account with the given stakes.
 /// The test `Chainspec` returned has eras with exactly two blocks.
 pub fn new_test_chainspec<I, T>(stakes: I) -> Chainspec
@@ -53,3 +62,30 @@ where
     chainspec.core_config.era_duration = 0.into();
     chainspec
 }
+
+/// Returns the header of a switch block in `era_id`, whose successor era has the given validators.
+pub fn switch_block_header(
+    chainspec: &Chainspec,
+    era_id: EraId,
+    validators: BTreeMap<PublicKey, U512>,
+) -> BlockHeader {
+    let finalized_block = FinalizedBlock::new(
+        BlockPayload::new(vec![], vec![], vec![], false),
+        Some(EraReport::default()),
+        Timestamp::now(),
+        era_id,
+        era_id.value(),
+        ALICE_PUBLIC_KEY.clone(),
+    );
+    Block::new(
+        BlockHash::default(),
+        Digest::default(),
+        Digest::default(),
+        finalized_block,
+        Some(validators),
+        chainspec.protocol_config.version,
+        chainspec.protocol_config.verifiable_chunked_hash_activation,
+    )
+    .expect("failed to create switch block")
+    .take_header()
+}
@@ -0,0 +1,119 @@
+//! Golden vectors for the serialized `ConsensusMessage` variants.
+//!
+//! Consensus messages are sent to peers that may run a different node version, so their
+//! serialized layout must not change by accident, e.g. by reordering or renaming variants or
+//! fields. If one of these tests fails after an intentional change, the network's wire version
+//! needs to be bumped together with the golden vectors. New variants need a golden vector, too:
+//! `golden_hex` doesn't compile until they have one.
+
+use bincode::Options;
+
+use casper_hashing::Digest;
+use casper_types::{EraId, PublicKey, Signature};
+
+use crate::{
+    components::{consensus::ConsensusMessage, small_network::BincodeFormat},
+    types::{BlockHash, BlockSignatures},
+};
+
+/// The hash used in all test messages that contain one.
+const BLOCK_HASH_BYTES: [u8; Digest::LENGTH] = [7; Digest::LENGTH];
+
+/// The serialized hash: its length, followed by the bytes.
+const BLOCK_HASH_HEX: &str = "200707070707070707070707070707070707070707070707070707070707070707";
+
+/// Returns the expected serialization of the given message from `test_messages`, in hex.
+fn golden_hex(message: &ConsensusMessage) -> String {
+    match message {
+        // Variant 0, era 1, payload [1, 2, 3].
+        ConsensusMessage::Protocol { .. } => "00_01_03010203".to_string(),
+        // Variant 1, era 1, system public key.
+        ConsensusMessage::EvidenceRequest { .. } => "01_01_00".to_string(),
+        // Variant 2, era 1, nonce 300 as a three-byte varint.
+        ConsensusMessage::Ping { .. } => "02_01_fb2c01".to_string(),
+        // Variant 3, era 1, nonce 300 as a three-byte varint.
+        ConsensusMessage::Pong { .. } => "03_01_fb2c01".to_string(),
+        // Variant 4, era 1, payloads [[1], [2, 3]].
+        ConsensusMessage::ProtocolBatch { .. } => "04_01_02_0101_020203".to_string(),
+        // Variant 5, era 1, block hash.
+        ConsensusMessage::BlockSignaturesRequest { .. } => format!("05_01_{}", BLOCK_HASH_HEX),
+        // Variant 6, era 1, block hash, no signatures.
+        ConsensusMessage::BlockSignaturesResponse {
+            signatures: None, ..
+        } => format!("06_01_{}_00", BLOCK_HASH_HEX),
+        // Variant 6, era 1, block hash, signatures with block hash, era 1 and one proof by the
+        // system key.
+        ConsensusMessage::BlockSignaturesResponse {
+            signatures: Some(_),
+            ..
+        } => format!("06_01_{}_01_{}_01_01_00_00", BLOCK_HASH_HEX, BLOCK_HASH_HEX),
+        // Variant 7, era 1, payload [1, 2, 3], system public key, system signature.
+        ConsensusMessage::SignedProtocol { .. } => "07_01_03010203_00_00".to_string(),
+    }
+    .replace('_', "")
+}
+
+/// Returns one message of each variant, with fixed field values.
+fn test_messages() -> Vec<ConsensusMessage> {
+    let era_id = EraId::from(1);
+    let block_hash = BlockHash::new(Digest::from(BLOCK_HASH_BYTES));
+    let mut signatures = BlockSignatures::new(block_hash, era_id);
+    signatures.insert_proof(PublicKey::System, Signature::System);
+    vec![
+        ConsensusMessage::Protocol {
+            era_id,
+            payload: vec![1, 2, 3],
+        },
+        ConsensusMessage::EvidenceRequest {
+            era_id,
+            pub_key: PublicKey::System,
+        },
+        ConsensusMessage::Ping { era_id, nonce: 300 },
+        ConsensusMessage::Pong { era_id, nonce: 300 },
+        ConsensusMessage::ProtocolBatch {
+            era_id,
+            payloads: vec![vec![1], vec![2, 3]],
+        },
+        ConsensusMessage::BlockSignaturesRequest { era_id, block_hash },
+        ConsensusMessage::BlockSignaturesResponse {
+            era_id,
+            block_hash,
+            signatures: None,
+        },
+        ConsensusMessage::BlockSignaturesResponse {
+            era_id,
+            block_hash,
+            signatures: Some(signatures),
+        },
+        ConsensusMessage::SignedProtocol {
+            era_id,
+            payload: vec![1, 2, 3],
+            public_key: PublicKey::System,
+            signature: Signature::System,
+        },
+    ]
+}
+
+#[test]
+fn should_match_golden_serialization() {
+    for message in test_messages() {
+        let bytes = BincodeFormat::default().0.serialize(&message).unwrap();
+        assert_eq!(
+            base16::encode_lower(&bytes),
+            golden_hex(&message),
+            "the serialized layout of {} changed; if this is intentional, bump the wire version \
+             and update the golden vector",
+            message
+        );
+    }
+}
+
+#[test]
+fn should_deserialize_golden_vectors() {
+    for message in test_messages() {
+        let bytes = base16::decode(&golden_hex(&message)).unwrap();
+        let deserialized: ConsensusMessage =
+            BincodeFormat::default().0.deserialize(&bytes).unwrap();
+        assert_eq!(deserialized, message);
+    }
+}
@@ -1,10 +1,40 @@
-use std::collections::{BTreeMap, HashSet};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::{self, Display, Formatter},
+};
 
-use casper_types::{PublicKey, U512};
+use hex_fmt::HexFmt;
 use num::rational::Ratio;
 
+use casper_hashing::Digest;
+use casper_types::{PublicKey, U512};
+
 use crate::{components::consensus::error::FinalitySignatureError, types::BlockSignatures};
 
+/// The maximum length of a payload that is logged in full; longer ones are redacted.
+const MAX_UNREDACTED_PAYLOAD_LENGTH: usize = 16;
+
+/// A wrapper for logging message payloads.
+///
+/// Short payloads are displayed in hex. Longer ones are only displayed by their length and a
+/// truncated hash, so that they don't flood the logs.
+pub(crate) struct RedactedPayload<'a>(pub(crate) &'a [u8]);
+
+impl<'a> Display for RedactedPayload<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.0.len() <= MAX_UNREDACTED_PAYLOAD_LENGTH {
+            write!(f, "{}", HexFmt(self.0))
+        } else {
+            write!(
+                f,
+                "len={} hash={:10}",
+                self.0.len(),
+                HexFmt(Digest::hash(self.0))
+            )
+        }
+    }
+}
+
 /// Computes the lower bound for the fraction of weight of signatures that will be considered
 /// sufficient.
 fn lower_bound(finality_threshold_fraction: Ratio<u64>) -> Ratio<u64> {
@@ -141,9 +171,11 @@ pub(crate) fn get_minimal_set_of_signatures(
 mod tests {
     use std::collections::BTreeMap;
 
+    use hex_fmt::HexFmt;
     use num::rational::Ratio;
     use rand::Rng;
 
+    use casper_hashing::Digest;
     use casper_types::{
         crypto::{generate_ed25519_keypair, sign},
         testing::TestRng,
@@ -152,7 +184,10 @@ mod tests {
 
     use crate::types::{BlockHash, BlockSignatures};
 
-    use super::{check_sufficient_finality_signatures, get_minimal_set_of_signatures};
+    use super::{
+        check_sufficient_finality_signatures, get_minimal_set_of_signatures, RedactedPayload,
+        MAX_UNREDACTED_PAYLOAD_LENGTH,
+    };
 
     fn generate_validators(
         n_validators: usize,
@@ -230,4 +265,19 @@ mod tests {
         test_number_of_validators(&mut rng, 9, ftt, 7);
         test_number_of_validators(&mut rng, 10, ftt, 7);
     }
+
+    #[test]
+    fn should_redact_large_payloads() {
+        let small_payload = vec![0xab; MAX_UNREDACTED_PAYLOAD_LENGTH];
+        assert_eq!(
+            RedactedPayload(&small_payload).to_string(),
+            "ab".repeat(MAX_UNREDACTED_PAYLOAD_LENGTH)
+        );
+
+        let large_payload = vec![0xab; 1234];
+        let rendered = RedactedPayload(&large_payload).to_string();
+        let expected_hash = format!("{:10}", HexFmt(Digest::hash(&large_payload)));
+        assert_eq!(rendered, format!("len=1234 hash={}", expected_hash));
+        assert!(rendered.len() < large_payload.len());
+    }
 }
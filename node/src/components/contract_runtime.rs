@@ -680,16 +680,31 @@ impl ContractRuntime {
         result
     }
 
+    /// Commits an upgrade request.
+    ///
+    /// On success, the chainspec's host function costs are scheduled for the new protocol version,
+    /// so that they apply to exactly the blocks from the upgrade on.
     fn commit_upgrade(
         &self,
         upgrade_config: UpgradeConfig,
     ) -> Result<UpgradeSuccess, engine_state::Error> {
         debug!(?upgrade_config, "upgrade");
         let start = Instant::now();
+        let new_protocol_version = upgrade_config.new_protocol_version();
         let result = self
             .engine_state
             .commit_upgrade(CorrelationId::new(), upgrade_config);
         self.engine_state.flush_environment()?;
+        if result.is_ok() {
+            let host_function_costs = self
+                .engine_state
+                .config()
+                .wasm_config()
+                .host_function_costs()
+                .clone();
+            self.engine_state
+                .schedule_host_function_costs(new_protocol_version, host_function_costs);
+        }
         self.metrics
             .commit_upgrade
             .observe(start.elapsed().as_secs_f64());
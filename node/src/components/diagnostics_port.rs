@@ -22,7 +22,8 @@ use tracing::{debug, warn};
 use super::Component;
 use crate::{
     effect::{
-        announcements::ControlAnnouncement, diagnostics_port::DumpConsensusStateRequest,
+        announcements::ControlAnnouncement,
+        diagnostics_port::{ConsensusActionRequest, DumpConsensusStateRequest},
         EffectBuilder, EffectExt, Effects,
     },
     reactor::EventQueueHandle,
@@ -70,7 +71,10 @@ impl DiagnosticsPort {
         event_queue: EventQueueHandle<REv>,
     ) -> Result<(Self, Effects<Event>), Error>
     where
-        REv: From<DumpConsensusStateRequest> + From<ControlAnnouncement> + Send,
+        REv: From<DumpConsensusStateRequest>
+            + From<ConsensusActionRequest>
+            + From<ControlAnnouncement>
+            + Send,
     {
         let config = cfg.value();
         let (shutdown_sender, shutdown_receiver) = watch::channel(());
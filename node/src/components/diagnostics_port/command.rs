@@ -1,5 +1,6 @@
 use std::{
     fmt::{self, Display, Formatter},
+    path::PathBuf,
     str::FromStr,
 };
 
@@ -86,6 +87,25 @@ pub(super) enum Action {
     },
     /// Dump the event queues.
     DumpQueues,
+    /// Replay a consensus message log, feeding the recorded messages into consensus again.
+    ///
+    /// This is meant for reproducing a stuck era in a node that was started from a copy of the
+    /// stuck node's storage, without any peers.
+    ReplayConsensusLog {
+        /// Path to the message log, on the node's machine.
+        path: PathBuf,
+    },
+    /// Request the units and evidence missing in an era from peers again.
+    ///
+    /// This is a recovery tool for a stalled node, and safe to use repeatedly.
+    ForceEraSync {
+        /// Era to sync. If omitted, syncs the current era.
+        era: Option<u64>,
+    },
+    /// Show the eras consensus keeps in memory, with the number of validators in each, and the
+    /// peers that sent consensus messages, the ones with the best reputation first, and how long
+    /// it took to detect the last equivocation.
+    ConsensusStatus,
     /// Close connection server-side.
     Quit,
 }
@@ -117,5 +137,18 @@ mod tests {
 
         let cmd = Command::from_line("dump-queues").expect("command parsing failed");
         assert!(matches!(cmd.action, Action::DumpQueues));
+
+        let cmd = Command::from_line("replay-consensus-log /tmp/consensus_messages.bin")
+            .expect("command parsing failed");
+        assert!(matches!(
+            cmd.action,
+            Action::ReplayConsensusLog { ref path } if path.ends_with("consensus_messages.bin")
+        ));
+
+        let cmd = Command::from_line("force-era-sync 5").expect("command parsing failed");
+        assert!(matches!(cmd.action, Action::ForceEraSync { era } if era == Some(5)));
+
+        let cmd = Command::from_line("consensus-status").expect("command parsing failed");
+        assert!(matches!(cmd.action, Action::ConsensusStatus));
     }
 }
@@ -31,7 +31,7 @@ use crate::{
     components::consensus::EraDump,
     effect::{
         announcements::{ControlAnnouncement, QueueDumpFormat},
-        diagnostics_port::DumpConsensusStateRequest,
+        diagnostics_port::{ConsensusAction, ConsensusActionRequest, DumpConsensusStateRequest},
         EffectBuilder,
     },
     utils::display_error,
@@ -195,7 +195,10 @@ impl Session {
         line: &str,
     ) -> io::Result<bool>
     where
-        REv: From<DumpConsensusStateRequest> + From<ControlAnnouncement> + Send,
+        REv: From<DumpConsensusStateRequest>
+            + From<ConsensusActionRequest>
+            + From<ControlAnnouncement>
+            + Send,
     {
         debug!(%line, "line received");
         match Command::from_line(line) {
@@ -276,6 +279,24 @@ impl Session {
                             }
                         };
                     }
+                    Action::ReplayConsensusLog { ref path } => {
+                        let action = ConsensusAction::ReplayMessageLog(path.clone());
+                        self.perform_consensus_action(effect_builder, writer, action)
+                            .await?;
+                    }
+                    Action::ForceEraSync { era } => {
+                        let action = ConsensusAction::ForceEraSync(era.map(EraId::new));
+                        self.perform_consensus_action(effect_builder, writer, action)
+                            .await?;
+                    }
+                    Action::ConsensusStatus => {
+                        self.perform_consensus_action(
+                            effect_builder,
+                            writer,
+                            ConsensusAction::Status,
+                        )
+                        .await?;
+                    }
                     Action::Quit => {
                         self.send_outcome(writer, &Outcome::success("goodbye!"))
                             .await?;
@@ -292,6 +313,26 @@ impl Session {
         Ok(true)
     }
 
+    /// Makes the consensus component perform an action, and sends the outcome to the client.
+    async fn perform_consensus_action<REv>(
+        &self,
+        effect_builder: EffectBuilder<REv>,
+        writer: &mut OwnedWriteHalf,
+        action: ConsensusAction,
+    ) -> io::Result<()>
+    where
+        REv: From<ConsensusActionRequest> + Send,
+    {
+        let outcome = match effect_builder
+            .diagnostics_port_consensus_action(action)
+            .await
+        {
+            Ok(msg) => Outcome::success(msg),
+            Err(err) => Outcome::failed(err),
+        };
+        self.send_outcome(writer, &outcome).await
+    }
+
     /// Obtains a queue dump from the reactor.
     ///
     /// Returns an open file that contains the entire dump.
@@ -393,7 +434,10 @@ async fn handler<REv>(
     mut shutdown_receiver: watch::Receiver<()>,
 ) -> io::Result<()>
 where
-    REv: From<DumpConsensusStateRequest> + From<ControlAnnouncement> + Send,
+    REv: From<DumpConsensusStateRequest>
+        + From<ConsensusActionRequest>
+        + From<ControlAnnouncement>
+        + Send,
 {
     debug!("accepted new connection on diagnostics port");
 
@@ -433,7 +477,10 @@ pub(super) async fn server<REv>(
     listener: UnixListener,
     mut shutdown_receiver: watch::Receiver<()>,
 ) where
-    REv: From<DumpConsensusStateRequest> + From<ControlAnnouncement> + Send,
+    REv: From<DumpConsensusStateRequest>
+        + From<ConsensusActionRequest>
+        + From<ControlAnnouncement>
+        + Send,
 {
     let handling_shutdown_receiver = shutdown_receiver.clone();
     let mut next_client_id: u64 = 0;
@@ -544,6 +591,7 @@ mod tests {
 
         requests: {
             DumpConsensusStateRequest -> !;
+            ConsensusActionRequest -> !;
         }
 
         announcements: {}
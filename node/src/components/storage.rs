@@ -637,7 +637,7 @@ impl StorageInner {
         req: StateStoreRequest,
     ) -> Result<Effects<Event>, FatalStorageError> {
         // Incoming requests are fairly simple database write. Errors are handled one level above on
-        // the call stack, so all we have to do is load or store a value.
+        // the call stack, so all we have to do is load, store or delete a value.
         match req {
             StateStoreRequest::Save {
                 key,
@@ -653,6 +653,15 @@ impl StorageInner {
                 let bytes = self.read_state_store(&key)?;
                 Ok(responder.respond(bytes).ignore())
             }
+            StateStoreRequest::Delete { key, responder } => {
+                let mut txn = self.env.begin_rw_txn()?;
+                match txn.del(self.state_store_db, &key, None) {
+                    Ok(()) | Err(lmdb::Error::NotFound) => {}
+                    Err(err) => return Err(err.into()),
+                }
+                txn.commit()?;
+                Ok(responder.respond(()).ignore())
+            }
         }
     }
 
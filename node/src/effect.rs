@@ -165,7 +165,7 @@ use requests::{
 
 use self::{
     announcements::QueueDumpFormat,
-    diagnostics_port::DumpConsensusStateRequest,
+    diagnostics_port::{ConsensusAction, ConsensusActionRequest, DumpConsensusStateRequest},
     requests::{BeginGossipRequest, StateStoreRequest},
 };
 
@@ -1569,6 +1569,78 @@ impl<REv> EffectBuilder<REv> {
             .await
     }
 
+    /// Announces that the current era has not made progress for too long.
+    pub(crate) async fn announce_era_stalled(self, era_id: EraId, since: Timestamp)
+    where
+        REv: From<ConsensusAnnouncement>,
+    {
+        self.event_queue
+            .schedule(
+                ConsensusAnnouncement::EraStalled { era_id, since },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
+    /// Announces that consensus has caught up with the current era.
+    pub(crate) async fn announce_caught_up(self, era_id: EraId)
+    where
+        REv: From<ConsensusAnnouncement>,
+    {
+        self.event_queue
+            .schedule(
+                ConsensusAnnouncement::CaughtUp { era_id },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
+    /// Announces that an era can't be created because its validators have no weight.
+    pub(crate) async fn announce_no_validator_weight(self, era_id: EraId)
+    where
+        REv: From<ConsensusAnnouncement>,
+    {
+        self.event_queue
+            .schedule(
+                ConsensusAnnouncement::NoValidatorWeight { era_id },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
+    /// Announces that we are the leader in the current slot and should propose a block.
+    pub(crate) async fn announce_we_are_leader(
+        self,
+        era_id: EraId,
+        block_context: BlockContext<ClContext>,
+    ) where
+        REv: From<ConsensusAnnouncement>,
+    {
+        self.event_queue
+            .schedule(
+                ConsensusAnnouncement::WeAreLeader {
+                    era_id,
+                    block_context,
+                },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
+    /// Announces that we stopped proposing blocks in the given era, because our own proposals
+    /// repeatedly failed validation.
+    pub(crate) async fn announce_proposals_halted(self, era_id: EraId, failures: u32)
+    where
+        REv: From<ConsensusAnnouncement>,
+    {
+        self.event_queue
+            .schedule(
+                ConsensusAnnouncement::ProposalsHalted { era_id, failures },
+                QueueKind::Regular,
+            )
+            .await
+    }
+
     /// Announce the intent to disconnect from a specific peer, which consensus thinks is faulty.
     pub(crate) async fn announce_disconnect_from_peer(self, peer: NodeId)
     where
@@ -1740,6 +1812,18 @@ impl<REv> EffectBuilder<REv> {
         }
     }
 
+    /// Deletes state from storage, if there is any under the given key.
+    pub(crate) async fn delete_state(self, key: Cow<'static, [u8]>)
+    where
+        REv: From<StateStoreRequest>,
+    {
+        self.make_request(
+            move |responder| StateStoreRequest::Delete { key, responder },
+            QueueKind::Regular,
+        )
+        .await
+    }
+
     /// Requests a query be executed on the Contract Runtime component.
     pub(crate) async fn query_global_state(
         self,
@@ -1996,6 +2080,21 @@ impl<REv> EffectBuilder<REv> {
         .await
     }
 
+    /// Makes the consensus component perform a debugging or recovery action.
+    pub(crate) async fn diagnostics_port_consensus_action(
+        self,
+        action: ConsensusAction,
+    ) -> Result<String, Cow<'static, str>>
+    where
+        REv: From<ConsensusActionRequest>,
+    {
+        self.make_request(
+            |responder| ConsensusActionRequest { action, responder },
+            QueueKind::Control,
+        )
+        .await
+    }
+
     /// Dump the event queue contents to the diagnostics port, using the given serializer.
     pub(crate) async fn diagnostics_port_dump_queue(self, dump_format: QueueDumpFormat)
     where
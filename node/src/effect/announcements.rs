@@ -16,7 +16,10 @@ use casper_types::{EraId, ExecutionEffect, ExecutionResult, PublicKey, Timestamp
 
 use crate::{
     components::{
-        chainspec_loader::NextUpgrade, deploy_acceptor::Error, diagnostics_port::FileSerializer,
+        chainspec_loader::NextUpgrade,
+        consensus::{BlockContext, ClContext},
+        deploy_acceptor::Error,
+        diagnostics_port::FileSerializer,
     },
     effect::Responder,
     types::{
@@ -197,6 +200,39 @@ pub(crate) enum ConsensusAnnouncement {
         /// The timestamp when the evidence of the equivocation was detected.
         timestamp: Timestamp,
     },
+    /// The current era has not finalized a block for longer than the configured timeout.
+    EraStalled {
+        /// The ID of the stalled era.
+        era_id: EraId,
+        /// The time of the last progress, i.e. the last finalized block or the era's start.
+        since: Timestamp,
+    },
+    /// We have caught up with the current era: We have its protocol state and are not missing any
+    /// of its units.
+    CaughtUp {
+        /// The ID of the current era.
+        era_id: EraId,
+    },
+    /// The switch block for an era has no validators with a nonzero weight, so the era can't run.
+    NoValidatorWeight {
+        /// The ID of the era that can't be created.
+        era_id: EraId,
+    },
+    /// We are the leader in the current slot, so a block payload should be prepared for us to
+    /// propose. This is announced once per slot.
+    WeAreLeader {
+        /// The ID of the era in which we propose.
+        era_id: EraId,
+        /// The context of the block to be proposed.
+        block_context: BlockContext<ClContext>,
+    },
+    /// Our own block proposals repeatedly failed validation, so we stopped proposing in this era.
+    ProposalsHalted {
+        /// The ID of the era in which we stopped proposing.
+        era_id: EraId,
+        /// The number of consecutive failed proposals.
+        failures: u32,
+    },
 }
 
 impl Display for ConsensusAnnouncement {
@@ -217,6 +253,31 @@ impl Display for ConsensusAnnouncement {
                 "Validator fault with public key: {} has been identified at time: {} in era: {}",
                 public_key, timestamp, era_id,
             ),
+            ConsensusAnnouncement::EraStalled { era_id, since } => write!(
+                formatter,
+                "{} has not made progress since {}",
+                era_id, since
+            ),
+            ConsensusAnnouncement::CaughtUp { era_id } => {
+                write!(formatter, "caught up with {}", era_id)
+            }
+            ConsensusAnnouncement::NoValidatorWeight { era_id } => {
+                write!(formatter, "no validator weight in {}", era_id)
+            }
+            ConsensusAnnouncement::WeAreLeader {
+                era_id,
+                block_context,
+            } => write!(
+                formatter,
+                "we are the leader in {} at {}",
+                era_id,
+                block_context.timestamp()
+            ),
+            ConsensusAnnouncement::ProposalsHalted { era_id, failures } => write!(
+                formatter,
+                "stopped proposing in {} after {} failed proposals",
+                era_id, failures
+            ),
         }
     }
 }
@@ -1,6 +1,7 @@
 use std::{
     borrow::Cow,
-    fmt::{Debug, Display},
+    fmt::{self, Debug, Display, Formatter},
+    path::PathBuf,
 };
 
 use casper_types::EraId;
@@ -41,7 +42,7 @@ impl DumpConsensusStateRequest {
 }
 
 impl Display for DumpConsensusStateRequest {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "dump consensus state for ")?;
         if let Some(ref era_id) = self.era_id {
             Display::fmt(era_id, f)
@@ -52,9 +53,48 @@ impl Display for DumpConsensusStateRequest {
 }
 
 impl Debug for DumpConsensusStateRequest {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_struct("DumpConsensusStateRequest")
             .field("era_id", &self.era_id)
             .finish_non_exhaustive()
     }
 }
+
+/// A debugging or recovery action to be performed by the consensus component.
+#[derive(DataSize, Debug, Serialize)]
+pub(crate) enum ConsensusAction {
+    /// Feed the messages from the consensus message log at the given path into consensus.
+    ReplayMessageLog(PathBuf),
+    /// Request the units and evidence missing in the given era, or the current one, from peers.
+    ForceEraSync(Option<EraId>),
+    /// Summarize the state of the eras in memory.
+    Status,
+}
+
+impl Display for ConsensusAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ConsensusAction::ReplayMessageLog(path) => {
+                write!(f, "replay consensus message log {}", path.display())
+            }
+            ConsensusAction::ForceEraSync(Some(era_id)) => write!(f, "force sync of {}", era_id),
+            ConsensusAction::ForceEraSync(None) => f.write_str("force sync of the current era"),
+            ConsensusAction::Status => f.write_str("consensus status"),
+        }
+    }
+}
+
+/// A request to perform a debugging or recovery action in the consensus component.
+#[derive(DataSize, Debug, Serialize)]
+pub(crate) struct ConsensusActionRequest {
+    /// The action to perform.
+    pub(crate) action: ConsensusAction,
+    /// Responder for the outcome, or the reason why the action failed.
+    pub(crate) responder: Responder<Result<String, Cow<'static, str>>>,
+}
+
+impl Display for ConsensusActionRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.action, f)
+    }
+}
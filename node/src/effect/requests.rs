@@ -58,7 +58,7 @@ use crate::{
 
 // Redirection for reactor macro.
 #[allow(unused_imports)]
-pub(crate) use super::diagnostics_port::DumpConsensusStateRequest;
+pub(crate) use super::diagnostics_port::{ConsensusActionRequest, DumpConsensusStateRequest};
 
 const _STORAGE_REQUEST_SIZE: usize = mem::size_of::<StorageRequest>();
 const_assert!(_STORAGE_REQUEST_SIZE < 89);
@@ -621,6 +621,13 @@ pub(crate) enum StateStoreRequest {
         /// Responder for value, if found, returning the previously passed in serialization form.
         responder: Responder<Option<Vec<u8>>>,
     },
+    /// Deletes a piece of state from storage.
+    Delete {
+        /// Key to delete.
+        key: Cow<'static, [u8]>,
+        /// Notification when deleting is complete.
+        responder: Responder<()>,
+    },
 }
 
 impl Display for StateStoreRequest {
@@ -637,6 +644,9 @@ impl Display for StateStoreRequest {
             StateStoreRequest::Load { key, .. } => {
                 write!(f, "load data from key {}", base16::encode_lower(key))
             }
+            StateStoreRequest::Delete { key, .. } => {
+                write!(f, "delete data under key {}", base16::encode_lower(key))
+            }
         }
     }
 }
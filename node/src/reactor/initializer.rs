@@ -210,7 +210,7 @@ impl Reactor {
             chainspec_loader.chainspec().protocol_config.version,
             storage.root_path(),
             &config.value().contract_runtime,
-            chainspec_loader.chainspec().wasm_config,
+            chainspec_loader.chainspec().wasm_config.clone(),
             chainspec_loader.chainspec().system_costs_config,
             chainspec_loader.chainspec().core_config.max_associated_keys,
             chainspec_loader
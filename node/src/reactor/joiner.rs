@@ -47,7 +47,7 @@ use crate::{
             ControlAnnouncement, DeployAcceptorAnnouncement, GossiperAnnouncement,
             LinearChainAnnouncement,
         },
-        diagnostics_port::DumpConsensusStateRequest,
+        diagnostics_port::{ConsensusActionRequest, DumpConsensusStateRequest},
         incoming::{
             ConsensusMessageIncoming, FinalitySignatureIncoming, GossiperIncoming,
             NetRequestIncoming, NetResponse, NetResponseIncoming, TrieDemand, TrieRequestIncoming,
@@ -291,6 +291,9 @@ pub(crate) enum JoinerEvent {
     /// Consensus dump request.
     #[from]
     DumpConsensusStateRequest(DumpConsensusStateRequest),
+    /// Consensus debugging or recovery action request.
+    #[from]
+    ConsensusActionRequest(ConsensusActionRequest),
 }
 
 impl ReactorEvent for JoinerEvent {
@@ -336,6 +339,7 @@ impl ReactorEvent for JoinerEvent {
             JoinerEvent::FinalizedApprovalsFetcherRequest(_) => "FinalizedApprovalsFetcherRequest",
             JoinerEvent::TrieOrChunkFetcherRequest(_) => "TrieOrChunkFetcherRequest",
             JoinerEvent::DumpConsensusStateRequest(_) => "DumpConsensusStateRequest",
+            JoinerEvent::ConsensusActionRequest(_) => "ConsensusActionRequest",
             JoinerEvent::ControlAnnouncement(_) => "ControlAnnouncement",
             JoinerEvent::ContractRuntimeAnnouncement(_) => "ContractRuntimeAnnouncement",
             JoinerEvent::AddressGossiperAnnouncement(_) => "AddressGossiperAnnouncement",
@@ -499,6 +503,9 @@ impl Display for JoinerEvent {
             JoinerEvent::DumpConsensusStateRequest(req) => {
                 write!(f, "consensus dump request: {}", req)
             }
+            JoinerEvent::ConsensusActionRequest(req) => {
+                write!(f, "consensus action request: {}", req)
+            }
             JoinerEvent::DeployGossiper(event) => write!(f, "deploy gossiper: {}", event),
             JoinerEvent::DeployGossiperAnnouncement(ann) => {
                 write!(f, "deploy gossiper announcement: {}", ann)
@@ -1035,6 +1042,10 @@ impl reactor::Reactor for Reactor {
                 req.answer(Err(Cow::Borrowed("node is joining, no running consensus")))
                     .ignore()
             }
+            JoinerEvent::ConsensusActionRequest(req) => req
+                .responder
+                .respond(Err(Cow::Borrowed("node is joining, no running consensus")))
+                .ignore(),
             JoinerEvent::DeployGossiper(event) => reactor::wrap_effects(
                 JoinerEvent::DeployGossiper,
                 self.deploy_gossiper
@@ -30,7 +30,7 @@ use crate::{
         block_validator::{self, BlockValidator},
         chain_synchronizer::JoiningOutcome,
         chainspec_loader::{self, ChainspecLoader},
-        consensus::{self, EraSupervisor, HighwayProtocol},
+        consensus::{self, EraSupervisor},
         contract_runtime::{BlockAndExecutionEffects, ContractRuntime, ExecutionPreState},
         deploy_acceptor::{self, DeployAcceptor},
         diagnostics_port::{self, DiagnosticsPort},
@@ -53,7 +53,7 @@ use crate::{
             DeployAcceptorAnnouncement, GossiperAnnouncement, LinearChainAnnouncement,
             RpcServerAnnouncement,
         },
-        diagnostics_port::DumpConsensusStateRequest,
+        diagnostics_port::{ConsensusActionRequest, DumpConsensusStateRequest},
         incoming::{
             ConsensusMessageIncoming, FinalitySignatureIncoming, GossiperIncoming,
             NetRequestIncoming, NetResponse, NetResponseIncoming, TrieDemand, TrieRequestIncoming,
@@ -171,6 +171,9 @@ pub(crate) enum ParticipatingEvent {
     /// Consensus dump request.
     #[from]
     DumpConsensusStateRequest(DumpConsensusStateRequest),
+    /// Consensus debugging or recovery action request.
+    #[from]
+    ConsensusActionRequest(ConsensusActionRequest),
 
     // Announcements
     /// Control announcement.
@@ -281,6 +284,7 @@ impl ReactorEvent for ParticipatingEvent {
             ParticipatingEvent::StorageRequest(_) => "StorageRequest",
             ParticipatingEvent::StateStoreRequest(_) => "StateStoreRequest",
             ParticipatingEvent::DumpConsensusStateRequest(_) => "DumpConsensusStateRequest",
+            ParticipatingEvent::ConsensusActionRequest(_) => "ConsensusActionRequest",
             ParticipatingEvent::ControlAnnouncement(_) => "ControlAnnouncement",
             ParticipatingEvent::RpcServerAnnouncement(_) => "RpcServerAnnouncement",
             ParticipatingEvent::DeployAcceptorAnnouncement(_) => "DeployAcceptorAnnouncement",
@@ -392,6 +396,9 @@ impl Display for ParticipatingEvent {
             ParticipatingEvent::DumpConsensusStateRequest(req) => {
                 write!(f, "dump consensus state: {}", req)
             }
+            ParticipatingEvent::ConsensusActionRequest(req) => {
+                write!(f, "consensus action: {}", req)
+            }
             ParticipatingEvent::RpcServerAnnouncement(ann) => {
                 write!(f, "api server announcement: {}", ann)
             }
@@ -712,7 +719,6 @@ impl reactor::Reactor for Reactor {
             &latest_block_header,
             next_upgrade_activation_point,
             registry,
-            Box::new(HighwayProtocol::new_boxed),
             &storage,
             rng,
         )?;
@@ -720,6 +726,11 @@ impl reactor::Reactor for Reactor {
             ParticipatingEvent::Consensus,
             init_consensus_effects,
         ));
+        effects.extend(
+            effect_builder
+                .immediately()
+                .event(|()| ParticipatingEvent::Consensus(consensus::Event::ResumeFromCheckpoint)),
+        );
 
         contract_runtime.set_initial_state(ExecutionPreState::from_block_header(
             &latest_block_header,
@@ -902,6 +913,10 @@ impl reactor::Reactor for Reactor {
                 ParticipatingEvent::Consensus,
                 self.consensus.handle_event(effect_builder, rng, req.into()),
             ),
+            ParticipatingEvent::ConsensusActionRequest(req) => reactor::wrap_effects(
+                ParticipatingEvent::Consensus,
+                self.consensus.handle_event(effect_builder, rng, req.into()),
+            ),
 
             // Announcements:
             ParticipatingEvent::ControlAnnouncement(ctrl_ann) => {
@@ -1009,6 +1024,11 @@ impl reactor::Reactor for Reactor {
                         );
                         self.dispatch_event(effect_builder, rng, reactor_event)
                     }
+                    ConsensusAnnouncement::EraStalled { .. }
+                    | ConsensusAnnouncement::CaughtUp { .. }
+                    | ConsensusAnnouncement::NoValidatorWeight { .. }
+                    | ConsensusAnnouncement::WeAreLeader { .. }
+                    | ConsensusAnnouncement::ProposalsHalted { .. } => Effects::new(),
                 }
             }
             ParticipatingEvent::ContractRuntimeAnnouncement(
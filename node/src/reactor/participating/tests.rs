@@ -1,4 +1,9 @@
-use std::{collections::BTreeMap, iter, sync::Arc, time::Duration};
+use std::{
+    collections::{BTreeMap, HashMap},
+    iter,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use anyhow::bail;
 use either::Either;
@@ -13,11 +18,14 @@ use casper_execution_engine::core::engine_state::GetBidsRequest;
 use casper_types::{
     system::auction::{Bids, DelegationRate},
     testing::TestRng,
-    EraId, Motes, ProtocolVersion, PublicKey, SecretKey, Timestamp, U512,
+    EraId, Motes, ProtocolVersion, PublicKey, SecretKey, TimeDiff, Timestamp, U512,
 };
 
 use crate::{
-    components::{chainspec_loader::NextUpgrade, gossiper, small_network, storage},
+    components::{
+        chainspec_loader::NextUpgrade, consensus::ConsensusMessage, gossiper, small_network,
+        storage,
+    },
     effect::{
         requests::{
             BlockPayloadRequest, BlockProposerRequest, ContractRuntimeRequest, NetworkRequest,
@@ -30,10 +38,15 @@ use crate::{
         participating::{self, ParticipatingEvent},
         Reactor, ReactorExit, Runner,
     },
-    testing::{self, filter_reactor::FilterReactor, network::Network, ConditionCheckReactor},
+    testing::{
+        self,
+        filter_reactor::{EventFilter, FilterReactor},
+        network::{Network, NetworkedReactor},
+        ConditionCheckReactor,
+    },
     types::{
         chainspec::{AccountConfig, AccountsConfig, ValidatorConfig},
-        ActivationPoint, BlockHeader, Chainspec, ChainspecRawBytes, Deploy, ExitCode,
+        ActivationPoint, BlockHeader, Chainspec, ChainspecRawBytes, Deploy, ExitCode, NodeId,
     },
     utils::{External, Loadable, Source, WithDir, RESOURCES_PATH},
     NodeRng,
@@ -218,6 +231,39 @@ fn is_in_era(era_id: EraId) -> impl Fn(&Nodes) -> bool {
     }
 }
 
+/// Returns an event filter that simulates network latency: Consensus messages from each of the
+/// given peers only reach the node after that peer's delay. Messages from other peers and all
+/// other events are not affected.
+fn delay_consensus_messages(
+    delays: HashMap<NodeId, Duration>,
+) -> impl EventFilter<ParticipatingEvent> {
+    // A delayed event passes through the filter again once its delay is over, so the timer marks
+    // it as released, to let it through instead of delaying it once more.
+    let released: Arc<Mutex<Vec<(NodeId, ConsensusMessage)>>> = Default::default();
+    move |event| {
+        let incoming = match &event {
+            ParticipatingEvent::ConsensusMessageIncoming(incoming) => incoming,
+            _ => return Either::Right(event),
+        };
+        let delay = match delays.get(&incoming.sender) {
+            Some(delay) => *delay,
+            None => return Either::Right(event),
+        };
+        let key = (incoming.sender, incoming.message.clone());
+        let mut released_guard = released.lock().expect("poisoned lock");
+        if let Some(index) = released_guard.iter().position(|entry| *entry == key) {
+            released_guard.swap_remove(index);
+            return Either::Right(event);
+        }
+        drop(released_guard);
+        let released = Arc::clone(&released);
+        Either::Left(time::sleep(delay).event(move |_| {
+            released.lock().expect("poisoned lock").push(key);
+            event
+        }))
+    }
+}
+
 /// A set of consecutive switch blocks.
 struct SwitchBlocks {
     headers: Vec<BlockHeader>,
@@ -744,3 +790,76 @@ async fn empty_block_validation_regression() {
         inactive => panic!("unexpected inactive validators: {:?}", inactive),
     }
 }
+
+/// Runs a network of two equally weighted validators with four-second rounds, in which every
+/// consensus message between them is delayed by `delay`. Returns the time from genesis until both
+/// nodes have stored the block at height 2.
+async fn time_to_height_two(rng: &mut NodeRng, delay: Duration) -> TimeDiff {
+    let keys: Vec<Arc<SecretKey>> = (0..2).map(|_| Arc::new(SecretKey::random(rng))).collect();
+    let stakes: BTreeMap<PublicKey, U512> = keys
+        .iter()
+        .map(|secret_key| (PublicKey::from(&*secret_key.clone()), U512::from(100u64)))
+        .collect();
+    let mut chain = TestChain::new_with_keys(rng, keys, stakes);
+    chain.chainspec_mut().highway_config.minimum_round_exponent = 12; // 4 seconds
+    chain.chainspec_mut().highway_config.maximum_round_exponent = 12; // 4 seconds
+    chain.chainspec_mut().core_config.minimum_era_height = 10;
+    let genesis_timestamp = chain
+        .chainspec
+        .protocol_config
+        .activation_point
+        .genesis_timestamp()
+        .expect("missing genesis timestamp");
+
+    let mut net = chain
+        .create_initialized_network(rng)
+        .await
+        .expect("network initialization failed");
+    let node_ids: Vec<NodeId> = net.nodes().keys().copied().collect();
+    for runner in net.runners_mut() {
+        let our_id = runner.reactor().node_id();
+        let delays = node_ids
+            .iter()
+            .filter(|node_id| **node_id != our_id)
+            .map(|node_id| (*node_id, delay))
+            .collect();
+        runner
+            .reactor_mut()
+            .inner_mut()
+            .set_filter(delay_consensus_messages(delays));
+    }
+
+    net.settle_on(
+        rng,
+        |nodes| {
+            nodes.values().all(|runner| {
+                runner
+                    .participating()
+                    .storage()
+                    .read_highest_block_header()
+                    .expect("failed to read from storage")
+                    .map_or(false, |header| header.height() >= 2)
+            })
+        },
+        Duration::from_secs(120),
+    )
+    .await;
+    Timestamp::now().saturating_diff(genesis_timestamp)
+}
+
+#[tokio::test]
+async fn simulated_message_delay_should_slow_down_finalization() {
+    testing::init_logging();
+
+    let mut rng = crate::new_rng();
+
+    let delay = Duration::from_secs(2);
+    let undelayed = time_to_height_two(&mut rng, Duration::from_secs(0)).await;
+    let delayed = time_to_height_two(&mut rng, delay).await;
+    info!(%undelayed, %delayed, "time from genesis to height 2");
+
+    // Neither validator has a quorum on its own, so no block can be finalized before each of
+    // them has received at least one of the other's units.
+    assert!(delayed >= TimeDiff::from(delay));
+    assert!(delayed > undelayed);
+}
@@ -90,6 +90,22 @@ impl Chainspec {
             warn!("era duration is less than minimum era height * round length!");
         }
 
+        let free_host_functions = self.wasm_config.host_function_costs().free_host_functions();
+        if !free_host_functions.is_empty() {
+            warn!(
+                ?free_host_functions,
+                "some host functions can be called without any cost"
+            );
+        }
+
+        if let Err(error) = self
+            .wasm_config
+            .host_function_costs()
+            .check_per_byte_weights()
+        {
+            warn!(%error, "large inputs to some host functions are charged like small ones");
+        }
+
         self.protocol_config.is_valid() && self.highway_config.is_valid()
     }
 
@@ -190,7 +206,7 @@ impl From<&Chainspec> for ExecConfig {
     fn from(chainspec: &Chainspec) -> Self {
         ExecConfig::new(
             chainspec.network_config.accounts_config.clone().into(),
-            chainspec.wasm_config,
+            chainspec.wasm_config.clone(),
             chainspec.system_costs_config,
             chainspec.core_config.validator_slots,
             chainspec.core_config.auction_delay,
@@ -259,8 +275,9 @@ mod tests {
             create_contract_user_group: HostFunction::new(107, [0, 1, 2, 3, 4, 5, 6, 7]),
             add_contract_version: HostFunction::new(102, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]),
             disable_contract_version: HostFunction::new(109, [0, 1, 2, 3]),
-            call_contract: HostFunction::new(104, [0, 1, 2, 3, 4, 5, 6]),
-            call_versioned_contract: HostFunction::new(105, [0, 1, 2, 3, 4, 5, 6, 7, 8]),
+            // The fixtures predate the per-arg weight of the contract calls, so it defaults to 0.
+            call_contract: HostFunction::new(104, [0, 1, 2, 3, 4, 5, 6, 0]),
+            call_versioned_contract: HostFunction::new(105, [0, 1, 2, 3, 4, 5, 6, 7, 8, 0]),
             get_named_arg_size: HostFunction::new(116, [0, 1, 2]),
             get_named_arg: HostFunction::new(115, [0, 1, 2, 3]),
             remove_contract_user_group: HostFunction::new(130, [0, 1, 2, 3]),
@@ -269,6 +286,9 @@ mod tests {
             print: HostFunction::new(123, [0, 1]),
             blake2b: HostFunction::new(133, [0, 1, 2, 3]),
             random_bytes: HostFunction::new(123, [0, 1]),
+            enable_contract_version: HostFunction::new(142, [0, 1, 2, 3]),
+            transfer: HostFunction::new(143, [0, 1, 2, 3]),
+            extra: Default::default(),
         });
     static EXPECTED_GENESIS_WASM_COSTS: Lazy<WasmConfig> = Lazy::new(|| {
         WasmConfig::new(
@@ -276,7 +296,7 @@ mod tests {
             19, // max_stack_height
             EXPECTED_GENESIS_COSTS,
             EXPECTED_GENESIS_STORAGE_COSTS,
-            *EXPECTED_GENESIS_HOST_FUNCTION_COSTS,
+            EXPECTED_GENESIS_HOST_FUNCTION_COSTS.clone(),
         )
     });
 
@@ -387,6 +407,46 @@ mod tests {
         check_spec(chainspec, false);
     }
 
+    /// Host functions that may cost nothing in the production chainspec.
+    ///
+    /// Every entry must be a deliberate, reviewed decision: Only add a host function here together
+    /// with a justification in the same change, and remove it once it has a non-zero cost.
+    const ZERO_COST_HOST_FUNCTIONS_ALLOWLIST: &[&str] = &[];
+
+    #[test]
+    fn production_host_functions_should_have_non_zero_costs() {
+        let (chainspec, _) = <(Chainspec, ChainspecRawBytes)>::from_resources("production");
+        let zero_cost: Vec<&str> = chainspec
+            .wasm_config
+            .host_function_costs()
+            .below_gas_floor(1)
+            .into_iter()
+            .filter(|name| !ZERO_COST_HOST_FUNCTIONS_ALLOWLIST.contains(name))
+            .collect();
+        assert!(
+            zero_cost.is_empty(),
+            "host functions without a cost in the production chainspec: {:?}",
+            zero_cost
+        );
+        // The allowlist must not contain stale or misspelled entries.
+        for name in ZERO_COST_HOST_FUNCTIONS_ALLOWLIST {
+            assert!(
+                HostFunctionCosts::default().to_map().contains_key(*name),
+                "unknown host function in allowlist: {}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn production_chainspec_should_use_mainnet_host_function_costs() {
+        let (chainspec, _) = <(Chainspec, ChainspecRawBytes)>::from_resources("production");
+        assert_eq!(
+            chainspec.wasm_config.take_host_function_costs(),
+            HostFunctionCosts::mainnet_v1()
+        );
+    }
+
     #[test]
     fn bytesrepr_roundtrip() {
         let mut rng = crate::new_rng();
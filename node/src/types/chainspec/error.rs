@@ -1,5 +1,6 @@
 use std::{io, path::PathBuf};
 
+use casper_execution_engine::shared::host_function_costs::HostFunctionCostsError;
 use casper_types::file_utils::ReadFileError;
 use thiserror::Error;
 use uint::FromDecStrErr;
@@ -15,6 +16,10 @@ pub enum Error {
     #[error("decoding motes from base-10 error: {0}")]
     DecodingMotes(#[from] FromDecStrErr),
 
+    /// The host function costs violate the gas floor.
+    #[error("invalid host function costs: {0}")]
+    HostFunctionCosts(#[from] HostFunctionCostsError),
+
     /// Error loading the chainspec.
     #[error("could not load chainspec: {0}")]
     LoadChainspec(ReadFileError),
@@ -10,7 +10,9 @@ use std::{convert::TryFrom, path::Path};
 
 use serde::{Deserialize, Serialize};
 
-use casper_execution_engine::shared::{system_config::SystemConfig, wasm_config::WasmConfig};
+use casper_execution_engine::shared::{
+    host_function_costs::GasFloor, system_config::SystemConfig, wasm_config::WasmConfig,
+};
 use casper_types::{bytesrepr::Bytes, file_utils, EraId, ProtocolVersion};
 
 use super::{
@@ -50,6 +52,9 @@ pub(super) struct TomlChainspec {
     highway: HighwayConfig,
     wasm: WasmConfig,
     system_costs: SystemConfig,
+    /// The minimum cost of every host function, checked when the chainspec is loaded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    host_function_gas_floor: Option<GasFloor>,
 }
 
 impl From<&Chainspec> for TomlChainspec {
@@ -70,7 +75,7 @@ impl From<&Chainspec> for TomlChainspec {
         let core = chainspec.core_config;
         let deploys = chainspec.deploy_config;
         let highway = chainspec.highway_config;
-        let wasm = chainspec.wasm_config;
+        let wasm = chainspec.wasm_config.clone();
         let system_costs = chainspec.system_costs_config;
 
         TomlChainspec {
@@ -81,6 +86,7 @@ impl From<&Chainspec> for TomlChainspec {
             highway,
             wasm,
             system_costs,
+            host_function_gas_floor: None,
         }
     }
 }
@@ -124,13 +130,20 @@ pub(super) fn parse_toml<P: AsRef<Path>>(
             .verifiable_chunked_hash_activation,
     };
 
+    let mut wasm_config = toml_chainspec.wasm;
+    if let Some(gas_floor) = toml_chainspec.host_function_gas_floor {
+        let mut host_function_costs = wasm_config.host_function_costs().clone();
+        host_function_costs.apply_gas_floor(gas_floor)?;
+        wasm_config = wasm_config.with_host_function_costs(host_function_costs);
+    }
+
     let chainspec = Chainspec {
         protocol_config,
         network_config,
         core_config: toml_chainspec.core,
         deploy_config: toml_chainspec.deploys,
         highway_config: toml_chainspec.highway,
-        wasm_config: toml_chainspec.wasm,
+        wasm_config,
         system_costs_config: toml_chainspec.system_costs,
     };
     let chainspec_raw_bytes = ChainspecRawBytes::new(
@@ -43,16 +43,20 @@ impl EraId {
     }
 
     /// Returns an iterator over era IDs of `num_eras` future eras starting from current.
+    ///
+    /// The iterator stops at [`EraId::MAX`] rather than overflowing.
     pub fn iter(&self, num_eras: u64) -> impl Iterator<Item = EraId> {
         let current_era_id = self.0;
-        (current_era_id..current_era_id + num_eras).map(EraId)
+        (current_era_id..current_era_id.saturating_add(num_eras)).map(EraId)
     }
 
     /// Returns an iterator over era IDs of `num_eras` future eras starting from current, plus the
     /// provided one.
+    ///
+    /// The iterator stops at [`EraId::MAX`] rather than overflowing.
     pub fn iter_inclusive(&self, num_eras: u64) -> impl Iterator<Item = EraId> {
         let current_era_id = self.0;
-        (current_era_id..=current_era_id + num_eras).map(EraId)
+        (current_era_id..=current_era_id.saturating_add(num_eras)).map(EraId)
     }
 
     /// Returns a successor to current era.
@@ -64,6 +68,19 @@ impl EraId {
         EraId::from(self.0.saturating_add(1))
     }
 
+    /// Returns a successor to current era, or `None` if this is [`EraId::MAX`].
+    ///
+    /// Code that starts a new era must use this rather than [`EraId::successor`]: The maximum era
+    /// is the last one, and the chain cannot continue past it.
+    pub fn checked_successor(self) -> Option<EraId> {
+        self.0.checked_add(1).map(EraId)
+    }
+
+    /// Returns a predecessor to current era, or `None` if this is era 0.
+    pub fn predecessor(self) -> Option<EraId> {
+        self.0.checked_sub(1).map(EraId)
+    }
+
     /// Returns the current era plus `x`, or `None` if that would overflow
     pub fn checked_add(&self, x: u64) -> Option<EraId> {
         self.0.checked_add(x).map(EraId)
@@ -225,6 +242,56 @@ mod tests {
         assert!(!expected_initial_era_id.successor().is_genesis())
     }
 
+    #[test]
+    fn should_calculate_successor_and_predecessor() {
+        let era_id = EraId::from(42);
+        assert_eq!(era_id.successor(), EraId::from(43));
+        assert_eq!(era_id.predecessor(), Some(EraId::from(41)));
+        assert_eq!(era_id.successor().predecessor(), Some(era_id));
+        assert_eq!(era_id.predecessor().map(EraId::successor), Some(era_id));
+    }
+
+    #[test]
+    fn should_handle_zero_boundary() {
+        let genesis = EraId::from(0);
+        assert_eq!(genesis.predecessor(), None);
+        assert_eq!(genesis.checked_sub(1), None);
+        assert_eq!(genesis.saturating_sub(1), genesis);
+        assert_eq!(genesis.successor(), EraId::from(1));
+        assert_eq!(EraId::from(1).predecessor(), Some(genesis));
+        assert_eq!(EraId::from(2).saturating_sub(5), genesis);
+        assert_eq!(EraId::MAX.successor(), EraId::MAX);
+    }
+
+    #[test]
+    fn should_handle_max_boundary() {
+        let max = EraId::MAX;
+        let before_max = EraId::from(u64::max_value() - 1);
+        assert_eq!(before_max.checked_successor(), Some(max));
+        assert_eq!(max.checked_successor(), None);
+        assert_eq!(max.successor(), max);
+        assert_eq!(max.saturating_add(1), max);
+        assert_eq!(max.checked_add(1), None);
+        assert_eq!(max.predecessor(), Some(before_max));
+        assert_eq!(before_max.iter(5).collect::<Vec<_>>(), vec![before_max]);
+        assert_eq!(
+            before_max.iter_inclusive(5).collect::<Vec<_>>(),
+            vec![before_max, max]
+        );
+    }
+
+    #[test]
+    fn should_order_era_ids() {
+        let mut era_ids = vec![EraId::from(3), EraId::from(0), EraId::MAX, EraId::from(1)];
+        era_ids.sort();
+        assert_eq!(
+            era_ids,
+            vec![EraId::from(0), EraId::from(1), EraId::from(3), EraId::MAX]
+        );
+        assert!(EraId::from(1) < EraId::from(2));
+        assert_eq!(EraId::from(5).max(EraId::from(7)), EraId::from(7));
+    }
+
     proptest! {
         #[test]
         fn bytesrepr_roundtrip(era_id in era_id_arb()) {